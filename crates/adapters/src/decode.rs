@@ -0,0 +1,286 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use image::codecs::jpeg::JpegDecoder;
+use image::io::Reader as ImageReader;
+use image::{DynamicImage, ImageFormat};
+use lite_room_application::ApplicationError;
+
+pub const DEFAULT_MAX_DECODE_DIMENSION: u32 = 16_384;
+
+const MARKER_PREFIX: u8 = 0xFF;
+const MARKER_SOI: u8 = 0xD8;
+const MARKER_APP14: u8 = 0xEE;
+const MARKER_SOS: u8 = 0xDA;
+const MARKER_EOI: u8 = 0xD9;
+const ADOBE_HEADER: &[u8] = b"Adobe";
+const ADOBE_TRANSFORM_OFFSET: usize = 11;
+const CMYK_COMPONENTS: u8 = 4;
+
+pub(crate) fn decode_within_limit(
+    path: &Path,
+    max_dimension: u32,
+) -> Result<DynamicImage, ApplicationError> {
+    let reader = open_reader(path)?;
+    if reader.format() == Some(ImageFormat::Jpeg) {
+        ensure_supported_jpeg_color(path)?;
+    }
+    let (width, height) = reader
+        .into_dimensions()
+        .map_err(|error| ApplicationError::Decode(error.to_string()))?;
+    ensure_within_decode_limit(path, width, height, max_dimension)?;
+
+    open_reader(path)?
+        .decode()
+        .map_err(|error| ApplicationError::Decode(error.to_string()))
+}
+
+pub(crate) fn decode_scaled_within_limit(
+    path: &Path,
+    min_width: u32,
+    min_height: u32,
+    max_dimension: u32,
+) -> Result<DynamicImage, ApplicationError> {
+    let reader = open_reader(path)?;
+    if reader.format() != Some(ImageFormat::Jpeg) {
+        return decode_within_limit(path, max_dimension);
+    }
+    ensure_supported_jpeg_color(path)?;
+    let (width, height) = reader
+        .into_dimensions()
+        .map_err(|error| ApplicationError::Decode(error.to_string()))?;
+    ensure_within_decode_limit(path, width, height, max_dimension)?;
+
+    // libjpeg-style IDCT scaling picks the smallest of 1/1, 1/2, 1/4, 1/8 that
+    // still covers the requested size, so the caller never upsamples.
+    let file = File::open(path).map_err(|error| ApplicationError::Io(error.to_string()))?;
+    let mut decoder = JpegDecoder::new(BufReader::new(file))
+        .map_err(|error| ApplicationError::Decode(error.to_string()))?;
+    let requested = |wanted: u32, full: u32| wanted.clamp(1, full).min(u16::MAX as u32) as u16;
+    if decoder
+        .scale(requested(min_width, width), requested(min_height, height))
+        .is_err()
+    {
+        return decode_within_limit(path, max_dimension);
+    }
+    DynamicImage::from_decoder(decoder).map_err(|error| ApplicationError::Decode(error.to_string()))
+}
+
+pub(crate) fn ensure_within_decode_limit(
+    path: &Path,
+    width: u32,
+    height: u32,
+    max_dimension: u32,
+) -> Result<(), ApplicationError> {
+    if width > max_dimension || height > max_dimension {
+        return Err(ApplicationError::InvalidInput(format!(
+            "image {width}x{height} exceeds max decode dimension {max_dimension}: {:?}",
+            path
+        )));
+    }
+    Ok(())
+}
+
+// The image crate converts four-channel JPEGs to RGB assuming the Adobe
+// convention (inverted CMYK, or YCCK), which is what Photoshop and most print
+// tools write. Without an Adobe segment the inks may be stored the other way
+// round and would decode as a negative, so those files are refused instead.
+fn ensure_supported_jpeg_color(path: &Path) -> Result<(), ApplicationError> {
+    let file = File::open(path).map_err(|error| ApplicationError::Io(error.to_string()))?;
+    match read_jpeg_layout(BufReader::new(file)) {
+        Some(layout)
+            if layout.components == CMYK_COMPONENTS && layout.adobe_transform.is_none() =>
+        {
+            Err(ApplicationError::Decode(format!(
+                "unsupported CMYK JPEG without an Adobe color marker: {:?}",
+                path
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct JpegLayout {
+    components: u8,
+    adobe_transform: Option<u8>,
+}
+
+// Walks the segments before the first scan for the frame's component count
+// and the Adobe APP14 color transform. `None` when the header is malformed;
+// the decoder then reports the real error.
+fn read_jpeg_layout<R: Read>(mut reader: R) -> Option<JpegLayout> {
+    let mut marker = [0_u8; 2];
+    reader.read_exact(&mut marker).ok()?;
+    if marker != [MARKER_PREFIX, MARKER_SOI] {
+        return None;
+    }
+
+    let mut components = None;
+    let mut adobe_transform = None;
+    loop {
+        reader.read_exact(&mut marker).ok()?;
+        if marker[0] != MARKER_PREFIX {
+            return None;
+        }
+        match marker[1] {
+            MARKER_SOS | MARKER_EOI => break,
+            MARKER_PREFIX => continue,
+            _ => {}
+        }
+        let mut length = [0_u8; 2];
+        reader.read_exact(&mut length).ok()?;
+        let payload_len = usize::from(u16::from_be_bytes(length)).checked_sub(2)?;
+        let mut payload = vec![0_u8; payload_len];
+        reader.read_exact(&mut payload).ok()?;
+
+        match marker[1] {
+            // SOF0..SOF15, skipping DHT, JPG and DAC which share the range.
+            0xC0..=0xCF if !matches!(marker[1], 0xC4 | 0xC8 | 0xCC) => {
+                components = payload.get(5).copied();
+            }
+            MARKER_APP14 if payload.starts_with(ADOBE_HEADER) => {
+                adobe_transform = payload.get(ADOBE_TRANSFORM_OFFSET).copied();
+            }
+            _ => {}
+        }
+    }
+    Some(JpegLayout {
+        components: components?,
+        adobe_transform,
+    })
+}
+
+fn open_reader(path: &Path) -> Result<ImageReader<BufReader<File>>, ApplicationError> {
+    ImageReader::open(path)
+        .map_err(|error| ApplicationError::Io(error.to_string()))?
+        .with_guessed_format()
+        .map_err(|error| ApplicationError::Decode(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+    use lite_room_application::ImageDecoder;
+    use tempfile::TempDir;
+
+    use crate::test_support::write_flat_cmyk_jpeg;
+    use crate::ImageCrateDecoder;
+
+    #[test]
+    fn oversized_image_is_rejected_from_probed_header() {
+        let dir = TempDir::new().expect("tempdir");
+        let source = dir.path().join("panorama.jpg");
+        ImageBuffer::from_fn(96, 32, |x, _y| Rgb([x as u8, 60, 90]))
+            .save(&source)
+            .expect("save");
+        let bytes = std::fs::read(&source).expect("read jpeg");
+        std::fs::write(&source, &bytes[..bytes.len() * 3 / 4]).expect("truncate jpeg");
+
+        let guarded = ImageCrateDecoder::new(64).decode_full(&source);
+        assert!(
+            matches!(&guarded, Err(ApplicationError::InvalidInput(message)) if message.contains("96x32")),
+            "unexpected result: {guarded:?}"
+        );
+        assert!(matches!(
+            ImageCrateDecoder::new(128).decode_full(&source),
+            Err(ApplicationError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn full_decode_returns_every_rgb_pixel_and_refuses_raw() {
+        let dir = TempDir::new().expect("tempdir");
+        let source = dir.path().join("full.jpg");
+        ImageBuffer::from_fn(37, 21, |x, y| Rgb([x as u8 * 6, y as u8 * 11, 128]))
+            .save(&source)
+            .expect("save");
+
+        let decoder = ImageCrateDecoder::default();
+        let full = decoder.decode_full(&source).expect("decode full");
+        assert_eq!((full.width, full.height), (37, 21));
+        assert_eq!(full.rgb.len(), 37 * 21 * 3);
+        let preview = decoder.decode_for_preview(&source).expect("decode preview");
+        assert_eq!((preview.width, preview.height), (full.width, full.height));
+
+        let raw = dir.path().join("shot.cr2");
+        std::fs::write(&raw, b"not really raw").expect("write raw");
+        assert!(matches!(
+            decoder.decode_full(&raw),
+            Err(ApplicationError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn large_jpeg_decodes_at_reduced_scale_for_small_target() {
+        let dir = TempDir::new().expect("tempdir");
+        let source = dir.path().join("large.jpg");
+        ImageBuffer::from_fn(1600, 1200, |x, _y| {
+            if x < 800 {
+                Rgb([200_u8, 40, 40])
+            } else {
+                Rgb([40_u8, 40, 200])
+            }
+        })
+        .save(&source)
+        .expect("save");
+
+        let scaled = decode_scaled_within_limit(&source, 180, 120, DEFAULT_MAX_DECODE_DIMENSION)
+            .expect("scaled decode")
+            .to_rgb8();
+        assert_eq!(scaled.dimensions(), (200, 150));
+        let left = scaled.get_pixel(50, 75);
+        let right = scaled.get_pixel(150, 75);
+        assert!(left[0] > 170 && left[2] < 70, "left pixel {left:?}");
+        assert!(right[2] > 170 && right[0] < 70, "right pixel {right:?}");
+
+        let full = decode_scaled_within_limit(&source, 1600, 1200, DEFAULT_MAX_DECODE_DIMENSION)
+            .expect("full decode");
+        assert_eq!((full.width(), full.height()), (1600, 1200));
+    }
+
+    #[test]
+    fn adobe_cmyk_jpeg_decodes_to_rgb_rather_than_a_negative() {
+        let dir = TempDir::new().expect("tempdir");
+        // Adobe files store ink inverted: full cyan is 0 on the cyan channel.
+        let cyan = dir.path().join("cyan.jpg");
+        write_flat_cmyk_jpeg(&cyan, [0, 255, 255, 255], Some(0));
+        let red = dir.path().join("red.jpg");
+        write_flat_cmyk_jpeg(&red, [255, 0, 0, 255], Some(0));
+
+        let decoder = ImageCrateDecoder::default();
+        let pixel = |path: &Path| {
+            let decoded = decoder.decode_full(path).expect("decode cmyk");
+            assert_eq!((decoded.width, decoded.height), (8, 8));
+            [decoded.rgb[0], decoded.rgb[1], decoded.rgb[2]]
+        };
+        let cyan = pixel(&cyan);
+        assert!(
+            cyan[0] < 30 && cyan[1] > 225 && cyan[2] > 225,
+            "cyan {cyan:?}"
+        );
+        let red = pixel(&red);
+        assert!(red[0] > 225 && red[1] < 30 && red[2] < 30, "red {red:?}");
+
+        let scaled = decode_scaled_within_limit(&dir.path().join("red.jpg"), 1, 1, 64)
+            .expect("scaled cmyk")
+            .to_rgb8();
+        assert!(scaled.get_pixel(0, 0)[0] > 225);
+    }
+
+    #[test]
+    fn cmyk_jpeg_without_adobe_marker_is_refused() {
+        let dir = TempDir::new().expect("tempdir");
+        let source = dir.path().join("plain-cmyk.jpg");
+        write_flat_cmyk_jpeg(&source, [0, 255, 255, 255], None);
+
+        let decoded = ImageCrateDecoder::default().decode_full(&source);
+        assert!(
+            matches!(&decoded, Err(ApplicationError::Decode(message)) if message.contains("CMYK")),
+            "unexpected result: {decoded:?}"
+        );
+        assert!(decode_scaled_within_limit(&source, 1, 1, 64).is_err());
+    }
+}
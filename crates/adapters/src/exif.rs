@@ -0,0 +1,199 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use ::exif::{Exif, In, Reader, Tag, Value};
+use lite_room_application::CaptureMetadata;
+
+// Only the tags the catalog stores are read. The container (JPEG, TIFF and
+// TIFF-based RAW, HEIF, PNG, WebP) is sniffed by the exif reader; missing,
+// malformed or truncated EXIF yields empty fields rather than failing the import.
+pub(crate) fn read_capture_metadata(path: &Path) -> CaptureMetadata {
+    let Some(exif) = File::open(path).ok().and_then(|file| {
+        Reader::new()
+            .read_from_container(&mut BufReader::new(file))
+            .ok()
+    }) else {
+        return CaptureMetadata::default();
+    };
+    let capture_date = ascii(&exif, Tag::DateTimeOriginal)
+        .or_else(|| ascii(&exif, Tag::DateTime))
+        .and_then(|value| exif_date_to_iso(&value));
+    CaptureMetadata {
+        capture_date,
+        camera_model: ascii(&exif, Tag::Model),
+        iso: exif
+            .get_field(Tag::PhotographicSensitivity, In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+            .filter(|iso| *iso > 0)
+            .map(i64::from),
+        lens_model: ascii(&exif, Tag::LensModel),
+        focal_length: rational(&exif, Tag::FocalLength),
+    }
+}
+
+// A zero denominator (unknown) or non-positive value counts as missing.
+fn rational(exif: &Exif, tag: Tag) -> Option<f64> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let Value::Rational(values) = &field.value else {
+        return None;
+    };
+    let value = values.first()?;
+    (value.denom != 0 && value.num != 0).then(|| value.to_f64())
+}
+
+fn ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let Value::Ascii(values) = &field.value else {
+        return None;
+    };
+    let text = String::from_utf8_lossy(values.first()?);
+    let text = text.trim_end_matches('\0').trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+// EXIF stores "YYYY:MM:DD HH:MM:SS"; the catalog keeps "YYYY-MM-DDTHH:MM:SS".
+fn exif_date_to_iso(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let digits_at = [0, 1, 2, 3, 5, 6, 8, 9, 11, 12, 14, 15, 17, 18];
+    if bytes.len() != 19
+        || !digits_at.iter().all(|index| bytes[*index].is_ascii_digit())
+        || bytes[4] != b':'
+        || bytes[7] != b':'
+        || bytes[10] != b' '
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+        || value.starts_with("0000")
+    {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}T{}",
+        &value[0..4],
+        &value[5..7],
+        &value[8..10],
+        &value[11..19]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    use crate::test_support::{exif_tiff, write_jpeg_with_exif, ExifTags};
+
+    #[test]
+    fn reads_model_date_iso_lens_and_focal_length_in_both_byte_orders() {
+        let dir = TempDir::new().expect("tempdir");
+        for big_endian in [false, true] {
+            let path = dir.path().join(format!("exif-{big_endian}.jpg"));
+            write_jpeg_with_exif(
+                &path,
+                &exif_tiff(
+                    big_endian,
+                    &ExifTags {
+                        model: Some("LR-100 Mark II"),
+                        date_time_original: Some("2024:05:06 07:08:09"),
+                        iso: Some(800),
+                        lens_model: Some("LR 24-70mm F2.8"),
+                        focal_length: Some((705, 10)),
+                    },
+                ),
+            );
+
+            assert_eq!(
+                read_capture_metadata(&path),
+                CaptureMetadata {
+                    capture_date: Some("2024-05-06T07:08:09".to_string()),
+                    camera_model: Some("LR-100 Mark II".to_string()),
+                    iso: Some(800),
+                    lens_model: Some("LR 24-70mm F2.8".to_string()),
+                    focal_length: Some(70.5),
+                },
+                "big_endian={big_endian}"
+            );
+        }
+    }
+
+    #[test]
+    fn reads_tags_from_a_tiff_container() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("scan.tif");
+        std::fs::write(
+            &path,
+            exif_tiff(
+                true,
+                &ExifTags {
+                    model: Some("Flatbed"),
+                    date_time_original: Some("2023:12:31 23:59:58"),
+                    iso: Some(100),
+                    focal_length: Some((50, 1)),
+                    ..ExifTags::default()
+                },
+            ),
+        )
+        .expect("write tiff");
+
+        assert_eq!(
+            read_capture_metadata(&path),
+            CaptureMetadata {
+                capture_date: Some("2023-12-31T23:59:58".to_string()),
+                camera_model: Some("Flatbed".to_string()),
+                iso: Some(100),
+                lens_model: None,
+                focal_length: Some(50.0),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_or_broken_exif_falls_back_to_empty_fields() {
+        let dir = TempDir::new().expect("tempdir");
+        let partial = dir.path().join("partial.jpg");
+        write_jpeg_with_exif(
+            &partial,
+            &exif_tiff(
+                false,
+                &ExifTags {
+                    model: Some("Cam"),
+                    focal_length: Some((35, 0)),
+                    ..ExifTags::default()
+                },
+            ),
+        );
+        assert_eq!(
+            read_capture_metadata(&partial),
+            CaptureMetadata {
+                camera_model: Some("Cam".to_string()),
+                ..CaptureMetadata::default()
+            }
+        );
+
+        let plain = dir.path().join("plain.jpg");
+        image::ImageBuffer::from_pixel(4, 4, image::Rgb([1_u8, 2, 3]))
+            .save(&plain)
+            .expect("save jpeg");
+        assert_eq!(read_capture_metadata(&plain), CaptureMetadata::default());
+
+        let truncated = dir.path().join("truncated.jpg");
+        let mut tiff = exif_tiff(
+            true,
+            &ExifTags {
+                model: Some("Cam"),
+                date_time_original: Some("2024:05:06 07:08:09"),
+                iso: Some(100),
+                ..ExifTags::default()
+            },
+        );
+        tiff.truncate(20);
+        write_jpeg_with_exif(&truncated, &tiff);
+        assert_eq!(
+            read_capture_metadata(&truncated),
+            CaptureMetadata::default()
+        );
+
+        assert_eq!(exif_date_to_iso("0000:00:00 00:00:00"), None);
+        assert_eq!(exif_date_to_iso("2024-05-06 07:08:09"), None);
+    }
+}
@@ -0,0 +1,286 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use image::codecs::jpeg::JpegEncoder;
+use image::{ColorType, GenericImageView, Rgb};
+use lite_room_application::{ApplicationError, ImageEncoder};
+use lite_room_domain::{ChannelOrder, DecodedBuffer, RawPixelFormat, TiledRender};
+
+#[derive(Debug, Default)]
+pub struct FsImageEncoder;
+
+impl ImageEncoder for FsImageEncoder {
+    fn write_jpeg(
+        &self,
+        path: &Path,
+        image: &DecodedBuffer,
+        quality: u8,
+    ) -> Result<PathBuf, ApplicationError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| ApplicationError::Io(error.to_string()))?;
+        }
+
+        let file =
+            fs::File::create(path).map_err(|error| ApplicationError::Io(error.to_string()))?;
+        let mut writer = std::io::BufWriter::new(file);
+        JpegEncoder::new_with_quality(&mut writer, quality)
+            .encode(&image.rgb, image.width, image.height, ColorType::Rgb8)
+            .map_err(|error| ApplicationError::Io(error.to_string()))?;
+        drop(writer);
+
+        path.canonicalize()
+            .map_err(|error| ApplicationError::Io(error.to_string()))
+    }
+
+    fn write_jpeg_tiled(
+        &self,
+        path: &Path,
+        image: &TiledRender<'_>,
+        quality: u8,
+    ) -> Result<PathBuf, ApplicationError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| ApplicationError::Io(error.to_string()))?;
+        }
+
+        let file =
+            fs::File::create(path).map_err(|error| ApplicationError::Io(error.to_string()))?;
+        let mut writer = std::io::BufWriter::new(file);
+        JpegEncoder::new_with_quality(&mut writer, quality)
+            .encode_image(&StripView::new(image))
+            .map_err(|error| ApplicationError::Io(error.to_string()))?;
+        writer
+            .flush()
+            .map_err(|error| ApplicationError::Io(error.to_string()))?;
+        drop(writer);
+
+        path.canonicalize()
+            .map_err(|error| ApplicationError::Io(error.to_string()))
+    }
+
+    fn write_raw(
+        &self,
+        path: &Path,
+        image: &TiledRender<'_>,
+        format: RawPixelFormat,
+    ) -> Result<PathBuf, ApplicationError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| ApplicationError::Io(error.to_string()))?;
+        }
+        let file =
+            fs::File::create(path).map_err(|error| ApplicationError::Io(error.to_string()))?;
+        let mut writer = std::io::BufWriter::new(file);
+        for strip in image.strips() {
+            writer
+                .write_all(&format.pack(&strip))
+                .map_err(|error| ApplicationError::Io(error.to_string()))?;
+        }
+        writer
+            .flush()
+            .map_err(|error| ApplicationError::Io(error.to_string()))?;
+        drop(writer);
+
+        let sidecar = serde_json::json!({
+            "width": image.width(),
+            "height": image.height(),
+            "format": format.name(),
+            "channel_order": match format.channel_order {
+                ChannelOrder::Rgb => "rgb",
+                ChannelOrder::Bgr => "bgr",
+            },
+            "channels": format.channels(),
+            "bits_per_channel": 8,
+            "row_stride": image.width() as usize * format.channels(),
+        });
+        fs::write(raw_sidecar_path(path), sidecar.to_string())
+            .map_err(|error| ApplicationError::Io(error.to_string()))?;
+
+        path.canonicalize()
+            .map_err(|error| ApplicationError::Io(error.to_string()))
+    }
+
+    fn write_sidecar(&self, path: &Path, contents: &str) -> Result<PathBuf, ApplicationError> {
+        fs::write(path, contents).map_err(|error| ApplicationError::Io(error.to_string()))?;
+        path.canonicalize()
+            .map_err(|error| ApplicationError::Io(error.to_string()))
+    }
+}
+
+// The JPEG encoder pulls pixels through `GenericImageView` in 8-row block
+// bands, top to bottom, so caching the strip that holds the requested row
+// renders each strip once when strips are a multiple of 8 rows (the export
+// default is). Rows outside the cached strip just render their own strip.
+struct StripView<'a> {
+    image: &'a TiledRender<'a>,
+    strip: RefCell<(u32, DecodedBuffer)>,
+}
+
+impl<'a> StripView<'a> {
+    fn new(image: &'a TiledRender<'a>) -> Self {
+        Self {
+            image,
+            strip: RefCell::new((0, image.strip(0))),
+        }
+    }
+}
+
+impl GenericImageView for StripView<'_> {
+    type Pixel = Rgb<u8>;
+
+    fn dimensions(&self) -> (u32, u32) {
+        (self.image.width(), self.image.height())
+    }
+
+    fn bounds(&self) -> (u32, u32, u32, u32) {
+        (0, 0, self.image.width(), self.image.height())
+    }
+
+    fn get_pixel(&self, x: u32, y: u32) -> Rgb<u8> {
+        let mut strip = self.strip.borrow_mut();
+        let first_row = y / self.image.strip_rows() * self.image.strip_rows();
+        if strip.0 != first_row {
+            *strip = (first_row, self.image.strip(first_row));
+        }
+        let offset = ((y - first_row) as usize * strip.1.width as usize + x as usize) * 3;
+        Rgb([
+            strip.1.rgb[offset],
+            strip.1.rgb[offset + 1],
+            strip.1.rgb[offset + 2],
+        ])
+    }
+}
+
+// `out.raw` gets `out.raw.json`, so the sidecar never clobbers a file that
+// shares the stem.
+pub(crate) fn raw_sidecar_path(path: &Path) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".json");
+    PathBuf::from(sidecar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lite_room_domain::{apply_edits, EditParams};
+    use tempfile::TempDir;
+
+    #[test]
+    fn writes_jpeg_with_source_dimensions() {
+        let dir = TempDir::new().expect("tempdir");
+        let target = dir.path().join("merged").join("out.jpg");
+        let buffer = DecodedBuffer {
+            width: 6,
+            height: 4,
+            rgb: vec![128_u8; 6 * 4 * 3],
+        };
+
+        let written = FsImageEncoder
+            .write_jpeg(&target, &buffer, 90)
+            .expect("write jpeg");
+
+        assert!(written.is_absolute());
+        let decoded = image::open(&written).expect("decode written jpeg");
+        assert_eq!((decoded.width(), decoded.height()), (6, 4));
+    }
+
+    #[test]
+    fn raw_export_writes_packed_bytes_and_sidecar() {
+        let dir = TempDir::new().expect("tempdir");
+        let target = dir.path().join("dumps").join("pair.raw");
+        let buffer = DecodedBuffer {
+            width: 2,
+            height: 1,
+            rgb: vec![255, 0, 0, 0, 128, 255],
+        };
+
+        let bgr = RawPixelFormat::parse("bgr").expect("format");
+        let image = TiledRender::new(&buffer, &EditParams::default(), 1);
+        let written = FsImageEncoder
+            .write_raw(&target, &image, bgr)
+            .expect("write raw");
+        assert_eq!(
+            fs::read(&written).expect("read raw"),
+            vec![0, 0, 255, 255, 128, 0]
+        );
+        let sidecar: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("dumps/pair.raw.json")).expect("read sidecar"),
+        )
+        .expect("sidecar json");
+        assert_eq!(sidecar["width"], 2);
+        assert_eq!(sidecar["height"], 1);
+        assert_eq!(sidecar["format"], "bgr8");
+        assert_eq!(sidecar["row_stride"], 6);
+
+        let rgb = RawPixelFormat::parse("rgb").expect("format");
+        FsImageEncoder
+            .write_raw(&target, &image, rgb)
+            .expect("write raw");
+        assert_eq!(
+            fs::read(&target).expect("read raw"),
+            vec![255, 0, 0, 0, 128, 255]
+        );
+    }
+
+    #[test]
+    fn tiled_export_matches_whole_image_export_byte_for_byte() {
+        let dir = TempDir::new().expect("tempdir");
+        let (width, height) = (45_u32, 37_u32);
+        let source = DecodedBuffer {
+            width,
+            height,
+            rgb: (0..height)
+                .flat_map(|y| {
+                    (0..width)
+                        .flat_map(move |x| [(x * 5) as u8, (y * 6) as u8, ((x + y) * 3) as u8])
+                })
+                .collect(),
+        };
+        let params = EditParams::default()
+            .with_exposure(0.6)
+            .with_contrast(0.4)
+            .with_temperature(-0.5)
+            .with_shadows(0.3)
+            .with_vibrance(0.8);
+        let whole = apply_edits(&source, &params);
+
+        let whole_jpeg = FsImageEncoder
+            .write_jpeg(&dir.path().join("whole.jpg"), &whole, 90)
+            .expect("whole jpeg");
+        let whole_raw = FsImageEncoder
+            .write_raw(
+                &dir.path().join("whole.raw"),
+                &TiledRender::new(&whole, &EditParams::default(), height),
+                RawPixelFormat::parse("rgba").expect("format"),
+            )
+            .expect("whole raw");
+        for strip_rows in [8, 16, 5] {
+            let tiled = TiledRender::new(&source, &params, strip_rows);
+            let jpeg = FsImageEncoder
+                .write_jpeg_tiled(
+                    &dir.path().join(format!("tiled-{strip_rows}.jpg")),
+                    &tiled,
+                    90,
+                )
+                .expect("tiled jpeg");
+            let raw = FsImageEncoder
+                .write_raw(
+                    &dir.path().join(format!("tiled-{strip_rows}.raw")),
+                    &tiled,
+                    RawPixelFormat::parse("rgba").expect("format"),
+                )
+                .expect("tiled raw");
+
+            assert_eq!(
+                fs::read(&jpeg).expect("read tiled"),
+                fs::read(&whole_jpeg).expect("read whole"),
+                "jpeg with {strip_rows}-row strips"
+            );
+            assert_eq!(
+                fs::read(&raw).expect("read tiled"),
+                fs::read(&whole_raw).expect("read whole"),
+                "raw with {strip_rows}-row strips"
+            );
+        }
+    }
+}
@@ -1,7 +1,10 @@
 mod clock;
+mod encoder;
+mod hash;
 mod scanner;
 mod thumbs;
 
 pub use clock::SystemClock;
+pub use encoder::FsImageEncoder;
 pub use scanner::WalkdirFileScanner;
-pub use thumbs::FsThumbnailGenerator;
+pub use thumbs::{FsThumbnailGenerator, ThumbnailFormat, THUMBNAIL_SIZE};
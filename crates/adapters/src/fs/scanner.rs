@@ -1,56 +1,336 @@
-use std::path::Path;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::thread;
 
-use lite_room_application::{ApplicationError, FileScanSummary, FileScanner, ScannedFile};
+use image::codecs::gif::GifDecoder;
+use image::codecs::jpeg::JpegDecoder;
+use image::{AnimationDecoder, ImageDecoder};
+use lite_room_application::{
+    ApplicationError, CaptureMetadata, FileScanSummary, FileScanner, ScannedFile,
+};
 use lite_room_domain::{detect_image_kind, ImageKind};
 use walkdir::WalkDir;
 
+use crate::exif::read_capture_metadata;
+use crate::fs::hash::content_hash;
+
 #[derive(Debug, Default)]
 pub struct WalkdirFileScanner;
 
 impl FileScanner for WalkdirFileScanner {
-    fn scan_supported(&self, folder: &str) -> Result<FileScanSummary, ApplicationError> {
+    fn scan_supported(
+        &self,
+        folder: &str,
+        resume_after: Option<&Path>,
+        workers: usize,
+    ) -> Result<FileScanSummary, ApplicationError> {
         let folder_path = Path::new(folder);
-        if !folder_path.is_dir() {
+        if !folder_path.is_dir() && !folder_path.is_file() {
             return Err(ApplicationError::InvalidInput(format!(
-                "folder does not exist or is not a directory: {folder}"
+                "path does not exist or is not a file or directory: {folder}"
             )));
         }
 
-        let mut summary = FileScanSummary::default();
-
-        for entry in WalkDir::new(folder_path).into_iter().filter_map(Result::ok) {
+        let mut walked = Vec::new();
+        for entry in WalkDir::new(folder_path)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok)
+        {
             if !entry.file_type().is_file() {
                 continue;
             }
-
-            summary.scanned_files += 1;
-            let file_path = entry.path();
-            let image_kind = detect_image_kind(file_path);
-            if image_kind == ImageKind::Unsupported {
-                continue;
+            if let Some(after) = resume_after {
+                let walked = entry
+                    .path()
+                    .canonicalize()
+                    .map_err(|error| ApplicationError::Io(error.to_string()))?;
+                if walked.as_path() <= after {
+                    continue;
+                }
             }
 
-            let canonical = file_path
-                .canonicalize()
-                .map_err(|error| ApplicationError::Io(error.to_string()))?;
-            let metadata = file_path
-                .metadata()
-                .map_err(|error| ApplicationError::Io(error.to_string()))?;
-            let extension = file_path
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or_default()
-                .to_ascii_lowercase();
-
-            summary.supported_files += 1;
-            summary.files.push(ScannedFile {
-                canonical_path: canonical,
-                extension,
-                file_size: metadata.len(),
-                image_kind,
-            });
+            walked.push(entry.into_path());
+        }
+
+        let mut summary = FileScanSummary {
+            scanned_files: walked.len(),
+            ..FileScanSummary::default()
+        };
+        let scanned = self.scan_files(&walked, workers)?;
+        for (path, scanned) in walked.into_iter().zip(scanned) {
+            match scanned {
+                Some(file) => {
+                    summary.supported_files += 1;
+                    summary.files.push(file);
+                }
+                None => summary.unsupported.push(path),
+            }
         }
 
+        // Import assigns ids in this order and resume compares canonical paths,
+        // so the result is ordered by canonical path rather than by walk order.
+        summary
+            .files
+            .sort_by(|left, right| left.canonical_path.cmp(&right.canonical_path));
+        summary.unsupported.sort();
         Ok(summary)
     }
+
+    fn scan_file(&self, file_path: &Path) -> Result<Option<ScannedFile>, ApplicationError> {
+        let image_kind = detect_image_kind(file_path);
+        if image_kind == ImageKind::Unsupported {
+            return Ok(None);
+        }
+
+        let canonical = file_path
+            .canonicalize()
+            .map_err(|error| ApplicationError::Io(error.to_string()))?;
+        let metadata = file_path
+            .metadata()
+            .map_err(|error| ApplicationError::Io(error.to_string()))?;
+        let extension = file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let frame_count = match image_kind {
+            ImageKind::Gif => Some(gif_frame_count(file_path)?),
+            _ => None,
+        };
+
+        Ok(Some(ScannedFile {
+            canonical_path: canonical,
+            extension,
+            file_size: metadata.len(),
+            image_kind,
+            frame_count,
+            grayscale: image_kind == ImageKind::Jpeg && jpeg_is_grayscale(file_path),
+            capture: match image_kind {
+                ImageKind::Gif | ImageKind::Unsupported => CaptureMetadata::default(),
+                _ => read_capture_metadata(file_path),
+            },
+            content_hash: Some(content_hash(file_path)?),
+        }))
+    }
+
+    fn file_exists(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+}
+
+impl WalkdirFileScanner {
+    // Hashing and EXIF dominate a scan, so walked files are split into one
+    // contiguous run per worker. Results keep `paths` order and the first error
+    // in that order wins, as it would scanning one file at a time.
+    fn scan_files(
+        &self,
+        paths: &[PathBuf],
+        workers: usize,
+    ) -> Result<Vec<Option<ScannedFile>>, ApplicationError> {
+        if workers <= 1 || paths.len() <= 1 {
+            return paths.iter().map(|path| self.scan_file(path)).collect();
+        }
+        let run = paths.len().div_ceil(workers);
+        thread::scope(|scope| {
+            let handles: Vec<_> = paths
+                .chunks(run)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|path| self.scan_file(path))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                })
+                .collect();
+            let mut scanned = Vec::with_capacity(paths.len());
+            for handle in handles {
+                let chunk = handle.join().unwrap_or_else(|_| {
+                    Err(ApplicationError::Io("scan worker panicked".to_string()))
+                })?;
+                scanned.extend(chunk);
+            }
+            Ok(scanned)
+        })
+    }
+}
+
+// Only the header is read. A header that cannot be parsed counts as color here;
+// the decode step reports the real error when the thumbnail is built.
+fn jpeg_is_grayscale(path: &Path) -> bool {
+    File::open(path)
+        .ok()
+        .and_then(|file| JpegDecoder::new(BufReader::new(file)).ok())
+        .is_some_and(|decoder| !decoder.color_type().has_color())
+}
+
+fn gif_frame_count(path: &Path) -> Result<u32, ApplicationError> {
+    let file = File::open(path).map_err(|error| ApplicationError::Io(error.to_string()))?;
+    let decoder = GifDecoder::new(BufReader::new(file))
+        .map_err(|error| ApplicationError::Decode(error.to_string()))?;
+    let mut count = 0_u32;
+    for frame in decoder.into_frames() {
+        frame.map_err(|error| ApplicationError::Decode(error.to_string()))?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+pub(crate) fn write_test_gif(path: &Path, frame_colors: &[[u8; 3]]) {
+    use image::codecs::gif::GifEncoder;
+    use image::{Frame, ImageBuffer, Rgba};
+
+    let file = File::create(path).expect("create gif");
+    let mut encoder = GifEncoder::new(file);
+    let frames = frame_colors.iter().map(|[red, green, blue]| {
+        Frame::new(ImageBuffer::from_pixel(
+            32,
+            24,
+            Rgba([*red, *green, *blue, 255]),
+        ))
+    });
+    encoder.encode_frames(frames).expect("encode gif");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn scan_counts_gif_frames() {
+        let dir = TempDir::new().expect("tempdir");
+        write_test_gif(
+            &dir.path().join("clip.gif"),
+            &[[200, 40, 40], [40, 200, 40], [40, 40, 200]],
+        );
+
+        let summary = WalkdirFileScanner
+            .scan_supported(&dir.path().to_string_lossy(), None, 1)
+            .expect("scan");
+
+        assert_eq!(summary.supported_files, 1);
+        assert_eq!(summary.files[0].image_kind, ImageKind::Gif);
+        assert_eq!(summary.files[0].frame_count, Some(3));
+    }
+
+    #[test]
+    fn scan_accepts_png_and_tiff_files() {
+        let dir = TempDir::new().expect("tempdir");
+        for name in ["a.png", "b.TIF", "c.tiff", "d.bmp"] {
+            std::fs::write(dir.path().join(name), b"pixels").expect("write file");
+        }
+
+        let summary = WalkdirFileScanner
+            .scan_supported(&dir.path().to_string_lossy(), None, 1)
+            .expect("scan");
+
+        let kinds: Vec<_> = summary.files.iter().map(|file| file.image_kind).collect();
+        assert_eq!(
+            kinds,
+            vec![ImageKind::Png, ImageKind::Tiff, ImageKind::Tiff]
+        );
+        assert_eq!(summary.files[1].extension, "tif");
+        assert_eq!(summary.unsupported.len(), 1);
+    }
+
+    #[test]
+    fn scan_detects_grayscale_jpeg_from_header() {
+        let dir = TempDir::new().expect("tempdir");
+        let mono = dir.path().join("mono.jpg");
+        image::ImageBuffer::from_fn(16, 8, |x, _y| image::Luma([(x * 16) as u8]))
+            .save(&mono)
+            .expect("save grayscale jpeg");
+        let color = dir.path().join("color.jpg");
+        image::ImageBuffer::from_pixel(16, 8, image::Rgb([90_u8, 60, 30]))
+            .save(&color)
+            .expect("save color jpeg");
+
+        let scanned = |path: &Path| {
+            WalkdirFileScanner
+                .scan_file(path)
+                .expect("scan")
+                .expect("supported")
+        };
+        assert!(scanned(&mono).grayscale);
+        assert!(!scanned(&color).grayscale);
+
+        let decoded = lite_room_application::ImageDecoder::decode_for_preview(
+            &crate::ImageCrateDecoder::default(),
+            &mono,
+        )
+        .expect("decode");
+        assert!(decoded.grayscale);
+    }
+
+    #[test]
+    fn scan_returns_files_sorted_by_canonical_path() {
+        let dir = TempDir::new().expect("tempdir");
+        let nested = dir.path().join("b-roll");
+        std::fs::create_dir_all(&nested).expect("nested dir");
+        let created = [
+            dir.path().join("c.jpg"),
+            nested.join("z.jpg"),
+            dir.path().join("a.jpg"),
+            nested.join("m.jpg"),
+            dir.path().join("b.jpg"),
+        ];
+        for path in &created {
+            image::ImageBuffer::from_pixel(4, 4, image::Rgb([90_u8, 60, 30]))
+                .save(path)
+                .expect("save jpeg");
+        }
+
+        let summary = WalkdirFileScanner
+            .scan_supported(&dir.path().to_string_lossy(), None, 1)
+            .expect("scan");
+
+        let scanned: Vec<_> = summary
+            .files
+            .iter()
+            .map(|file| file.canonical_path.clone())
+            .collect();
+        let mut expected: Vec<_> = created
+            .iter()
+            .map(|path| path.canonicalize().expect("canonical"))
+            .collect();
+        expected.sort();
+        assert_eq!(scanned, expected);
+        assert_eq!(
+            scanned
+                .iter()
+                .map(|path| path.strip_prefix(dir.path().canonicalize().expect("root")))
+                .collect::<Result<Vec<_>, _>>()
+                .expect("inside root"),
+            ["a.jpg", "b-roll/m.jpg", "b-roll/z.jpg", "b.jpg", "c.jpg"].map(Path::new)
+        );
+    }
+
+    #[test]
+    fn scan_accepts_single_image_file() {
+        let dir = TempDir::new().expect("tempdir");
+        let photo = dir.path().join("single.jpg");
+        image::ImageBuffer::from_pixel(4, 4, image::Rgb([90_u8, 60, 30]))
+            .save(&photo)
+            .expect("save jpeg");
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, "not an image").expect("write notes");
+
+        let summary = WalkdirFileScanner
+            .scan_supported(&photo.to_string_lossy(), None, 1)
+            .expect("scan file");
+        assert_eq!(summary.scanned_files, 1);
+        assert_eq!(summary.supported_files, 1);
+        assert_eq!(summary.files[0].image_kind, ImageKind::Jpeg);
+
+        let unsupported = WalkdirFileScanner
+            .scan_supported(&notes.to_string_lossy(), None, 1)
+            .expect("scan unsupported file");
+        assert_eq!(unsupported.scanned_files, 1);
+        assert_eq!(unsupported.supported_files, 0);
+        assert_eq!(unsupported.unsupported, vec![notes]);
+    }
 }
@@ -1,12 +1,91 @@
 use std::fs;
+use std::io::{self, Cursor};
 use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
-use image::{io::Reader as ImageReader, ImageBuffer, ImageFormat, Rgb};
+use image::{io::Reader as ImageReader, DynamicImage, ImageBuffer, ImageFormat, Rgb};
 use lite_room_application::{ApplicationError, ThumbnailArtifact, ThumbnailGenerator};
 use lite_room_domain::{detect_image_kind, ImageId, ImageKind};
 
-#[derive(Debug, Default)]
-pub struct FsThumbnailGenerator;
+use crate::decode::{decode_within_limit, DEFAULT_MAX_DECODE_DIMENSION};
+use crate::heic::decode_heic;
+
+pub const THUMBNAIL_SIZE: u32 = 256;
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+const IO_ATTEMPTS: u32 = 4;
+const IO_RETRY_BACKOFF: Duration = Duration::from_millis(25);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThumbnailFormat {
+    #[default]
+    Jpeg,
+    Png,
+    WebP,
+}
+
+impl ThumbnailFormat {
+    pub fn parse(name: &str) -> Result<Self, ApplicationError> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            "png" => Ok(Self::Png),
+            "webp" => Ok(Self::WebP),
+            _ => Err(ApplicationError::InvalidInput(format!(
+                "thumbnail format must be jpeg, png or webp, got {name}"
+            ))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpeg",
+            Self::Png => "png",
+            Self::WebP => "webp",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::WebP => "webp",
+        }
+    }
+
+    fn image_format(self) -> ImageFormat {
+        match self {
+            Self::Jpeg => ImageFormat::Jpeg,
+            Self::Png => ImageFormat::Png,
+            Self::WebP => ImageFormat::WebP,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FsThumbnailGenerator {
+    max_decode_dimension: u32,
+    format: ThumbnailFormat,
+}
+
+impl FsThumbnailGenerator {
+    pub fn new(max_decode_dimension: u32) -> Self {
+        Self {
+            max_decode_dimension,
+            format: ThumbnailFormat::default(),
+        }
+    }
+
+    pub fn with_format(self, format: ThumbnailFormat) -> Self {
+        Self { format, ..self }
+    }
+}
+
+impl Default for FsThumbnailGenerator {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_DECODE_DIMENSION)
+    }
+}
 
 impl ThumbnailGenerator for FsThumbnailGenerator {
     fn ensure_thumbnail(
@@ -15,27 +94,70 @@ impl ThumbnailGenerator for FsThumbnailGenerator {
         cache_root: &str,
         image_id: ImageId,
     ) -> Result<ThumbnailArtifact, ApplicationError> {
-        let thumb_path = format!("{cache_root}/thumbs/{}.jpg", image_id.get());
+        let thumb_path = format!(
+            "{cache_root}/thumbs/{}.{}",
+            image_id.get(),
+            self.format.extension()
+        );
         let thumb_path_ref = Path::new(&thumb_path);
+        let format = self.format.image_format();
 
         let (width, height) = match detect_image_kind(source_path) {
-            ImageKind::Jpeg => ensure_jpeg_thumbnail(source_path, thumb_path_ref)?,
-            ImageKind::Raw | ImageKind::Unsupported => {
-                ensure_placeholder_thumbnail(thumb_path_ref)?
+            ImageKind::Jpeg | ImageKind::Gif | ImageKind::Png | ImageKind::Tiff => {
+                ensure_decoded_thumbnail(thumb_path_ref, format, || {
+                    decode_within_limit(source_path, self.max_decode_dimension)
+                })?
             }
+            ImageKind::Heic if cfg!(feature = "heic") => {
+                ensure_decoded_thumbnail(thumb_path_ref, format, || {
+                    decode_heic(source_path, self.max_decode_dimension).map(DynamicImage::ImageRgb8)
+                })?
+            }
+            _ => ensure_placeholder_thumbnail(thumb_path_ref, format)?,
         };
 
+        let checksum = self.thumbnail_checksum(thumb_path_ref)?;
         Ok(ThumbnailArtifact {
             file_path: thumb_path,
             width,
             height,
+            checksum,
         })
     }
+
+    fn thumbnail_checksum(
+        &self,
+        thumbnail_path: &Path,
+    ) -> Result<Option<String>, ApplicationError> {
+        match fs::read(thumbnail_path) {
+            Ok(bytes) => Ok(Some(checksum_bytes(&bytes))),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(ApplicationError::Io(error.to_string())),
+        }
+    }
+
+    fn discard_thumbnail(&self, thumbnail_path: &Path) -> Result<(), ApplicationError> {
+        match fs::remove_file(thumbnail_path) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(ApplicationError::Io(error.to_string())),
+        }
+    }
 }
 
-fn ensure_jpeg_thumbnail(
-    source_path: &Path,
+// FNV-1a catches truncated writes and edited bytes; it is not a defence against
+// someone who can also rewrite the catalog.
+fn checksum_bytes(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    });
+    format!("fnv1a64:{hash:016x}")
+}
+
+fn ensure_decoded_thumbnail(
     thumb_path: &Path,
+    format: ImageFormat,
+    decode_source: impl FnOnce() -> Result<DynamicImage, ApplicationError>,
 ) -> Result<(u32, u32), ApplicationError> {
     if thumb_path.exists() {
         let existing = ImageReader::open(thumb_path)
@@ -47,30 +169,17 @@ fn ensure_jpeg_thumbnail(
         return Ok((existing.width(), existing.height()));
     }
 
-    let image = ImageReader::open(source_path)
-        .map_err(|error| ApplicationError::Io(error.to_string()))?
-        .with_guessed_format()
-        .map_err(|error| ApplicationError::Decode(error.to_string()))?
-        .decode()
-        .map_err(|error| ApplicationError::Decode(error.to_string()))?;
-
-    let thumb = image.thumbnail(256, 256);
-    if let Some(parent) = thumb_path.parent() {
-        fs::create_dir_all(parent).map_err(|error| ApplicationError::Io(error.to_string()))?;
-    }
-
-    thumb
-        .save_with_format(thumb_path, ImageFormat::Jpeg)
-        .map_err(|error| ApplicationError::Io(error.to_string()))?;
+    let image = decode_source()?;
+    let thumb = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    write_thumbnail(thumb_path, &thumb, format)?;
 
     Ok((thumb.width(), thumb.height()))
 }
 
-fn ensure_placeholder_thumbnail(thumb_path: &Path) -> Result<(u32, u32), ApplicationError> {
-    if let Some(parent) = thumb_path.parent() {
-        fs::create_dir_all(parent).map_err(|error| ApplicationError::Io(error.to_string()))?;
-    }
-
+fn ensure_placeholder_thumbnail(
+    thumb_path: &Path,
+    format: ImageFormat,
+) -> Result<(u32, u32), ApplicationError> {
     if thumb_path.exists() {
         let existing = ImageReader::open(thumb_path)
             .map_err(|error| ApplicationError::Io(error.to_string()))?
@@ -81,18 +190,64 @@ fn ensure_placeholder_thumbnail(thumb_path: &Path) -> Result<(u32, u32), Applica
         return Ok((existing.width(), existing.height()));
     }
 
-    let placeholder = ImageBuffer::from_fn(256, 256, |_x, _y| Rgb([48_u8, 48_u8, 48_u8]));
-    placeholder
-        .save_with_format(thumb_path, ImageFormat::Jpeg)
+    let placeholder = ImageBuffer::from_fn(THUMBNAIL_SIZE, THUMBNAIL_SIZE, |_x, _y| {
+        Rgb([48_u8, 48_u8, 48_u8])
+    });
+    write_thumbnail(thumb_path, &DynamicImage::ImageRgb8(placeholder), format)?;
+
+    Ok((THUMBNAIL_SIZE, THUMBNAIL_SIZE))
+}
+
+// The thumbnail is encoded in memory once, so only the filesystem calls are
+// retried.
+fn write_thumbnail(
+    thumb_path: &Path,
+    thumb: &DynamicImage,
+    format: ImageFormat,
+) -> Result<(), ApplicationError> {
+    let mut encoded = Cursor::new(Vec::new());
+    thumb
+        .write_to(&mut encoded, format)
         .map_err(|error| ApplicationError::Io(error.to_string()))?;
+    if let Some(parent) = thumb_path.parent() {
+        retry_transient_io(|| fs::create_dir_all(parent))
+            .map_err(|error| ApplicationError::Io(error.to_string()))?;
+    }
+    retry_transient_io(|| fs::write(thumb_path, encoded.get_ref()))
+        .map_err(|error| ApplicationError::Io(error.to_string()))
+}
 
-    Ok((256, 256))
+// Network and removable drives report short-lived failures as EAGAIN/EINTR/EBUSY
+// or timeouts; anything else (permission denied, disk full) fails on the first try.
+fn retry_transient_io<T>(mut operation: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut backoff = IO_RETRY_BACKOFF;
+    for _ in 1..IO_ATTEMPTS {
+        match operation() {
+            Err(error) if is_transient_io_error(&error) => {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+    operation()
+}
+
+fn is_transient_io_error(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::ResourceBusy
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use image::{ImageBuffer, Rgb};
+    use lite_room_domain::ImageId;
     use tempfile::TempDir;
 
     #[test]
@@ -102,7 +257,7 @@ mod tests {
         let img = ImageBuffer::from_fn(500, 300, |_x, _y| Rgb([10_u8, 20_u8, 30_u8]));
         img.save(&src).expect("save");
 
-        let generator = FsThumbnailGenerator;
+        let generator = FsThumbnailGenerator::default();
         let out = generator
             .ensure_thumbnail(
                 &src,
@@ -114,4 +269,130 @@ mod tests {
         assert_eq!(out.width, 256);
         assert_eq!(out.height, 154);
     }
+
+    #[test]
+    fn webp_and_png_generators_write_their_format_within_thumbnail_bounds() {
+        let dir = TempDir::new().expect("tempdir");
+        let src = dir.path().join("wide.jpg");
+        ImageBuffer::from_fn(600, 400, |x, y| {
+            Rgb([(x % 256) as u8, (y % 256) as u8, 90_u8])
+        })
+        .save(&src)
+        .expect("save");
+
+        for (name, expected) in [("webp", ImageFormat::WebP), ("png", ImageFormat::Png)] {
+            let generator = FsThumbnailGenerator::default()
+                .with_format(ThumbnailFormat::parse(name).expect("thumbnail format"));
+            let out = generator
+                .ensure_thumbnail(
+                    &src,
+                    &dir.path().to_string_lossy(),
+                    ImageId::new(3).expect("id"),
+                )
+                .expect("thumbnail");
+
+            assert!(
+                out.file_path.ends_with(&format!("/thumbs/3.{name}")),
+                "{}",
+                out.file_path
+            );
+            let reader = ImageReader::open(&out.file_path)
+                .expect("open thumbnail")
+                .with_guessed_format()
+                .expect("sniff format");
+            assert_eq!(reader.format(), Some(expected));
+            let decoded = reader.decode().expect("decode thumbnail");
+            assert_eq!((decoded.width(), decoded.height()), (256, 171));
+            assert_eq!((out.width, out.height), (256, 171));
+        }
+        assert!(matches!(
+            ThumbnailFormat::parse("avif"),
+            Err(ApplicationError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn png_and_tiff_sources_get_decoded_thumbnails() {
+        let dir = TempDir::new().expect("tempdir");
+        for (name, format) in [
+            ("scan.png", ImageFormat::Png),
+            ("scan.tif", ImageFormat::Tiff),
+        ] {
+            let src = dir.path().join(name);
+            ImageBuffer::from_fn(512, 128, |x, _y| Rgb([(x % 256) as u8, 60, 200]))
+                .save_with_format(&src, format)
+                .expect("save source");
+
+            let out = FsThumbnailGenerator::default()
+                .ensure_thumbnail(
+                    &src,
+                    &dir.path()
+                        .join(name)
+                        .with_extension("cache")
+                        .to_string_lossy(),
+                    ImageId::new(1).expect("id"),
+                )
+                .expect("thumbnail");
+            assert_eq!((out.width, out.height), (256, 64), "{name}");
+        }
+    }
+
+    #[test]
+    fn transient_write_errors_are_retried_and_permanent_ones_are_not() {
+        let dir = TempDir::new().expect("tempdir");
+        let target = dir.path().join("thumb.jpg");
+        let mut attempts = 0;
+        retry_transient_io(|| {
+            attempts += 1;
+            if attempts < 3 {
+                return Err(io::Error::from(io::ErrorKind::WouldBlock));
+            }
+            fs::write(&target, b"jpeg")
+        })
+        .expect("third attempt succeeds");
+        assert_eq!(attempts, 3);
+        assert!(target.exists());
+
+        let mut attempts = 0;
+        let denied = retry_transient_io(|| {
+            attempts += 1;
+            Err::<(), _>(io::Error::from(io::ErrorKind::PermissionDenied))
+        });
+        assert_eq!(
+            denied.map_err(|error| error.kind()),
+            Err(io::ErrorKind::PermissionDenied)
+        );
+        assert_eq!(attempts, 1);
+
+        let mut attempts = 0;
+        let busy = retry_transient_io(|| {
+            attempts += 1;
+            Err::<(), _>(io::Error::from(io::ErrorKind::ResourceBusy))
+        });
+        assert!(busy.is_err());
+        assert_eq!(attempts, IO_ATTEMPTS);
+    }
+
+    #[test]
+    fn gif_thumbnail_uses_first_frame() {
+        let dir = TempDir::new().expect("tempdir");
+        let src = dir.path().join("clip.gif");
+        crate::fs::scanner::write_test_gif(&src, &[[200, 40, 40], [40, 40, 200]]);
+
+        let out = FsThumbnailGenerator::default()
+            .ensure_thumbnail(
+                &src,
+                &dir.path().to_string_lossy(),
+                ImageId::new(7).expect("id"),
+            )
+            .expect("thumbnail");
+
+        let thumb = ImageReader::open(&out.file_path)
+            .expect("open thumb")
+            .decode()
+            .expect("decode thumb")
+            .to_rgb8();
+        let [red, _, blue] = thumb.get_pixel(thumb.width() / 2, thumb.height() / 2).0;
+        assert!(red > 150 && blue < 100, "expected first (red) frame");
+    }
 }
@@ -0,0 +1,115 @@
+use std::path::Path;
+
+use image::RgbImage;
+use lite_room_application::ApplicationError;
+
+#[cfg(feature = "heic")]
+pub(crate) fn decode_heic(path: &Path, max_dimension: u32) -> Result<RgbImage, ApplicationError> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| ApplicationError::Decode(format!("HEIC path is not UTF-8: {:?}", path)))?;
+    let context = HeifContext::read_from_file(path_str)
+        .map_err(|error| ApplicationError::Decode(error.to_string()))?;
+    let handle = context
+        .primary_image_handle()
+        .map_err(|error| ApplicationError::Decode(error.to_string()))?;
+    crate::decode::ensure_within_decode_limit(
+        path,
+        handle.width(),
+        handle.height(),
+        max_dimension,
+    )?;
+    let image = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
+        .map_err(|error| ApplicationError::Decode(error.to_string()))?;
+
+    let planes = image.planes();
+    let plane = planes.interleaved.ok_or_else(|| {
+        ApplicationError::Decode(format!("HEIC decode produced no RGB plane: {:?}", path))
+    })?;
+    let row_bytes = plane.width as usize * 3;
+    let mut rgb = Vec::with_capacity(row_bytes * plane.height as usize);
+    for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        rgb.extend_from_slice(&row[..row_bytes]);
+    }
+
+    RgbImage::from_raw(plane.width, plane.height, rgb).ok_or_else(|| {
+        ApplicationError::Decode(format!("HEIC plane has unexpected size: {:?}", path))
+    })
+}
+
+#[cfg(not(feature = "heic"))]
+pub(crate) fn decode_heic(path: &Path, _max_dimension: u32) -> Result<RgbImage, ApplicationError> {
+    Err(ApplicationError::Decode(format!(
+        "HEIC decode requires the heic feature: {:?}",
+        path
+    )))
+}
+
+#[cfg(all(test, feature = "heic"))]
+mod tests {
+    use libheif_rs::{
+        Channel, ColorSpace, CompressionFormat, EncoderQuality, HeifContext, Image, LibHeif,
+        RgbChroma,
+    };
+    use lite_room_application::{FileScanner, ImageDecoder};
+    use lite_room_domain::ImageKind;
+    use tempfile::TempDir;
+
+    use crate::{ImageCrateDecoder, WalkdirFileScanner};
+
+    fn write_heic(path: &std::path::Path, width: u32, height: u32) {
+        let mut image = Image::new(width, height, ColorSpace::Rgb(RgbChroma::Rgb)).expect("image");
+        image
+            .create_plane(Channel::Interleaved, width, height, 8)
+            .expect("plane");
+        {
+            let planes = image.planes_mut();
+            let plane = planes.interleaved.expect("interleaved plane");
+            for y in 0..height as usize {
+                for x in 0..width as usize {
+                    let offset = y * plane.stride + x * 3;
+                    plane.data[offset] = (x * 4) as u8;
+                    plane.data[offset + 1] = (y * 4) as u8;
+                    plane.data[offset + 2] = 128;
+                }
+            }
+        }
+
+        let lib_heif = LibHeif::new();
+        let mut encoder = lib_heif
+            .encoder_for_format(CompressionFormat::Hevc)
+            .expect("hevc encoder");
+        encoder
+            .set_quality(EncoderQuality::Lossy(80))
+            .expect("quality");
+        let mut context = HeifContext::new().expect("context");
+        context
+            .encode_image(&image, &mut encoder, None)
+            .expect("encode");
+        context
+            .write_to_file(path.to_str().expect("utf-8 path"))
+            .expect("write heic");
+    }
+
+    #[test]
+    fn heic_file_imports_and_decodes_to_dimensions() {
+        let dir = TempDir::new().expect("tempdir");
+        let source = dir.path().join("phone.heic");
+        write_heic(&source, 64, 48);
+
+        let scan = WalkdirFileScanner
+            .scan_supported(&dir.path().to_string_lossy(), None, 1)
+            .expect("scan");
+        assert_eq!(scan.supported_files, 1);
+        assert_eq!(scan.files[0].image_kind, ImageKind::Heic);
+
+        let decoded = ImageCrateDecoder::default()
+            .decode_for_preview(&scan.files[0].canonical_path)
+            .expect("decode heic");
+        assert_eq!(decoded.kind, ImageKind::Heic);
+        assert_eq!((decoded.width, decoded.height), (64, 48));
+    }
+}
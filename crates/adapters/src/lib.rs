@@ -1,37 +1,76 @@
 pub mod fs;
+mod decode;
+mod exif;
+mod heic;
 pub mod migrations;
 pub mod presenters;
 pub mod preview;
+#[cfg(test)]
+mod service_integration;
 pub mod sqlite;
-
-pub use fs::{FsThumbnailGenerator, SystemClock, WalkdirFileScanner};
-pub use presenters::{present_decoded, present_edit_params, present_image_row};
-pub use preview::BackgroundPreviewPipeline;
+#[cfg(test)]
+mod test_support;
+pub use decode::DEFAULT_MAX_DECODE_DIMENSION;
+pub use fs::{
+    FsImageEncoder, FsThumbnailGenerator, SystemClock, ThumbnailFormat, WalkdirFileScanner,
+    THUMBNAIL_SIZE,
+};
+pub use presenters::{
+    present_caption, present_caption_json, present_decoded, present_edit_params,
+    present_edit_params_json, present_error_json, present_image_row, present_images_json,
+    present_import_failure, present_import_report_json, present_preset,
+};
+pub use preview::{
+    BackgroundPreviewPipeline, CpuPreviewRenderer, PreviewRenderer, RenderedPreview,
+    DEFAULT_PREVIEW_CACHE_BYTES, DEFAULT_PREVIEW_MAX_PIXELS,
+};
 pub use sqlite::SqliteCatalogRepository;
 
+use decode::{decode_scaled_within_limit, decode_within_limit};
 use lite_room_application::ApplicationError;
 use lite_room_application::ImageDecoder;
-use lite_room_domain::{detect_image_kind, DecodedImage, ImageKind};
+use lite_room_domain::{detect_image_kind, DecodedBuffer, DecodedImage, ImageKind};
 use std::path::Path;
 
-#[derive(Debug, Default)]
-pub struct ImageCrateDecoder;
+#[derive(Debug, Clone, Copy)]
+pub struct ImageCrateDecoder {
+    max_decode_dimension: u32,
+}
+
+impl ImageCrateDecoder {
+    pub fn new(max_decode_dimension: u32) -> Self {
+        Self {
+            max_decode_dimension,
+        }
+    }
+}
+
+impl Default for ImageCrateDecoder {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_DECODE_DIMENSION)
+    }
+}
 
 impl ImageDecoder for ImageCrateDecoder {
     fn decode_for_preview(&self, path: &Path) -> Result<DecodedImage, ApplicationError> {
         match detect_image_kind(path) {
-            ImageKind::Jpeg => {
-                let image = image::io::Reader::open(path)
-                    .map_err(|error| ApplicationError::Decode(error.to_string()))?
-                    .with_guessed_format()
-                    .map_err(|error| ApplicationError::Decode(error.to_string()))?
-                    .decode()
-                    .map_err(|error| ApplicationError::Decode(error.to_string()))?;
+            kind @ (ImageKind::Jpeg | ImageKind::Gif | ImageKind::Png | ImageKind::Tiff) => {
+                let image = decode_within_limit(path, self.max_decode_dimension)?;
 
                 Ok(DecodedImage {
                     width: image.width(),
                     height: image.height(),
-                    kind: ImageKind::Jpeg,
+                    kind,
+                    grayscale: !image.color().has_color(),
+                })
+            }
+            ImageKind::Heic => {
+                let image = heic::decode_heic(path, self.max_decode_dimension)?;
+                Ok(DecodedImage {
+                    width: image.width(),
+                    height: image.height(),
+                    kind: ImageKind::Heic,
+                    grayscale: false,
                 })
             }
             ImageKind::Raw => Err(ApplicationError::Decode(format!(
@@ -44,4 +83,104 @@ impl ImageDecoder for ImageCrateDecoder {
             ))),
         }
     }
+
+    fn decode_full(&self, path: &Path) -> Result<DecodedBuffer, ApplicationError> {
+        match detect_image_kind(path) {
+            ImageKind::Jpeg | ImageKind::Gif | ImageKind::Png | ImageKind::Tiff => {
+                let image = decode_within_limit(path, self.max_decode_dimension)?.to_rgb8();
+
+                Ok(DecodedBuffer {
+                    width: image.width(),
+                    height: image.height(),
+                    rgb: image.into_raw(),
+                })
+            }
+            ImageKind::Heic => {
+                let image = heic::decode_heic(path, self.max_decode_dimension)?;
+                Ok(DecodedBuffer {
+                    width: image.width(),
+                    height: image.height(),
+                    rgb: image.into_raw(),
+                })
+            }
+            ImageKind::Raw => Err(ApplicationError::Decode(format!(
+                "RAW decode not implemented yet for {:?}",
+                path
+            ))),
+            ImageKind::Unsupported => Err(ApplicationError::Decode(format!(
+                "unsupported image format: {:?}",
+                path
+            ))),
+        }
+    }
+
+    fn decode_downsampled(
+        &self,
+        path: &Path,
+        max_side: u32,
+    ) -> Result<DecodedBuffer, ApplicationError> {
+        let image = match detect_image_kind(path) {
+            ImageKind::Jpeg | ImageKind::Gif | ImageKind::Png | ImageKind::Tiff => {
+                decode_scaled_within_limit(path, max_side, max_side, self.max_decode_dimension)?
+            }
+            ImageKind::Heic => {
+                image::DynamicImage::from(heic::decode_heic(path, self.max_decode_dimension)?)
+            }
+            ImageKind::Raw => {
+                return Err(ApplicationError::Decode(format!(
+                    "RAW decode not implemented yet for {:?}",
+                    path
+                )))
+            }
+            ImageKind::Unsupported => {
+                return Err(ApplicationError::Decode(format!(
+                    "unsupported image format: {:?}",
+                    path
+                )))
+            }
+        };
+        let max_side = max_side.max(1);
+        let image = if image.width() > max_side || image.height() > max_side {
+            image.thumbnail(max_side, max_side)
+        } else {
+            image
+        }
+        .to_rgb8();
+        Ok(DecodedBuffer {
+            width: image.width(),
+            height: image.height(),
+            rgb: image.into_raw(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, ImageFormat, Rgb};
+    use tempfile::TempDir;
+
+    #[test]
+    fn png_and_tiff_decode_with_their_kind_and_dimensions() {
+        let dir = TempDir::new().expect("tempdir");
+        let decoder = ImageCrateDecoder::default();
+        for (name, format, kind) in [
+            ("scan.png", ImageFormat::Png, ImageKind::Png),
+            ("scan.tiff", ImageFormat::Tiff, ImageKind::Tiff),
+        ] {
+            let path = dir.path().join(name);
+            ImageBuffer::from_pixel(40, 30, Rgb([10_u8, 120, 230]))
+                .save_with_format(&path, format)
+                .expect("save source");
+
+            let decoded = decoder.decode_for_preview(&path).expect("decode preview");
+            assert_eq!(decoded.kind, kind);
+            assert_eq!((decoded.width, decoded.height), (40, 30));
+            assert!(!decoded.grayscale);
+
+            let full = decoder.decode_full(&path).expect("decode full");
+            assert_eq!((full.width, full.height), (40, 30));
+            assert_eq!(&full.rgb[..3], &[10, 120, 230]);
+        }
+    }
 }
@@ -0,0 +1,6 @@
+ALTER TABLE images ADD COLUMN sort_index INTEGER NOT NULL DEFAULT 0;
+
+UPDATE images
+SET sort_index = (SELECT COUNT(*) FROM images AS earlier WHERE earlier.id < images.id);
+
+CREATE INDEX IF NOT EXISTS idx_images_sort_index ON images(sort_index);
@@ -0,0 +1,4 @@
+ALTER TABLE images ADD COLUMN lens_model TEXT;
+ALTER TABLE images ADD COLUMN focal_length REAL;
+
+CREATE INDEX IF NOT EXISTS idx_images_focal_length ON images(focal_length);
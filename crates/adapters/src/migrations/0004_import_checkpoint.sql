@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS import_checkpoint (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  folder TEXT NOT NULL,
+  last_path TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
@@ -0,0 +1,3 @@
+ALTER TABLE images ADD COLUMN stack_id INTEGER;
+ALTER TABLE images ADD COLUMN is_stack_pick INTEGER NOT NULL DEFAULT 0;
+CREATE INDEX IF NOT EXISTS idx_images_stack_id ON images(stack_id);
@@ -0,0 +1 @@
+ALTER TABLE thumbnails ADD COLUMN checksum TEXT;
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS import_failures (
+  file_path TEXT PRIMARY KEY,
+  reason TEXT NOT NULL,
+  failed_at TEXT NOT NULL
+);
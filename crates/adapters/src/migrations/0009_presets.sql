@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS presets (
+  name TEXT PRIMARY KEY,
+  edit_params_json TEXT NOT NULL
+);
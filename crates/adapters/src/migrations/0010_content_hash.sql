@@ -0,0 +1,3 @@
+ALTER TABLE images ADD COLUMN content_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_images_content_hash ON images(content_hash);
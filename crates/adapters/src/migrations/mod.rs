@@ -1 +1,26 @@
-pub const MIGRATIONS: &[&str] = &[include_str!("0001_initial.sql")];
+// (name, sql) in apply order; the 1-based position is the schema version
+// recorded in `schema_migrations`, so entries are only ever appended.
+pub const MIGRATIONS: &[(&str, &str)] = &[
+    ("0001_initial", include_str!("0001_initial.sql")),
+    ("0002_sort_index", include_str!("0002_sort_index.sql")),
+    (
+        "0003_lens_focal_length",
+        include_str!("0003_lens_focal_length.sql"),
+    ),
+    (
+        "0004_import_checkpoint",
+        include_str!("0004_import_checkpoint.sql"),
+    ),
+    ("0005_caption", include_str!("0005_caption.sql")),
+    ("0006_stacks", include_str!("0006_stacks.sql")),
+    (
+        "0007_thumbnail_checksum",
+        include_str!("0007_thumbnail_checksum.sql"),
+    ),
+    (
+        "0008_import_failures",
+        include_str!("0008_import_failures.sql"),
+    ),
+    ("0009_presets", include_str!("0009_presets.sql")),
+    ("0010_content_hash", include_str!("0010_content_hash.sql")),
+];
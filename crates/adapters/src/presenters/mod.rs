@@ -1,4 +1,5 @@
-use lite_room_domain::{DecodedImage, EditParams, ImageRecord};
+use lite_room_application::{EditPreset, ImportFailure};
+use lite_room_domain::{DecodedImage, EditParams, ImageRecord, ImportReport};
 
 pub fn present_image_row(image: &ImageRecord) -> String {
     format!(
@@ -10,26 +11,120 @@ pub fn present_image_row(image: &ImageRecord) -> String {
     )
 }
 
+pub fn present_import_failure(failure: &ImportFailure) -> String {
+    format!(
+        "{}\t{}\t{}",
+        failure.failed_at, failure.file_path, failure.reason
+    )
+}
+
 pub fn present_decoded(image_id: i64, decoded: &DecodedImage) -> String {
     format!(
-        "opened image {} (kind={:?}, {}x{})",
-        image_id, decoded.kind, decoded.width, decoded.height
+        "opened image {} (kind={:?}, {}x{}{})",
+        image_id,
+        decoded.kind,
+        decoded.width,
+        decoded.height,
+        if decoded.grayscale { ", grayscale" } else { "" }
     )
 }
 
 pub fn present_edit_params(image_id: i64, params: &EditParams) -> String {
     format!(
-        "image {} edit exposure={} contrast={} temperature={} tint={} highlights={} shadows={}",
+        "image {} edit exposure={} contrast={} temperature={} tint={} highlights={} shadows={} saturation={} vibrance={} grain={}",
         image_id,
         params.exposure,
         params.contrast,
         params.temperature,
         params.tint,
         params.highlights,
-        params.shadows
+        params.shadows,
+        params.saturation,
+        params.vibrance,
+        params.grain
+    )
+}
+
+pub fn present_images_json(images: &[ImageRecord]) -> String {
+    serde_json::json!({ "images": images }).to_string()
+}
+
+pub fn present_edit_params_json(image_id: i64, params: &EditParams) -> String {
+    serde_json::json!({
+        "image_id": image_id,
+        "edit": params,
+    })
+    .to_string()
+}
+
+pub fn present_preset(preset: &EditPreset) -> String {
+    let params = &preset.params;
+    format!(
+        "{}\texposure={} contrast={} temperature={} tint={} highlights={} shadows={} saturation={} vibrance={} grain={}",
+        preset.name,
+        params.exposure,
+        params.contrast,
+        params.temperature,
+        params.tint,
+        params.highlights,
+        params.shadows,
+        params.saturation,
+        params.vibrance,
+        params.grain
     )
 }
 
+pub fn present_caption(image_id: i64, caption: Option<&str>) -> String {
+    match caption {
+        Some(caption) => format!("image {image_id} caption: {caption}"),
+        None => format!("image {image_id} has no caption"),
+    }
+}
+
+pub fn present_caption_json(image_id: i64, caption: Option<&str>) -> String {
+    serde_json::json!({
+        "image_id": image_id,
+        "caption": caption,
+    })
+    .to_string()
+}
+
+pub fn present_import_report_json(
+    folder: &str,
+    finished_at: &str,
+    report: &ImportReport,
+) -> String {
+    serde_json::json!({
+        "folder": folder,
+        "finished_at": finished_at,
+        "scanned_files": report.scanned_files,
+        "supported_files": report.supported_files,
+        "newly_imported": report.newly_imported,
+        "skipped_duplicates": report.skipped_duplicates,
+        "failed": report.failures.len(),
+        "imported": report.imported,
+        "failures": report
+            .failures
+            .iter()
+            .map(|failure| serde_json::json!({
+                "file_path": failure.file_path,
+                "reason": failure.reason,
+            }))
+            .collect::<Vec<_>>(),
+    })
+    .to_string()
+}
+
+pub fn present_error_json(code: &str, message: &str) -> String {
+    serde_json::json!({
+        "error": {
+            "code": code,
+            "message": message,
+        }
+    })
+    .to_string()
+}
+
 fn image_kind_from_path(path: &str) -> &'static str {
     use std::path::Path;
     match Path::new(path)
@@ -38,7 +133,54 @@ fn image_kind_from_path(path: &str) -> &'static str {
         .map(|ext| ext.to_ascii_lowercase())
     {
         Some(ext) if ext == "jpg" || ext == "jpeg" => "JPEG",
+        Some(ext) if ext == "gif" => "GIF",
+        Some(ext) if ext == "png" => "PNG",
+        Some(ext) if ext == "tif" || ext == "tiff" => "TIFF",
+        Some(ext) if ext == "heic" || ext == "heif" => "HEIC",
         Some(ext) if ext == "cr2" || ext == "nef" || ext == "arw" || ext == "dng" => "RAW",
         _ => "UNKNOWN",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lite_room_domain::ImageId;
+
+    #[test]
+    fn json_presenters_parse_back_into_records_and_edits() {
+        let image = ImageRecord {
+            id: ImageId::new(7).expect("id"),
+            file_path: "/photos/a.jpg".to_string(),
+            import_date: "1700000000".to_string(),
+            capture_date: Some("2024-05-06T07:08:09".to_string()),
+            rating: 4,
+            flag: 1,
+            metadata_json: "{}".to_string(),
+            caption: Some("Harbour at dawn".to_string()),
+        };
+        let listed: serde_json::Value =
+            serde_json::from_str(&present_images_json(std::slice::from_ref(&image)))
+                .expect("list json");
+        assert_eq!(
+            listed["images"],
+            serde_json::json!([{
+                "id": 7,
+                "file_path": "/photos/a.jpg",
+                "import_date": "1700000000",
+                "capture_date": "2024-05-06T07:08:09",
+                "rating": 4,
+                "flag": 1,
+                "metadata_json": "{}",
+                "caption": "Harbour at dawn",
+            }])
+        );
+
+        let params = EditParams::default().with_exposure(0.75).with_tint(-1.0);
+        let shown: serde_json::Value =
+            serde_json::from_str(&present_edit_params_json(7, &params)).expect("edit json");
+        assert_eq!(shown["image_id"], 7);
+        let parsed: EditParams = serde_json::from_value(shown["edit"].clone()).expect("params");
+        assert_eq!(parsed, params);
+    }
+}
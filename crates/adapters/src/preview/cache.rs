@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+
+use lite_room_domain::{ImageId, PreviewRequest};
+use serde_json::{json, Number, Value};
+
+use super::RenderedPreview;
+
+pub const DEFAULT_PREVIEW_CACHE_BYTES: usize = 64 * 1024 * 1024;
+// Slider values this close render the same for practical purposes, so
+// revisiting one after a drag still hits.
+const PARAM_SCALE: f64 = 1_000.0;
+
+// Everything that changes a rendered frame except the decoded pixels, which
+// follow from the source path. Float settings are rounded to PARAM_SCALE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RenderKey {
+    image_id: ImageId,
+    source_path: String,
+    target: (u32, u32),
+    settings: String,
+}
+
+impl RenderKey {
+    pub(super) fn new(request: &PreviewRequest) -> Self {
+        let settings = json!({
+            "params": request.params,
+            "source_region": request
+                .source_region
+                .map(|region| [region.left, region.top, region.width, region.height]),
+            "view_region": request
+                .view_region
+                .map(|view| [view.center_x, view.center_y, view.zoom]),
+            "comparison": request.comparison.map(|mode| format!("{mode:?}")),
+            "split_fraction": request.split_fraction,
+        });
+        Self {
+            image_id: request.image_id,
+            source_path: request.source_path.clone(),
+            target: (request.target_width, request.target_height),
+            settings: quantized(settings).to_string(),
+        }
+    }
+}
+
+fn quantized(value: Value) -> Value {
+    match value {
+        Value::Number(number) if !number.is_i64() && !number.is_u64() => number
+            .as_f64()
+            .and_then(|float| Number::from_f64((float * PARAM_SCALE).round() / PARAM_SCALE + 0.0))
+            .map_or(Value::Number(number), Value::Number),
+        Value::Array(values) => Value::Array(values.into_iter().map(quantized).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, quantized(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+// Rendered frames, evicting the least recently used once the byte budget is
+// exceeded. A budget of zero turns the cache off.
+#[derive(Debug)]
+pub(super) struct RenderCache {
+    budget_bytes: usize,
+    used_bytes: usize,
+    entries: VecDeque<(RenderKey, RenderedPreview)>,
+}
+
+impl RenderCache {
+    pub(super) fn new(budget_bytes: usize) -> Self {
+        Self {
+            budget_bytes,
+            used_bytes: 0,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub(super) fn get(&mut self, key: &RenderKey) -> Option<RenderedPreview> {
+        let position = self.entries.iter().position(|(cached, _)| cached == key)?;
+        let entry = self.entries.remove(position)?;
+        let rendered = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(rendered)
+    }
+
+    pub(super) fn insert(&mut self, key: RenderKey, rendered: &RenderedPreview) {
+        if let Some(position) = self.entries.iter().position(|(cached, _)| *cached == key) {
+            if let Some((_, old)) = self.entries.remove(position) {
+                self.used_bytes -= frame_bytes(&old);
+            }
+        }
+        let bytes = frame_bytes(rendered);
+        if bytes > self.budget_bytes {
+            return;
+        }
+        self.used_bytes += bytes;
+        self.entries.push_back((key, rendered.clone()));
+        self.evict_to_budget();
+    }
+
+    pub(super) fn set_budget(&mut self, budget_bytes: usize) {
+        self.budget_bytes = budget_bytes;
+        self.evict_to_budget();
+    }
+
+    fn evict_to_budget(&mut self) {
+        while self.used_bytes > self.budget_bytes {
+            let Some((_, evicted)) = self.entries.pop_front() else {
+                break;
+            };
+            self.used_bytes -= frame_bytes(&evicted);
+        }
+    }
+}
+
+fn frame_bytes(rendered: &RenderedPreview) -> usize {
+    rendered.pixels.len() * std::mem::size_of::<u32>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lite_room_domain::{EditParams, DEFAULT_SPLIT_FRACTION};
+
+    fn request(exposure: f32) -> PreviewRequest {
+        PreviewRequest {
+            image_id: ImageId::new(1).expect("id"),
+            source_path: "a.jpg".to_string(),
+            source_pixels: None,
+            source_region: None,
+            view_region: None,
+            params: EditParams::default().with_exposure(exposure),
+            target_width: 2,
+            target_height: 2,
+            want_histogram: false,
+            comparison: None,
+            split_fraction: DEFAULT_SPLIT_FRACTION,
+        }
+    }
+
+    fn frame(fill: u32) -> RenderedPreview {
+        RenderedPreview {
+            width: 2,
+            height: 2,
+            pixels: vec![fill; 4],
+        }
+    }
+
+    #[test]
+    fn nearby_params_share_a_key_and_old_frames_are_evicted() {
+        assert_eq!(
+            RenderKey::new(&request(0.5)),
+            RenderKey::new(&request(0.50004))
+        );
+        assert_ne!(
+            RenderKey::new(&request(0.5)),
+            RenderKey::new(&request(0.51))
+        );
+        assert_eq!(
+            RenderKey::new(&request(-0.0001)),
+            RenderKey::new(&request(0.0))
+        );
+
+        let mut cache = RenderCache::new(32);
+        cache.insert(RenderKey::new(&request(1.0)), &frame(1));
+        cache.insert(RenderKey::new(&request(2.0)), &frame(2));
+        assert_eq!(cache.get(&RenderKey::new(&request(1.0))), Some(frame(1)));
+        cache.insert(RenderKey::new(&request(3.0)), &frame(3));
+        assert_eq!(cache.get(&RenderKey::new(&request(2.0))), None);
+        assert_eq!(cache.used_bytes, 32);
+
+        cache.set_budget(0);
+        assert!(cache.entries.is_empty());
+        cache.insert(RenderKey::new(&request(1.0)), &frame(1));
+        assert_eq!(cache.get(&RenderKey::new(&request(1.0))), None);
+    }
+}
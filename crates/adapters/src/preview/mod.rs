@@ -1,16 +1,38 @@
+mod cache;
+
+pub use cache::DEFAULT_PREVIEW_CACHE_BYTES;
+
+use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use image::io::Reader as ImageReader;
 use lite_room_application::{ApplicationError, PreviewPipeline};
-use lite_room_domain::{PreviewFrame, PreviewMetrics, PreviewRequest};
+use lite_room_domain::{
+    detect_image_kind, ComparisonMode, Framing, ImageKind, PixelAdjustments, PreviewBackend,
+    PreviewBackendInfo, PreviewFrame, PreviewHistogram, PreviewMetrics, PreviewRequest,
+    SourceRegion,
+};
+#[cfg(feature = "gpu")]
 use wgpu::util::DeviceExt;
 
+use crate::decode::{decode_scaled_within_limit, DEFAULT_MAX_DECODE_DIMENSION};
+use crate::heic::decode_heic;
+use cache::{RenderCache, RenderKey};
+
 const METRIC_WINDOW_SIZE: usize = 64;
-const MAX_RENDER_PIXELS: usize = 2_000_000;
+const PREVIEW_THREAD_NAME: &str = "lite-room-preview";
+#[cfg(target_os = "linux")]
+const PREVIEW_THREAD_NICE_INCREMENT: i32 = 2;
+pub const DEFAULT_PREVIEW_MAX_PIXELS: usize = 2_000_000;
+// Consecutive GPU failures (errors or renders slower than the timeout) before
+// the worker gives up on the GPU for the rest of the session.
+const GPU_FAILURE_LIMIT: u32 = 3;
+const GPU_RENDER_TIMEOUT: Duration = Duration::from_secs(2);
+#[cfg(feature = "gpu")]
 const PREVIEW_WORKGROUP_SIZE: u32 = 64;
+#[cfg(feature = "gpu")]
 const PREVIEW_SHADER: &str = r#"
 struct Params {
     pixel_count: u32,
@@ -21,6 +43,10 @@ struct Params {
     tint: f32,
     highlights: f32,
     shadows: f32,
+    saturation: f32,
+    vibrance: f32,
+    grain: f32,
+    _padding: f32,
 }
 
 @group(0) @binding(0)
@@ -32,10 +58,34 @@ var<storage, read_write> output_pixels: array<u32>;
 @group(0) @binding(2)
 var<uniform> params: Params;
 
+// Red, green, blue and tone curve LUTs back to back, 256 entries each.
+@group(0) @binding(3)
+var<storage, read> curve_luts: array<u32>;
+
 fn to_u8(value: f32) -> u32 {
     return u32(clamp(value * 255.0, 0.0, 255.0));
 }
 
+// Grain is the last stage, so it rounds like the CPU renderer's `to_channel`.
+fn round_to_u8(value: f32) -> u32 {
+    return u32(clamp(value * 255.0, 0.0, 255.0) + 0.5);
+}
+
+// Same lowbias32 hash as the CPU renderer's grain, keyed by pixel position.
+fn grain_hash(input: u32) -> u32 {
+    var value = input;
+    value = value ^ (value >> 16u);
+    value = value * 0x7feb352du;
+    value = value ^ (value >> 15u);
+    value = value * 0x846ca68bu;
+    return value ^ (value >> 16u);
+}
+
+fn grain_noise(x: u32, y: u32) -> f32 {
+    let bits = grain_hash(x + grain_hash(y)) >> 8u;
+    return f32(bits) / 16777215.0 * 2.0 - 1.0;
+}
+
 @compute @workgroup_size(64)
 fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
     let i = gid.x;
@@ -49,21 +99,25 @@ fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
     var green = f32((source >> 8u) & 255u) / 255.0;
     var blue = f32(source & 255u) / 255.0;
 
-    let exposure_gain = exp2(clamp(params.exposure, -5.0, 5.0));
-    let contrast_factor = 1.0 + clamp(params.contrast, -5.0, 5.0) * 0.12;
+    let exposure_gain = exp2(params.exposure);
+    let contrast_factor = 1.0 + params.contrast * 0.12;
 
     red = clamp((red * exposure_gain - 0.5) * contrast_factor + 0.5, 0.0, 1.0);
     green = clamp((green * exposure_gain - 0.5) * contrast_factor + 0.5, 0.0, 1.0);
     blue = clamp((blue * exposure_gain - 0.5) * contrast_factor + 0.5, 0.0, 1.0);
 
-    let temp = clamp(params.temperature, -5.0, 5.0) * 0.035;
-    let tint = clamp(params.tint, -5.0, 5.0) * 0.035;
+    red = f32(curve_luts[768u + to_u8(red)]) / 255.0;
+    green = f32(curve_luts[768u + to_u8(green)]) / 255.0;
+    blue = f32(curve_luts[768u + to_u8(blue)]) / 255.0;
+
+    let temp = params.temperature * 0.035;
+    let tint = params.tint * 0.035;
     red = clamp(red + temp, 0.0, 1.0);
     blue = clamp(blue - temp, 0.0, 1.0);
     green = clamp(green + tint, 0.0, 1.0);
 
-    let highlights = clamp(params.highlights, -5.0, 5.0) * 0.08;
-    let shadows = clamp(params.shadows, -5.0, 5.0) * 0.08;
+    let highlights = params.highlights * 0.08;
+    let shadows = params.shadows * 0.08;
     let high_component = max(red - 0.5, 0.0) * highlights;
     let shadow_component = max(0.5 - red, 0.0) * shadows;
     red = clamp(red + shadow_component - high_component, 0.0, 1.0);
@@ -76,9 +130,30 @@ fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
     let shadow_component_b = max(0.5 - blue, 0.0) * shadows;
     blue = clamp(blue + shadow_component_b - high_component_b, 0.0, 1.0);
 
-    let r = to_u8(red);
-    let g = to_u8(green);
-    let b = to_u8(blue);
+    let saturation_factor = 1.0 + params.saturation * 0.2;
+    let vibrance_strength = params.vibrance * 0.2;
+    let luma = 0.2126 * red + 0.7152 * green + 0.0722 * blue;
+    let chroma = max(max(red, green), blue) - min(min(red, green), blue);
+    var skin_weight = 1.0;
+    if (red > green && green > blue) {
+        skin_weight = 0.5;
+    }
+    let vibrance_factor = 1.0 + vibrance_strength * (1.0 - chroma) * skin_weight;
+    let intensity = max(saturation_factor * vibrance_factor, 0.0);
+    red = clamp(luma + (red - luma) * intensity, 0.0, 1.0);
+    green = clamp(luma + (green - luma) * intensity, 0.0, 1.0);
+    blue = clamp(luma + (blue - luma) * intensity, 0.0, 1.0);
+
+    var r = curve_luts[to_u8(red)];
+    var g = curve_luts[256u + to_u8(green)];
+    var b = curve_luts[512u + to_u8(blue)];
+
+    if (params.grain > 0.0) {
+        let offset = grain_noise(i % width, i / width) * params.grain * 0.04;
+        r = round_to_u8(f32(r) / 255.0 + offset);
+        g = round_to_u8(f32(g) / 255.0 + offset);
+        b = round_to_u8(f32(b) / 255.0 + offset);
+    }
     output_pixels[i] = (r << 16u) | (g << 8u) | b;
 }
 "#;
@@ -91,6 +166,9 @@ struct MetricsState {
     dropped_frames: u64,
     last_render_time_ms: Option<u64>,
     render_samples_ms: Vec<u64>,
+    gpu_failures: u64,
+    gpu_disabled: bool,
+    cache_hits: u64,
 }
 
 impl MetricsState {
@@ -102,6 +180,9 @@ impl MetricsState {
             dropped_frames: self.dropped_frames,
             last_render_time_ms: self.last_render_time_ms,
             p95_render_time_ms: percentile_95(&self.render_samples_ms),
+            gpu_failures: self.gpu_failures,
+            gpu_disabled: self.gpu_disabled,
+            cache_hits: self.cache_hits,
         }
     }
 
@@ -131,25 +212,37 @@ struct ScheduledJob {
     request: PreviewRequest,
 }
 
-trait PreviewRenderer: Send + Sync {
+// A backend renders one request at a time on the pipeline worker thread. It
+// returns row-major 0x00RRGGBB pixels at `target_width` x `target_height`, or a
+// smaller size with the same aspect when it caps its render size (the built-in
+// renderers stop at their `max_pixels` ceiling); `pixels.len()` must equal
+// `width * height`. Sequencing, cancellation, metrics and histograms stay with
+// `BackgroundPreviewPipeline`, and a returned error becomes an error frame.
+pub trait PreviewRenderer: Send + Sync {
     fn render(&self, request: PreviewRequest) -> Result<RenderedPreview, ApplicationError>;
+    fn backend_info(&self) -> PreviewBackendInfo;
 }
 
-struct RenderedPreview {
-    width: u32,
-    height: u32,
-    pixels: Vec<u32>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedPreview {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u32>,
 }
 
+#[cfg(feature = "gpu")]
 struct WgpuRenderer {
+    adapter_name: String,
     device: wgpu::Device,
     queue: wgpu::Queue,
     bind_group_layout: wgpu::BindGroupLayout,
     pipeline: wgpu::ComputePipeline,
+    max_pixels: usize,
 }
 
+#[cfg(feature = "gpu")]
 impl WgpuRenderer {
-    fn new() -> Result<Self, String> {
+    fn new(max_pixels: usize) -> Result<Self, String> {
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
         let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
             power_preference: wgpu::PowerPreference::HighPerformance,
@@ -157,6 +250,7 @@ impl WgpuRenderer {
             force_fallback_adapter: false,
         }))
         .ok_or_else(|| "no suitable wgpu adapter found".to_string())?;
+        let adapter_name = adapter.get_info().name;
 
         let (device, queue) = pollster::block_on(adapter.request_device(
             &wgpu::DeviceDescriptor {
@@ -206,6 +300,16 @@ impl WgpuRenderer {
                     },
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         });
 
@@ -223,14 +327,17 @@ impl WgpuRenderer {
         });
 
         Ok(Self {
+            adapter_name,
             device,
             queue,
             bind_group_layout,
             pipeline,
+            max_pixels,
         })
     }
 }
 
+#[cfg(feature = "gpu")]
 impl PreviewRenderer for WgpuRenderer {
     fn render(&self, request: PreviewRequest) -> Result<RenderedPreview, ApplicationError> {
         let width = request.target_width as usize;
@@ -240,11 +347,18 @@ impl PreviewRenderer for WgpuRenderer {
                 "preview target dimensions must be non-zero".to_string(),
             ));
         }
+        // The shader trusts its uniforms, so out-of-range params are bounded here.
+        let request = PreviewRequest {
+            params: request.params.effective().clamp_to_range(),
+            ..request
+        };
 
-        let (render_width, render_height, pixel_count) = render_target(width, height)?;
+        let (width, height) = framed_target(&request, width, height);
+        let (render_width, render_height, pixel_count) =
+            render_target(width, height, self.max_pixels)?;
         let pixel_bytes = (pixel_count as u64) * 4;
 
-        let source_pixels = decode_source_pixels(&request.source_path, render_width, render_height)?;
+        let source_pixels = decode_source_pixels(&request, render_width, render_height)?;
         let source_bytes = source_pixels_as_le_bytes(&source_pixels);
         let source = self
             .device
@@ -261,7 +375,7 @@ impl PreviewRenderer for WgpuRenderer {
             mapped_at_creation: false,
         });
 
-        let params = pack_gpu_params(request, render_width as u32, pixel_count as u32);
+        let params = pack_gpu_params(&request, render_width as u32, pixel_count as u32);
         let params_buffer = self
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -269,6 +383,13 @@ impl PreviewRenderer for WgpuRenderer {
                 contents: &params,
                 usage: wgpu::BufferUsages::UNIFORM,
             });
+        let curve_luts = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("lite-room-preview-curve-luts"),
+                contents: &pack_curve_luts(&request),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
 
         let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("lite-room-preview-readback"),
@@ -293,6 +414,10 @@ impl PreviewRenderer for WgpuRenderer {
                     binding: 2,
                     resource: params_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: curve_luts.as_entire_binding(),
+                },
             ],
         });
 
@@ -308,7 +433,7 @@ impl PreviewRenderer for WgpuRenderer {
             });
             compute_pass.set_pipeline(&self.pipeline);
             compute_pass.set_bind_group(0, &bind_group, &[]);
-            let workgroups = ((pixel_count as u32) + PREVIEW_WORKGROUP_SIZE - 1) / PREVIEW_WORKGROUP_SIZE;
+            let workgroups = (pixel_count as u32).div_ceil(PREVIEW_WORKGROUP_SIZE);
             compute_pass.dispatch_workgroups(workgroups, 1, 1);
         }
         encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, pixel_bytes);
@@ -325,26 +450,59 @@ impl PreviewRenderer for WgpuRenderer {
             .map_err(|error| ApplicationError::Io(format!("gpu readback map failed: {error}")))?;
 
         let data = slice.get_mapped_range();
-        let pixels = data
+        let mut pixels = data
             .chunks_exact(4)
             .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
             .collect::<Vec<_>>();
         black_box_bytes(&data);
         drop(data);
         readback.unmap();
+        if let Some(mode) = request.comparison {
+            restore_original_region(
+                mode,
+                request.split_fraction,
+                &source_pixels,
+                &mut pixels,
+                render_width,
+            );
+        }
         Ok(RenderedPreview {
             width: render_width as u32,
             height: render_height as u32,
             pixels,
         })
     }
+
+    fn backend_info(&self) -> PreviewBackendInfo {
+        PreviewBackendInfo {
+            backend: PreviewBackend::Gpu,
+            adapter_name: self.adapter_name.clone(),
+        }
+    }
 }
 
-#[derive(Default)]
-struct CpuStageRenderer;
+#[cfg(test)]
+thread_local! {
+    static ADJUSTMENT_PASSES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
 
-impl PreviewRenderer for CpuStageRenderer {
-    fn render(&self, request: PreviewRequest) -> Result<RenderedPreview, ApplicationError> {
+#[derive(Debug, Clone, Copy)]
+pub struct CpuPreviewRenderer {
+    max_pixels: usize,
+}
+
+impl Default for CpuPreviewRenderer {
+    fn default() -> Self {
+        Self::new(DEFAULT_PREVIEW_MAX_PIXELS)
+    }
+}
+
+impl CpuPreviewRenderer {
+    pub fn new(max_pixels: usize) -> Self {
+        Self { max_pixels }
+    }
+
+    pub fn render(&self, request: PreviewRequest) -> Result<RenderedPreview, ApplicationError> {
         let width = request.target_width as usize;
         let height = request.target_height as usize;
         if width == 0 || height == 0 {
@@ -352,12 +510,34 @@ impl PreviewRenderer for CpuStageRenderer {
                 "preview target dimensions must be non-zero".to_string(),
             ));
         }
+        let request = PreviewRequest {
+            params: request.params.effective(),
+            ..request
+        };
 
-        let (render_width, render_height, _) = render_target(width, height)?;
-        let mut pixels = decode_source_pixels(&request.source_path, render_width, render_height)?;
-        apply_exposure_contrast(&mut pixels, request.params.exposure, request.params.contrast);
-        apply_temperature_tint(&mut pixels, request.params.temperature, request.params.tint);
-        apply_highlights_shadows(&mut pixels, request.params.highlights, request.params.shadows);
+        let (width, height) = framed_target(&request, width, height);
+        let (render_width, render_height, _) = render_target(width, height, self.max_pixels)?;
+        let mut pixels = decode_source_pixels(&request, render_width, render_height)?;
+        let original = request.comparison.map(|mode| (mode, pixels.clone()));
+        let adjustments = PixelAdjustments::new(&request.params);
+        if !adjustments.is_identity() && request.comparison != Some(ComparisonMode::FullOriginal) {
+            #[cfg(test)]
+            ADJUSTMENT_PASSES.with(|passes| passes.set(passes.get() + 1));
+            for (index, pixel) in pixels.iter_mut().enumerate() {
+                let (x, y) = (index % render_width, index / render_width);
+                let [r, g, b] = adjustments.apply_at(unpack_rgb(*pixel), x as u32, y as u32);
+                *pixel = pack_rgb(r, g, b);
+            }
+        }
+        if let Some((mode, original)) = original {
+            restore_original_region(
+                mode,
+                request.split_fraction,
+                &original,
+                &mut pixels,
+                render_width,
+            );
+        }
         black_box_checksum(&pixels);
         Ok(RenderedPreview {
             width: render_width as u32,
@@ -367,47 +547,163 @@ impl PreviewRenderer for CpuStageRenderer {
     }
 }
 
+impl PreviewRenderer for CpuPreviewRenderer {
+    fn render(&self, request: PreviewRequest) -> Result<RenderedPreview, ApplicationError> {
+        CpuPreviewRenderer::render(self, request)
+    }
+
+    fn backend_info(&self) -> PreviewBackendInfo {
+        PreviewBackendInfo {
+            backend: PreviewBackend::Cpu,
+            adapter_name: "cpu-stage".to_string(),
+        }
+    }
+}
+
 pub struct BackgroundPreviewPipeline {
     next_sequence: AtomicU64,
     latest_sequence: Arc<AtomicU64>,
-    submit_tx: mpsc::Sender<ScheduledJob>,
-    result_rx: Mutex<mpsc::Receiver<PreviewFrame>>,
+    submit_tx: Option<mpsc::Sender<ScheduledJob>>,
+    result_rx: Mutex<mpsc::Receiver<Result<PreviewFrame, ApplicationError>>>,
     metrics: Arc<Mutex<MetricsState>>,
-    _renderer: Arc<dyn PreviewRenderer>,
+    renderer: Arc<dyn PreviewRenderer>,
+    max_pixels: usize,
+    cache: Arc<Mutex<RenderCache>>,
+    workers: Vec<thread::JoinHandle<()>>,
 }
 
 impl BackgroundPreviewPipeline {
     pub fn new() -> Self {
-        let renderer: Arc<dyn PreviewRenderer> = match WgpuRenderer::new() {
-            Ok(renderer) => Arc::new(renderer),
-            Err(_) => Arc::new(CpuStageRenderer),
+        Self::with_max_pixels(DEFAULT_PREVIEW_MAX_PIXELS)
+    }
+
+    // Same backend choice as `new`, with a different render-size ceiling.
+    pub fn with_max_pixels(max_pixels: usize) -> Self {
+        Self::with_shared_renderer(default_renderer(max_pixels), max_pixels)
+    }
+
+    // Same backend choice as `new`, rendering on `workers` threads (at least
+    // one) that pull from one queue. Only the latest job is ever delivered.
+    pub fn with_workers(workers: usize) -> Self {
+        Self::spawn(
+            default_renderer(DEFAULT_PREVIEW_MAX_PIXELS),
+            DEFAULT_PREVIEW_MAX_PIXELS,
+            workers,
+        )
+    }
+
+    pub fn with_backend(backend: PreviewBackend) -> Result<Self, ApplicationError> {
+        Self::with_backend_and_max_pixels(backend, DEFAULT_PREVIEW_MAX_PIXELS)
+    }
+
+    pub fn with_backend_and_max_pixels(
+        backend: PreviewBackend,
+        max_pixels: usize,
+    ) -> Result<Self, ApplicationError> {
+        let renderer: Arc<dyn PreviewRenderer> = match backend {
+            PreviewBackend::Cpu => Arc::new(CpuPreviewRenderer::new(max_pixels)),
+            #[cfg(feature = "gpu")]
+            PreviewBackend::Gpu => {
+                Arc::new(WgpuRenderer::new(max_pixels).map_err(ApplicationError::Io)?)
+            }
+            #[cfg(not(feature = "gpu"))]
+            PreviewBackend::Gpu => {
+                return Err(ApplicationError::InvalidInput(
+                    "gpu backend requires the gpu feature".to_string(),
+                ))
+            }
         };
-        Self::with_renderer(renderer)
+        Ok(Self::with_shared_renderer(renderer, max_pixels))
     }
 
-    fn with_renderer(renderer: Arc<dyn PreviewRenderer>) -> Self {
+    pub fn with_renderer(renderer: Box<dyn PreviewRenderer>) -> Self {
+        Self::with_shared_renderer(Arc::from(renderer), DEFAULT_PREVIEW_MAX_PIXELS)
+    }
+
+    fn with_shared_renderer(renderer: Arc<dyn PreviewRenderer>, max_pixels: usize) -> Self {
+        Self::spawn(renderer, max_pixels, 1)
+    }
+
+    fn spawn(renderer: Arc<dyn PreviewRenderer>, max_pixels: usize, workers: usize) -> Self {
         let (submit_tx, submit_rx) = mpsc::channel::<ScheduledJob>();
-        let (result_tx, result_rx) = mpsc::channel::<PreviewFrame>();
+        let (result_tx, result_rx) = mpsc::channel::<Result<PreviewFrame, ApplicationError>>();
         let latest_sequence = Arc::new(AtomicU64::new(0));
         let metrics = Arc::new(Mutex::new(MetricsState::default()));
-
-        spawn_worker(
-            submit_rx,
-            result_tx,
-            Arc::clone(&latest_sequence),
-            Arc::clone(&metrics),
-            Arc::clone(&renderer),
-        );
+        let submit_rx = Arc::new(Mutex::new(submit_rx));
+        let result_tx = Arc::new(Mutex::new(result_tx));
+        let cache = Arc::new(Mutex::new(RenderCache::new(DEFAULT_PREVIEW_CACHE_BYTES)));
+
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                spawn_worker(
+                    Arc::clone(&submit_rx),
+                    Arc::clone(&result_tx),
+                    Arc::clone(&latest_sequence),
+                    Arc::clone(&metrics),
+                    Arc::clone(&cache),
+                    Arc::clone(&renderer),
+                    max_pixels,
+                )
+            })
+            .collect();
 
         Self {
             next_sequence: AtomicU64::new(0),
             latest_sequence,
-            submit_tx,
+            submit_tx: Some(submit_tx),
             result_rx: Mutex::new(result_rx),
             metrics,
-            _renderer: renderer,
+            renderer,
+            max_pixels,
+            cache,
+            workers,
+        }
+    }
+
+    // Caps the memory kept for rendered frames (default
+    // DEFAULT_PREVIEW_CACHE_BYTES); zero turns the cache off, as benchmarks
+    // need so every iteration renders.
+    pub fn with_cache_bytes(self, budget_bytes: usize) -> Self {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.set_budget(budget_bytes);
         }
+        self
     }
+
+    // Stops the workers and waits for their threads to exit. Queued jobs and
+    // renders still in flight are superseded: they count as canceled and never
+    // arrive, so the returned metrics are final.
+    pub fn shutdown(mut self) -> Result<PreviewMetrics, ApplicationError> {
+        self.stop_worker();
+        self.metrics()
+    }
+
+    fn stop_worker(&mut self) {
+        self.latest_sequence.store(u64::MAX, Ordering::SeqCst);
+        self.submit_tx = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for BackgroundPreviewPipeline {
+    fn drop(&mut self) {
+        self.stop_worker();
+    }
+}
+
+#[cfg(feature = "gpu")]
+fn default_renderer(max_pixels: usize) -> Arc<dyn PreviewRenderer> {
+    match WgpuRenderer::new(max_pixels) {
+        Ok(renderer) => Arc::new(renderer),
+        Err(_) => Arc::new(CpuPreviewRenderer::new(max_pixels)),
+    }
+}
+
+#[cfg(not(feature = "gpu"))]
+fn default_renderer(max_pixels: usize) -> Arc<dyn PreviewRenderer> {
+    Arc::new(CpuPreviewRenderer::new(max_pixels))
 }
 
 impl Default for BackgroundPreviewPipeline {
@@ -427,7 +723,12 @@ impl PreviewPipeline for BackgroundPreviewPipeline {
                 .map_err(|_| ApplicationError::Io("preview metrics lock poisoned".to_string()))?;
             metrics.submitted_jobs += 1;
         }
-        self.submit_tx
+        let Some(submit_tx) = &self.submit_tx else {
+            return Err(ApplicationError::Io(
+                "preview pipeline is shut down".to_string(),
+            ));
+        };
+        submit_tx
             .send(ScheduledJob { sequence, request })
             .map_err(|error| ApplicationError::Io(format!("failed to enqueue preview job: {error}")))
     }
@@ -463,7 +764,7 @@ impl PreviewPipeline for BackgroundPreviewPipeline {
             metrics.dropped_frames += dropped;
         }
 
-        Ok(Some(newest))
+        newest.map(Some)
     }
 
     fn metrics(&self) -> Result<PreviewMetrics, ApplicationError> {
@@ -473,34 +774,124 @@ impl PreviewPipeline for BackgroundPreviewPipeline {
             .map_err(|_| ApplicationError::Io("preview metrics lock poisoned".to_string()))?;
         Ok(metrics.snapshot())
     }
+
+    fn backend_info(&self) -> Result<PreviewBackendInfo, ApplicationError> {
+        let gpu_disabled = self
+            .metrics
+            .lock()
+            .map_err(|_| ApplicationError::Io("preview metrics lock poisoned".to_string()))?
+            .gpu_disabled;
+        if gpu_disabled {
+            return Ok(CpuPreviewRenderer::new(self.max_pixels).backend_info());
+        }
+        Ok(self.renderer.backend_info())
+    }
 }
 
+// Every worker takes the newest queued job and drops the rest. Checking that
+// a frame is still the latest and sending it happen under the result lock, so
+// frames reach the channel in sequence order even when workers finish out of
+// order.
 fn spawn_worker(
-    submit_rx: mpsc::Receiver<ScheduledJob>,
-    result_tx: mpsc::Sender<PreviewFrame>,
+    submit_rx: Arc<Mutex<mpsc::Receiver<ScheduledJob>>>,
+    result_tx: Arc<Mutex<mpsc::Sender<Result<PreviewFrame, ApplicationError>>>>,
     latest_sequence: Arc<AtomicU64>,
     metrics: Arc<Mutex<MetricsState>>,
+    cache: Arc<Mutex<RenderCache>>,
     renderer: Arc<dyn PreviewRenderer>,
-) {
-    thread::spawn(move || {
-        while let Ok(mut job) = submit_rx.recv() {
-            while let Ok(next) = submit_rx.try_recv() {
-                mark_canceled(&metrics, 1);
-                job = next;
+    max_pixels: usize,
+) -> thread::JoinHandle<()> {
+    // `None` when a newer job arrived first, otherwise whether the send
+    // went through.
+    let send_latest = {
+        let latest_sequence = Arc::clone(&latest_sequence);
+        move |sequence: u64, result: Result<PreviewFrame, ApplicationError>| {
+            let result_tx = result_tx.lock().ok()?;
+            if sequence < latest_sequence.load(Ordering::SeqCst) {
+                return None;
             }
+            Some(result_tx.send(result).is_ok())
+        }
+    };
+    let builder = thread::Builder::new().name(PREVIEW_THREAD_NAME.to_string());
+    let spawned = builder.spawn(move || {
+        lower_current_thread_priority();
+        let mut renderer = renderer;
+        let mut gpu_strikes = 0_u32;
+        loop {
+            let job = {
+                let Ok(submit_rx) = submit_rx.lock() else {
+                    return;
+                };
+                let Ok(mut job) = submit_rx.recv() else {
+                    return;
+                };
+                while let Ok(next) = submit_rx.try_recv() {
+                    mark_canceled(&metrics, 1);
+                    job = next;
+                }
+                job
+            };
 
             if job.sequence < latest_sequence.load(Ordering::SeqCst) {
                 mark_canceled(&metrics, 1);
                 continue;
             }
 
+            if renderer.backend_info().backend == PreviewBackend::Gpu
+                && metrics.lock().is_ok_and(|m| m.gpu_disabled)
+            {
+                renderer = Arc::new(CpuPreviewRenderer::new(max_pixels));
+            }
             let image_id = job.request.image_id;
+            let want_histogram = job.request.want_histogram;
             let started = Instant::now();
-            let rendered = match renderer.render(job.request) {
+            let key = RenderKey::new(&job.request);
+            let cached = cache.lock().ok().and_then(|mut cache| cache.get(&key));
+            let cache_hit = cached.is_some();
+            let result = match cached {
+                Some(rendered) => Ok(rendered),
+                None => {
+                    let on_gpu = renderer.backend_info().backend == PreviewBackend::Gpu;
+                    let retry = on_gpu.then(|| job.request.clone());
+                    let mut result = renderer.render(job.request).and_then(check_rendered_size);
+                    if on_gpu {
+                        if result.is_err() || started.elapsed() > GPU_RENDER_TIMEOUT {
+                            gpu_strikes += 1;
+                            if let Ok(mut m) = metrics.lock() {
+                                m.gpu_failures += 1;
+                            }
+                        } else {
+                            gpu_strikes = 0;
+                        }
+                        if gpu_strikes >= GPU_FAILURE_LIMIT {
+                            renderer = Arc::new(CpuPreviewRenderer::new(max_pixels));
+                            if let Ok(mut m) = metrics.lock() {
+                                m.gpu_disabled = true;
+                            }
+                            if let (Err(_), Some(request)) = (&result, retry) {
+                                result = renderer.render(request).and_then(check_rendered_size);
+                            }
+                        }
+                    }
+                    if let (Ok(rendered), Ok(mut cache)) = (&result, cache.lock()) {
+                        cache.insert(key, rendered);
+                    }
+                    result
+                }
+            };
+            let rendered = match result {
                 Ok(rendered) => rendered,
-                Err(_) => {
-                mark_canceled(&metrics, 1);
-                continue;
+                Err(error) => {
+                    mark_canceled(&metrics, 1);
+                    let error = error.with_context(format!(
+                        "preview render failed for image {}",
+                        image_id.get()
+                    ));
+                    if send_latest(job.sequence, Err(error)) == Some(false) {
+                        return;
+                    }
+                    continue;
                 }
             };
             let elapsed = started.elapsed().as_millis() as u64;
@@ -516,18 +907,50 @@ fn spawn_worker(
                 width: rendered.width,
                 height: rendered.height,
                 render_time_ms: elapsed,
+                histogram: want_histogram.then(|| PreviewHistogram::from_pixels(&rendered.pixels)),
                 pixels: rendered.pixels,
             };
-            if result_tx.send(frame).is_err() {
-                return;
-            }
-
-            if let Ok(mut m) = metrics.lock() {
-                m.completed_jobs += 1;
-                m.push_render_sample(elapsed);
+            match send_latest(job.sequence, Ok(frame)) {
+                Some(true) => {
+                    if let Ok(mut m) = metrics.lock() {
+                        m.completed_jobs += 1;
+                        if cache_hit {
+                            m.cache_hits += 1;
+                        } else {
+                            m.push_render_sample(elapsed);
+                        }
+                    }
+                }
+                Some(false) => return,
+                None => mark_canceled(&metrics, 1),
             }
         }
     });
+    spawned.expect("failed to spawn preview worker thread")
+}
+
+#[cfg(target_os = "linux")]
+fn lower_current_thread_priority() {
+    // Linux nice values are per thread; failure just leaves the default priority.
+    unsafe {
+        libc::nice(PREVIEW_THREAD_NICE_INCREMENT);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn lower_current_thread_priority() {}
+
+fn check_rendered_size(rendered: RenderedPreview) -> Result<RenderedPreview, ApplicationError> {
+    let expected = (rendered.width as usize).checked_mul(rendered.height as usize);
+    if expected != Some(rendered.pixels.len()) {
+        return Err(ApplicationError::Io(format!(
+            "renderer returned {} pixels for a {}x{} frame",
+            rendered.pixels.len(),
+            rendered.width,
+            rendered.height
+        )));
+    }
+    Ok(rendered)
 }
 
 fn mark_canceled(metrics: &Arc<Mutex<MetricsState>>, count: u64) {
@@ -536,34 +959,188 @@ fn mark_canceled(metrics: &Arc<Mutex<MetricsState>>, count: u64) {
     }
 }
 
+// Both renderers edit the whole frame, then copy the unedited source back over
+// the comparison region so the divider lands on the same pixel for each backend.
+fn restore_original_region(
+    mode: ComparisonMode,
+    split_fraction: f32,
+    original: &[u32],
+    edited: &mut [u32],
+    width: usize,
+) {
+    let height = edited.len() / width.max(1);
+    let (columns, rows) = mode.original_extent(split_fraction, width, height);
+    for (edited_row, original_row) in edited
+        .chunks_exact_mut(width)
+        .zip(original.chunks_exact(width))
+        .take(rows)
+    {
+        edited_row[..columns].copy_from_slice(&original_row[..columns]);
+    }
+}
+
+// The requested size is the unrotated, uncropped frame; rotation and crop
+// reshape it the same way they reshape the image.
+fn framed_target(request: &PreviewRequest, width: usize, height: usize) -> (usize, usize) {
+    Framing::new(&request.params).map_or((width, height), |framing| {
+        framing.output_size(width, height)
+    })
+}
+
 fn decode_source_pixels(
-    source_path: &str,
+    request: &PreviewRequest,
     target_width: usize,
     target_height: usize,
 ) -> Result<Vec<u32>, ApplicationError> {
-    let image = ImageReader::open(source_path)
-        .map_err(|error| ApplicationError::Decode(error.to_string()))?
-        .with_guessed_format()
-        .map_err(|error| ApplicationError::Decode(error.to_string()))?
-        .decode()
-        .map_err(|error| ApplicationError::Decode(error.to_string()))?;
-    let source = image.to_rgb8();
-    let src_width = source.width() as usize;
-    let src_height = source.height() as usize;
+    if let Some(crop) = &request.params.crop {
+        crop.validate()
+            .map_err(|error| ApplicationError::InvalidInput(error.to_string()))?;
+    }
+    if let Some(view) = &request.view_region {
+        view.validate()
+            .map_err(|error| ApplicationError::InvalidInput(error.to_string()))?;
+    }
+    if let Some(buffer) = &request.source_pixels {
+        let expected_len = (buffer.width as usize)
+            .checked_mul(buffer.height as usize)
+            .and_then(|pixels| pixels.checked_mul(3));
+        if expected_len != Some(buffer.rgb.len()) {
+            return Err(ApplicationError::InvalidInput(format!(
+                "source pixels length {} does not match {}x{} rgb",
+                buffer.rgb.len(),
+                buffer.width,
+                buffer.height
+            )));
+        }
+        return resample_source(
+            &buffer.rgb,
+            buffer.width as usize,
+            buffer.height as usize,
+            request,
+            target_width,
+            target_height,
+        );
+    }
+
+    let source_path = request.source_path.as_str();
+    let source = match detect_image_kind(Path::new(source_path)) {
+        ImageKind::Heic => decode_heic(Path::new(source_path), DEFAULT_MAX_DECODE_DIMENSION)?,
+        _ => {
+            let (min_width, min_height) = region_decode_size(
+                request.effective_region(),
+                Framing::new(&request.params),
+                target_width,
+                target_height,
+            );
+            decode_scaled_within_limit(
+                Path::new(source_path),
+                min_width,
+                min_height,
+                DEFAULT_MAX_DECODE_DIMENSION,
+            )?
+            .to_rgb8()
+        }
+    };
+    resample_source(
+        source.as_raw(),
+        source.width() as usize,
+        source.height() as usize,
+        request,
+        target_width,
+        target_height,
+    )
+}
+
+fn resample_source(
+    rgb: &[u8],
+    src_width: usize,
+    src_height: usize,
+    request: &PreviewRequest,
+    target_width: usize,
+    target_height: usize,
+) -> Result<Vec<u32>, ApplicationError> {
+    match Framing::new(&request.params) {
+        Some(framing) if src_width > 0 && src_height > 0 => resample_framed(
+            rgb,
+            src_width,
+            src_height,
+            request.effective_region(),
+            framing,
+            target_width,
+            target_height,
+        ),
+        _ => resample_rgb(
+            rgb,
+            src_width,
+            src_height,
+            request.effective_region(),
+            target_width,
+            target_height,
+            &request.source_path,
+        ),
+    }
+}
+
+fn region_decode_size(
+    region: Option<SourceRegion>,
+    framing: Option<Framing>,
+    target_width: usize,
+    target_height: usize,
+) -> (u32, u32) {
+    let (mut width_fraction, mut height_fraction) =
+        region.map_or((1.0, 1.0), |region| (region.width, region.height));
+    if let Some(crop) = framing.map(|framing| framing.crop()) {
+        width_fraction *= crop.width;
+        height_fraction *= crop.height;
+    }
+    let scaled = |target: usize, fraction: f32| {
+        (target as f32 / fraction.max(f32::EPSILON))
+            .ceil()
+            .min(u32::MAX as f32) as u32
+    };
+    let size = (
+        scaled(target_width, width_fraction),
+        scaled(target_height, height_fraction),
+    );
+    // Sizes so far are in the rotated frame; the decoder wants source axes.
+    match framing {
+        Some(framing) if framing.swaps_axes() => (size.1, size.0),
+        _ => size,
+    }
+}
+
+fn resample_rgb(
+    rgb: &[u8],
+    src_width: usize,
+    src_height: usize,
+    region: Option<SourceRegion>,
+    target_width: usize,
+    target_height: usize,
+    source_path: &str,
+) -> Result<Vec<u32>, ApplicationError> {
     if src_width == 0 || src_height == 0 {
         return Err(ApplicationError::Decode(format!(
             "empty image dimensions for source path: {}",
             source_path
         )));
     }
+    let (left, top, region_width, region_height) = match region {
+        Some(region) => {
+            region
+                .validate()
+                .map_err(|error| ApplicationError::InvalidInput(error.to_string()))?;
+            region.pixel_bounds(src_width, src_height)
+        }
+        None => (0, 0, src_width, src_height),
+    };
 
     let mut pixels = vec![0_u32; target_width * target_height];
     for y in 0..target_height {
-        let src_y = y * src_height / target_height;
+        let src_y = top + y * region_height / target_height;
         for x in 0..target_width {
-            let src_x = x * src_width / target_width;
-            let pixel = source.get_pixel(src_x as u32, src_y as u32);
-            let [red, green, blue] = pixel.0;
+            let src_x = left + x * region_width / target_width;
+            let offset = (src_y * src_width + src_x) * 3;
+            let (red, green, blue) = (rgb[offset], rgb[offset + 1], rgb[offset + 2]);
             pixels[y * target_width + x] =
                 ((red as u32) << 16) | ((green as u32) << 8) | (blue as u32);
         }
@@ -571,6 +1148,46 @@ fn decode_source_pixels(
     Ok(pixels)
 }
 
+// Rotated and cropped sampling. `region` is a viewport into the framed image
+// here, so zooming works the same with or without geometry.
+fn resample_framed(
+    rgb: &[u8],
+    src_width: usize,
+    src_height: usize,
+    region: Option<SourceRegion>,
+    framing: Framing,
+    target_width: usize,
+    target_height: usize,
+) -> Result<Vec<u32>, ApplicationError> {
+    let region = match region {
+        Some(region) => {
+            region
+                .validate()
+                .map_err(|error| ApplicationError::InvalidInput(error.to_string()))?;
+            region
+        }
+        None => SourceRegion {
+            left: 0.0,
+            top: 0.0,
+            width: 1.0,
+            height: 1.0,
+        },
+    };
+
+    let mut pixels = vec![0_u32; target_width * target_height];
+    for y in 0..target_height {
+        let v = region.top + (y as f32 + 0.5) / target_height as f32 * region.height;
+        for x in 0..target_width {
+            let u = region.left + (x as f32 + 0.5) / target_width as f32 * region.width;
+            let (src_x, src_y) = framing.source_pixel(u, v, src_width, src_height);
+            let offset = (src_y * src_width + src_x) * 3;
+            pixels[y * target_width + x] = pack_rgb(rgb[offset], rgb[offset + 1], rgb[offset + 2]);
+        }
+    }
+    Ok(pixels)
+}
+
+#[cfg(feature = "gpu")]
 fn source_pixels_as_le_bytes(pixels: &[u32]) -> Vec<u8> {
     let mut bytes = Vec::with_capacity(pixels.len() * 4);
     for pixel in pixels {
@@ -579,50 +1196,6 @@ fn source_pixels_as_le_bytes(pixels: &[u32]) -> Vec<u8> {
     bytes
 }
 
-fn apply_exposure_contrast(pixels: &mut [u32], exposure: f32, contrast: f32) {
-    let exposure_gain = 2_f32.powf(exposure.clamp(-5.0, 5.0));
-    let contrast_factor = 1.0 + contrast.clamp(-5.0, 5.0) * 0.12;
-
-    for pixel in pixels.iter_mut() {
-        let [mut r, mut g, mut b] = unpack_rgb(*pixel);
-        r = apply_exposure_and_contrast_channel(r, exposure_gain, contrast_factor);
-        g = apply_exposure_and_contrast_channel(g, exposure_gain, contrast_factor);
-        b = apply_exposure_and_contrast_channel(b, exposure_gain, contrast_factor);
-        *pixel = pack_rgb(r, g, b);
-    }
-}
-
-fn apply_temperature_tint(pixels: &mut [u32], temperature: f32, tint: f32) {
-    let temp = temperature.clamp(-5.0, 5.0) * 0.035;
-    let tint_shift = tint.clamp(-5.0, 5.0) * 0.035;
-
-    for pixel in pixels.iter_mut() {
-        let [r, g, b] = unpack_rgb(*pixel);
-        let red = (r as f32 / 255.0 + temp).clamp(0.0, 1.0);
-        let blue = (b as f32 / 255.0 - temp).clamp(0.0, 1.0);
-        let green = (g as f32 / 255.0 + tint_shift).clamp(0.0, 1.0);
-        *pixel = pack_rgb(
-            (red * 255.0).round() as u8,
-            (green * 255.0).round() as u8,
-            (blue * 255.0).round() as u8,
-        );
-    }
-}
-
-fn apply_highlights_shadows(pixels: &mut [u32], highlights: f32, shadows: f32) {
-    let highlights_strength = highlights.clamp(-5.0, 5.0) * 0.08;
-    let shadows_strength = shadows.clamp(-5.0, 5.0) * 0.08;
-
-    for pixel in pixels.iter_mut() {
-        let [r, g, b] = unpack_rgb(*pixel);
-        *pixel = pack_rgb(
-            apply_highlights_shadows_channel(r, highlights_strength, shadows_strength),
-            apply_highlights_shadows_channel(g, highlights_strength, shadows_strength),
-            apply_highlights_shadows_channel(b, highlights_strength, shadows_strength),
-        );
-    }
-}
-
 fn black_box_checksum(pixels: &[u32]) {
     let checksum = pixels
         .iter()
@@ -630,6 +1203,7 @@ fn black_box_checksum(pixels: &[u32]) {
     std::hint::black_box(checksum);
 }
 
+#[cfg(feature = "gpu")]
 fn black_box_bytes(bytes: &[u8]) {
     let checksum = bytes
         .iter()
@@ -637,8 +1211,10 @@ fn black_box_bytes(bytes: &[u8]) {
     std::hint::black_box(checksum);
 }
 
-fn pack_gpu_params(request: PreviewRequest, render_width: u32, pixel_count: u32) -> [u8; 32] {
-    let mut out = [0_u8; 32];
+#[cfg(feature = "gpu")]
+fn pack_gpu_params(request: &PreviewRequest, render_width: u32, pixel_count: u32) -> [u8; 48] {
+    // Uniform structs round up to 16 bytes; the last 4 bytes are padding.
+    let mut out = [0_u8; 48];
     out[0..4].copy_from_slice(&pixel_count.to_le_bytes());
     out[4..8].copy_from_slice(&render_width.to_le_bytes());
     out[8..12].copy_from_slice(&request.params.exposure.to_le_bytes());
@@ -647,24 +1223,43 @@ fn pack_gpu_params(request: PreviewRequest, render_width: u32, pixel_count: u32)
     out[20..24].copy_from_slice(&request.params.tint.to_le_bytes());
     out[24..28].copy_from_slice(&request.params.highlights.to_le_bytes());
     out[28..32].copy_from_slice(&request.params.shadows.to_le_bytes());
+    out[32..36].copy_from_slice(&request.params.saturation.to_le_bytes());
+    out[36..40].copy_from_slice(&request.params.vibrance.to_le_bytes());
+    out[40..44].copy_from_slice(&request.params.grain.to_le_bytes());
     out
 }
 
-fn render_target(width: usize, height: usize) -> Result<(usize, usize, usize), ApplicationError> {
+// Identity curves still upload their LUTs so the shader never branches.
+#[cfg(feature = "gpu")]
+fn pack_curve_luts(request: &PreviewRequest) -> Vec<u8> {
+    let params = &request.params;
+    let [red, green, blue] = params.channel_curves.luts();
+    [red, green, blue, params.tone_curve.lut()]
+        .iter()
+        .flatten()
+        .flat_map(|&value| u32::from(value).to_le_bytes())
+        .collect()
+}
+
+fn render_target(
+    width: usize,
+    height: usize,
+    max_pixels: usize,
+) -> Result<(usize, usize, usize), ApplicationError> {
     let requested_pixels = width
         .checked_mul(height)
         .ok_or_else(|| ApplicationError::InvalidInput("preview dimensions overflow".to_string()))?;
-    if requested_pixels <= MAX_RENDER_PIXELS {
+    if requested_pixels <= max_pixels {
         return Ok((width, height, requested_pixels));
     }
 
-    let scale = (MAX_RENDER_PIXELS as f64 / requested_pixels as f64).sqrt();
+    let scale = (max_pixels as f64 / requested_pixels as f64).sqrt();
     let render_width = ((width as f64 * scale).floor() as usize).max(1);
     let render_height = ((height as f64 * scale).floor() as usize).max(1);
     let pixel_count = render_width
         .checked_mul(render_height)
         .ok_or_else(|| ApplicationError::InvalidInput("preview dimensions overflow".to_string()))?;
-    Ok((render_width, render_height, pixel_count.min(MAX_RENDER_PIXELS)))
+    Ok((render_width, render_height, pixel_count.min(max_pixels)))
 }
 
 fn unpack_rgb(pixel: u32) -> [u8; 3] {
@@ -679,29 +1274,19 @@ fn pack_rgb(red: u8, green: u8, blue: u8) -> u32 {
     ((red as u32) << 16) | ((green as u32) << 8) | (blue as u32)
 }
 
-fn apply_exposure_and_contrast_channel(channel: u8, exposure_gain: f32, contrast_factor: f32) -> u8 {
-    let normalized = channel as f32 / 255.0;
-    let exposed = normalized * exposure_gain;
-    let contrasted = ((exposed - 0.5) * contrast_factor + 0.5).clamp(0.0, 1.0);
-    (contrasted * 255.0).round() as u8
-}
-
-fn apply_highlights_shadows_channel(channel: u8, highlights_strength: f32, shadows_strength: f32) -> u8 {
-    let value = channel as f32 / 255.0;
-    let highlight_component = (value - 0.5).max(0.0) * highlights_strength;
-    let shadow_component = (0.5 - value).max(0.0) * shadows_strength;
-    let adjusted = (value + shadow_component - highlight_component).clamp(0.0, 1.0);
-    (adjusted * 255.0).round() as u8
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use image::{ImageBuffer, Rgb};
-    use lite_room_domain::{EditParams, ImageId};
+    use lite_room_domain::{
+        CropRect, DecodedBuffer, EditField, EditParams, ImageId, ViewRegion,
+        DEFAULT_SPLIT_FRACTION, MAX_VIEW_ZOOM,
+    };
     use tempfile::tempdir;
     use std::time::{Duration, Instant};
 
+    use crate::test_support::{assert_render_matches, golden_path, gradient_request};
+
     fn write_test_jpeg(dir: &tempfile::TempDir) -> String {
         let path = dir.path().join("preview.jpg");
         let pixels = ImageBuffer::from_pixel(8, 8, Rgb([120_u8, 80_u8, 40_u8]));
@@ -725,9 +1310,15 @@ mod tests {
                 .submit_preview(PreviewRequest {
                     image_id,
                     source_path: source_path.clone(),
+                    source_pixels: None,
+                    source_region: None,
+                    view_region: None,
                     params,
                     target_width: 1200,
                     target_height: 800,
+                    want_histogram: false,
+                    comparison: None,
+                    split_fraction: DEFAULT_SPLIT_FRACTION,
                 })
                 .expect("submit preview");
         }
@@ -750,18 +1341,884 @@ mod tests {
         assert_eq!(metrics.completed_jobs, 1);
     }
 
+    #[test]
+    fn render_failure_reports_image_id() {
+        let pipeline =
+            BackgroundPreviewPipeline::with_backend(PreviewBackend::Cpu).expect("cpu pipeline");
+        let temp = tempdir().expect("tempdir");
+        pipeline
+            .submit_preview(PreviewRequest {
+                image_id: ImageId::new(42).expect("id"),
+                source_path: temp
+                    .path()
+                    .join("missing.jpg")
+                    .to_string_lossy()
+                    .to_string(),
+                source_pixels: None,
+                source_region: None,
+                view_region: None,
+                params: EditParams::default(),
+                target_width: 16,
+                target_height: 16,
+                want_histogram: false,
+                comparison: None,
+                split_fraction: DEFAULT_SPLIT_FRACTION,
+            })
+            .expect("submit preview");
+
+        let deadline = Instant::now() + Duration::from_millis(600);
+        let error = loop {
+            match pipeline.try_receive_preview() {
+                Ok(None) => {}
+                Ok(Some(frame)) => panic!("unexpected frame for missing file: {frame:?}"),
+                Err(error) => break error,
+            }
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for preview error"
+            );
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert!(
+            error
+                .to_string()
+                .contains("preview render failed for image 42: "),
+            "unexpected error: {error}"
+        );
+    }
+
+    #[test]
+    fn worker_fills_histogram_only_when_requested() {
+        let pipeline =
+            BackgroundPreviewPipeline::with_backend(PreviewBackend::Cpu).expect("cpu pipeline");
+        let source = Arc::new(DecodedBuffer {
+            width: 4,
+            height: 4,
+            rgb: [200_u8, 100, 32].repeat(16),
+        });
+        let receive = |want_histogram: bool| {
+            pipeline
+                .submit_preview(PreviewRequest {
+                    image_id: ImageId::new(1).expect("id"),
+                    source_path: String::new(),
+                    source_pixels: Some(Arc::clone(&source)),
+                    source_region: None,
+                    view_region: None,
+                    params: EditParams::default(),
+                    target_width: 8,
+                    target_height: 8,
+                    want_histogram,
+                    comparison: None,
+                    split_fraction: DEFAULT_SPLIT_FRACTION,
+                })
+                .expect("submit preview");
+            let deadline = Instant::now() + Duration::from_millis(600);
+            loop {
+                if let Some(frame) = pipeline.try_receive_preview().expect("receive") {
+                    break frame;
+                }
+                assert!(Instant::now() < deadline, "timed out waiting for frame");
+                thread::sleep(Duration::from_millis(5));
+            }
+        };
+
+        let frame = receive(true);
+        let histogram = frame.histogram.expect("requested histogram");
+        let pixel_count = frame.pixels.len() as u32;
+        assert_eq!(histogram.red[200], pixel_count);
+        assert_eq!(histogram.green[100], pixel_count);
+        assert_eq!(histogram.blue[32], pixel_count);
+        assert_eq!(histogram.luma[116], pixel_count);
+
+        assert_eq!(receive(false).histogram, None);
+    }
+
+    #[derive(Default)]
+    struct ThreadNameRenderer {
+        thread_names: Mutex<Vec<Option<String>>>,
+    }
+
+    impl PreviewRenderer for ThreadNameRenderer {
+        fn render(&self, _request: PreviewRequest) -> Result<RenderedPreview, ApplicationError> {
+            let name = thread::current().name().map(str::to_string);
+            self.thread_names.lock().expect("names lock").push(name);
+            Ok(RenderedPreview {
+                width: 1,
+                height: 1,
+                pixels: vec![0],
+            })
+        }
+
+        fn backend_info(&self) -> PreviewBackendInfo {
+            PreviewBackendInfo {
+                backend: PreviewBackend::Cpu,
+                adapter_name: "thread-name".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn worker_thread_is_named() {
+        let renderer = Arc::new(ThreadNameRenderer::default());
+        let pipeline = BackgroundPreviewPipeline::with_shared_renderer(
+            renderer.clone(),
+            DEFAULT_PREVIEW_MAX_PIXELS,
+        );
+        pipeline
+            .submit_preview(PreviewRequest {
+                image_id: ImageId::new(1).expect("id"),
+                source_path: "unused.jpg".to_string(),
+                source_pixels: None,
+                source_region: None,
+                view_region: None,
+                params: EditParams::default(),
+                target_width: 1,
+                target_height: 1,
+                want_histogram: false,
+                comparison: None,
+                split_fraction: DEFAULT_SPLIT_FRACTION,
+            })
+            .expect("submit preview");
+
+        let deadline = Instant::now() + Duration::from_millis(600);
+        while pipeline.try_receive_preview().expect("poll").is_none() {
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for preview frame"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let names = renderer.thread_names.lock().expect("names lock");
+        assert_eq!(names.as_slice(), &[Some(PREVIEW_THREAD_NAME.to_string())]);
+    }
+
+    #[derive(Default)]
+    struct SlowRenderer {
+        started: AtomicU64,
+    }
+
+    impl PreviewRenderer for SlowRenderer {
+        fn render(&self, _request: PreviewRequest) -> Result<RenderedPreview, ApplicationError> {
+            self.started.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            Ok(RenderedPreview {
+                width: 1,
+                height: 1,
+                pixels: vec![0],
+            })
+        }
+
+        fn backend_info(&self) -> PreviewBackendInfo {
+            PreviewBackendInfo {
+                backend: PreviewBackend::Cpu,
+                adapter_name: "slow".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn shutdown_cancels_pending_jobs_and_joins_the_worker() {
+        let renderer = Arc::new(SlowRenderer::default());
+        let pipeline = BackgroundPreviewPipeline::with_shared_renderer(
+            renderer.clone(),
+            DEFAULT_PREVIEW_MAX_PIXELS,
+        );
+        let submit = |pipeline: &BackgroundPreviewPipeline| {
+            pipeline
+                .submit_preview(PreviewRequest {
+                    image_id: ImageId::new(1).expect("id"),
+                    source_path: "unused.jpg".to_string(),
+                    source_pixels: None,
+                    source_region: None,
+                    view_region: None,
+                    params: EditParams::default(),
+                    target_width: 1,
+                    target_height: 1,
+                    want_histogram: false,
+                    comparison: None,
+                    split_fraction: DEFAULT_SPLIT_FRACTION,
+                })
+                .expect("submit preview");
+        };
+        submit(&pipeline);
+        let deadline = Instant::now() + Duration::from_millis(600);
+        while renderer.started.load(Ordering::SeqCst) == 0 {
+            assert!(Instant::now() < deadline, "render never started");
+            thread::sleep(Duration::from_millis(1));
+        }
+        for _ in 0..4 {
+            submit(&pipeline);
+        }
+
+        let metrics = pipeline.shutdown().expect("shutdown");
+        assert_eq!(metrics.submitted_jobs, 5);
+        assert_eq!(metrics.completed_jobs, 0);
+        assert_eq!(metrics.canceled_jobs, 5);
+        assert_eq!(renderer.started.load(Ordering::SeqCst), 1);
+        // The worker's clone of the renderer went away with its thread.
+        assert_eq!(Arc::strong_count(&renderer), 1);
+    }
+
+    #[test]
+    fn repeating_a_request_is_served_from_the_render_cache() {
+        let renderer = Arc::new(SlowRenderer::default());
+        let pipeline = BackgroundPreviewPipeline::with_shared_renderer(
+            renderer.clone(),
+            DEFAULT_PREVIEW_MAX_PIXELS,
+        );
+        let request = |exposure: f32| PreviewRequest {
+            image_id: ImageId::new(1).expect("id"),
+            source_path: "unused.jpg".to_string(),
+            source_pixels: None,
+            source_region: None,
+            view_region: None,
+            params: EditParams::default().with_exposure(exposure),
+            target_width: 1,
+            target_height: 1,
+            want_histogram: true,
+            comparison: None,
+            split_fraction: DEFAULT_SPLIT_FRACTION,
+        };
+        let receive = || {
+            let deadline = Instant::now() + Duration::from_millis(600);
+            loop {
+                if let Some(frame) = pipeline.try_receive_preview().expect("poll") {
+                    break frame;
+                }
+                assert!(
+                    Instant::now() < deadline,
+                    "timed out waiting for preview frame"
+                );
+                thread::sleep(Duration::from_millis(5));
+            }
+        };
+
+        for exposure in [0.5, 1.0, 0.5] {
+            pipeline
+                .submit_preview(request(exposure))
+                .expect("submit preview");
+            let frame = receive();
+            assert_eq!(frame.pixels, vec![0]);
+            assert!(frame.histogram.is_some());
+        }
+
+        assert_eq!(renderer.started.load(Ordering::SeqCst), 2);
+        let metrics = pipeline.metrics().expect("metrics");
+        assert_eq!(metrics.completed_jobs, 3);
+        assert_eq!(metrics.cache_hits, 1);
+    }
+
+    #[test]
+    fn four_workers_deliver_only_the_latest_of_a_burst() {
+        let renderer = Arc::new(SlowRenderer::default());
+        let pipeline =
+            BackgroundPreviewPipeline::spawn(renderer.clone(), DEFAULT_PREVIEW_MAX_PIXELS, 4);
+        assert_eq!(pipeline.workers.len(), 4);
+        for exposure in 0..8 {
+            pipeline
+                .submit_preview(PreviewRequest {
+                    image_id: ImageId::new(1).expect("id"),
+                    source_path: "unused.jpg".to_string(),
+                    source_pixels: None,
+                    source_region: None,
+                    view_region: None,
+                    params: EditParams::default().with_exposure(exposure as f32 * 0.1),
+                    target_width: 1,
+                    target_height: 1,
+                    want_histogram: false,
+                    comparison: None,
+                    split_fraction: DEFAULT_SPLIT_FRACTION,
+                })
+                .expect("submit preview");
+        }
+
+        let deadline = Instant::now() + Duration::from_millis(600);
+        let frame = loop {
+            if let Some(frame) = pipeline.try_receive_preview().expect("poll") {
+                break frame;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for preview frame"
+            );
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(frame.sequence, 8);
+
+        let metrics = pipeline.shutdown().expect("shutdown");
+        assert_eq!(metrics.submitted_jobs, 8);
+        assert_eq!(metrics.completed_jobs, 1);
+        assert_eq!(metrics.canceled_jobs, 7);
+        assert_eq!(metrics.dropped_frames, 0);
+    }
+
+    struct SolidFillRenderer {
+        fill: u32,
+        missing_pixels: usize,
+    }
+
+    impl PreviewRenderer for SolidFillRenderer {
+        fn render(&self, request: PreviewRequest) -> Result<RenderedPreview, ApplicationError> {
+            let pixel_count = (request.target_width * request.target_height) as usize;
+            Ok(RenderedPreview {
+                width: request.target_width,
+                height: request.target_height,
+                pixels: vec![self.fill; pixel_count - self.missing_pixels],
+            })
+        }
+
+        fn backend_info(&self) -> PreviewBackendInfo {
+            PreviewBackendInfo {
+                backend: PreviewBackend::Gpu,
+                adapter_name: "solid-fill".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn custom_renderer_frames_are_delivered_and_size_checked() {
+        let receive = |renderer: SolidFillRenderer| {
+            let pipeline = BackgroundPreviewPipeline::with_renderer(Box::new(renderer));
+            pipeline
+                .submit_preview(PreviewRequest {
+                    image_id: ImageId::new(5).expect("id"),
+                    source_path: "unused.jpg".to_string(),
+                    source_pixels: None,
+                    source_region: None,
+                    view_region: None,
+                    params: EditParams::default(),
+                    target_width: 6,
+                    target_height: 4,
+                    want_histogram: true,
+                    comparison: None,
+                    split_fraction: DEFAULT_SPLIT_FRACTION,
+                })
+                .expect("submit preview");
+            let deadline = Instant::now() + Duration::from_millis(600);
+            loop {
+                if let Some(frame) = pipeline.try_receive_preview().transpose() {
+                    break (frame, pipeline.backend_info().expect("backend"));
+                }
+                assert!(
+                    Instant::now() < deadline,
+                    "timed out waiting for preview frame"
+                );
+                thread::sleep(Duration::from_millis(10));
+            }
+        };
+
+        let (frame, backend) = receive(SolidFillRenderer {
+            fill: 0x0020_4060,
+            missing_pixels: 0,
+        });
+        let frame = frame.expect("solid frame");
+        assert_eq!(backend.adapter_name, "solid-fill");
+        assert_eq!((frame.width, frame.height), (6, 4));
+        assert_eq!(frame.pixels, vec![0x0020_4060; 24]);
+        assert_eq!(frame.histogram.expect("histogram").red[0x20], 24);
+
+        let (short, _) = receive(SolidFillRenderer {
+            fill: 0,
+            missing_pixels: 1,
+        });
+        let error = short.expect_err("short frame is rejected");
+        assert!(error.to_string().contains("image 5"), "{error}");
+    }
+
+    #[derive(Default)]
+    struct FailingGpuRenderer {
+        calls: Mutex<u32>,
+    }
+
+    impl PreviewRenderer for FailingGpuRenderer {
+        fn render(&self, _request: PreviewRequest) -> Result<RenderedPreview, ApplicationError> {
+            *self.calls.lock().expect("calls lock") += 1;
+            Err(ApplicationError::Io("device lost".to_string()))
+        }
+
+        fn backend_info(&self) -> PreviewBackendInfo {
+            PreviewBackendInfo {
+                backend: PreviewBackend::Gpu,
+                adapter_name: "flaky-gpu".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_gpu_failures_switch_the_pipeline_to_cpu() {
+        let renderer = Arc::new(FailingGpuRenderer::default());
+        let pipeline = BackgroundPreviewPipeline::with_shared_renderer(
+            renderer.clone(),
+            DEFAULT_PREVIEW_MAX_PIXELS,
+        );
+        let source = Arc::new(DecodedBuffer {
+            width: 4,
+            height: 4,
+            rgb: [60_u8, 90, 120].repeat(16),
+        });
+        let receive = || {
+            pipeline
+                .submit_preview(PreviewRequest {
+                    image_id: ImageId::new(3).expect("id"),
+                    source_path: String::new(),
+                    source_pixels: Some(Arc::clone(&source)),
+                    source_region: None,
+                    view_region: None,
+                    params: EditParams::default(),
+                    target_width: 4,
+                    target_height: 4,
+                    want_histogram: false,
+                    comparison: None,
+                    split_fraction: DEFAULT_SPLIT_FRACTION,
+                })
+                .expect("submit preview");
+            let deadline = Instant::now() + Duration::from_millis(600);
+            loop {
+                if let Some(result) = pipeline.try_receive_preview().transpose() {
+                    break result;
+                }
+                assert!(Instant::now() < deadline, "timed out waiting for frame");
+                thread::sleep(Duration::from_millis(5));
+            }
+        };
+
+        for _ in 1..GPU_FAILURE_LIMIT {
+            assert!(receive().is_err());
+        }
+        assert!(!pipeline.metrics().expect("metrics").gpu_disabled);
+
+        let fallback = receive().expect("failing job is retried on cpu");
+        assert_eq!(fallback.pixels, vec![0x003C_5A78; 16]);
+        assert!(receive().is_ok());
+        assert!(receive().is_ok());
+
+        assert_eq!(
+            *renderer.calls.lock().expect("calls lock"),
+            GPU_FAILURE_LIMIT
+        );
+        let metrics = pipeline.metrics().expect("metrics");
+        assert!(metrics.gpu_disabled);
+        assert_eq!(metrics.gpu_failures, u64::from(GPU_FAILURE_LIMIT));
+        assert_eq!(
+            pipeline.backend_info().expect("backend").backend,
+            PreviewBackend::Cpu
+        );
+    }
+
+    #[test]
+    fn small_pixel_ceiling_downscales_frames_with_the_same_aspect() {
+        let pipeline =
+            BackgroundPreviewPipeline::with_backend_and_max_pixels(PreviewBackend::Cpu, 100)
+                .expect("cpu pipeline");
+        pipeline
+            .submit_preview(PreviewRequest {
+                image_id: ImageId::new(1).expect("id"),
+                source_path: String::new(),
+                source_pixels: Some(Arc::new(DecodedBuffer {
+                    width: 4,
+                    height: 2,
+                    rgb: [10_u8, 20, 30].repeat(8),
+                })),
+                source_region: None,
+                view_region: None,
+                params: EditParams::default(),
+                target_width: 40,
+                target_height: 20,
+                want_histogram: false,
+                comparison: None,
+                split_fraction: DEFAULT_SPLIT_FRACTION,
+            })
+            .expect("submit preview");
+
+        let deadline = Instant::now() + Duration::from_millis(600);
+        let frame = loop {
+            if let Some(frame) = pipeline.try_receive_preview().expect("receive") {
+                break frame;
+            }
+            assert!(Instant::now() < deadline, "timed out waiting for frame");
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!((frame.width, frame.height), (14, 7));
+        assert_eq!(frame.pixels.len(), 98);
+        assert_eq!(
+            render_target(40, 20, DEFAULT_PREVIEW_MAX_PIXELS).expect("target"),
+            (40, 20, 800)
+        );
+    }
+
+    #[test]
+    fn cpu_renderer_applies_params_to_solid_color() {
+        let temp = tempdir().expect("tempdir");
+        let path = temp.path().join("solid.jpg");
+        ImageBuffer::from_pixel(16, 16, Rgb([128_u8, 128_u8, 128_u8]))
+            .save(&path)
+            .expect("save jpeg");
+
+        let rendered = CpuPreviewRenderer::default()
+            .render(PreviewRequest {
+                image_id: ImageId::new(1).expect("id"),
+                source_path: path.to_string_lossy().to_string(),
+                source_pixels: None,
+                source_region: None,
+                view_region: None,
+                params: EditParams {
+                    exposure: -1.0,
+                    temperature: 1.0,
+                    ..EditParams::default()
+                },
+                target_width: 4,
+                target_height: 4,
+                want_histogram: false,
+                comparison: None,
+                split_fraction: DEFAULT_SPLIT_FRACTION,
+            })
+            .expect("render");
+
+        assert_eq!((rendered.width, rendered.height), (4, 4));
+        assert!(rendered
+            .pixels
+            .iter()
+            .all(|pixel| *pixel == pack_rgb(73, 64, 55)));
+    }
+
+    #[test]
+    fn disabled_contrast_renders_like_zero_contrast() {
+        let temp = tempdir().expect("tempdir");
+        let source_path = write_test_jpeg(&temp);
+        let render = |params: EditParams| {
+            CpuPreviewRenderer::default()
+                .render(PreviewRequest {
+                    image_id: ImageId::new(1).expect("id"),
+                    source_path: source_path.clone(),
+                    source_pixels: None,
+                    source_region: None,
+                    view_region: None,
+                    params,
+                    target_width: 8,
+                    target_height: 8,
+                    want_histogram: false,
+                    comparison: None,
+                    split_fraction: DEFAULT_SPLIT_FRACTION,
+                })
+                .expect("render")
+        };
+
+        let mut disabled = EditParams {
+            contrast: 3.0,
+            ..EditParams::default()
+        };
+        disabled.set_enabled(EditField::Contrast, false);
+
+        assert_eq!(render(disabled), render(EditParams::default()));
+        assert_ne!(
+            render(EditParams {
+                contrast: 3.0,
+                ..EditParams::default()
+            }),
+            render(EditParams::default())
+        );
+        assert_eq!(disabled.contrast, 3.0);
+    }
+
+    #[test]
+    fn embedded_source_pixels_skip_file_decode() {
+        let rendered = CpuPreviewRenderer::default()
+            .render(PreviewRequest {
+                image_id: ImageId::new(1).expect("id"),
+                source_path: "/definitely/missing/source.jpg".to_string(),
+                source_pixels: Some(Arc::new(DecodedBuffer {
+                    width: 2,
+                    height: 2,
+                    rgb: [10_u8, 20, 30].repeat(4),
+                })),
+                source_region: None,
+                view_region: None,
+                params: EditParams::default(),
+                target_width: 4,
+                target_height: 4,
+                want_histogram: false,
+                comparison: None,
+                split_fraction: DEFAULT_SPLIT_FRACTION,
+            })
+            .expect("render from embedded pixels");
+
+        assert_eq!(rendered.pixels, vec![pack_rgb(10, 20, 30); 16]);
+    }
+
+    #[test]
+    fn quarter_turn_swaps_rendered_width_and_height() {
+        // Columns a b c d over e f g h, one gray level per pixel.
+        let source = Arc::new(DecodedBuffer {
+            width: 4,
+            height: 2,
+            rgb: (0..8_u8).flat_map(|level| [level * 30; 3]).collect(),
+        });
+        let request = |params: EditParams| PreviewRequest {
+            image_id: ImageId::new(1).expect("id"),
+            source_path: "/definitely/missing/source.jpg".to_string(),
+            source_pixels: Some(Arc::clone(&source)),
+            source_region: None,
+            view_region: None,
+            params,
+            target_width: 4,
+            target_height: 2,
+            want_histogram: false,
+            comparison: None,
+            split_fraction: DEFAULT_SPLIT_FRACTION,
+        };
+        let gray = |levels: &[u8]| -> Vec<u32> {
+            levels
+                .iter()
+                .map(|&level| pack_rgb(level * 30, level * 30, level * 30))
+                .collect()
+        };
+
+        let rotated = CpuPreviewRenderer::default()
+            .render(request(EditParams {
+                rotation_degrees: 90.0,
+                ..EditParams::default()
+            }))
+            .expect("render rotated");
+        assert_eq!((rotated.width, rotated.height), (2, 4));
+        assert_eq!(rotated.pixels, gray(&[4, 0, 5, 1, 6, 2, 7, 3]));
+
+        let cropped = CpuPreviewRenderer::default()
+            .render(request(EditParams {
+                rotation_degrees: -90.0,
+                crop: Some(CropRect {
+                    x: 0.0,
+                    y: 0.5,
+                    width: 1.0,
+                    height: 0.5,
+                }),
+                ..EditParams::default()
+            }))
+            .expect("render rotated crop");
+        assert_eq!((cropped.width, cropped.height), (2, 2));
+        assert_eq!(cropped.pixels, gray(&[1, 5, 0, 4]));
+    }
+
+    #[test]
+    fn default_params_return_decoded_pixels_without_adjustment_pass() {
+        let request = gradient_request(EditParams::default());
+        let decoded = decode_source_pixels(
+            &request,
+            request.target_width as usize,
+            request.target_height as usize,
+        )
+        .expect("decode source");
+        let passes_before = ADJUSTMENT_PASSES.with(|passes| passes.get());
+
+        let rendered = CpuPreviewRenderer::default()
+            .render(request)
+            .expect("render default");
+        assert_eq!(rendered.pixels, decoded);
+        assert_eq!(ADJUSTMENT_PASSES.with(|passes| passes.get()), passes_before);
+
+        CpuPreviewRenderer::default()
+            .render(gradient_request(EditParams::default().with_exposure(0.5)))
+            .expect("render edited");
+        assert_eq!(
+            ADJUSTMENT_PASSES.with(|passes| passes.get()),
+            passes_before + 1
+        );
+    }
+
+    #[test]
+    fn cropped_quadrant_matches_full_render_quadrant() {
+        let params = EditParams::default()
+            .with_exposure(0.7)
+            .with_contrast(-0.4)
+            .with_shadows(1.5);
+        let full_request = gradient_request(params);
+        let full_width = full_request.target_width as usize;
+        let full_height = full_request.target_height as usize;
+        let full = CpuPreviewRenderer::default()
+            .render(full_request)
+            .expect("full render");
+
+        let (half_width, half_height) = (full_width / 2, full_height / 2);
+        let cropped = CpuPreviewRenderer::default()
+            .render(PreviewRequest {
+                source_region: Some(SourceRegion {
+                    left: 0.5,
+                    top: 0.5,
+                    width: 0.5,
+                    height: 0.5,
+                }),
+                target_width: half_width as u32,
+                target_height: half_height as u32,
+                ..gradient_request(params)
+            })
+            .expect("cropped render");
+
+        let quadrant: Vec<u32> = (half_height..full_height)
+            .flat_map(|y| (half_width..full_width).map(move |x| (x, y)))
+            .map(|(x, y)| full.pixels[y * full_width + x])
+            .collect();
+        assert_eq!(cropped.pixels, quadrant);
+
+        let outside = CpuPreviewRenderer::default().render(PreviewRequest {
+            source_region: Some(SourceRegion {
+                left: 0.75,
+                top: 0.0,
+                width: 0.5,
+                height: 1.0,
+            }),
+            ..gradient_request(params)
+        });
+        assert!(matches!(outside, Err(ApplicationError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn centered_double_zoom_samples_the_central_quarter() {
+        let params = EditParams::default().with_exposure(0.4);
+        let full_request = gradient_request(params);
+        let full_width = full_request.target_width as usize;
+        let full_height = full_request.target_height as usize;
+        let full = CpuPreviewRenderer::default()
+            .render(full_request)
+            .expect("full render");
+
+        let (half_width, half_height) = (full_width / 2, full_height / 2);
+        let zoomed = CpuPreviewRenderer::default()
+            .render(PreviewRequest {
+                view_region: Some(ViewRegion {
+                    zoom: 2.0,
+                    ..ViewRegion::FIT
+                }),
+                target_width: half_width as u32,
+                target_height: half_height as u32,
+                ..gradient_request(params)
+            })
+            .expect("zoomed render");
+
+        let (left, top) = (full_width / 4, full_height / 4);
+        let quarter: Vec<u32> = (top..top + half_height)
+            .flat_map(|y| (left..left + half_width).map(move |x| (x, y)))
+            .map(|(x, y)| full.pixels[y * full_width + x])
+            .collect();
+        assert_eq!(zoomed.pixels, quarter);
+
+        let too_close = CpuPreviewRenderer::default().render(PreviewRequest {
+            view_region: Some(ViewRegion {
+                zoom: MAX_VIEW_ZOOM * 2.0,
+                ..ViewRegion::FIT
+            }),
+            ..gradient_request(params)
+        });
+        assert!(matches!(too_close, Err(ApplicationError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn comparison_keeps_unedited_source_on_the_leading_side_of_the_split() {
+        let params = EditParams::default().with_exposure(0.8).with_vibrance(1.0);
+        let request = gradient_request(params);
+        let width = request.target_width as usize;
+        let height = request.target_height as usize;
+        let original = decode_source_pixels(&request, width, height).expect("decode source");
+        let edited = CpuPreviewRenderer::default()
+            .render(request)
+            .expect("edited render");
+        let compare = |comparison, split_fraction| {
+            CpuPreviewRenderer::default()
+                .render(PreviewRequest {
+                    comparison: Some(comparison),
+                    split_fraction,
+                    ..gradient_request(params)
+                })
+                .expect("comparison render")
+                .pixels
+        };
+
+        let vertical = compare(ComparisonMode::SplitVertical, 0.25);
+        let split_x = (width as f32 * 0.25).round() as usize;
+        for (index, pixel) in vertical.iter().enumerate() {
+            let expected = if index % width < split_x {
+                original[index]
+            } else {
+                edited.pixels[index]
+            };
+            assert_eq!(*pixel, expected, "pixel {index}");
+        }
+
+        let horizontal = compare(ComparisonMode::SplitHorizontal, 0.5);
+        let split_index = (height as f32 * 0.5).round() as usize * width;
+        assert_eq!(horizontal[..split_index], original[..split_index]);
+        assert_eq!(horizontal[split_index..], edited.pixels[split_index..]);
+
+        assert_eq!(compare(ComparisonMode::FullOriginal, 0.5), original);
+    }
+
     #[test]
     fn renderer_rejects_zero_dimensions() {
-        let renderer = CpuStageRenderer;
+        let renderer = CpuPreviewRenderer::default();
         let image_id = ImageId::new(1).expect("id");
         let result = renderer.render(PreviewRequest {
             image_id,
             source_path: "ignored.jpg".to_string(),
+            source_pixels: None,
+            source_region: None,
+            view_region: None,
             params: EditParams::default(),
             target_width: 0,
             target_height: 512,
+            want_histogram: false,
+            comparison: None,
+            split_fraction: DEFAULT_SPLIT_FRACTION,
         });
 
         assert!(matches!(result, Err(ApplicationError::InvalidInput(_))));
     }
+
+    #[test]
+    fn exposure_render_matches_golden() {
+        assert_render_matches(
+            gradient_request(EditParams::default().with_exposure(1.0)),
+            &golden_path("exposure_plus_one.png"),
+        );
+    }
+
+    #[test]
+    fn temperature_render_matches_golden() {
+        assert_render_matches(
+            gradient_request(EditParams::default().with_temperature(2.5)),
+            &golden_path("temperature_warm.png"),
+        );
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn gpu_grain_matches_cpu_grain_on_the_same_base_pixels() {
+        // Without an adapter there is no shader output to compare.
+        let Ok(gpu) = WgpuRenderer::new(DEFAULT_PREVIEW_MAX_PIXELS) else {
+            return;
+        };
+        let params = EditParams {
+            grain: 3.0,
+            ..EditParams::default()
+        };
+        let base = gpu
+            .render(gradient_request(EditParams::default()))
+            .expect("render without grain");
+        let grainy = gpu
+            .render(gradient_request(params))
+            .expect("render with grain");
+
+        let adjustments = PixelAdjustments::new(&params);
+        let width = base.width as usize;
+        for (index, (base, grainy)) in base.pixels.iter().zip(&grainy.pixels).enumerate() {
+            let [red, green, blue] = adjustments.apply_at(
+                unpack_rgb(*base),
+                (index % width) as u32,
+                (index / width) as u32,
+            );
+            assert_eq!(
+                *grainy,
+                pack_rgb(red, green, blue),
+                "pixel {index} differs from the CPU grain"
+            );
+        }
+    }
 }
@@ -0,0 +1,702 @@
+// End-to-end tests that wire the real adapters into an ApplicationService.
+use std::fs;
+use std::path::Path;
+
+use image::{ImageBuffer, Rgb};
+use lite_room_application::{
+    ApplicationError, ApplicationService, ApplyPresetCommand, AutoExposureCommand,
+    BackfillMetadataCommand, BootstrapCatalogCommand, ExportImageCommand, ImageDecoder,
+    ImportFolderCommand, ListImagesCommand, ListImportFailuresCommand, OpenImageCommand,
+    RelinkCommand, RetryImportFailuresCommand, SavePresetCommand, SetCaptionCommand,
+    SetEditCommand, ShowEditCommand, VerifyThumbnailsCommand, WriteSidecarCommand,
+};
+use lite_room_domain::{EditParams, ImageFilter, ImageId, PreviewBackend};
+use tempfile::TempDir;
+
+use crate::test_support::{exif_tiff, write_jpeg_with_exif, ExifTags};
+use crate::{
+    BackgroundPreviewPipeline, FsImageEncoder, FsThumbnailGenerator, ImageCrateDecoder,
+    SqliteCatalogRepository, SystemClock, WalkdirFileScanner,
+};
+
+fn catalog_service(dir: &Path) -> ApplicationService {
+    let service = ApplicationService::new(
+        Box::new(SqliteCatalogRepository::new(
+            dir.join("catalog.sqlite3").to_string_lossy().to_string(),
+        )),
+        Box::new(WalkdirFileScanner),
+        Box::new(FsThumbnailGenerator::default()),
+        Box::new(ImageCrateDecoder::default()),
+        Box::new(FsImageEncoder),
+        Box::new(SystemClock),
+        Box::new(BackgroundPreviewPipeline::with_backend(PreviewBackend::Cpu).expect("cpu")),
+    );
+    service
+        .bootstrap_catalog(BootstrapCatalogCommand)
+        .expect("bootstrap");
+    service
+}
+
+#[test]
+fn corrupted_thumbnail_fails_verification_and_regenerates() {
+    let dir = TempDir::new().expect("tempdir");
+    let cache_root = dir.path().join("cache").to_string_lossy().to_string();
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    ImageBuffer::from_fn(320, 200, |x, _y| Rgb([x as u8, 90_u8, 160_u8]))
+        .save(photos.join("a.jpg"))
+        .expect("save");
+
+    let service = catalog_service(dir.path());
+    service
+        .import_folder(ImportFolderCommand {
+            folder: photos.to_string_lossy().to_string(),
+            cache_root: cache_root.clone(),
+            resume: false,
+            strict: false,
+            limit: None,
+            workers: 1,
+        })
+        .expect("import");
+    let verify = || {
+        service
+            .verify_thumbnails(VerifyThumbnailsCommand {
+                cache_root: cache_root.clone(),
+            })
+            .expect("verify")
+    };
+    assert_eq!(verify().checked, 1);
+    assert!(verify().regenerated.is_empty());
+
+    let listed = service
+        .list_images_with_thumbnails(ListImagesCommand::default())
+        .expect("list");
+    let thumbnail = listed[0].thumbnail.clone().expect("thumbnail");
+    let mut bytes = fs::read(&thumbnail.file_path).expect("read thumb");
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0xFF;
+    fs::write(&thumbnail.file_path, &bytes).expect("corrupt thumb");
+
+    let report = verify();
+    assert_eq!(report.regenerated, vec![listed[0].image.id]);
+    assert!(report.failures.is_empty());
+    assert_ne!(fs::read(&thumbnail.file_path).expect("regenerated"), bytes);
+    assert!(verify().regenerated.is_empty());
+}
+
+#[test]
+fn failed_import_is_recorded_and_cleared_by_successful_retry() {
+    let dir = TempDir::new().expect("tempdir");
+    let cache_root = dir.path().join("cache").to_string_lossy().to_string();
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    let good = photos.join("good.jpg");
+    let broken = photos.join("broken.jpg");
+    ImageBuffer::from_fn(64, 48, |_x, _y| Rgb([120_u8, 80_u8, 40_u8]))
+        .save(&good)
+        .expect("save");
+    fs::write(&broken, b"not a jpeg").expect("write broken");
+
+    let service = catalog_service(dir.path());
+    let report = service
+        .import_folder(ImportFolderCommand {
+            folder: photos.to_string_lossy().to_string(),
+            cache_root: cache_root.clone(),
+            resume: false,
+            strict: false,
+            limit: None,
+            workers: 1,
+        })
+        .expect("import should survive a bad file");
+    assert_eq!((report.supported_files, report.failures.len()), (2, 1));
+    let listed = service
+        .list_images(ListImagesCommand::default())
+        .expect("list");
+    assert_eq!(listed.len(), 1, "the failed file must not stay cataloged");
+
+    let failures = service
+        .list_import_failures(ListImportFailuresCommand)
+        .expect("failures");
+    assert_eq!(failures.len(), 1);
+    let broken_path = broken.canonicalize().expect("canonical");
+    assert_eq!(failures[0].file_path, broken_path.to_string_lossy());
+    assert!(
+        failures[0].reason.contains("decode"),
+        "{}",
+        failures[0].reason
+    );
+
+    // Different pixels from `good.jpg`, so the repaired file is not a duplicate.
+    ImageBuffer::from_fn(64, 48, |_x, _y| Rgb([40_u8, 80_u8, 120_u8]))
+        .save_with_format(&broken, image::ImageFormat::Jpeg)
+        .expect("repair file");
+    let retried = service
+        .retry_import_failures(RetryImportFailuresCommand { cache_root })
+        .expect("retry");
+    assert_eq!((retried.scanned_files, retried.failures.len()), (1, 0));
+    assert_eq!(retried.newly_imported, 1);
+    assert!(service
+        .list_import_failures(ListImportFailuresCommand)
+        .expect("failures")
+        .is_empty());
+}
+
+#[test]
+fn export_renders_stored_edits_at_source_resolution() {
+    let dir = TempDir::new().expect("tempdir");
+    let cache_root = dir.path().join("cache").to_string_lossy().to_string();
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    ImageBuffer::from_fn(90, 60, |_x, _y| Rgb([100_u8, 100_u8, 100_u8]))
+        .save(photos.join("solid.jpg"))
+        .expect("save");
+    fs::write(photos.join("shot.cr2"), b"raw bytes").expect("write raw");
+
+    let service = catalog_service(dir.path());
+    service
+        .import_folder(ImportFolderCommand {
+            folder: photos.to_string_lossy().to_string(),
+            cache_root,
+            resume: false,
+            strict: false,
+            limit: None,
+            workers: 1,
+        })
+        .expect("import");
+    let images = service
+        .list_images(ListImagesCommand::default())
+        .expect("list");
+    let image_id = |extension: &str| {
+        images
+            .iter()
+            .find(|image| image.file_path.ends_with(extension))
+            .expect("imported image")
+            .id
+    };
+    service
+        .set_edit(SetEditCommand {
+            image_id: image_id(".jpg"),
+            params: EditParams::default().with_exposure(1.0),
+        })
+        .expect("set edit");
+
+    let output = dir.path().join("out").join("solid-edited.jpg");
+    let written = service
+        .export_image(ExportImageCommand {
+            image_id: image_id(".jpg"),
+            output_path: output.to_string_lossy().to_string(),
+            quality: Some(85),
+            raw_format: None,
+        })
+        .expect("export");
+    let exported = image::open(&written).expect("decode export").to_rgb8();
+    assert_eq!(exported.dimensions(), (90, 60));
+    let pixel = exported.get_pixel(45, 30);
+    assert!(pixel[0] > 180, "exposure +1 should brighten: {pixel:?}");
+
+    let raw = service.export_image(ExportImageCommand {
+        image_id: image_id(".cr2"),
+        output_path: dir.path().join("raw.jpg").to_string_lossy().to_string(),
+        quality: None,
+        raw_format: None,
+    });
+    assert!(
+        matches!(&raw, Err(ApplicationError::InvalidInput(message)) if message.contains("RAW")),
+        "unexpected result: {raw:?}"
+    );
+}
+
+#[test]
+fn sidecar_next_to_the_original_carries_camera_raw_settings() {
+    let dir = TempDir::new().expect("tempdir");
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    let raw = photos.join("shot.cr2");
+    fs::write(&raw, b"raw bytes").expect("write raw");
+
+    let service = catalog_service(dir.path());
+    service
+        .import_folder(ImportFolderCommand {
+            folder: photos.to_string_lossy().to_string(),
+            cache_root: dir.path().join("cache").to_string_lossy().to_string(),
+            resume: false,
+            strict: false,
+            limit: None,
+            workers: 1,
+        })
+        .expect("import");
+    let image_id = service
+        .list_images(ListImagesCommand::default())
+        .expect("list")[0]
+        .id;
+    service
+        .set_edit(SetEditCommand {
+            image_id,
+            params: EditParams {
+                exposure: -1.25,
+                contrast: 2.0,
+                temperature: -1.0,
+                tint: -0.2,
+                highlights: -5.0,
+                shadows: 0.5,
+                ..EditParams::default()
+            },
+        })
+        .expect("set edit");
+    service
+        .set_caption(SetCaptionCommand {
+            image_id,
+            caption: "Harbour & boats".to_string(),
+        })
+        .expect("set caption");
+
+    let written = service
+        .write_sidecar(WriteSidecarCommand { image_id })
+        .expect("sidecar");
+    assert_eq!(
+        written,
+        photos.join("shot.cr2.xmp").canonicalize().expect("path")
+    );
+    let xmp = fs::read_to_string(&written).expect("read sidecar");
+    for expected in [
+        "crs:Exposure2012=\"-1.25\"",
+        "crs:Contrast2012=\"+40\"",
+        "crs:Temperature=\"4700\"",
+        "crs:Tint=\"-6\"",
+        "crs:Highlights2012=\"-100\"",
+        "crs:Shadows2012=\"+10\"",
+        "<rdf:li xml:lang=\"x-default\">Harbour &amp; boats</rdf:li>",
+    ] {
+        assert!(xmp.contains(expected), "missing {expected} in {xmp}");
+    }
+    assert_eq!(fs::read(&raw).expect("original"), b"raw bytes");
+}
+
+#[test]
+fn auto_exposure_samples_a_downsampled_decode_and_brightens_dark_shots() {
+    let dir = TempDir::new().expect("tempdir");
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    let dark = photos.join("dark.jpg");
+    ImageBuffer::from_fn(1200, 800, |_x, _y| Rgb([40_u8, 40_u8, 40_u8]))
+        .save(&dark)
+        .expect("save");
+
+    let sample = ImageCrateDecoder::default()
+        .decode_downsampled(&dark, 256)
+        .expect("downsampled");
+    assert!(sample.width <= 256 && sample.height <= 256);
+    assert_eq!(
+        sample.rgb.len(),
+        (sample.width * sample.height * 3) as usize
+    );
+
+    let service = catalog_service(dir.path());
+    service
+        .import_folder(ImportFolderCommand {
+            folder: photos.to_string_lossy().to_string(),
+            cache_root: dir.path().join("cache").to_string_lossy().to_string(),
+            resume: false,
+            strict: false,
+            limit: None,
+            workers: 1,
+        })
+        .expect("import");
+    let image_id = service
+        .list_images(ListImagesCommand::default())
+        .expect("list")[0]
+        .id;
+    let exposure = service
+        .suggest_auto_exposure(AutoExposureCommand {
+            image_id,
+            apply: true,
+        })
+        .expect("auto exposure");
+    assert!(exposure > 1.0, "exposure={exposure}");
+    let stored = service
+        .show_edit(ShowEditCommand { image_id })
+        .expect("edit");
+    assert_eq!(stored.exposure, exposure);
+}
+
+#[test]
+fn import_stores_exif_capture_metadata_in_catalog() {
+    let dir = TempDir::new().expect("tempdir");
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    write_jpeg_with_exif(
+        &photos.join("tagged.jpg"),
+        &exif_tiff(
+            true,
+            &ExifTags {
+                model: Some("LR-100 Mark II"),
+                date_time_original: Some("2023:11:24 18:30:05"),
+                iso: Some(3200),
+                lens_model: Some("LR 85mm F1.8"),
+                focal_length: Some((85, 1)),
+            },
+        ),
+    );
+    ImageBuffer::from_pixel(8, 8, Rgb([40_u8, 40, 40]))
+        .save(photos.join("untagged.jpg"))
+        .expect("save");
+
+    let service = catalog_service(dir.path());
+    service
+        .import_folder(ImportFolderCommand {
+            folder: photos.to_string_lossy().to_string(),
+            cache_root: dir.path().join("cache").to_string_lossy().to_string(),
+            resume: false,
+            strict: false,
+            limit: None,
+            workers: 1,
+        })
+        .expect("import");
+
+    let conn = rusqlite::Connection::open(dir.path().join("catalog.sqlite3")).expect("open");
+    let row = |name: &str| {
+        conn.query_row(
+            "SELECT capture_date, camera_model, iso, lens_model, focal_length
+             FROM images WHERE file_path LIKE ?1",
+            [format!("%{name}")],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<f64>>(4)?,
+                ))
+            },
+        )
+        .expect("image row")
+    };
+    assert_eq!(
+        row("tagged.jpg"),
+        (
+            Some("2023-11-24T18:30:05".to_string()),
+            Some("LR-100 Mark II".to_string()),
+            Some(3200),
+            Some("LR 85mm F1.8".to_string()),
+            Some(85.0)
+        )
+    );
+    assert_eq!(row("untagged.jpg"), (None, None, None, None, None));
+
+    let by_lens = service
+        .list_images(ListImagesCommand {
+            filter: ImageFilter {
+                lens: Some("85mm".to_string()),
+                focal_min: Some(50.0),
+                focal_max: Some(100.0),
+                ..ImageFilter::default()
+            },
+            ..ListImagesCommand::default()
+        })
+        .expect("filtered list");
+    assert_eq!(by_lens.len(), 1);
+    assert!(by_lens[0].file_path.ends_with("tagged.jpg"));
+}
+
+#[test]
+fn relinking_a_missing_image_to_its_moved_file_makes_it_open_again() {
+    let dir = TempDir::new().expect("tempdir");
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    ImageBuffer::from_pixel(12, 6, Rgb([90_u8, 60, 30]))
+        .save(photos.join("trip.jpg"))
+        .expect("save");
+    let service = catalog_service(dir.path());
+    service
+        .import_folder(ImportFolderCommand {
+            folder: photos.to_string_lossy().to_string(),
+            cache_root: dir.path().join("cache").to_string_lossy().to_string(),
+            resume: false,
+            strict: false,
+            limit: None,
+            workers: 1,
+        })
+        .expect("import");
+    let image_id = service
+        .list_images(ListImagesCommand::default())
+        .expect("list")[0]
+        .id;
+
+    let not_missing = service.relink(RelinkCommand {
+        image_id,
+        new_path: photos.join("trip.jpg").to_string_lossy().to_string(),
+    });
+    assert!(matches!(
+        not_missing,
+        Err(ApplicationError::InvalidInput(_))
+    ));
+
+    let moved = dir.path().join("archive/trip-2024.jpg");
+    fs::create_dir_all(moved.parent().expect("parent")).expect("archive dir");
+    fs::rename(photos.join("trip.jpg"), &moved).expect("move");
+    assert!(service.open_image(OpenImageCommand { image_id }).is_err());
+    fs::write(dir.path().join("notes.jpg"), b"not a jpeg").expect("write broken");
+    assert!(service
+        .relink(RelinkCommand {
+            image_id,
+            new_path: dir.path().join("notes.jpg").to_string_lossy().to_string(),
+        })
+        .is_err());
+
+    let relinked = service
+        .relink(RelinkCommand {
+            image_id,
+            new_path: moved.to_string_lossy().to_string(),
+        })
+        .expect("relink");
+    assert_eq!(
+        Path::new(&relinked.file_path),
+        moved.canonicalize().expect("canonical")
+    );
+    let opened = service
+        .open_image(OpenImageCommand { image_id })
+        .expect("open after relink");
+    assert_eq!((opened.width, opened.height), (12, 6));
+}
+
+#[test]
+fn identical_files_under_different_names_import_once() {
+    let dir = TempDir::new().expect("tempdir");
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(photos.join("copies")).expect("photos dir");
+    ImageBuffer::from_pixel(10, 10, Rgb([70_u8, 110, 150]))
+        .save(photos.join("original.jpg"))
+        .expect("save");
+    fs::copy(
+        photos.join("original.jpg"),
+        photos.join("copies/backup.jpg"),
+    )
+    .expect("copy");
+    let service = catalog_service(dir.path());
+    let import = || {
+        service
+            .import_folder(ImportFolderCommand {
+                folder: photos.to_string_lossy().to_string(),
+                cache_root: dir.path().join("cache").to_string_lossy().to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import")
+    };
+
+    let report = import();
+    assert_eq!((report.scanned_files, report.supported_files), (2, 2));
+    assert_eq!((report.newly_imported, report.skipped_duplicates), (1, 1));
+    assert_eq!(
+        service
+            .list_images(ListImagesCommand::default())
+            .expect("list")
+            .len(),
+        1
+    );
+
+    let again = import();
+    assert_eq!((again.newly_imported, again.skipped_duplicates), (0, 1));
+}
+
+#[test]
+fn parallel_import_matches_a_serial_import_of_the_same_folder() {
+    let dir = TempDir::new().expect("tempdir");
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    for index in 0..20_u8 {
+        ImageBuffer::from_pixel(48, 32, Rgb([index * 12, 80, 200 - index * 5]))
+            .save(photos.join(format!("img_{index:02}.jpg")))
+            .expect("save");
+    }
+    fs::copy(photos.join("img_07.jpg"), photos.join("copy.jpg")).expect("copy");
+    fs::write(photos.join("broken.jpg"), b"not a jpeg").expect("broken");
+
+    let import = |name: &str, workers: usize, limit: Option<usize>| {
+        let root = dir.path().join(name);
+        fs::create_dir_all(&root).expect("catalog dir");
+        let service = catalog_service(&root);
+        let report = service
+            .import_folder(ImportFolderCommand {
+                folder: photos.to_string_lossy().to_string(),
+                cache_root: root.join("cache").to_string_lossy().to_string(),
+                resume: false,
+                strict: false,
+                limit,
+                workers,
+            })
+            .expect("import");
+        let listed = service
+            .list_images_with_thumbnails(ListImagesCommand::default())
+            .expect("list");
+        (report, listed)
+    };
+
+    let (parallel, listed) = import("parallel", 4, None);
+    assert_eq!(parallel.scanned_files, 22);
+    assert_eq!(parallel.newly_imported, 20);
+    assert_eq!(parallel.skipped_duplicates, 1);
+    assert_eq!(parallel.failures.len(), 1);
+    assert!(parallel.failures[0].file_path.ends_with("broken.jpg"));
+    assert_eq!(listed.len(), 20);
+    assert!(listed.iter().all(|entry| entry
+        .thumbnail
+        .as_ref()
+        .is_some_and(|thumb| Path::new(&thumb.file_path).is_file())));
+
+    let (serial, _) = import("serial", 1, None);
+    assert_eq!(parallel, serial);
+    assert_eq!(
+        import("limited", 8, Some(5)).0,
+        import("limited-serial", 1, Some(5)).0
+    );
+}
+
+#[test]
+fn backfill_fills_null_capture_metadata_from_exif_and_skips_missing_files() {
+    let dir = TempDir::new().expect("tempdir");
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    for (shade, name) in [(40_u8, "late.jpg"), (50, "gone.jpg")] {
+        ImageBuffer::from_pixel(8, 8, Rgb([shade, shade, shade]))
+            .save(photos.join(name))
+            .expect("save");
+    }
+    let service = catalog_service(dir.path());
+    service
+        .import_folder(ImportFolderCommand {
+            folder: photos.to_string_lossy().to_string(),
+            cache_root: dir.path().join("cache").to_string_lossy().to_string(),
+            resume: false,
+            strict: false,
+            limit: None,
+            workers: 1,
+        })
+        .expect("import");
+
+    let conn = rusqlite::Connection::open(dir.path().join("catalog.sqlite3")).expect("open");
+    conn.execute(
+        "UPDATE images SET camera_model = 'Typed In' WHERE file_path LIKE '%late.jpg'",
+        [],
+    )
+    .expect("seed camera model");
+    write_jpeg_with_exif(
+        &photos.join("late.jpg"),
+        &exif_tiff(
+            false,
+            &ExifTags {
+                model: Some("LR-100"),
+                date_time_original: Some("2021:06:01 09:15:00"),
+                iso: Some(400),
+                lens_model: Some("LR 35mm F2"),
+                focal_length: Some((35, 1)),
+            },
+        ),
+    );
+    fs::remove_file(photos.join("gone.jpg")).expect("remove");
+
+    let report = service
+        .backfill_metadata(BackfillMetadataCommand)
+        .expect("backfill");
+    assert_eq!((report.checked, report.updated, report.missing), (2, 1, 1));
+    let row: (Option<String>, Option<String>, Option<i64>) = conn
+        .query_row(
+            "SELECT capture_date, camera_model, iso FROM images WHERE file_path LIKE '%late.jpg'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .expect("image row");
+    assert_eq!(
+        row,
+        (
+            Some("2021-06-01T09:15:00".to_string()),
+            Some("Typed In".to_string()),
+            Some(400)
+        )
+    );
+    let lens: (Option<String>, Option<f64>) = conn
+        .query_row(
+            "SELECT lens_model, focal_length FROM images WHERE file_path LIKE '%late.jpg'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("lens row");
+    assert_eq!(lens, (Some("LR 35mm F2".to_string()), Some(35.0)));
+
+    let again = service
+        .backfill_metadata(BackfillMetadataCommand)
+        .expect("second backfill");
+    assert_eq!(again.updated, 0);
+}
+
+#[test]
+fn saved_preset_applies_to_other_images_and_missing_preset_is_not_found() {
+    let dir = TempDir::new().expect("tempdir");
+    let photos = dir.path().join("photos");
+    fs::create_dir_all(&photos).expect("photos dir");
+    for (shade, name) in [(90_u8, "a.jpg"), (100, "b.jpg"), (110, "c.jpg")] {
+        ImageBuffer::from_pixel(8, 8, Rgb([shade, shade, shade]))
+            .save(photos.join(name))
+            .expect("save");
+    }
+    let service = catalog_service(dir.path());
+    service
+        .import_folder(ImportFolderCommand {
+            folder: photos.to_string_lossy().to_string(),
+            cache_root: dir.path().join("cache").to_string_lossy().to_string(),
+            resume: false,
+            strict: false,
+            limit: None,
+            workers: 1,
+        })
+        .expect("import");
+    let ids: Vec<ImageId> = service
+        .list_images(ListImagesCommand::default())
+        .expect("list")
+        .into_iter()
+        .map(|image| image.id)
+        .collect();
+    let look = EditParams::default()
+        .with_contrast(0.8)
+        .with_saturation(-1.0);
+    service
+        .set_edit(SetEditCommand {
+            image_id: ids[0],
+            params: look,
+        })
+        .expect("set edit");
+
+    service
+        .save_preset(SavePresetCommand {
+            name: " faded ".to_string(),
+            image_id: ids[0],
+        })
+        .expect("save preset");
+    service
+        .apply_preset(ApplyPresetCommand {
+            name: "faded".to_string(),
+            image_ids: ids[1..].to_vec(),
+        })
+        .expect("apply preset");
+    for image_id in &ids[1..] {
+        assert_eq!(
+            service
+                .show_edit(ShowEditCommand {
+                    image_id: *image_id
+                })
+                .expect("show edit"),
+            look
+        );
+    }
+
+    let missing = service.apply_preset(ApplyPresetCommand {
+        name: "vivid".to_string(),
+        image_ids: ids[1..].to_vec(),
+    });
+    assert!(
+        matches!(&missing, Err(ApplicationError::NotFound(message)) if message.contains("vivid")),
+        "unexpected result: {missing:?}"
+    );
+}
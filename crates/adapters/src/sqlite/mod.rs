@@ -1,31 +1,106 @@
 mod queries;
 
+use std::collections::HashMap;
 use std::fs;
+use std::ops::{Deref, DerefMut};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
 use lite_room_application::{
-    ApplicationError, CatalogRepository, NewImage, StoredEdit, UpsertImageResult,
+    ApplicationError, CaptureMetadata, CatalogRepository, EditPreset, ImageWithThumbnail,
+    ImportCheckpoint, ImportFailure, NewImage, OptimizeReport, SchemaReport, StoredEdit,
+    ThumbnailArtifact, UpsertImageResult,
 };
-use lite_room_domain::{ImageId, ImageRecord};
-use rusqlite::{params, Connection};
+use lite_room_domain::{
+    EditParams, ImageFilter, ImageId, ImagePage, ImageRecord, ImageSort, SortMove,
+};
+use rusqlite::{params, Connection, OpenFlags};
 
 use crate::migrations::MIGRATIONS;
 
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// One connection per repository, opened on first use and shared by clones.
+// Queries take turns on it through the mutex.
 #[derive(Debug, Clone)]
 pub struct SqliteCatalogRepository {
     path: PathBuf,
+    connection: Arc<Mutex<Option<Connection>>>,
+}
+
+struct SharedConnection<'a>(MutexGuard<'a, Option<Connection>>);
+
+impl Deref for SharedConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.0
+            .as_ref()
+            .expect("shared connection is opened before use")
+    }
+}
+
+impl DerefMut for SharedConnection<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.0
+            .as_mut()
+            .expect("shared connection is opened before use")
+    }
 }
 
 impl SqliteCatalogRepository {
     pub fn new(path: String) -> Self {
         Self {
             path: PathBuf::from(path),
+            connection: Arc::new(Mutex::new(None)),
         }
     }
 
-    fn open_connection(&self) -> Result<Connection, ApplicationError> {
-        Connection::open(&self.path)
-            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    fn open_connection(&self) -> Result<SharedConnection<'_>, ApplicationError> {
+        let mut guard = self.connection.lock().map_err(|_| {
+            ApplicationError::Persistence("catalog connection lock poisoned".to_string())
+        })?;
+        if guard.is_none() {
+            let conn = Connection::open(&self.path)
+                .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+            conn.busy_timeout(BUSY_TIMEOUT)
+                .and_then(|_| {
+                    conn.execute_batch("PRAGMA foreign_keys=ON; PRAGMA journal_mode=WAL;")
+                })
+                .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+            *guard = Some(conn);
+        }
+        Ok(SharedConnection(guard))
+    }
+
+    // The catalog as it is on disk, for inspection only: nothing is created or
+    // migrated, and `None` means there is no catalog file yet.
+    fn open_read_only(&self) -> Result<Option<Connection>, ApplicationError> {
+        if !self.path.is_file() {
+            return Ok(None);
+        }
+        Connection::open_with_flags(
+            &self.path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map(Some)
+        .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    // Number of migrations recorded in `schema_migrations`; 0 for a catalog
+    // that was never initialized.
+    pub fn current_schema_version(&self) -> Result<usize, ApplicationError> {
+        match self.open_read_only()? {
+            Some(conn) => applied_schema_version(&conn),
+            None => Ok(0),
+        }
+    }
+
+    fn catalog_file_size(&self) -> Result<u64, ApplicationError> {
+        fs::metadata(&self.path)
+            .map(|metadata| metadata.len())
+            .map_err(|error| ApplicationError::Io(error.to_string()))
     }
 }
 
@@ -44,13 +119,24 @@ impl CatalogRepository for SqliteCatalogRepository {
             }
         }
 
-        let conn = self.open_connection()?;
-        conn.execute_batch("PRAGMA foreign_keys=ON; PRAGMA journal_mode=WAL;")
+        let mut conn = self.open_connection()?;
+        queries::create_schema_migrations(&conn)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        adopt_user_version(&mut conn)
             .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
 
-        for migration in MIGRATIONS {
-            conn.execute_batch(migration)
+        let applied = queries::schema_version(&conn)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        for (index, (name, sql)) in MIGRATIONS.iter().enumerate().skip(applied) {
+            let tx = conn
+                .transaction()
                 .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+            tx.execute_batch(sql)
+                .and_then(|_| queries::record_migration(&tx, index + 1, name))
+                .and_then(|_| tx.commit())
+                .map_err(|error| {
+                    ApplicationError::Persistence(format!("migration {name} failed: {error}"))
+                })?;
         }
 
         Ok(())
@@ -61,20 +147,33 @@ impl CatalogRepository for SqliteCatalogRepository {
         let inserted = conn
             .execute(
                 "INSERT OR IGNORE INTO images
-                 (file_path, import_date, capture_date, camera_model, iso, rating, flag, metadata_json)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                 (file_path, import_date, capture_date, camera_model, iso, lens_model, focal_length,
+                  rating, flag, metadata_json, content_hash, sort_index)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11,
+                         (SELECT COALESCE(MAX(sort_index) + 1, 0) FROM images))",
                 params![
                     image.file_path,
                     image.import_date,
                     image.capture_date,
                     image.camera_model,
                     image.iso,
+                    image.lens_model,
+                    image.focal_length,
                     image.rating,
                     image.flag,
                     image.metadata_json,
+                    image.content_hash,
                 ],
             )
             .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        // Rows imported before content hashes existed pick one up on re-import.
+        if inserted == 0 && image.content_hash.is_some() {
+            conn.execute(
+                "UPDATE images SET content_hash = ?2 WHERE file_path = ?1 AND content_hash IS NULL",
+                params![image.file_path, image.content_hash],
+            )
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        }
 
         let image_id_value: i64 = conn
             .query_row(
@@ -123,22 +222,94 @@ impl CatalogRepository for SqliteCatalogRepository {
         }))
     }
 
+    fn find_edits(
+        &self,
+        image_ids: &[ImageId],
+    ) -> Result<HashMap<ImageId, StoredEdit>, ApplicationError> {
+        let conn = self.open_connection()?;
+        let ids: Vec<i64> = image_ids.iter().map(|id| id.get()).collect();
+        let rows = queries::find_edits(&conn, &ids)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        rows.into_iter()
+            .map(|(image_id, edit_params_json, updated_at)| {
+                let image_id = ImageId::new(image_id).map_err(|error| {
+                    ApplicationError::Persistence(format!("invalid image id in edits: {error}"))
+                })?;
+                Ok((
+                    image_id,
+                    StoredEdit {
+                        edit_params_json,
+                        updated_at,
+                    },
+                ))
+            })
+            .collect()
+    }
+
     fn upsert_thumbnail(
         &self,
         image_id: ImageId,
         file_path: &str,
         width: i64,
         height: i64,
+        checksum: Option<&str>,
         updated_at: &str,
     ) -> Result<(), ApplicationError> {
+        thumbnail_dimension(width).map_err(ApplicationError::InvalidInput)?;
+        thumbnail_dimension(height).map_err(ApplicationError::InvalidInput)?;
+        let conn = self.open_connection()?;
+        queries::upsert_thumbnail(
+            &conn,
+            image_id.get(),
+            file_path,
+            width,
+            height,
+            checksum,
+            updated_at,
+        )
+        .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn list_images(
+        &self,
+        sort: ImageSort,
+        filter: &ImageFilter,
+        page: ImagePage,
+    ) -> Result<Vec<ImageRecord>, ApplicationError> {
         let conn = self.open_connection()?;
-        queries::upsert_thumbnail(&conn, image_id.get(), file_path, width, height, updated_at)
+        queries::list_images(&conn, sort, filter, page)
             .map_err(|error| ApplicationError::Persistence(error.to_string()))
     }
 
-    fn list_images(&self) -> Result<Vec<ImageRecord>, ApplicationError> {
+    fn list_images_with_thumbnails(
+        &self,
+        sort: ImageSort,
+        filter: &ImageFilter,
+        page: ImagePage,
+    ) -> Result<Vec<ImageWithThumbnail>, ApplicationError> {
+        let conn = self.open_connection()?;
+        let rows = queries::list_images_with_thumbnails(&conn, sort, filter, page)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        rows.into_iter()
+            .map(|(image, thumbnail)| {
+                let thumbnail = match thumbnail {
+                    Some((file_path, width, height, checksum)) => Some(ThumbnailArtifact {
+                        file_path,
+                        width: thumbnail_dimension(width).map_err(ApplicationError::Persistence)?,
+                        height: thumbnail_dimension(height)
+                            .map_err(ApplicationError::Persistence)?,
+                        checksum,
+                    }),
+                    None => None,
+                };
+                Ok(ImageWithThumbnail { image, thumbnail })
+            })
+            .collect()
+    }
+
+    fn move_image(&self, image_id: ImageId, target: SortMove) -> Result<i64, ApplicationError> {
         let conn = self.open_connection()?;
-        queries::list_images(&conn)
+        queries::move_image(&conn, image_id.get(), target)
             .map_err(|error| ApplicationError::Persistence(error.to_string()))
     }
 
@@ -147,14 +318,299 @@ impl CatalogRepository for SqliteCatalogRepository {
         queries::find_image_by_id(&conn, image_id.get())
             .map_err(|error| ApplicationError::Persistence(error.to_string()))
     }
+
+    fn find_content_duplicate(
+        &self,
+        content_hash: &str,
+        file_path: &str,
+    ) -> Result<Option<ImageId>, ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::find_content_duplicate(&conn, content_hash, file_path)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?
+            .map(ImageId::new)
+            .transpose()
+            .map_err(ApplicationError::from)
+    }
+
+    fn set_rating(&self, image_id: ImageId, rating: i64) -> Result<(), ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::set_rating(&conn, image_id.get(), rating)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn set_flag(&self, image_id: ImageId, flag: i64) -> Result<(), ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::set_flag(&conn, image_id.get(), flag)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn fill_capture_metadata(
+        &self,
+        image_id: ImageId,
+        capture: &CaptureMetadata,
+    ) -> Result<bool, ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::fill_capture_metadata(&conn, image_id.get(), capture)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn relink_image(&self, image_id: ImageId, file_path: &str) -> Result<(), ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::relink_image(&conn, image_id.get(), file_path).map_err(|error| {
+            if error.sqlite_error_code() == Some(rusqlite::ErrorCode::ConstraintViolation) {
+                ApplicationError::InvalidInput(format!(
+                    "{file_path} is already in the catalog as another image"
+                ))
+            } else {
+                ApplicationError::Persistence(error.to_string())
+            }
+        })
+    }
+
+    fn delete_image(&self, image_id: ImageId) -> Result<Option<Vec<String>>, ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::delete_image(&conn, image_id.get())
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn set_caption(
+        &self,
+        image_id: ImageId,
+        caption: Option<&str>,
+    ) -> Result<(), ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::set_caption(&conn, image_id.get(), caption)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn find_caption(&self, image_id: ImageId) -> Result<Option<String>, ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::find_caption(&conn, image_id.get())
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn create_stack(&self, image_ids: &[ImageId]) -> Result<i64, ApplicationError> {
+        let conn = self.open_connection()?;
+        let ids: Vec<i64> = image_ids.iter().map(|id| id.get()).collect();
+        queries::create_stack(&conn, &ids)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn set_stack_pick(&self, image_id: ImageId) -> Result<Option<i64>, ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::set_stack_pick(&conn, image_id.get())
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn find_import_checkpoint(&self) -> Result<Option<ImportCheckpoint>, ApplicationError> {
+        let conn = self.open_connection()?;
+        let found = queries::find_import_checkpoint(&conn)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        Ok(found.map(|(folder, last_path)| ImportCheckpoint { folder, last_path }))
+    }
+
+    fn save_import_checkpoint(
+        &self,
+        checkpoint: &ImportCheckpoint,
+        updated_at: &str,
+    ) -> Result<(), ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::save_import_checkpoint(
+            &conn,
+            &checkpoint.folder,
+            &checkpoint.last_path,
+            updated_at,
+        )
+        .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn clear_import_checkpoint(&self) -> Result<(), ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::clear_import_checkpoint(&conn)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn record_import_failure(&self, failure: &ImportFailure) -> Result<(), ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::record_import_failure(
+            &conn,
+            &failure.file_path,
+            &failure.reason,
+            &failure.failed_at,
+        )
+        .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn list_import_failures(&self) -> Result<Vec<ImportFailure>, ApplicationError> {
+        let conn = self.open_connection()?;
+        let rows = queries::list_import_failures(&conn)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        Ok(rows
+            .into_iter()
+            .map(|(file_path, reason, failed_at)| ImportFailure {
+                file_path,
+                reason,
+                failed_at,
+            })
+            .collect())
+    }
+
+    fn clear_import_failure(&self, file_path: &str) -> Result<(), ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::clear_import_failure(&conn, file_path)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn save_preset(&self, name: &str, params: &EditParams) -> Result<(), ApplicationError> {
+        let edit_params_json = serde_json::to_string(params)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        let conn = self.open_connection()?;
+        queries::save_preset(&conn, name, &edit_params_json)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    fn list_presets(&self) -> Result<Vec<EditPreset>, ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::list_presets(&conn)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?
+            .into_iter()
+            .map(|(name, edit_params_json)| preset_from_row(name, &edit_params_json))
+            .collect()
+    }
+
+    fn find_preset(&self, name: &str) -> Result<Option<EditPreset>, ApplicationError> {
+        let conn = self.open_connection()?;
+        queries::find_preset(&conn, name)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?
+            .map(|edit_params_json| preset_from_row(name.to_string(), &edit_params_json))
+            .transpose()
+    }
+
+    fn optimize(&self) -> Result<OptimizeReport, ApplicationError> {
+        let conn = self.open_connection()?;
+        if !conn.is_autocommit() {
+            return Err(ApplicationError::Persistence(
+                "cannot vacuum catalog inside an open transaction".to_string(),
+            ));
+        }
+
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        let size_before_bytes = self.catalog_file_size()?;
+        conn.execute_batch("PRAGMA optimize; VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        let size_after_bytes = self.catalog_file_size()?;
+
+        Ok(OptimizeReport {
+            size_before_bytes,
+            size_after_bytes,
+        })
+    }
+
+    // The expected schema is whatever the migrations build in a scratch
+    // in-memory database, so it never drifts from the migration files.
+    fn inspect_schema(&self) -> Result<SchemaReport, ApplicationError> {
+        let expected = Connection::open_in_memory()
+            .and_then(|conn| {
+                queries::create_schema_migrations(&conn)?;
+                for (_, sql) in MIGRATIONS {
+                    conn.execute_batch(sql)?;
+                }
+                queries::table_columns(&conn)
+            })
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        let conn = self.open_read_only()?;
+        let actual: HashMap<String, Vec<String>> = match &conn {
+            Some(conn) => queries::table_columns(conn)
+                .map_err(|error| ApplicationError::Persistence(error.to_string()))?
+                .into_iter()
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        let mut report = SchemaReport {
+            applied_version: match &conn {
+                Some(conn) => applied_schema_version(conn)?,
+                None => 0,
+            },
+            expected_version: MIGRATIONS.len(),
+            ..SchemaReport::default()
+        };
+        for (table, columns) in expected {
+            let Some(actual_columns) = actual.get(&table) else {
+                report.missing_tables.push(table);
+                continue;
+            };
+            report.missing_columns.extend(
+                columns
+                    .into_iter()
+                    .filter(|column| !actual_columns.contains(column))
+                    .map(|column| format!("{table}.{column}")),
+            );
+        }
+        Ok(report)
+    }
+}
+
+// Read-only: a catalog without `schema_migrations` has no recorded version.
+fn applied_schema_version(conn: &Connection) -> Result<usize, ApplicationError> {
+    queries::table_exists(conn, "schema_migrations")
+        .and_then(|exists| {
+            if exists {
+                queries::schema_version(conn)
+            } else {
+                Ok(0)
+            }
+        })
+        .map_err(|error| ApplicationError::Persistence(error.to_string()))
+}
+
+// Catalogs created before `schema_migrations` existed tracked progress in
+// `PRAGMA user_version`; record those migrations as applied once.
+fn adopt_user_version(conn: &mut Connection) -> rusqlite::Result<()> {
+    let legacy: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if legacy == 0 || queries::schema_version(conn)? > 0 {
+        return Ok(());
+    }
+    let tx = conn.transaction()?;
+    for (index, (name, _)) in MIGRATIONS.iter().enumerate().take(legacy) {
+        queries::record_migration(&tx, index + 1, name)?;
+    }
+    tx.execute_batch("PRAGMA user_version = 0;")?;
+    tx.commit()
+}
+
+fn preset_from_row(name: String, edit_params_json: &str) -> Result<EditPreset, ApplicationError> {
+    let params = serde_json::from_str(edit_params_json)
+        .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+    Ok(EditPreset { name, params })
+}
+
+fn thumbnail_dimension(value: i64) -> Result<u32, String> {
+    u32::try_from(value).map_err(|_| format!("thumbnail dimension out of range: {value}"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use lite_room_domain::EditParams;
     use tempfile::TempDir;
 
+    fn new_image(path: &str) -> NewImage {
+        NewImage {
+            file_path: path.to_string(),
+            import_date: "1".to_string(),
+            capture_date: None,
+            camera_model: None,
+            iso: None,
+            lens_model: None,
+            focal_length: None,
+            rating: 0,
+            flag: 0,
+            metadata_json: "{}".to_string(),
+            content_hash: None,
+        }
+    }
+
     #[test]
     fn initialize_creates_schema() {
         let dir = TempDir::new().expect("tempdir");
@@ -173,6 +629,69 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn initialize_twice_records_each_migration_once() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        assert_eq!(repo.current_schema_version().expect("version"), 0);
+
+        repo.initialize().expect("first initialize");
+        let conn = Connection::open(&db_path).expect("open");
+        let rows = || -> Vec<(i64, String, String)> {
+            let mut statement = conn
+                .prepare("SELECT version, name, applied_at FROM schema_migrations ORDER BY version")
+                .expect("prepare");
+            statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .expect("query")
+                .collect::<Result<_, _>>()
+                .expect("rows")
+        };
+        let first = rows();
+        assert_eq!(first.len(), MIGRATIONS.len());
+        assert_eq!(first[0].1, "0001_initial");
+        assert_eq!(
+            repo.current_schema_version().expect("version"),
+            MIGRATIONS.len()
+        );
+
+        conn.execute("UPDATE schema_migrations SET applied_at = 'marker'", [])
+            .expect("mark rows");
+        repo.initialize().expect("second initialize");
+        let second = rows();
+        assert_eq!(second.len(), MIGRATIONS.len());
+        assert!(second
+            .iter()
+            .all(|(_, _, applied_at)| applied_at == "marker"));
+    }
+
+    #[test]
+    fn legacy_user_version_catalog_runs_only_newer_migrations() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let conn = Connection::open(&db_path).expect("open");
+        for (_, sql) in &MIGRATIONS[..3] {
+            conn.execute_batch(sql).expect("legacy migration");
+        }
+        conn.execute_batch("PRAGMA user_version = 3;")
+            .expect("legacy version");
+
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize()
+            .expect("initialize does not re-add legacy columns");
+
+        assert_eq!(
+            repo.current_schema_version().expect("version"),
+            MIGRATIONS.len()
+        );
+        let user_version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .expect("user_version");
+        assert_eq!(user_version, 0);
+        assert!(repo.list_presets().expect("presets table").is_empty());
+    }
+
     #[test]
     fn upsert_and_find_edit_roundtrip() {
         let dir = TempDir::new().expect("tempdir");
@@ -183,14 +702,8 @@ mod tests {
         let now = "2026-02-17T00:00:00Z";
         let upsert = repo
             .upsert_image(&NewImage {
-                file_path: "/tmp/sample.jpg".to_string(),
                 import_date: now.to_string(),
-                capture_date: None,
-                camera_model: None,
-                iso: None,
-                rating: 0,
-                flag: 0,
-                metadata_json: "{}".to_string(),
+                ..new_image("/tmp/sample.jpg")
             })
             .expect("upsert image");
 
@@ -201,6 +714,7 @@ mod tests {
             tint: 3.0,
             highlights: 4.0,
             shadows: 5.0,
+            ..EditParams::default()
         };
         let params_json = serde_json::to_string(&params).expect("json");
 
@@ -212,4 +726,852 @@ mod tests {
             .expect("edit exists");
         assert_eq!(stored.edit_params_json, params_json);
     }
+
+    #[test]
+    fn find_edits_loads_several_images_in_one_call() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let now = "2026-02-17T00:00:00Z";
+        let mut expected = HashMap::new();
+        for index in 0..4 {
+            let image_id = repo
+                .upsert_image(&new_image(&format!("/tmp/bulk-{index}.jpg")))
+                .expect("upsert image")
+                .image_id;
+            let params_json =
+                serde_json::to_string(&EditParams::default().with_exposure(index as f32))
+                    .expect("json");
+            repo.upsert_edit(image_id, &params_json, now)
+                .expect("upsert edit");
+            expected.insert(image_id, params_json);
+        }
+
+        let mut requested: Vec<ImageId> = expected.keys().copied().collect();
+        requested.sort_by_key(|id| id.get());
+        requested.truncate(3);
+        let found = repo.find_edits(&requested).expect("find edits");
+
+        assert_eq!(found.len(), 3);
+        for image_id in &requested {
+            assert_eq!(found[image_id].edit_params_json, expected[image_id]);
+        }
+        assert!(repo.find_edits(&[]).expect("empty lookup").is_empty());
+    }
+
+    #[test]
+    fn caption_roundtrips_unicode_and_clears() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_id = repo
+            .upsert_image(&new_image("/tmp/captioned.jpg"))
+            .expect("upsert image")
+            .image_id;
+        assert_eq!(repo.find_caption(image_id).expect("no caption"), None);
+
+        let caption = "Client pick — golden hour 🌅";
+        repo.set_caption(image_id, Some(caption))
+            .expect("set caption");
+        assert_eq!(
+            repo.find_caption(image_id)
+                .expect("find caption")
+                .as_deref(),
+            Some(caption)
+        );
+        let found = repo
+            .find_image_by_id(image_id)
+            .expect("find image")
+            .expect("image exists");
+        assert_eq!(found.caption.as_deref(), Some(caption));
+        let listed = repo
+            .list_images(
+                ImageSort::default(),
+                &ImageFilter::default(),
+                ImagePage::default(),
+            )
+            .expect("list images");
+        assert_eq!(listed[0].caption.as_deref(), Some(caption));
+        let with_thumbnail = repo
+            .list_images_with_thumbnails(
+                ImageSort::default(),
+                &ImageFilter::default(),
+                ImagePage::default(),
+            )
+            .expect("list with thumbnails");
+        assert_eq!(with_thumbnail[0].image.caption.as_deref(), Some(caption));
+
+        repo.set_caption(image_id, None).expect("clear caption");
+        assert_eq!(repo.find_caption(image_id).expect("cleared"), None);
+    }
+
+    #[test]
+    fn delete_image_removes_its_edit_and_thumbnail_rows() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_id = repo
+            .upsert_image(&new_image("/tmp/deleted.jpg"))
+            .expect("upsert image")
+            .image_id;
+        repo.upsert_edit(image_id, "{}", "1").expect("upsert edit");
+        repo.upsert_thumbnail(image_id, "cache/thumbs/1.jpg", 256, 171, None, "1")
+            .expect("upsert thumbnail");
+
+        assert_eq!(
+            repo.delete_image(image_id).expect("delete"),
+            Some(vec!["cache/thumbs/1.jpg".to_string()])
+        );
+        let conn = Connection::open(&db_path).expect("open");
+        for table in ["images", "edits", "thumbnails"] {
+            let count: i64 = conn
+                .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                    row.get(0)
+                })
+                .expect("count");
+            assert_eq!(count, 0, "{table} rows left after delete");
+        }
+        assert_eq!(repo.delete_image(image_id).expect("delete again"), None);
+    }
+
+    #[test]
+    fn rating_and_flag_roundtrip_through_find_image() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_id = repo
+            .upsert_image(&new_image("/tmp/rated.jpg"))
+            .expect("upsert image")
+            .image_id;
+
+        repo.set_rating(image_id, 4).expect("set rating");
+        repo.set_flag(image_id, -1).expect("set flag");
+        let image = repo
+            .find_image_by_id(image_id)
+            .expect("find image")
+            .expect("image exists");
+        assert_eq!((image.rating, image.flag), (4, -1));
+
+        repo.set_flag(image_id, 1).expect("pick");
+        let image = repo
+            .find_image_by_id(image_id)
+            .expect("find image")
+            .expect("image exists");
+        assert_eq!((image.rating, image.flag), (4, 1));
+    }
+
+    #[test]
+    fn presets_are_saved_by_name_and_overwritten_on_resave() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let warm = EditParams::default().with_temperature(1.5);
+        repo.save_preset("warm", &warm).expect("save warm");
+        repo.save_preset("bright", &EditParams::default().with_exposure(1.0))
+            .expect("save bright");
+        let warmer = warm.with_tint(0.5);
+        repo.save_preset("warm", &warmer).expect("resave warm");
+
+        let names: Vec<String> = repo
+            .list_presets()
+            .expect("list presets")
+            .into_iter()
+            .map(|preset| preset.name)
+            .collect();
+        assert_eq!(names, vec!["bright".to_string(), "warm".to_string()]);
+        assert_eq!(
+            repo.find_preset("warm").expect("find warm"),
+            Some(EditPreset {
+                name: "warm".to_string(),
+                params: warmer,
+            })
+        );
+        assert_eq!(repo.find_preset("cool").expect("find cool"), None);
+    }
+
+    #[test]
+    fn moving_last_image_to_front_reorders_custom_list() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let paths: Vec<String> = (0..4)
+            .map(|index| format!("/tmp/essay-{index}.jpg"))
+            .collect();
+        let image_ids: Vec<ImageId> = paths
+            .iter()
+            .map(|path| {
+                repo.upsert_image(&new_image(path))
+                    .expect("upsert image")
+                    .image_id
+            })
+            .collect();
+
+        let moved_to = repo
+            .move_image(image_ids[3], SortMove::To(0))
+            .expect("move image");
+        assert_eq!(moved_to, 0);
+
+        let ordered: Vec<String> = repo
+            .list_images(
+                ImageSort::Custom,
+                &ImageFilter::default(),
+                ImagePage::default(),
+            )
+            .expect("list images")
+            .into_iter()
+            .map(|image| image.file_path)
+            .collect();
+        assert_eq!(
+            ordered,
+            vec![
+                paths[3].clone(),
+                paths[0].clone(),
+                paths[1].clone(),
+                paths[2].clone()
+            ]
+        );
+
+        let moved_down = repo
+            .move_image(image_ids[3], SortMove::Down)
+            .expect("move image down");
+        assert_eq!(moved_down, 1);
+        let first = repo
+            .list_images(
+                ImageSort::Custom,
+                &ImageFilter::default(),
+                ImagePage::default(),
+            )
+            .expect("list images")
+            .remove(0);
+        assert_eq!(first.file_path, paths[0]);
+    }
+
+    #[test]
+    fn list_with_thumbnails_joins_optional_thumbnail() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_ids: Vec<ImageId> = ["/tmp/thumbed.jpg", "/tmp/bare.jpg"]
+            .iter()
+            .map(|path| {
+                repo.upsert_image(&new_image(path))
+                    .expect("upsert image")
+                    .image_id
+            })
+            .collect();
+        repo.upsert_thumbnail(image_ids[0], "cache/thumbs/1.jpg", 256, 171, None, "1")
+            .expect("upsert thumbnail");
+
+        let listed = repo
+            .list_images_with_thumbnails(
+                ImageSort::Custom,
+                &ImageFilter::default(),
+                ImagePage::default(),
+            )
+            .expect("list with thumbnails");
+
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].image.id, image_ids[0]);
+        let thumbnail = listed[0].thumbnail.as_ref().expect("thumbnail joined");
+        assert_eq!(thumbnail.file_path, "cache/thumbs/1.jpg");
+        assert_eq!((thumbnail.width, thumbnail.height), (256, 171));
+        assert_eq!(listed[1].image.id, image_ids[1]);
+        assert!(listed[1].thumbnail.is_none());
+    }
+
+    #[test]
+    fn list_with_thumbnails_applies_filter_and_page() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_ids: Vec<ImageId> = [("a", 100), ("b", 800), ("c", 1600), ("d", 3200)]
+            .iter()
+            .map(|(name, iso)| {
+                let image_id = repo
+                    .upsert_image(&NewImage {
+                        iso: Some(*iso),
+                        ..new_image(&format!("/tmp/{name}.jpg"))
+                    })
+                    .expect("upsert image")
+                    .image_id;
+                repo.upsert_thumbnail(image_id, &format!("cache/{name}.jpg"), 8, 8, None, "1")
+                    .expect("upsert thumbnail");
+                image_id
+            })
+            .collect();
+
+        let listed = repo
+            .list_images_with_thumbnails(
+                ImageSort::Custom,
+                &ImageFilter {
+                    iso_min: Some(800),
+                    ..ImageFilter::default()
+                },
+                ImagePage {
+                    limit: Some(1),
+                    offset: 1,
+                },
+            )
+            .expect("list with thumbnails");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].image.id, image_ids[2]);
+        assert_eq!(
+            listed[0]
+                .thumbnail
+                .as_ref()
+                .map(|thumb| thumb.file_path.as_str()),
+            Some("cache/c.jpg")
+        );
+    }
+
+    #[test]
+    fn thumbnail_dimensions_are_range_checked() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_id = repo
+            .upsert_image(&new_image("/tmp/panorama.jpg"))
+            .expect("upsert image")
+            .image_id;
+
+        assert!(matches!(
+            repo.upsert_thumbnail(image_id, "cache/thumbs/1.jpg", -1, 171, None, "1"),
+            Err(ApplicationError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            repo.upsert_thumbnail(
+                image_id,
+                "cache/thumbs/1.jpg",
+                256,
+                i64::from(u32::MAX) + 1,
+                None,
+                "1"
+            ),
+            Err(ApplicationError::InvalidInput(_))
+        ));
+        let listed = repo
+            .list_images_with_thumbnails(
+                ImageSort::Custom,
+                &ImageFilter::default(),
+                ImagePage::default(),
+            )
+            .expect("list without thumbnail");
+        assert!(listed[0].thumbnail.is_none());
+
+        repo.upsert_thumbnail(
+            image_id,
+            "cache/thumbs/1.jpg",
+            120_000,
+            3_000_000_000,
+            None,
+            "1",
+        )
+        .expect("large thumbnail dimensions fit u32");
+        let listed = repo
+            .list_images_with_thumbnails(
+                ImageSort::Custom,
+                &ImageFilter::default(),
+                ImagePage::default(),
+            )
+            .expect("list with thumbnail");
+        let thumbnail = listed[0].thumbnail.as_ref().expect("thumbnail joined");
+        assert_eq!(
+            (thumbnail.width, thumbnail.height),
+            (120_000, 3_000_000_000)
+        );
+    }
+
+    #[test]
+    fn list_images_filters_by_focal_range_and_lens() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_ids: Vec<ImageId> = [
+            ("/tmp/wide.jpg", "EF 24mm f/1.4L", 24.0),
+            ("/tmp/normal.jpg", "EF 50mm f/1.8", 50.0),
+            ("/tmp/portrait.jpg", "EF 85mm f/1.8", 85.0),
+        ]
+        .iter()
+        .map(|(path, lens, focal_length)| {
+            repo.upsert_image(&NewImage {
+                lens_model: Some(lens.to_string()),
+                focal_length: Some(*focal_length),
+                ..new_image(path)
+            })
+            .expect("upsert image")
+            .image_id
+        })
+        .collect();
+
+        let in_range = repo
+            .list_images(
+                ImageSort::Custom,
+                &ImageFilter {
+                    focal_min: Some(35.0),
+                    focal_max: Some(60.0),
+                    ..ImageFilter::default()
+                },
+                ImagePage::default(),
+            )
+            .expect("list focal range");
+        assert_eq!(in_range.len(), 1);
+        assert_eq!(in_range[0].id, image_ids[1]);
+
+        let by_lens = repo
+            .list_images(
+                ImageSort::Custom,
+                &ImageFilter {
+                    lens: Some("f/1.8".to_string()),
+                    focal_min: Some(60.0),
+                    ..ImageFilter::default()
+                },
+                ImagePage::default(),
+            )
+            .expect("list lens");
+        let ids: Vec<ImageId> = by_lens.iter().map(|image| image.id).collect();
+        assert_eq!(ids, vec![image_ids[2]]);
+    }
+
+    #[test]
+    fn list_images_filters_by_camera_and_iso_range() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_ids: Vec<ImageId> = [
+            ("/tmp/street.jpg", Some("X-T5"), Some(200)),
+            ("/tmp/night.jpg", Some("X-T5"), Some(6400)),
+            ("/tmp/gig.jpg", Some("EOS R6"), Some(3200)),
+            ("/tmp/scan.jpg", None, None),
+        ]
+        .iter()
+        .map(|(path, camera, iso)| {
+            repo.upsert_image(&NewImage {
+                camera_model: camera.map(str::to_string),
+                iso: *iso,
+                ..new_image(path)
+            })
+            .expect("upsert image")
+            .image_id
+        })
+        .collect();
+        let listed = |filter: ImageFilter| -> Vec<ImageId> {
+            repo.list_images(ImageSort::Custom, &filter, ImagePage::default())
+                .expect("list")
+                .iter()
+                .map(|image| image.id)
+                .collect()
+        };
+
+        assert_eq!(
+            listed(ImageFilter {
+                camera: Some("X-T".to_string()),
+                ..ImageFilter::default()
+            }),
+            vec![image_ids[0], image_ids[1]]
+        );
+        assert_eq!(
+            listed(ImageFilter {
+                iso_min: Some(3200),
+                ..ImageFilter::default()
+            }),
+            vec![image_ids[1], image_ids[2]]
+        );
+        assert_eq!(
+            listed(ImageFilter {
+                camera: Some("X-T5".to_string()),
+                iso_max: Some(3200),
+                ..ImageFilter::default()
+            }),
+            vec![image_ids[0]]
+        );
+        assert!(listed(ImageFilter {
+            camera: Some("%".to_string()),
+            iso_min: Some(100),
+            iso_max: Some(100),
+            ..ImageFilter::default()
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn list_images_filters_by_inclusive_date_range() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_ids: Vec<ImageId> = [
+            ("winter", "2024-01-05T09:00:00", Some("2023-12-31T23:59:59")),
+            ("spring", "2024-04-01T00:00:00", Some("2024-03-01T08:30:00")),
+            ("summer", "2024-07-01T00:00:00", Some("2024-06-30T18:00:00")),
+            ("scan", "2024-05-15T12:00:00", None),
+            // What `SystemClock` writes: 2024-02-10T10:00:00Z in epoch seconds.
+            ("phone", "1707559200", None),
+        ]
+        .iter()
+        .map(|(name, import_date, capture_date)| {
+            repo.upsert_image(&NewImage {
+                import_date: import_date.to_string(),
+                capture_date: capture_date.map(str::to_string),
+                ..new_image(&format!("/tmp/{name}.jpg"))
+            })
+            .expect("upsert image")
+            .image_id
+        })
+        .collect();
+        let listed = |from: Option<&str>, to: Option<&str>| -> Vec<ImageId> {
+            repo.list_images(
+                ImageSort::CaptureDateAsc,
+                &ImageFilter {
+                    from: from.map(str::to_string),
+                    to: to.map(str::to_string),
+                    ..ImageFilter::default()
+                },
+                ImagePage::default(),
+            )
+            .expect("list by date")
+            .iter()
+            .map(|image| image.id)
+            .collect()
+        };
+
+        assert_eq!(
+            listed(Some("2024-01-01"), Some("2024-06-30")),
+            vec![image_ids[4], image_ids[1], image_ids[3], image_ids[2]]
+        );
+        assert_eq!(listed(None, Some("2023-12-31")), vec![image_ids[0]]);
+        assert_eq!(listed(Some("2024-06-01"), None), vec![image_ids[2]]);
+        assert_eq!(
+            listed(Some("2024-02-10"), Some("2024-02-10")),
+            vec![image_ids[4]]
+        );
+        assert_eq!(
+            listed(None, None),
+            vec![
+                image_ids[0],
+                image_ids[4],
+                image_ids[1],
+                image_ids[3],
+                image_ids[2]
+            ]
+        );
+    }
+
+    #[test]
+    fn collapsed_list_keeps_only_stack_pick() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let image_ids: Vec<ImageId> = ["burst-1", "burst-2", "burst-3", "single"]
+            .iter()
+            .map(|name| {
+                repo.upsert_image(&new_image(&format!("/tmp/{name}.jpg")))
+                    .expect("upsert image")
+                    .image_id
+            })
+            .collect();
+
+        let stack_id = repo.create_stack(&image_ids[..3]).expect("create stack");
+        assert_eq!(
+            repo.set_stack_pick(image_ids[1]).expect("pick"),
+            Some(stack_id)
+        );
+        assert_eq!(repo.set_stack_pick(image_ids[3]).expect("unstacked"), None);
+
+        let collapsed = repo
+            .list_images(
+                ImageSort::Custom,
+                &ImageFilter {
+                    collapse_stacks: true,
+                    ..ImageFilter::default()
+                },
+                ImagePage::default(),
+            )
+            .expect("list collapsed");
+        let ids: Vec<ImageId> = collapsed.iter().map(|image| image.id).collect();
+        assert_eq!(ids, vec![image_ids[1], image_ids[3]]);
+
+        let expanded = repo
+            .list_images(
+                ImageSort::Custom,
+                &ImageFilter::default(),
+                ImagePage::default(),
+            )
+            .expect("list expanded");
+        assert_eq!(expanded.len(), 4);
+    }
+
+    #[test]
+    fn optimize_keeps_populated_catalog_valid() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        for index in 0..200 {
+            let upsert = repo
+                .upsert_image(&new_image(&format!("/tmp/optimize-{index}.jpg")))
+                .expect("upsert image");
+            repo.upsert_edit(upsert.image_id, &"x".repeat(512), "1")
+                .expect("upsert edit");
+        }
+        let conn = Connection::open(&db_path).expect("open");
+        conn.execute("DELETE FROM edits", []).expect("delete edits");
+        drop(conn);
+
+        let report = repo.optimize().expect("optimize");
+        assert!(report.size_after_bytes <= report.size_before_bytes);
+
+        let conn = Connection::open(&db_path).expect("open");
+        let integrity: String = conn
+            .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .expect("integrity check");
+        assert_eq!(integrity, "ok");
+        assert_eq!(
+            repo.list_images(
+                ImageSort::Custom,
+                &ImageFilter::default(),
+                ImagePage::default()
+            )
+            .expect("list")
+            .len(),
+            200
+        );
+    }
+
+    #[test]
+    fn concurrent_edit_writes_wait_for_lock() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir
+            .path()
+            .join("catalog.sqlite3")
+            .to_string_lossy()
+            .to_string();
+        let repo = SqliteCatalogRepository::new(db_path.clone());
+        repo.initialize().expect("initialize");
+
+        let image_ids: Vec<ImageId> = (0..2)
+            .map(|index| {
+                repo.upsert_image(&new_image(&format!("/tmp/concurrent-{index}.jpg")))
+                    .expect("upsert image")
+                    .image_id
+            })
+            .collect();
+
+        let handles: Vec<_> = image_ids
+            .iter()
+            .copied()
+            .map(|image_id| {
+                let writer = SqliteCatalogRepository::new(db_path.clone());
+                std::thread::spawn(move || {
+                    for step in 0..50 {
+                        let params = EditParams {
+                            exposure: step as f32 / 10.0,
+                            ..EditParams::default()
+                        };
+                        let json = serde_json::to_string(&params).expect("json");
+                        writer
+                            .upsert_edit(image_id, &json, &step.to_string())
+                            .expect("concurrent upsert edit");
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().expect("writer thread");
+        }
+
+        for image_id in image_ids {
+            let stored = repo
+                .find_edit(image_id)
+                .expect("find edit")
+                .expect("edit exists");
+            assert_eq!(stored.updated_at, "49");
+        }
+    }
+
+    #[test]
+    fn repository_reuses_one_connection_across_many_queries() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+        // Temp tables live only as long as the connection that made them.
+        repo.open_connection()
+            .expect("connection")
+            .execute_batch("CREATE TEMP TABLE connection_marker (id INTEGER);")
+            .expect("marker");
+
+        for index in 0..100 {
+            let image_id = repo
+                .upsert_image(&new_image(&format!("/tmp/shared-{index}.jpg")))
+                .expect("upsert image")
+                .image_id;
+            repo.set_rating(image_id, index % 6).expect("set rating");
+            let image = repo
+                .find_image_by_id(image_id)
+                .expect("find image")
+                .expect("image exists");
+            assert_eq!(image.rating, index % 6);
+        }
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let repo = repo.clone();
+                std::thread::spawn(move || {
+                    repo.list_images(
+                        ImageSort::default(),
+                        &ImageFilter::default(),
+                        ImagePage::default(),
+                    )
+                    .expect("list from another thread")
+                    .len()
+                })
+            })
+            .collect();
+        for writer in writers {
+            assert_eq!(writer.join().expect("thread"), 100);
+        }
+
+        let conn = repo.open_connection().expect("connection");
+        let markers: i64 = conn
+            .query_row("SELECT COUNT(*) FROM temp.connection_marker", [], |row| {
+                row.get(0)
+            })
+            .expect("marker table survives on the same connection");
+        assert_eq!(markers, 0);
+        let (foreign_keys, journal_mode): (i64, String) = conn
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .and_then(|keys| {
+                conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))
+                    .map(|mode| (keys, mode))
+            })
+            .expect("pragmas");
+        assert_eq!((foreign_keys, journal_mode.as_str()), (1, "wal"));
+    }
+
+    #[test]
+    fn schema_inspection_reports_tables_and_columns_the_migrations_expect() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let report = repo.inspect_schema().expect("inspect");
+        assert!(report.is_healthy(), "fresh catalog: {report:?}");
+        assert_eq!(report.applied_version, MIGRATIONS.len());
+
+        let conn = Connection::open(&db_path).expect("open");
+        conn.execute_batch(
+            "DROP TABLE edits;
+             CREATE TABLE presets_old AS SELECT name FROM presets;
+             DROP TABLE presets;
+             ALTER TABLE presets_old RENAME TO presets;",
+        )
+        .expect("damage schema");
+        let report = repo.inspect_schema().expect("inspect damaged");
+        assert!(!report.is_healthy());
+        assert_eq!(report.missing_tables, vec!["edits".to_string()]);
+        assert_eq!(
+            report.missing_columns,
+            vec!["presets.edit_params_json".to_string()]
+        );
+    }
+
+    #[test]
+    fn list_sorts_by_rating_and_returns_an_offset_window() {
+        let dir = TempDir::new().expect("tempdir");
+        let db_path = dir.path().join("catalog.sqlite3");
+        let repo = SqliteCatalogRepository::new(db_path.to_string_lossy().to_string());
+        repo.initialize().expect("initialize");
+
+        let images: Vec<ImageId> = [
+            ("/tmp/d.jpg", "2026-01-04", 3),
+            ("/tmp/a.jpg", "2026-01-01", 5),
+            ("/tmp/c.jpg", "2026-01-03", 3),
+            ("/tmp/b.jpg", "2026-01-02", 0),
+        ]
+        .iter()
+        .map(|(path, captured, rating)| {
+            let image_id = repo
+                .upsert_image(&NewImage {
+                    import_date: "2026-02-01".to_string(),
+                    capture_date: Some(captured.to_string()),
+                    ..new_image(path)
+                })
+                .expect("upsert image")
+                .image_id;
+            repo.set_rating(image_id, *rating).expect("rate");
+            image_id
+        })
+        .collect();
+        let list = |sort, page| -> Vec<ImageId> {
+            repo.list_images(sort, &ImageFilter::default(), page)
+                .expect("list")
+                .iter()
+                .map(|image| image.id)
+                .collect()
+        };
+        let all = ImagePage::default();
+
+        // Equal ratings fall back to newest capture first.
+        assert_eq!(
+            list(ImageSort::RatingDesc, all),
+            vec![images[1], images[0], images[2], images[3]]
+        );
+        assert_eq!(
+            list(ImageSort::CaptureDate, all),
+            vec![images[0], images[2], images[3], images[1]]
+        );
+        assert_eq!(
+            list(ImageSort::CaptureDateAsc, all),
+            vec![images[1], images[3], images[2], images[0]]
+        );
+        assert_eq!(
+            list(ImageSort::FilePathAsc, all),
+            vec![images[1], images[3], images[2], images[0]]
+        );
+
+        let window = ImagePage {
+            limit: Some(2),
+            offset: 1,
+        };
+        assert_eq!(
+            list(ImageSort::RatingDesc, window),
+            vec![images[0], images[2]]
+        );
+        let tail = ImagePage {
+            limit: None,
+            offset: 3,
+        };
+        assert_eq!(list(ImageSort::RatingDesc, tail), vec![images[3]]);
+        let past_end = ImagePage {
+            limit: Some(10),
+            offset: 4,
+        };
+        assert!(list(ImageSort::RatingDesc, past_end).is_empty());
+    }
 }
@@ -1,5 +1,66 @@
-use lite_room_domain::{ImageId, ImageRecord};
-use rusqlite::{params, Connection, Result};
+use lite_room_application::CaptureMetadata;
+use lite_room_domain::{ImageFilter, ImageId, ImagePage, ImageRecord, ImageSort, SortMove};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Result};
+
+pub type ThumbnailRow = (String, i64, i64, Option<String>);
+
+pub fn create_schema_migrations(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        );",
+    )
+}
+
+pub fn schema_version(conn: &Connection) -> Result<usize> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )
+}
+
+pub fn table_exists(conn: &Connection, name: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        params![name],
+        |row| row.get(0),
+    )
+}
+
+// Every user table with its column names, ordered by table name.
+pub fn table_columns(conn: &Connection) -> Result<Vec<(String, Vec<String>)>> {
+    let mut tables = conn.prepare(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+         ORDER BY name",
+    )?;
+    let names = tables
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    let mut columns = conn.prepare("SELECT name FROM pragma_table_info(?1)")?;
+    names
+        .into_iter()
+        .map(|table| {
+            let table_columns = columns
+                .query_map(params![table], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>>>()?;
+            Ok((table, table_columns))
+        })
+        .collect()
+}
+
+pub fn record_migration(conn: &Connection, version: usize, name: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO schema_migrations (version, name, applied_at)
+         VALUES (?1, ?2, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+        params![version as i64, name],
+    )?;
+    Ok(())
+}
 
 pub fn upsert_thumbnail(
     conn: &Connection,
@@ -7,17 +68,19 @@ pub fn upsert_thumbnail(
     file_path: &str,
     width: i64,
     height: i64,
+    checksum: Option<&str>,
     updated_at: &str,
 ) -> Result<()> {
     conn.execute(
-        "INSERT INTO thumbnails (image_id, file_path, width, height, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5)
+        "INSERT INTO thumbnails (image_id, file_path, width, height, checksum, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
          ON CONFLICT(image_id) DO UPDATE SET
             file_path = excluded.file_path,
             width = excluded.width,
             height = excluded.height,
+            checksum = excluded.checksum,
             updated_at = excluded.updated_at",
-        params![image_id, file_path, width, height, updated_at],
+        params![image_id, file_path, width, height, checksum, updated_at],
     )?;
     Ok(())
 }
@@ -68,14 +131,117 @@ pub fn find_edit(conn: &Connection, image_id: i64) -> Result<Option<(String, Str
     Ok(None)
 }
 
-pub fn list_images(conn: &Connection) -> Result<Vec<ImageRecord>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, file_path, import_date, capture_date, rating, flag, metadata_json
-         FROM images
-         ORDER BY COALESCE(capture_date, import_date) DESC, id DESC",
-    )?;
+pub fn find_edits(conn: &Connection, image_ids: &[i64]) -> Result<Vec<(i64, String, String)>> {
+    if image_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders = vec!["?"; image_ids.len()].join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT image_id, edit_params_json, updated_at
+         FROM edits
+         WHERE image_id IN ({placeholders})"
+    ))?;
+    let rows = stmt.query_map(params_from_iter(image_ids), |row| {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    })?;
+    rows.collect()
+}
+
+// The capture date, else the import date. The clock writes import dates as
+// epoch seconds, so an all-digit one is rendered in the ISO form EXIF capture
+// dates use before the two are compared as text.
+const IMAGE_DATE: &str = "COALESCE(i.capture_date, CASE
+        WHEN i.import_date GLOB '[0-9]*' AND i.import_date NOT GLOB '*[^0-9]*'
+        THEN strftime('%Y-%m-%dT%H:%M:%S', CAST(i.import_date AS INTEGER), 'unixepoch')
+        ELSE i.import_date
+    END)";
+
+// Fixed ORDER BY clauses over the `i` alias; the sort never reaches the SQL
+// as text, only as one of these clauses. Each ends on the id so ties come
+// back in a stable order across pages.
+fn image_order_by(sort: ImageSort) -> String {
+    match sort {
+        ImageSort::CaptureDate => format!("{IMAGE_DATE} DESC, i.id DESC"),
+        ImageSort::CaptureDateAsc => format!("{IMAGE_DATE} ASC, i.id ASC"),
+        ImageSort::RatingDesc => format!("i.rating DESC, {IMAGE_DATE} DESC, i.id DESC"),
+        ImageSort::FilePathAsc => "i.file_path ASC, i.id ASC".to_string(),
+        ImageSort::Custom => "i.sort_index ASC, i.id ASC".to_string(),
+    }
+}
+
+// The WHERE clause and its bound values for `filter`, over the `i` alias, so
+// both list queries narrow the catalog the same way.
+fn image_filter_clause(filter: &ImageFilter) -> (String, Vec<Value>) {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if let Some(lens) = &filter.lens {
+        conditions.push("i.lens_model LIKE '%' || ? || '%'".to_string());
+        values.push(Value::Text(lens.clone()));
+    }
+    if let Some(focal_min) = filter.focal_min {
+        conditions.push("i.focal_length >= ?".to_string());
+        values.push(Value::Real(focal_min));
+    }
+    if let Some(focal_max) = filter.focal_max {
+        conditions.push("i.focal_length <= ?".to_string());
+        values.push(Value::Real(focal_max));
+    }
+    if let Some(camera) = &filter.camera {
+        conditions.push("i.camera_model LIKE '%' || ? || '%'".to_string());
+        values.push(Value::Text(camera.clone()));
+    }
+    if let Some(iso_min) = filter.iso_min {
+        conditions.push("i.iso >= ?".to_string());
+        values.push(Value::Integer(iso_min));
+    }
+    if let Some(iso_max) = filter.iso_max {
+        conditions.push("i.iso <= ?".to_string());
+        values.push(Value::Integer(iso_max));
+    }
+    if let Some(from) = &filter.from {
+        conditions.push(format!("substr({IMAGE_DATE}, 1, 10) >= ?"));
+        values.push(Value::Text(from.clone()));
+    }
+    if let Some(to) = &filter.to {
+        conditions.push(format!("substr({IMAGE_DATE}, 1, 10) <= ?"));
+        values.push(Value::Text(to.clone()));
+    }
+    if filter.collapse_stacks {
+        conditions.push("(i.stack_id IS NULL OR i.is_stack_pick = 1)".to_string());
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    (where_clause, values)
+}
 
-    let rows = stmt.query_map([], |row| {
+// A negative LIMIT is sqlite's "no limit".
+fn push_page_values(values: &mut Vec<Value>, page: ImagePage) {
+    values.push(Value::Integer(page.limit.map_or(-1, |limit| limit as i64)));
+    values.push(Value::Integer(page.offset as i64));
+}
+
+pub fn list_images(
+    conn: &Connection,
+    sort: ImageSort,
+    filter: &ImageFilter,
+    page: ImagePage,
+) -> Result<Vec<ImageRecord>> {
+    let order_by = image_order_by(sort);
+    let (where_clause, mut values) = image_filter_clause(filter);
+    push_page_values(&mut values, page);
+    let mut stmt = conn.prepare(&format!(
+        "SELECT i.id, i.file_path, i.import_date, i.capture_date, i.rating, i.flag,
+                i.metadata_json, i.caption
+         FROM images i
+         {where_clause}
+         ORDER BY {order_by}
+         LIMIT ? OFFSET ?"
+    ))?;
+
+    let rows = stmt.query_map(params_from_iter(values), |row| {
         let id_value: i64 = row.get(0)?;
         Ok(ImageRecord {
             id: ImageId::new(id_value).expect("database returned non-positive image id"),
@@ -85,15 +251,59 @@ pub fn list_images(conn: &Connection) -> Result<Vec<ImageRecord>> {
             rating: row.get(4)?,
             flag: row.get(5)?,
             metadata_json: row.get(6)?,
+            caption: row.get(7)?,
         })
     })?;
 
     rows.collect()
 }
 
+pub fn list_images_with_thumbnails(
+    conn: &Connection,
+    sort: ImageSort,
+    filter: &ImageFilter,
+    page: ImagePage,
+) -> Result<Vec<(ImageRecord, Option<ThumbnailRow>)>> {
+    let order_by = image_order_by(sort);
+    let (where_clause, mut values) = image_filter_clause(filter);
+    push_page_values(&mut values, page);
+    let mut stmt = conn.prepare(&format!(
+        "SELECT i.id, i.file_path, i.import_date, i.capture_date, i.rating, i.flag,
+                i.metadata_json, i.caption, t.file_path, t.width, t.height, t.checksum
+         FROM images i
+         LEFT JOIN thumbnails t ON t.image_id = i.id
+         {where_clause}
+         ORDER BY {order_by}
+         LIMIT ? OFFSET ?"
+    ))?;
+
+    let rows = stmt.query_map(params_from_iter(values), |row| {
+        let id_value: i64 = row.get(0)?;
+        let image = ImageRecord {
+            id: ImageId::new(id_value).expect("database returned non-positive image id"),
+            file_path: row.get(1)?,
+            import_date: row.get(2)?,
+            capture_date: row.get(3)?,
+            rating: row.get(4)?,
+            flag: row.get(5)?,
+            metadata_json: row.get(6)?,
+            caption: row.get(7)?,
+        };
+        let thumbnail_path: Option<String> = row.get(8)?;
+        let thumbnail = match thumbnail_path {
+            Some(path) => Some((path, row.get(9)?, row.get(10)?, row.get(11)?)),
+            None => None,
+        };
+        Ok((image, thumbnail))
+    })?;
+
+    rows.collect()
+}
+
 pub fn find_image_by_id(conn: &Connection, image_id: i64) -> Result<Option<ImageRecord>> {
     let mut stmt = conn.prepare(
-        "SELECT id, file_path, import_date, capture_date, rating, flag, metadata_json
+        "SELECT id, file_path, import_date, capture_date, rating, flag, metadata_json,
+                caption
          FROM images
          WHERE id = ?1",
     )?;
@@ -109,8 +319,284 @@ pub fn find_image_by_id(conn: &Connection, image_id: i64) -> Result<Option<Image
             rating: row.get(4)?,
             flag: row.get(5)?,
             metadata_json: row.get(6)?,
+            caption: row.get(7)?,
         }));
     }
 
     Ok(None)
 }
+
+pub fn set_rating(conn: &Connection, image_id: i64, rating: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE images SET rating = ?2 WHERE id = ?1",
+        params![image_id, rating],
+    )?;
+    Ok(())
+}
+
+pub fn set_flag(conn: &Connection, image_id: i64, flag: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE images SET flag = ?2 WHERE id = ?1",
+        params![image_id, flag],
+    )?;
+    Ok(())
+}
+
+pub fn fill_capture_metadata(
+    conn: &Connection,
+    image_id: i64,
+    capture: &CaptureMetadata,
+) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE images
+         SET capture_date = COALESCE(capture_date, ?2),
+             camera_model = COALESCE(camera_model, ?3),
+             iso = COALESCE(iso, ?4),
+             lens_model = COALESCE(lens_model, ?5),
+             focal_length = COALESCE(focal_length, ?6)
+         WHERE id = ?1
+           AND ((capture_date IS NULL AND ?2 IS NOT NULL)
+             OR (camera_model IS NULL AND ?3 IS NOT NULL)
+             OR (iso IS NULL AND ?4 IS NOT NULL)
+             OR (lens_model IS NULL AND ?5 IS NOT NULL)
+             OR (focal_length IS NULL AND ?6 IS NOT NULL))",
+        params![
+            image_id,
+            capture.capture_date,
+            capture.camera_model,
+            capture.iso,
+            capture.lens_model,
+            capture.focal_length
+        ],
+    )?;
+    Ok(changed == 1)
+}
+
+pub fn find_content_duplicate(
+    conn: &Connection,
+    content_hash: &str,
+    file_path: &str,
+) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT id FROM images WHERE content_hash = ?1 AND file_path <> ?2 ORDER BY id LIMIT 1",
+        params![content_hash, file_path],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn relink_image(conn: &Connection, image_id: i64, file_path: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE images SET file_path = ?2 WHERE id = ?1",
+        params![image_id, file_path],
+    )?;
+    Ok(())
+}
+
+// The edit and thumbnail rows go with the image through ON DELETE CASCADE;
+// the catalog connection always runs with `foreign_keys=ON`.
+pub fn delete_image(conn: &Connection, image_id: i64) -> Result<Option<Vec<String>>> {
+    let tx = conn.unchecked_transaction()?;
+    let thumbnails = {
+        let mut stmt = tx.prepare("SELECT file_path FROM thumbnails WHERE image_id = ?1")?;
+        let rows = stmt.query_map(params![image_id], |row| row.get(0))?;
+        rows.collect::<Result<Vec<String>>>()?
+    };
+    let deleted = tx.execute("DELETE FROM images WHERE id = ?1", params![image_id])?;
+    tx.commit()?;
+    Ok((deleted == 1).then_some(thumbnails))
+}
+
+pub fn set_caption(conn: &Connection, image_id: i64, caption: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE images SET caption = ?2 WHERE id = ?1",
+        params![image_id, caption],
+    )?;
+    Ok(())
+}
+
+pub fn find_caption(conn: &Connection, image_id: i64) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT caption FROM images WHERE id = ?1",
+        params![image_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+}
+
+pub fn create_stack(conn: &Connection, image_ids: &[i64]) -> Result<i64> {
+    let tx = conn.unchecked_transaction()?;
+    let stack_id: i64 = tx.query_row(
+        "SELECT COALESCE(MAX(stack_id), 0) + 1 FROM images",
+        [],
+        |row| row.get(0),
+    )?;
+    for (index, image_id) in image_ids.iter().enumerate() {
+        tx.execute(
+            "UPDATE images SET stack_id = ?2, is_stack_pick = ?3 WHERE id = ?1",
+            params![image_id, stack_id, index == 0],
+        )?;
+    }
+    // Regrouping can take the pick out of an older stack; promote its lowest id.
+    tx.execute(
+        "UPDATE images SET is_stack_pick = 1
+         WHERE id IN (
+            SELECT MIN(id) FROM images
+            WHERE stack_id IS NOT NULL
+            GROUP BY stack_id
+            HAVING MAX(is_stack_pick) = 0
+         )",
+        [],
+    )?;
+    tx.commit()?;
+    Ok(stack_id)
+}
+
+pub fn set_stack_pick(conn: &Connection, image_id: i64) -> Result<Option<i64>> {
+    let tx = conn.unchecked_transaction()?;
+    let stack_id: Option<i64> = tx
+        .query_row(
+            "SELECT stack_id FROM images WHERE id = ?1",
+            params![image_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    if let Some(stack_id) = stack_id {
+        tx.execute(
+            "UPDATE images SET is_stack_pick = (id = ?1) WHERE stack_id = ?2",
+            params![image_id, stack_id],
+        )?;
+    }
+    tx.commit()?;
+    Ok(stack_id)
+}
+
+pub fn find_import_checkpoint(conn: &Connection) -> Result<Option<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT folder, last_path FROM import_checkpoint WHERE id = 1")?;
+    let mut rows = stmt.query([])?;
+    if let Some(row) = rows.next()? {
+        return Ok(Some((row.get(0)?, row.get(1)?)));
+    }
+    Ok(None)
+}
+
+pub fn save_import_checkpoint(
+    conn: &Connection,
+    folder: &str,
+    last_path: &str,
+    updated_at: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO import_checkpoint (id, folder, last_path, updated_at)
+         VALUES (1, ?1, ?2, ?3)
+         ON CONFLICT(id) DO UPDATE SET
+            folder = excluded.folder,
+            last_path = excluded.last_path,
+            updated_at = excluded.updated_at",
+        params![folder, last_path, updated_at],
+    )?;
+    Ok(())
+}
+
+pub fn clear_import_checkpoint(conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM import_checkpoint", [])?;
+    Ok(())
+}
+
+pub fn record_import_failure(
+    conn: &Connection,
+    file_path: &str,
+    reason: &str,
+    failed_at: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO import_failures (file_path, reason, failed_at)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(file_path) DO UPDATE SET
+            reason = excluded.reason,
+            failed_at = excluded.failed_at",
+        params![file_path, reason, failed_at],
+    )?;
+    Ok(())
+}
+
+pub fn list_import_failures(conn: &Connection) -> Result<Vec<(String, String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT file_path, reason, failed_at FROM import_failures ORDER BY failed_at, file_path",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+    rows.collect()
+}
+
+pub fn clear_import_failure(conn: &Connection, file_path: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM import_failures WHERE file_path = ?1",
+        params![file_path],
+    )?;
+    Ok(())
+}
+
+pub fn save_preset(conn: &Connection, name: &str, edit_params_json: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO presets (name, edit_params_json)
+         VALUES (?1, ?2)
+         ON CONFLICT(name) DO UPDATE SET edit_params_json = excluded.edit_params_json",
+        params![name, edit_params_json],
+    )?;
+    Ok(())
+}
+
+pub fn list_presets(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT name, edit_params_json FROM presets ORDER BY name")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+pub fn find_preset(conn: &Connection, name: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT edit_params_json FROM presets WHERE name = ?1",
+        params![name],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn move_image(conn: &Connection, image_id: i64, target: SortMove) -> Result<i64> {
+    let tx = conn.unchecked_transaction()?;
+    let current: i64 = tx.query_row(
+        "SELECT sort_index FROM images WHERE id = ?1",
+        params![image_id],
+        |row| row.get(0),
+    )?;
+    let count: i64 = tx.query_row("SELECT COUNT(*) FROM images", [], |row| row.get(0))?;
+
+    let requested = match target {
+        SortMove::Up => current - 1,
+        SortMove::Down => current + 1,
+        SortMove::To(index) => index,
+    };
+    let destination = requested.clamp(0, count - 1);
+
+    if destination < current {
+        tx.execute(
+            "UPDATE images SET sort_index = sort_index + 1
+             WHERE sort_index >= ?1 AND sort_index < ?2",
+            params![destination, current],
+        )?;
+    } else if destination > current {
+        tx.execute(
+            "UPDATE images SET sort_index = sort_index - 1
+             WHERE sort_index > ?1 AND sort_index <= ?2",
+            params![current, destination],
+        )?;
+    }
+    tx.execute(
+        "UPDATE images SET sort_index = ?1 WHERE id = ?2",
+        params![destination, image_id],
+    )?;
+    tx.commit()?;
+
+    Ok(destination)
+}
@@ -0,0 +1,266 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use image::{ImageFormat, Rgb, RgbImage};
+use lite_room_domain::{
+    DecodedBuffer, EditParams, ImageId, PreviewRequest, DEFAULT_SPLIT_FRACTION,
+};
+
+use crate::CpuPreviewRenderer;
+
+const GOLDEN_TOLERANCE: u8 = 1;
+const UPDATE_GOLDEN_ENV: &str = "LITE_ROOM_UPDATE_GOLDEN";
+const GRADIENT_WIDTH: u32 = 16;
+const GRADIENT_HEIGHT: u32 = 8;
+
+pub fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures/golden")
+        .join(name)
+}
+
+pub fn gradient_request(params: EditParams) -> PreviewRequest {
+    let mut rgb = Vec::with_capacity((GRADIENT_WIDTH * GRADIENT_HEIGHT * 3) as usize);
+    for y in 0..GRADIENT_HEIGHT {
+        for x in 0..GRADIENT_WIDTH {
+            rgb.extend_from_slice(&[(x * 17) as u8, (y * 36) as u8, (255 - x * 8) as u8]);
+        }
+    }
+    PreviewRequest {
+        image_id: ImageId::new(1).expect("id"),
+        source_path: "golden-gradient".to_string(),
+        source_pixels: Some(Arc::new(DecodedBuffer {
+            width: GRADIENT_WIDTH,
+            height: GRADIENT_HEIGHT,
+            rgb,
+        })),
+        source_region: None,
+        view_region: None,
+        params,
+        target_width: GRADIENT_WIDTH,
+        target_height: GRADIENT_HEIGHT,
+        want_histogram: false,
+        comparison: None,
+        split_fraction: DEFAULT_SPLIT_FRACTION,
+    }
+}
+
+pub fn assert_render_matches(request: PreviewRequest, expected: &Path) {
+    let rendered = CpuPreviewRenderer::default()
+        .render(request)
+        .expect("render golden request");
+    let actual = RgbImage::from_fn(rendered.width, rendered.height, |x, y| {
+        let pixel = rendered.pixels[(y * rendered.width + x) as usize];
+        Rgb([(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+    });
+
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        actual
+            .save_with_format(expected, ImageFormat::Png)
+            .expect("write golden fixture");
+        return;
+    }
+
+    let golden = image::open(expected)
+        .unwrap_or_else(|error| {
+            panic!("cannot read golden {expected:?} ({error}); rerun with {UPDATE_GOLDEN_ENV}=1")
+        })
+        .to_rgb8();
+    assert_eq!(
+        golden.dimensions(),
+        actual.dimensions(),
+        "golden {expected:?} dimensions differ"
+    );
+
+    let mismatches: Vec<(u32, u32, [u8; 3], [u8; 3])> = actual
+        .enumerate_pixels()
+        .filter_map(|(x, y, pixel)| {
+            let want = golden.get_pixel(x, y);
+            let within = pixel
+                .0
+                .iter()
+                .zip(want.0.iter())
+                .all(|(got, want)| got.abs_diff(*want) <= GOLDEN_TOLERANCE);
+            (!within).then_some((x, y, pixel.0, want.0))
+        })
+        .collect();
+    if let Some((x, y, got, want)) = mismatches.first() {
+        panic!(
+            "{} pixels differ from golden {expected:?}; first at ({x}, {y}): got {got:?}, want {want:?}",
+            mismatches.len()
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExifTags<'a> {
+    pub model: Option<&'a str>,
+    pub date_time_original: Option<&'a str>,
+    pub iso: Option<u16>,
+    pub lens_model: Option<&'a str>,
+    // Numerator and denominator of the RATIONAL value.
+    pub focal_length: Option<(u32, u32)>,
+}
+
+// Builds a minimal TIFF block: IFD0 holds Model and the Exif IFD pointer, the
+// Exif IFD holds ISO, DateTimeOriginal, FocalLength and LensModel.
+pub fn exif_tiff(big_endian: bool, tags: &ExifTags) -> Vec<u8> {
+    let u16_bytes = |value: u16| {
+        if big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    };
+    let u32_bytes = |value: u32| {
+        if big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    };
+    let ascii = |text: &str| {
+        let mut bytes = text.as_bytes().to_vec();
+        bytes.push(0);
+        (2_u16, bytes.len() as u32, bytes)
+    };
+
+    let mut ifd0 = Vec::new();
+    if let Some(model) = tags.model {
+        ifd0.push((0x0110_u16, ascii(model)));
+    }
+    let mut exif = Vec::new();
+    if let Some(iso) = tags.iso {
+        exif.push((0x8827_u16, (3_u16, 1_u32, u16_bytes(iso).to_vec())));
+    }
+    if let Some(date) = tags.date_time_original {
+        exif.push((0x9003, ascii(date)));
+    }
+    if let Some((numerator, denominator)) = tags.focal_length {
+        let mut rational = u32_bytes(numerator).to_vec();
+        rational.extend(u32_bytes(denominator));
+        exif.push((0x920A, (5, 1, rational)));
+    }
+    if let Some(lens) = tags.lens_model {
+        exif.push((0xA434, ascii(lens)));
+    }
+    let ifd_size = |entries: usize| 2 + entries * 12 + 4;
+    let ifd0_offset = 8;
+    let exif_offset = ifd0_offset + ifd_size(ifd0.len() + 1);
+    ifd0.push((0x8769, (4, 1, u32_bytes(exif_offset as u32).to_vec())));
+    let mut data_offset = exif_offset + ifd_size(exif.len());
+
+    let mut tiff = if big_endian {
+        b"MM".to_vec()
+    } else {
+        b"II".to_vec()
+    };
+    tiff.extend(u16_bytes(42));
+    tiff.extend(u32_bytes(ifd0_offset as u32));
+    let mut data: Vec<u8> = Vec::new();
+    for entries in [&ifd0, &exif] {
+        tiff.extend(u16_bytes(entries.len() as u16));
+        for (tag, (kind, count, payload)) in entries.iter() {
+            tiff.extend(u16_bytes(*tag));
+            tiff.extend(u16_bytes(*kind));
+            tiff.extend(u32_bytes(*count));
+            if payload.len() <= 4 {
+                let mut field = payload.clone();
+                field.resize(4, 0);
+                tiff.extend(field);
+            } else {
+                tiff.extend(u32_bytes(data_offset as u32));
+                data_offset += payload.len();
+                data.extend(payload);
+            }
+        }
+        tiff.extend(u32_bytes(0));
+    }
+    tiff.extend(data);
+    tiff
+}
+
+pub fn write_jpeg_with_exif(path: &Path, tiff: &[u8]) {
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    RgbImage::from_pixel(8, 6, Rgb([90, 120, 150]))
+        .write_to(&mut encoded, ImageFormat::Jpeg)
+        .expect("encode jpeg");
+    let jpeg = encoded.into_inner();
+    let mut segment = b"Exif\0\0".to_vec();
+    segment.extend_from_slice(tiff);
+
+    let mut bytes = jpeg[..2].to_vec();
+    bytes.extend([0xFF, 0xE1]);
+    bytes.extend(((segment.len() + 2) as u16).to_be_bytes());
+    bytes.extend(segment);
+    bytes.extend(&jpeg[2..]);
+    std::fs::write(path, bytes).expect("write exif jpeg");
+}
+
+// A flat 8x8 four-channel baseline JPEG whose channels store `stored`, with an
+// Adobe APP14 segment carrying `adobe_transform` when given. Every block is
+// DC-only, so each channel decodes back to its stored byte.
+pub fn write_flat_cmyk_jpeg(path: &Path, stored: [u8; 4], adobe_transform: Option<u8>) {
+    let mut bytes = vec![0xFF, 0xD8];
+    let mut segment = |marker: u8, payload: &[u8]| {
+        bytes.extend([0xFF, marker]);
+        bytes.extend(((payload.len() + 2) as u16).to_be_bytes());
+        bytes.extend(payload);
+    };
+    if let Some(transform) = adobe_transform {
+        let mut adobe = b"Adobe".to_vec();
+        adobe.extend([0, 100, 0, 0, 0, 0, transform]);
+        segment(0xEE, &adobe);
+    }
+    let mut quantization = vec![0];
+    quantization.extend([1; 64]);
+    segment(0xDB, &quantization);
+    let mut frame = vec![8, 0, 8, 0, 8, 4];
+    for id in 1..=4 {
+        frame.extend([id, 0x11, 0]);
+    }
+    segment(0xC0, &frame);
+    // DC categories 0..=11 all get 4-bit codes equal to the category; the only
+    // AC symbol is end-of-block, coded as a single 0 bit.
+    let mut huffman = vec![0x00, 0, 0, 0, 12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    huffman.extend(0..12_u8);
+    huffman.extend([0x10, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x00]);
+    segment(0xC4, &huffman);
+    let mut scan = vec![4];
+    for id in 1..=4 {
+        scan.extend([id, 0x00]);
+    }
+    scan.extend([0, 63, 0]);
+    segment(0xDA, &scan);
+
+    let mut bits: Vec<bool> = Vec::new();
+    let mut push = |value: u32, count: u32| {
+        bits.extend((0..count).rev().map(|bit| value >> bit & 1 == 1));
+    };
+    for value in stored {
+        let coefficient = (i32::from(value) - 128) * 8;
+        let category = 32 - coefficient.unsigned_abs().leading_zeros();
+        push(category, 4);
+        let magnitude = if coefficient < 0 {
+            coefficient + (1 << category) - 1
+        } else {
+            coefficient
+        };
+        push(magnitude as u32, category);
+        push(0, 1);
+    }
+    while !bits.len().is_multiple_of(8) {
+        bits.push(true);
+    }
+    for byte in bits.chunks(8) {
+        let byte = byte
+            .iter()
+            .fold(0_u8, |byte, &bit| byte << 1 | u8::from(bit));
+        bytes.push(byte);
+        if byte == 0xFF {
+            bytes.push(0x00);
+        }
+    }
+    bytes.extend([0xFF, 0xD9]);
+    std::fs::write(path, bytes).expect("write cmyk jpeg");
+}
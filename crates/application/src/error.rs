@@ -25,6 +25,30 @@ impl Display for ApplicationError {
     }
 }
 
+impl ApplicationError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Domain(_) => "domain",
+            Self::InvalidInput(_) => "invalid_input",
+            Self::NotFound(_) => "not_found",
+            Self::Io(_) => "io",
+            Self::Persistence(_) => "persistence",
+            Self::Decode(_) => "decode",
+        }
+    }
+
+    pub fn with_context(self, context: impl Display) -> Self {
+        match self {
+            Self::Domain(error) => Self::Domain(error),
+            Self::InvalidInput(msg) => Self::InvalidInput(format!("{context}: {msg}")),
+            Self::NotFound(msg) => Self::NotFound(format!("{context}: {msg}")),
+            Self::Io(msg) => Self::Io(format!("{context}: {msg}")),
+            Self::Persistence(msg) => Self::Persistence(format!("{context}: {msg}")),
+            Self::Decode(msg) => Self::Decode(format!("{context}: {msg}")),
+        }
+    }
+}
+
 impl std::error::Error for ApplicationError {}
 
 impl From<DomainError> for ApplicationError {
@@ -32,3 +56,40 @@ impl From<DomainError> for ApplicationError {
         Self::Domain(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_variant_has_stable_code() {
+        let cases = [
+            (
+                ApplicationError::Domain(DomainError::InvalidImageId(0)),
+                "domain",
+            ),
+            (
+                ApplicationError::InvalidInput(String::new()),
+                "invalid_input",
+            ),
+            (ApplicationError::NotFound(String::new()), "not_found"),
+            (ApplicationError::Io(String::new()), "io"),
+            (ApplicationError::Persistence(String::new()), "persistence"),
+            (ApplicationError::Decode(String::new()), "decode"),
+        ];
+        for (error, code) in cases {
+            assert_eq!(error.code(), code);
+        }
+    }
+
+    #[test]
+    fn context_prefixes_message_and_keeps_code() {
+        let error = ApplicationError::Decode("bad marker".to_string())
+            .with_context("preview render failed for image 42");
+        assert_eq!(error.code(), "decode");
+        assert_eq!(
+            error.to_string(),
+            "decode error: preview render failed for image 42: bad marker"
+        );
+    }
+}
@@ -5,13 +5,23 @@ mod use_cases;
 
 pub use error::ApplicationError;
 pub use ports::{
-    CatalogRepository, Clock, FileScanSummary, FileScanner, ImageDecoder, NewImage,
-    PreviewPipeline, ScannedFile, StoredEdit, ThumbnailArtifact, ThumbnailGenerator,
+    BackfillMetadataReport, CaptureMetadata, CatalogRepository, Clock, EditPreset, ExportFailure,
+    ExportProgress, ExportReport, FileScanSummary, FileScanner, ImageDecoder, ImageEncoder,
+    ImageWithThumbnail, ImportCheckpoint, ImportFailure, ImportProgress, NewImage, OptimizeReport,
+    PasteEditFailure, PasteEditReport, PreviewPipeline, PruneMissingReport, ScannedFile,
+    SchemaReport, StoredEdit, ThumbnailArtifact, ThumbnailGenerator, ThumbnailVerifyReport,
     UpsertImageResult,
 };
 pub use service::ApplicationService;
 pub use use_cases::{
-    BootstrapCatalogCommand, ImportFolderCommand, ListImagesCommand, OpenImageCommand,
-    PollPreviewCommand, PreviewMetricsQuery, SetEditCommand, ShowEditCommand,
-    SubmitPreviewCommand,
+    ApplyPresetCommand, AutoExposureCommand, BackfillMetadataCommand, BootstrapCatalogCommand,
+    CopyEditCommand, DeleteImageCommand, ExportAllCommand, ExportImageCommand, ImportFilesCommand,
+    ImportFolderCommand, InspectSchemaCommand, ListImagesCommand, ListImportFailuresCommand,
+    ListPresetsCommand, MatchExposureCommand, MergeHdrCommand, MoveImageCommand, OpenImageCommand,
+    OptimizeCatalogCommand, PasteEditCommand, PollPreviewCommand, PreviewBackendQuery,
+    PreviewMetricsQuery, PruneMissingCommand, RelinkCommand, ResetAllEditsCommand,
+    ResetEditCommand, RetryImportFailuresCommand, SavePresetCommand, SetCaptionCommand,
+    SetEditCommand, SetFlagCommand, SetRatingCommand, SetStackPickCommand, ShowCaptionCommand,
+    ShowEditCommand, ShowEditsCommand, StackImagesCommand, SubmitPreviewCommand,
+    VerifyThumbnailsCommand, WriteSidecarCommand,
 };
@@ -1,6 +1,11 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use lite_room_domain::{ImageId, ImageKind, ImageRecord, PreviewFrame, PreviewMetrics, PreviewRequest};
+use lite_room_domain::{
+    DecodedBuffer, EditParams, ImageFilter, ImageId, ImageKind, ImagePage, ImageRecord, ImageSort,
+    PreviewBackendInfo, PreviewFrame, PreviewMetrics, PreviewRequest, RawPixelFormat, SortMove,
+    TiledRender,
+};
 
 use crate::ApplicationError;
 
@@ -11,9 +16,25 @@ pub struct NewImage {
     pub capture_date: Option<String>,
     pub camera_model: Option<String>,
     pub iso: Option<i64>,
+    pub lens_model: Option<String>,
+    pub focal_length: Option<f64>,
     pub rating: i64,
     pub flag: i64,
     pub metadata_json: String,
+    pub content_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportCheckpoint {
+    pub folder: String,
+    pub last_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportFailure {
+    pub file_path: String,
+    pub reason: String,
+    pub failed_at: String,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -22,12 +43,106 @@ pub struct UpsertImageResult {
     pub inserted: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptimizeReport {
+    pub size_before_bytes: u64,
+    pub size_after_bytes: u64,
+}
+
+// The catalog's tables and columns compared against what the migrations
+// create. Missing columns are listed as `table.column`; a missing table's
+// columns are not repeated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaReport {
+    pub applied_version: usize,
+    pub expected_version: usize,
+    pub missing_tables: Vec<String>,
+    pub missing_columns: Vec<String>,
+}
+
+impl SchemaReport {
+    pub fn is_healthy(&self) -> bool {
+        self.applied_version == self.expected_version
+            && self.missing_tables.is_empty()
+            && self.missing_columns.is_empty()
+    }
+}
+
+// Emitted after each file of a folder import. `processed` counts every file
+// handled so far, including duplicates and failures, out of the `total`
+// supported files queued for this run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub imported: usize,
+    pub current_path: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportProgress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportFailure {
+    pub image_id: ImageId,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportReport {
+    pub total: usize,
+    pub exported: Vec<PathBuf>,
+    pub failures: Vec<ExportFailure>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasteEditFailure {
+    pub image_id: ImageId,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PasteEditReport {
+    pub applied: Vec<ImageId>,
+    pub skipped: Vec<PasteEditFailure>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillMetadataReport {
+    pub checked: usize,
+    pub updated: usize,
+    pub missing: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneMissingReport {
+    pub checked: usize,
+    pub pruned: Vec<(ImageId, String)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThumbnailVerifyReport {
+    pub checked: usize,
+    pub unchecked: usize,
+    pub regenerated: Vec<ImageId>,
+    pub failures: Vec<(ImageId, String)>,
+}
+
 #[derive(Debug, Clone)]
 pub struct StoredEdit {
     pub edit_params_json: String,
     pub updated_at: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditPreset {
+    pub name: String,
+    pub params: EditParams,
+}
+
 pub trait CatalogRepository {
     fn initialize(&self) -> Result<(), ApplicationError>;
 
@@ -49,18 +164,102 @@ pub trait CatalogRepository {
 
     fn find_edit(&self, image_id: ImageId) -> Result<Option<StoredEdit>, ApplicationError>;
 
+    fn find_edits(
+        &self,
+        image_ids: &[ImageId],
+    ) -> Result<HashMap<ImageId, StoredEdit>, ApplicationError>;
+
     fn upsert_thumbnail(
         &self,
         image_id: ImageId,
         file_path: &str,
         width: i64,
         height: i64,
+        checksum: Option<&str>,
         updated_at: &str,
     ) -> Result<(), ApplicationError>;
 
-    fn list_images(&self) -> Result<Vec<ImageRecord>, ApplicationError>;
+    fn list_images(
+        &self,
+        sort: ImageSort,
+        filter: &ImageFilter,
+        page: ImagePage,
+    ) -> Result<Vec<ImageRecord>, ApplicationError>;
+
+    fn list_images_with_thumbnails(
+        &self,
+        sort: ImageSort,
+        filter: &ImageFilter,
+        page: ImagePage,
+    ) -> Result<Vec<ImageWithThumbnail>, ApplicationError>;
+
+    fn move_image(&self, image_id: ImageId, target: SortMove) -> Result<i64, ApplicationError>;
 
     fn find_image_by_id(&self, image_id: ImageId) -> Result<Option<ImageRecord>, ApplicationError>;
+
+    // Another image, under a different path, whose file has this content hash.
+    fn find_content_duplicate(
+        &self,
+        content_hash: &str,
+        file_path: &str,
+    ) -> Result<Option<ImageId>, ApplicationError>;
+
+    fn set_rating(&self, image_id: ImageId, rating: i64) -> Result<(), ApplicationError>;
+
+    fn set_flag(&self, image_id: ImageId, flag: i64) -> Result<(), ApplicationError>;
+
+    // Fills only the capture columns that are still NULL and reports whether
+    // any of them changed.
+    fn fill_capture_metadata(
+        &self,
+        image_id: ImageId,
+        capture: &CaptureMetadata,
+    ) -> Result<bool, ApplicationError>;
+
+    // Points the image at another file; a path already used by another image
+    // is `InvalidInput`.
+    fn relink_image(&self, image_id: ImageId, file_path: &str) -> Result<(), ApplicationError>;
+
+    // Removes the image with its edit and thumbnail rows and returns the
+    // thumbnail file paths those rows pointed at; `None` when no such image.
+    fn delete_image(&self, image_id: ImageId) -> Result<Option<Vec<String>>, ApplicationError>;
+
+    fn set_caption(&self, image_id: ImageId, caption: Option<&str>)
+        -> Result<(), ApplicationError>;
+
+    fn find_caption(&self, image_id: ImageId) -> Result<Option<String>, ApplicationError>;
+
+    fn create_stack(&self, image_ids: &[ImageId]) -> Result<i64, ApplicationError>;
+
+    fn set_stack_pick(&self, image_id: ImageId) -> Result<Option<i64>, ApplicationError>;
+
+    fn find_import_checkpoint(&self) -> Result<Option<ImportCheckpoint>, ApplicationError>;
+
+    fn save_import_checkpoint(
+        &self,
+        checkpoint: &ImportCheckpoint,
+        updated_at: &str,
+    ) -> Result<(), ApplicationError>;
+
+    fn clear_import_checkpoint(&self) -> Result<(), ApplicationError>;
+
+    fn record_import_failure(&self, failure: &ImportFailure) -> Result<(), ApplicationError>;
+
+    fn list_import_failures(&self) -> Result<Vec<ImportFailure>, ApplicationError>;
+
+    fn clear_import_failure(&self, file_path: &str) -> Result<(), ApplicationError>;
+
+    fn save_preset(&self, name: &str, params: &EditParams) -> Result<(), ApplicationError>;
+
+    fn list_presets(&self) -> Result<Vec<EditPreset>, ApplicationError>;
+
+    fn find_preset(&self, name: &str) -> Result<Option<EditPreset>, ApplicationError>;
+
+    fn optimize(&self) -> Result<OptimizeReport, ApplicationError>;
+
+    // Compares the catalog on disk with the schema the migrations build,
+    // without creating, migrating or otherwise writing to it.
+    fn inspect_schema(&self) -> Result<SchemaReport, ApplicationError>;
 }
 
 #[derive(Debug, Clone)]
@@ -69,6 +268,20 @@ pub struct ScannedFile {
     pub extension: String,
     pub file_size: u64,
     pub image_kind: ImageKind,
+    pub frame_count: Option<u32>,
+    pub grayscale: bool,
+    pub capture: CaptureMetadata,
+    pub content_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CaptureMetadata {
+    pub capture_date: Option<String>,
+    pub camera_model: Option<String>,
+    pub iso: Option<i64>,
+    pub lens_model: Option<String>,
+    // Millimetres.
+    pub focal_length: Option<f64>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -76,10 +289,20 @@ pub struct FileScanSummary {
     pub scanned_files: usize,
     pub supported_files: usize,
     pub files: Vec<ScannedFile>,
+    pub unsupported: Vec<PathBuf>,
 }
 
 pub trait FileScanner {
-    fn scan_supported(&self, folder: &str) -> Result<FileScanSummary, ApplicationError>;
+    fn scan_supported(
+        &self,
+        folder: &str,
+        resume_after: Option<&Path>,
+        workers: usize,
+    ) -> Result<FileScanSummary, ApplicationError>;
+
+    fn scan_file(&self, path: &Path) -> Result<Option<ScannedFile>, ApplicationError>;
+
+    fn file_exists(&self, path: &Path) -> bool;
 }
 
 #[derive(Debug, Clone)]
@@ -87,22 +310,74 @@ pub struct ThumbnailArtifact {
     pub file_path: String,
     pub width: u32,
     pub height: u32,
+    pub checksum: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageWithThumbnail {
+    pub image: ImageRecord,
+    pub thumbnail: Option<ThumbnailArtifact>,
 }
 
-pub trait ThumbnailGenerator {
+pub trait ThumbnailGenerator: Send + Sync {
     fn ensure_thumbnail(
         &self,
         source_path: &Path,
         cache_root: &str,
         image_id: ImageId,
     ) -> Result<ThumbnailArtifact, ApplicationError>;
+
+    fn thumbnail_checksum(&self, thumbnail_path: &Path)
+        -> Result<Option<String>, ApplicationError>;
+
+    fn discard_thumbnail(&self, thumbnail_path: &Path) -> Result<(), ApplicationError>;
 }
 
-pub trait ImageDecoder {
+pub trait ImageDecoder: Send + Sync {
     fn decode_for_preview(
         &self,
         path: &Path,
     ) -> Result<lite_room_domain::DecodedImage, ApplicationError>;
+
+    fn decode_full(&self, path: &Path) -> Result<DecodedBuffer, ApplicationError>;
+
+    // Fits within `max_side` on both axes; smaller sources come back as is.
+    fn decode_downsampled(
+        &self,
+        path: &Path,
+        max_side: u32,
+    ) -> Result<DecodedBuffer, ApplicationError>;
+}
+
+pub trait ImageEncoder: Send + Sync {
+    fn write_jpeg(
+        &self,
+        path: &Path,
+        image: &DecodedBuffer,
+        quality: u8,
+    ) -> Result<PathBuf, ApplicationError>;
+
+    // Encodes `image` strip by strip, so only one edited strip is in memory
+    // at a time; the output matches `write_jpeg` of the whole edited buffer.
+    fn write_jpeg_tiled(
+        &self,
+        path: &Path,
+        image: &TiledRender<'_>,
+        quality: u8,
+    ) -> Result<PathBuf, ApplicationError>;
+
+    // Writes each strip packed with `format.pack` to `path` plus a JSON
+    // sidecar describing the dimensions and layout, and returns the data
+    // file's path.
+    fn write_raw(
+        &self,
+        path: &Path,
+        image: &TiledRender<'_>,
+        format: RawPixelFormat,
+    ) -> Result<PathBuf, ApplicationError>;
+
+    // Writes an XMP document next to an original, replacing any earlier one.
+    fn write_sidecar(&self, path: &Path, contents: &str) -> Result<PathBuf, ApplicationError>;
 }
 
 pub trait Clock {
@@ -113,4 +388,5 @@ pub trait PreviewPipeline {
     fn submit_preview(&self, request: PreviewRequest) -> Result<(), ApplicationError>;
     fn try_receive_preview(&self) -> Result<Option<PreviewFrame>, ApplicationError>;
     fn metrics(&self) -> Result<PreviewMetrics, ApplicationError>;
+    fn backend_info(&self) -> Result<PreviewBackendInfo, ApplicationError>;
 }
@@ -1,21 +1,62 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
 use lite_room_domain::{
-    DecodedImage, EditParams, ImageRecord, ImportReport, PreviewFrame, PreviewMetrics,
-    PreviewRequest,
+    apply_edits, apply_geometry, auto_exposure, detect_image_kind, edit_params_to_xmp,
+    matching_exposure, mean_linear_luminance, merge_exposure_bracket, ComparisonMode, DecodedImage,
+    DomainError, EditParams, ImageFilter, ImageId, ImageKind, ImagePage, ImageRecord, ImageSort,
+    ImportFileFailure, ImportReport, PreviewBackendInfo, PreviewFrame, PreviewMetrics,
+    PreviewRequest, SortMove, TiledRender, EXPORT_STRIP_ROWS, FLAG_PICK, FLAG_REJECT, MAX_RATING,
 };
 use serde_json::json;
 
 use crate::{
-    ApplicationError, BootstrapCatalogCommand, CatalogRepository, Clock, FileScanner,
-    ImageDecoder, ImportFolderCommand, ListImagesCommand, OpenImageCommand, PollPreviewCommand,
-    PreviewMetricsQuery, PreviewPipeline, SetEditCommand, ShowEditCommand, SubmitPreviewCommand,
-    ThumbnailGenerator,
+    ApplicationError, ApplyPresetCommand, AutoExposureCommand, BackfillMetadataCommand,
+    BackfillMetadataReport, BootstrapCatalogCommand, CaptureMetadata, CatalogRepository, Clock,
+    CopyEditCommand, DeleteImageCommand, EditPreset, ExportAllCommand, ExportFailure,
+    ExportImageCommand, ExportProgress, ExportReport, FileScanSummary, FileScanner, ImageDecoder,
+    ImageEncoder, ImageWithThumbnail, ImportCheckpoint, ImportFailure, ImportFilesCommand,
+    ImportFolderCommand, ImportProgress, InspectSchemaCommand, ListImagesCommand,
+    ListImportFailuresCommand, ListPresetsCommand, MatchExposureCommand, MergeHdrCommand,
+    MoveImageCommand, OpenImageCommand, OptimizeCatalogCommand, OptimizeReport, PasteEditCommand,
+    PasteEditFailure, PasteEditReport, PollPreviewCommand, PreviewBackendQuery,
+    PreviewMetricsQuery, PreviewPipeline, PruneMissingCommand, PruneMissingReport, RelinkCommand,
+    ResetAllEditsCommand, ResetEditCommand, RetryImportFailuresCommand, SavePresetCommand,
+    ScannedFile, SchemaReport, SetCaptionCommand, SetEditCommand, SetFlagCommand, SetRatingCommand,
+    SetStackPickCommand, ShowCaptionCommand, ShowEditCommand, ShowEditsCommand, StackImagesCommand,
+    SubmitPreviewCommand, ThumbnailArtifact, ThumbnailGenerator, ThumbnailVerifyReport,
+    UpsertImageResult, VerifyThumbnailsCommand, WriteSidecarCommand,
 };
 
+const MERGED_JPEG_QUALITY: u8 = 95;
+const EXPORT_JPEG_QUALITY: u8 = 90;
+// Plenty of pixels for a mean, and cheap to decode from a full-size jpeg.
+const AUTO_EXPOSURE_SAMPLE_SIDE: u32 = 256;
+
+// Settings shared by every batch of one import run.
+#[derive(Clone, Copy)]
+struct ImportRun<'a> {
+    default_edit_json: &'a str,
+    cache_root: &'a str,
+    checkpoint_folder: Option<&'a str>,
+    now: &'a str,
+}
+
+type StagedImport = (String, Option<Result<UpsertImageResult, ApplicationError>>);
+type BuiltImport = (
+    UpsertImageResult,
+    Result<ThumbnailArtifact, ApplicationError>,
+);
+
 pub struct ApplicationService {
     catalog: Box<dyn CatalogRepository>,
     scanner: Box<dyn FileScanner>,
     thumbnails: Box<dyn ThumbnailGenerator>,
     decoder: Box<dyn ImageDecoder>,
+    encoder: Box<dyn ImageEncoder>,
     clock: Box<dyn Clock>,
     preview: Box<dyn PreviewPipeline>,
 }
@@ -26,6 +67,7 @@ impl ApplicationService {
         scanner: Box<dyn FileScanner>,
         thumbnails: Box<dyn ThumbnailGenerator>,
         decoder: Box<dyn ImageDecoder>,
+        encoder: Box<dyn ImageEncoder>,
         clock: Box<dyn Clock>,
         preview: Box<dyn PreviewPipeline>,
     ) -> Self {
@@ -34,6 +76,7 @@ impl ApplicationService {
             scanner,
             thumbnails,
             decoder,
+            encoder,
             clock,
             preview,
         }
@@ -46,9 +89,31 @@ impl ApplicationService {
         self.catalog.initialize()
     }
 
+    pub fn optimize_catalog(
+        &self,
+        _command: OptimizeCatalogCommand,
+    ) -> Result<OptimizeReport, ApplicationError> {
+        self.catalog.optimize()
+    }
+
+    pub fn inspect_schema(
+        &self,
+        _command: InspectSchemaCommand,
+    ) -> Result<SchemaReport, ApplicationError> {
+        self.catalog.inspect_schema()
+    }
+
     pub fn import_folder(
         &self,
         command: ImportFolderCommand,
+    ) -> Result<ImportReport, ApplicationError> {
+        self.import_folder_with_progress(command, |_| {})
+    }
+
+    pub fn import_folder_with_progress(
+        &self,
+        command: ImportFolderCommand,
+        mut on_progress: impl FnMut(ImportProgress),
     ) -> Result<ImportReport, ApplicationError> {
         if command.folder.trim().is_empty() {
             return Err(ApplicationError::InvalidInput(
@@ -60,218 +125,1351 @@ impl ApplicationService {
                 "cache root must not be empty".to_string(),
             ));
         }
+        if command.limit == Some(0) {
+            return Err(ApplicationError::InvalidInput(
+                "import limit must be at least 1".to_string(),
+            ));
+        }
+        if command.workers == 0 {
+            return Err(ApplicationError::InvalidInput(
+                "import workers must be at least 1".to_string(),
+            ));
+        }
 
-        let scan = self.scanner.scan_supported(&command.folder)?;
-        let now = self.clock.now_timestamp_string();
-        let edit = EditParams::default();
-        edit.validate()?;
-        let default_edit_json = serde_json::to_string(&edit)
-            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
-
-        let mut report = ImportReport {
-            scanned_files: scan.scanned_files,
-            supported_files: scan.supported_files,
-            newly_imported: 0,
-        };
-
-        for file in scan.files {
-            let metadata_json = json!({
-                "file_size": file.file_size,
-                "extension": file.extension,
-            })
-            .to_string();
-
-            let upsert = self.catalog.upsert_image(&crate::NewImage {
-                file_path: file.canonical_path.to_string_lossy().to_string(),
-                import_date: now.clone(),
-                capture_date: None,
-                camera_model: None,
-                iso: None,
-                rating: 0,
-                flag: 0,
-                metadata_json,
+        let resume_after = if command.resume {
+            let checkpoint = self.catalog.find_import_checkpoint()?.ok_or_else(|| {
+                ApplicationError::NotFound("no import checkpoint to resume".to_string())
             })?;
-
-            if upsert.inserted {
-                report.newly_imported += 1;
+            if checkpoint.folder != command.folder {
+                return Err(ApplicationError::InvalidInput(format!(
+                    "import checkpoint belongs to {}, not {}",
+                    checkpoint.folder, command.folder
+                )));
             }
+            Some(PathBuf::from(checkpoint.last_path))
+        } else {
+            None
+        };
 
-            self.catalog
-                .ensure_default_edit(upsert.image_id, &default_edit_json, &now)?;
+        let scan = self.scanner.scan_supported(
+            &command.folder,
+            resume_after.as_deref(),
+            command.workers,
+        )?;
+        if command.strict && !scan.unsupported.is_empty() {
+            let paths: Vec<String> = scan
+                .unsupported
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(ApplicationError::InvalidInput(format!(
+                "strict import found {} unsupported file(s): {}",
+                paths.len(),
+                paths.join(", ")
+            )));
+        }
+        let supported_files = scan.supported_files;
+        let report = self.import_scanned(
+            scan,
+            &command.cache_root,
+            Some(&command.folder),
+            command.limit,
+            command.workers,
+            &mut on_progress,
+        )?;
+        // A run cut short by the limit keeps its checkpoint so `resume` picks up
+        // the remaining files.
+        if report.supported_files == supported_files {
+            self.catalog.clear_import_checkpoint()?;
+        }
+        Ok(report)
+    }
 
-            let thumb = self.thumbnails.ensure_thumbnail(
-                &file.canonical_path,
-                &command.cache_root,
-                upsert.image_id,
-            )?;
+    pub fn import_files(
+        &self,
+        command: ImportFilesCommand,
+    ) -> Result<ImportReport, ApplicationError> {
+        if command.cache_root.trim().is_empty() {
+            return Err(ApplicationError::InvalidInput(
+                "cache root must not be empty".to_string(),
+            ));
+        }
 
-            self.catalog.upsert_thumbnail(
-                upsert.image_id,
-                &thumb.file_path,
-                i64::from(thumb.width),
-                i64::from(thumb.height),
-                &now,
-            )?;
+        let mut scan = FileScanSummary {
+            scanned_files: command.paths.len(),
+            ..FileScanSummary::default()
+        };
+        for path in &command.paths {
+            if let Some(file) = self.scanner.scan_file(path)? {
+                scan.supported_files += 1;
+                scan.files.push(file);
+            }
         }
+        self.import_scanned(scan, &command.cache_root, None, None, 1, &mut |_| {})
+    }
 
-        Ok(report)
+    pub fn list_import_failures(
+        &self,
+        _command: ListImportFailuresCommand,
+    ) -> Result<Vec<ImportFailure>, ApplicationError> {
+        self.catalog.list_import_failures()
     }
 
-    pub fn list_images(
+    // Re-reads capture metadata for images imported before it was extracted.
+    // Existing values are never overwritten; files gone from disk are counted
+    // as missing and skipped.
+    pub fn backfill_metadata(
         &self,
-        _command: ListImagesCommand,
-    ) -> Result<Vec<ImageRecord>, ApplicationError> {
-        self.catalog.list_images()
+        _command: BackfillMetadataCommand,
+    ) -> Result<BackfillMetadataReport, ApplicationError> {
+        let images = self.catalog.list_images(
+            ImageSort::CaptureDate,
+            &ImageFilter::default(),
+            ImagePage::default(),
+        )?;
+        let mut report = BackfillMetadataReport {
+            checked: images.len(),
+            ..BackfillMetadataReport::default()
+        };
+        for image in images {
+            let file = match self.scanner.scan_file(Path::new(&image.file_path)) {
+                Ok(Some(file)) => file,
+                Ok(None) => continue,
+                Err(_) => {
+                    report.missing += 1;
+                    continue;
+                }
+            };
+            if file.capture != CaptureMetadata::default()
+                && self
+                    .catalog
+                    .fill_capture_metadata(image.id, &file.capture)?
+            {
+                report.updated += 1;
+            }
+        }
+        Ok(report)
     }
 
-    pub fn open_image(&self, command: OpenImageCommand) -> Result<DecodedImage, ApplicationError> {
-        let image = self
-            .catalog
-            .find_image_by_id(command.image_id)?
+    // Only a missing image can be relinked, and only to an existing file the
+    // decoder can read. The new path is stored canonicalized, like import does.
+    pub fn relink(&self, command: RelinkCommand) -> Result<ImageRecord, ApplicationError> {
+        let image = self.require_image(command.image_id)?;
+        if self.scanner.file_exists(Path::new(&image.file_path)) {
+            return Err(ApplicationError::InvalidInput(format!(
+                "image {} is not missing: {}",
+                command.image_id.get(),
+                image.file_path
+            )));
+        }
+        let file = self
+            .scanner
+            .scan_file(Path::new(&command.new_path))?
             .ok_or_else(|| {
-                ApplicationError::NotFound(format!(
-                    "image not found for id={}",
-                    command.image_id.get()
+                ApplicationError::InvalidInput(format!(
+                    "unsupported image format: {}",
+                    command.new_path
                 ))
             })?;
-        self.decoder
-            .decode_for_preview(std::path::Path::new(&image.file_path))
+        self.decoder.decode_for_preview(&file.canonical_path)?;
+        let new_path = file.canonical_path.to_string_lossy().to_string();
+        self.catalog.relink_image(command.image_id, &new_path)?;
+        self.require_image(command.image_id)
     }
 
-    pub fn show_edit(&self, command: ShowEditCommand) -> Result<EditParams, ApplicationError> {
-        self.catalog
-            .find_edit(command.image_id)?
-            .map(|stored| {
-                serde_json::from_str::<EditParams>(&stored.edit_params_json)
-                    .map_err(|error| ApplicationError::Persistence(error.to_string()))
-            })
-            .transpose()?
+    // The source file is left alone; only the catalog rows and the cached
+    // thumbnails go. Returns the thumbnail paths that were removed.
+    pub fn delete_image(
+        &self,
+        command: DeleteImageCommand,
+    ) -> Result<Vec<String>, ApplicationError> {
+        let thumbnails = self
+            .catalog
+            .delete_image(command.image_id)?
             .ok_or_else(|| {
                 ApplicationError::NotFound(format!(
-                    "edit not found for image id={}",
+                    "image not found for id={}",
                     command.image_id.get()
                 ))
-            })
+            })?;
+        for thumbnail in &thumbnails {
+            self.thumbnails.discard_thumbnail(Path::new(thumbnail))?;
+        }
+        Ok(thumbnails)
     }
 
-    pub fn set_edit(&self, command: SetEditCommand) -> Result<(), ApplicationError> {
-        command.params.validate()?;
+    // Drops the catalog rows and cached thumbnails of images whose source file
+    // is gone from disk, as `delete_image` would one at a time.
+    pub fn prune_missing(
+        &self,
+        _command: PruneMissingCommand,
+    ) -> Result<PruneMissingReport, ApplicationError> {
+        let images = self.catalog.list_images(
+            ImageSort::CaptureDate,
+            &ImageFilter::default(),
+            ImagePage::default(),
+        )?;
+        let mut report = PruneMissingReport {
+            checked: images.len(),
+            ..PruneMissingReport::default()
+        };
+        for image in images {
+            if self.scanner.file_exists(Path::new(&image.file_path)) {
+                continue;
+            }
+            let thumbnails = self.catalog.delete_image(image.id)?.unwrap_or_default();
+            for thumbnail in &thumbnails {
+                self.thumbnails.discard_thumbnail(Path::new(thumbnail))?;
+            }
+            report.pruned.push((image.id, image.file_path));
+        }
+        Ok(report)
+    }
+
+    pub fn retry_import_failures(
+        &self,
+        command: RetryImportFailuresCommand,
+    ) -> Result<ImportReport, ApplicationError> {
+        if command.cache_root.trim().is_empty() {
+            return Err(ApplicationError::InvalidInput(
+                "cache root must not be empty".to_string(),
+            ));
+        }
+
+        let failures = self.catalog.list_import_failures()?;
         let now = self.clock.now_timestamp_string();
-        let edit_json = serde_json::to_string(&command.params)
-            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
-        self.catalog
-            .upsert_edit(command.image_id, &edit_json, &now)?;
-        Ok(())
+        let mut scan = FileScanSummary {
+            scanned_files: failures.len(),
+            ..FileScanSummary::default()
+        };
+        let mut still_missing = Vec::new();
+        for failure in failures {
+            match self.scanner.scan_file(Path::new(&failure.file_path)) {
+                Ok(Some(file)) => {
+                    scan.supported_files += 1;
+                    scan.files.push(file);
+                }
+                Ok(None) => self.catalog.clear_import_failure(&failure.file_path)?,
+                Err(error) => {
+                    still_missing.push(ImportFileFailure {
+                        file_path: failure.file_path.clone(),
+                        reason: error.to_string(),
+                    });
+                    self.catalog.record_import_failure(&ImportFailure {
+                        reason: error.to_string(),
+                        failed_at: now.clone(),
+                        ..failure
+                    })?;
+                }
+            }
+        }
+
+        let mut report =
+            self.import_scanned(scan, &command.cache_root, None, None, 1, &mut |_| {})?;
+        report.failures.extend(still_missing);
+        Ok(report)
     }
 
-    pub fn submit_preview(&self, command: SubmitPreviewCommand) -> Result<(), ApplicationError> {
-        command.params.validate()?;
-        let image = self
-            .catalog
-            .find_image_by_id(command.image_id)?
-            .ok_or_else(|| {
-                ApplicationError::NotFound(format!(
-                    "image not found for id={}",
-                    command.image_id.get()
-                ))
+    // Files go through in batches of up to `workers`: catalog rows are written
+    // in scan order on the one connection, thumbnails for the batch are built
+    // concurrently, then results are recorded in scan order again. A batch never
+    // outgrows the remaining limit, so counts match a one-at-a-time import.
+    fn import_scanned(
+        &self,
+        scan: FileScanSummary,
+        cache_root: &str,
+        checkpoint_folder: Option<&str>,
+        limit: Option<usize>,
+        workers: usize,
+        on_progress: &mut dyn FnMut(ImportProgress),
+    ) -> Result<ImportReport, ApplicationError> {
+        let now = self.clock.now_timestamp_string();
+        let default_edit_json = default_edit_json()?;
+        let run = ImportRun {
+            default_edit_json: &default_edit_json,
+            cache_root,
+            checkpoint_folder,
+            now: &now,
+        };
+
+        let mut report = ImportReport {
+            scanned_files: scan.scanned_files,
+            supported_files: scan.supported_files,
+            ..ImportReport::default()
+        };
+
+        let total_files = scan.files.len();
+        let mut taken = 0;
+        let mut processed = 0;
+        let mut files = scan.files.into_iter();
+        loop {
+            let batch_size = match limit {
+                Some(limit) if report.newly_imported >= limit => {
+                    let skipped = total_files - taken;
+                    report.scanned_files -= skipped;
+                    report.supported_files -= skipped;
+                    break;
+                }
+                Some(limit) => workers.min(limit - report.newly_imported),
+                None => workers,
+            };
+            let batch: Vec<ScannedFile> = files.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                break;
+            }
+            taken += batch.len();
+            self.import_batch(batch, &run, &mut report, &mut |report, file_path| {
+                processed += 1;
+                on_progress(ImportProgress {
+                    processed,
+                    total: total_files,
+                    imported: report.newly_imported,
+                    current_path: file_path.to_string(),
+                });
             })?;
+        }
 
-        self.preview.submit_preview(PreviewRequest {
-            image_id: command.image_id,
-            source_path: image.file_path,
-            params: command.params,
-            target_width: command.target_width,
-            target_height: command.target_height,
-        })
+        Ok(report)
     }
 
-    pub fn poll_preview(
+    // `on_file` runs once per file of the batch after its result is recorded.
+    fn import_batch(
         &self,
-        _command: PollPreviewCommand,
-    ) -> Result<Option<PreviewFrame>, ApplicationError> {
-        self.preview.try_receive_preview()
+        batch: Vec<ScannedFile>,
+        run: &ImportRun,
+        report: &mut ImportReport,
+        on_file: &mut dyn FnMut(&ImportReport, &str),
+    ) -> Result<(), ApplicationError> {
+        let ImportRun {
+            default_edit_json,
+            cache_root,
+            checkpoint_folder,
+            now,
+        } = *run;
+        let mut staged = Vec::with_capacity(batch.len());
+        for file in batch {
+            let mut metadata = json!({
+                "file_size": file.file_size,
+                "extension": file.extension,
+            });
+            if let Some(frame_count) = file.frame_count {
+                metadata["frame_count"] = json!(frame_count);
+            }
+            if file.grayscale {
+                metadata["grayscale"] = json!(true);
+            }
+            let metadata_json = metadata.to_string();
+
+            let file_path = file.canonical_path.to_string_lossy().to_string();
+            // Same bytes already cataloged under another path: counted, not registered.
+            let duplicate = match file.content_hash.as_deref() {
+                Some(hash) => self.catalog.find_content_duplicate(hash, &file_path)?,
+                None => None,
+            };
+            let registered = match duplicate {
+                Some(_) => None,
+                None => Some(self.register_row(
+                    &crate::NewImage {
+                        content_hash: file.content_hash,
+                        ..new_image(&file.canonical_path, metadata_json, file.capture, now)
+                    },
+                    default_edit_json,
+                )),
+            };
+            if let Some(Err(error @ ApplicationError::Persistence(_))) = registered {
+                return Err(error);
+            }
+            staged.push((file_path, registered));
+        }
+
+        for (file_path, built) in self.build_thumbnails(staged, cache_root) {
+            // A file that cannot be decoded or thumbnailed is recorded and skipped,
+            // and the row it just inserted is removed again so a retry imports it
+            // as new; catalog errors still abort the import.
+            let registered = built.map(|built| {
+                let (upsert, thumb) = built?;
+                match thumb {
+                    Ok(thumb) => {
+                        self.store_thumbnail(upsert.image_id, &thumb, now)?;
+                        Ok(upsert)
+                    }
+                    Err(error) => {
+                        if upsert.inserted {
+                            self.catalog.delete_image(upsert.image_id)?;
+                        }
+                        Err(error)
+                    }
+                }
+            });
+            match registered {
+                None => {
+                    self.catalog.clear_import_failure(&file_path)?;
+                    report.skipped_duplicates += 1;
+                }
+                Some(Ok(upsert)) => {
+                    self.catalog.clear_import_failure(&file_path)?;
+                    if upsert.inserted {
+                        report.newly_imported += 1;
+                        report.imported.push(file_path.clone());
+                    }
+                }
+                Some(Err(error @ ApplicationError::Persistence(_))) => return Err(error),
+                Some(Err(error)) => {
+                    self.catalog.record_import_failure(&ImportFailure {
+                        file_path: file_path.clone(),
+                        reason: error.to_string(),
+                        failed_at: now.to_string(),
+                    })?;
+                    report.failures.push(ImportFileFailure {
+                        file_path: file_path.clone(),
+                        reason: error.to_string(),
+                    });
+                }
+            }
+            if let Some(folder) = checkpoint_folder {
+                self.catalog.save_import_checkpoint(
+                    &ImportCheckpoint {
+                        folder: folder.to_string(),
+                        last_path: file_path.clone(),
+                    },
+                    now,
+                )?;
+            }
+            on_file(report, &file_path);
+        }
+        Ok(())
     }
 
-    pub fn preview_metrics(
+    // Builds the thumbnail of every registered row on its own thread, keeping
+    // `staged` order. Each row comes back with its thumbnail result; duplicates
+    // and rows that failed to register pass through untouched.
+    fn build_thumbnails(
         &self,
-        _query: PreviewMetricsQuery,
-    ) -> Result<PreviewMetrics, ApplicationError> {
-        self.preview.metrics()
+        staged: Vec<StagedImport>,
+        cache_root: &str,
+    ) -> Vec<(String, Option<Result<BuiltImport, ApplicationError>>)> {
+        let thumbs = self.thumbnails.as_ref();
+        thread::scope(|scope| {
+            let pending: Vec<_> = staged
+                .into_iter()
+                .map(|(file_path, registered)| {
+                    let handle = registered.map(|upsert| {
+                        upsert.map(|upsert| {
+                            let source_path = PathBuf::from(&file_path);
+                            let handle = scope.spawn(move || {
+                                thumbs.ensure_thumbnail(&source_path, cache_root, upsert.image_id)
+                            });
+                            (upsert, handle)
+                        })
+                    });
+                    (file_path, handle)
+                })
+                .collect();
+            pending
+                .into_iter()
+                .map(|(file_path, handle)| {
+                    let built = handle.map(|handle| {
+                        let (upsert, handle) = handle?;
+                        let thumb = handle.join().unwrap_or_else(|_| {
+                            Err(ApplicationError::Io(
+                                "thumbnail worker panicked".to_string(),
+                            ))
+                        });
+                        Ok((upsert, thumb))
+                    });
+                    (file_path, built)
+                })
+                .collect()
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashMap;
-    use std::path::{Path, PathBuf};
+    pub fn merge_hdr(&self, command: MergeHdrCommand) -> Result<ImageId, ApplicationError> {
+        if command.image_ids.len() < 2 {
+            return Err(DomainError::BracketTooSmall(command.image_ids.len()).into());
+        }
+        if command.cache_root.trim().is_empty() {
+            return Err(ApplicationError::InvalidInput(
+                "cache root must not be empty".to_string(),
+            ));
+        }
 
-    use lite_room_domain::{detect_image_kind, DecodedImage, ImageId, ImageKind, ImageRecord};
+        let mut frames = Vec::with_capacity(command.image_ids.len());
+        for image_id in &command.image_ids {
+            let image = self.require_image(*image_id)?;
+            frames.push(self.decoder.decode_full(Path::new(&image.file_path))?);
+        }
+        let merged = merge_exposure_bracket(&frames)?;
 
-    use super::*;
+        let now = self.clock.now_timestamp_string();
+        let output_path = format!(
+            "{}/merged/hdr-{}-{}.jpg",
+            command.cache_root,
+            command.image_ids[0].get(),
+            now
+        );
+        let written =
+            self.encoder
+                .write_jpeg(Path::new(&output_path), &merged, MERGED_JPEG_QUALITY)?;
 
-    struct FakeCatalog {
-        initialized: std::cell::Cell<bool>,
-        next_id: std::cell::Cell<i64>,
-        images: std::cell::RefCell<HashMap<i64, ImageRecord>>,
-        edits: std::cell::RefCell<HashMap<i64, crate::StoredEdit>>,
+        let merged_from: Vec<i64> = command.image_ids.iter().map(|id| id.get()).collect();
+        let metadata_json = json!({
+            "extension": "jpg",
+            "merged_from": merged_from,
+        })
+        .to_string();
+        let upsert = self.register_image(
+            new_image(&written, metadata_json, CaptureMetadata::default(), &now),
+            &default_edit_json()?,
+            &command.cache_root,
+        )?;
+        Ok(upsert.image_id)
     }
 
-    #[derive(Default)]
-    struct FakePreviewPipeline {
-        submitted: std::cell::RefCell<Vec<lite_room_domain::PreviewRequest>>,
-        responses: std::cell::RefCell<Vec<lite_room_domain::PreviewFrame>>,
+    pub fn match_exposure(
+        &self,
+        command: MatchExposureCommand,
+    ) -> Result<Vec<(ImageId, f32)>, ApplicationError> {
+        if command.target_ids.is_empty() {
+            return Err(ApplicationError::InvalidInput(
+                "exposure match needs at least one target image".to_string(),
+            ));
+        }
+
+        let reference_params = self.show_edit(ShowEditCommand {
+            image_id: command.reference_id,
+        })?;
+        let reference_luminance =
+            self.rendered_luminance(command.reference_id, &reference_params)?;
+
+        let mut matched = Vec::with_capacity(command.target_ids.len());
+        for image_id in command.target_ids {
+            let params = self.show_edit(ShowEditCommand { image_id })?;
+            let target_luminance = self.rendered_luminance(image_id, &params.with_exposure(0.0))?;
+            let exposure =
+                matching_exposure(reference_luminance, target_luminance).ok_or_else(|| {
+                    ApplicationError::InvalidInput(format!(
+                        "cannot match exposure for black image id={} or reference id={}",
+                        image_id.get(),
+                        command.reference_id.get()
+                    ))
+                })?;
+            self.set_edit(SetEditCommand {
+                image_id,
+                params: params.with_exposure(exposure),
+            })?;
+            matched.push((image_id, exposure));
+        }
+        Ok(matched)
     }
 
-    impl PreviewPipeline for FakePreviewPipeline {
-        fn submit_preview(
-            &self,
-            request: lite_room_domain::PreviewRequest,
-        ) -> Result<(), ApplicationError> {
-            self.submitted.borrow_mut().push(request);
-            Ok(())
+    pub fn suggest_auto_exposure(
+        &self,
+        command: AutoExposureCommand,
+    ) -> Result<f32, ApplicationError> {
+        let image = self.require_image(command.image_id)?;
+        let sample = self
+            .decoder
+            .decode_downsampled(Path::new(&image.file_path), AUTO_EXPOSURE_SAMPLE_SIDE)?;
+        let exposure = auto_exposure(mean_linear_luminance(&sample)).ok_or_else(|| {
+            ApplicationError::InvalidInput(format!(
+                "cannot suggest exposure for black image id={}",
+                command.image_id.get()
+            ))
+        })?;
+        if command.apply {
+            let params = self.show_edit(ShowEditCommand {
+                image_id: command.image_id,
+            })?;
+            self.set_edit(SetEditCommand {
+                image_id: command.image_id,
+                params: params.with_exposure(exposure),
+            })?;
         }
+        Ok(exposure)
+    }
 
-        fn try_receive_preview(
-            &self,
-        ) -> Result<Option<lite_room_domain::PreviewFrame>, ApplicationError> {
-            Ok(self.responses.borrow_mut().pop())
+    fn rendered_luminance(
+        &self,
+        image_id: ImageId,
+        params: &EditParams,
+    ) -> Result<f32, ApplicationError> {
+        let image = self.require_image(image_id)?;
+        let source = self.decoder.decode_full(Path::new(&image.file_path))?;
+        Ok(mean_linear_luminance(&apply_edits(&source, params)))
+    }
+
+    pub fn export_image(&self, command: ExportImageCommand) -> Result<PathBuf, ApplicationError> {
+        if command.output_path.trim().is_empty() {
+            return Err(ApplicationError::InvalidInput(
+                "output path must not be empty".to_string(),
+            ));
+        }
+        if command.raw_format.is_some() && command.quality.is_some() {
+            return Err(ApplicationError::InvalidInput(
+                "export quality only applies to JPEG output".to_string(),
+            ));
+        }
+        let quality = command.quality.unwrap_or(EXPORT_JPEG_QUALITY);
+        if !(1..=100).contains(&quality) {
+            return Err(ApplicationError::InvalidInput(format!(
+                "export quality must be within 1..=100, got {quality}"
+            )));
         }
 
-        fn metrics(&self) -> Result<lite_room_domain::PreviewMetrics, ApplicationError> {
-            Ok(lite_room_domain::PreviewMetrics::default())
+        let image = self.require_image(command.image_id)?;
+        if detect_image_kind(Path::new(&image.file_path)) == ImageKind::Raw {
+            return Err(ApplicationError::InvalidInput(format!(
+                "cannot export image {}: RAW decode is not implemented",
+                command.image_id.get()
+            )));
+        }
+        let params = self.show_edit(ShowEditCommand {
+            image_id: command.image_id,
+        })?;
+        let source = self.decoder.decode_full(Path::new(&image.file_path))?;
+        // Rotation and crop reshape the frame, so they run on the whole source
+        // before the strip-by-strip tonal pass.
+        let source = apply_geometry(&source, &params).unwrap_or(source);
+        let edited = TiledRender::new(&source, &params, EXPORT_STRIP_ROWS);
+        let output_path = Path::new(&command.output_path);
+        match command.raw_format {
+            Some(format) => self.encoder.write_raw(output_path, &edited, format),
+            None => self.encoder.write_jpeg_tiled(output_path, &edited, quality),
         }
     }
 
-    impl FakeCatalog {
-        fn new() -> Self {
-            Self {
-                initialized: std::cell::Cell::new(false),
-                next_id: std::cell::Cell::new(1),
-                images: std::cell::RefCell::new(HashMap::new()),
-                edits: std::cell::RefCell::new(HashMap::new()),
-            }
-        }
+    // Saves the stored edit as `<original>.xmp` so other editors can pick up
+    // the basic adjustments.
+    pub fn write_sidecar(&self, command: WriteSidecarCommand) -> Result<PathBuf, ApplicationError> {
+        let image = self.require_image(command.image_id)?;
+        let params = self.show_edit(ShowEditCommand {
+            image_id: command.image_id,
+        })?;
+        let path = format!("{}.xmp", image.file_path);
+        let xmp = edit_params_to_xmp(&params, image.caption.as_deref());
+        self.encoder.write_sidecar(Path::new(&path), &xmp)
     }
 
-    impl CatalogRepository for FakeCatalog {
-        fn initialize(&self) -> Result<(), ApplicationError> {
-            self.initialized.set(true);
-            Ok(())
+    pub fn export_all(
+        &self,
+        command: ExportAllCommand,
+        mut on_progress: impl FnMut(ExportProgress),
+    ) -> Result<ExportReport, ApplicationError> {
+        if command.output_dir.trim().is_empty() {
+            return Err(ApplicationError::InvalidInput(
+                "output directory must not be empty".to_string(),
+            ));
+        }
+        if command.workers == 0 {
+            return Err(ApplicationError::InvalidInput(
+                "export workers must be at least 1".to_string(),
+            ));
         }
 
-        fn upsert_image(
-            &self,
-            image: &crate::NewImage,
-        ) -> Result<crate::UpsertImageResult, ApplicationError> {
-            let mut images = self.images.borrow_mut();
-            if let Some(found) = images
-                .values()
-                .find(|entry| entry.file_path == image.file_path)
-            {
-                return Ok(crate::UpsertImageResult {
-                    image_id: found.id,
-                    inserted: false,
-                });
+        let images = self.catalog.list_images(
+            ImageSort::CaptureDate,
+            &ImageFilter::default(),
+            ImagePage::default(),
+        )?;
+        let mut report = ExportReport {
+            total: images.len(),
+            ..ExportReport::default()
+        };
+        let mut completed = 0;
+        let mut jobs = Vec::with_capacity(images.len());
+        for image in images {
+            match self.show_edit(ShowEditCommand { image_id: image.id }) {
+                Ok(params) => jobs.push((image, params)),
+                Err(error) => {
+                    report.failures.push(ExportFailure {
+                        image_id: image.id,
+                        message: error.to_string(),
+                    });
+                    completed += 1;
+                    on_progress(ExportProgress {
+                        completed,
+                        total: report.total,
+                    });
+                }
+            }
+        }
+
+        let decoder = self.decoder.as_ref();
+        let encoder = self.encoder.as_ref();
+        let output_dir = command.output_dir.as_str();
+        let next_job = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..command.workers.min(jobs.len()) {
+                let sender = sender.clone();
+                let (jobs, next_job) = (&jobs, &next_job);
+                scope.spawn(move || loop {
+                    let index = next_job.fetch_add(1, Ordering::Relaxed);
+                    let Some((image, params)) = jobs.get(index) else {
+                        break;
+                    };
+                    let result = export_rendered(decoder, encoder, image, params, output_dir);
+                    if sender.send((image.id, result)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            for (image_id, result) in receiver {
+                match result {
+                    Ok(path) => report.exported.push(path),
+                    Err(error) => report.failures.push(ExportFailure {
+                        image_id,
+                        message: error.to_string(),
+                    }),
+                }
+                completed += 1;
+                on_progress(ExportProgress {
+                    completed,
+                    total: report.total,
+                });
+            }
+        });
+
+        report.exported.sort();
+        report
+            .failures
+            .sort_by_key(|failure| failure.image_id.get());
+        Ok(report)
+    }
+
+    fn register_image(
+        &self,
+        image: crate::NewImage,
+        default_edit_json: &str,
+        cache_root: &str,
+    ) -> Result<UpsertImageResult, ApplicationError> {
+        let upsert = self.register_row(&image, default_edit_json)?;
+        let thumb = self.thumbnails.ensure_thumbnail(
+            Path::new(&image.file_path),
+            cache_root,
+            upsert.image_id,
+        )?;
+        self.store_thumbnail(upsert.image_id, &thumb, &image.import_date)?;
+        Ok(upsert)
+    }
+
+    fn register_row(
+        &self,
+        image: &crate::NewImage,
+        default_edit_json: &str,
+    ) -> Result<UpsertImageResult, ApplicationError> {
+        let upsert = self.catalog.upsert_image(image)?;
+        self.catalog
+            .ensure_default_edit(upsert.image_id, default_edit_json, &image.import_date)?;
+        Ok(upsert)
+    }
+
+    fn store_thumbnail(
+        &self,
+        image_id: ImageId,
+        thumb: &ThumbnailArtifact,
+        now: &str,
+    ) -> Result<(), ApplicationError> {
+        self.catalog.upsert_thumbnail(
+            image_id,
+            &thumb.file_path,
+            i64::from(thumb.width),
+            i64::from(thumb.height),
+            thumb.checksum.as_deref(),
+            now,
+        )
+    }
+
+    fn require_image(&self, image_id: ImageId) -> Result<ImageRecord, ApplicationError> {
+        self.catalog.find_image_by_id(image_id)?.ok_or_else(|| {
+            ApplicationError::NotFound(format!("image not found for id={}", image_id.get()))
+        })
+    }
+
+    pub fn list_images(
+        &self,
+        command: ListImagesCommand,
+    ) -> Result<Vec<ImageRecord>, ApplicationError> {
+        validate_list_command(&command)?;
+        self.catalog
+            .list_images(command.sort, &command.filter, command.page)
+    }
+
+    pub fn list_images_with_thumbnails(
+        &self,
+        command: ListImagesCommand,
+    ) -> Result<Vec<ImageWithThumbnail>, ApplicationError> {
+        validate_list_command(&command)?;
+        self.catalog
+            .list_images_with_thumbnails(command.sort, &command.filter, command.page)
+    }
+
+    pub fn verify_thumbnails(
+        &self,
+        command: VerifyThumbnailsCommand,
+    ) -> Result<ThumbnailVerifyReport, ApplicationError> {
+        let mut report = ThumbnailVerifyReport::default();
+        for entry in self.catalog.list_images_with_thumbnails(
+            ImageSort::Custom,
+            &ImageFilter::default(),
+            ImagePage::default(),
+        )? {
+            let Some(thumbnail) = entry.thumbnail else {
+                continue;
+            };
+            let Some(expected) = thumbnail.checksum.as_deref() else {
+                report.unchecked += 1;
+                continue;
+            };
+            report.checked += 1;
+            let thumbnail_path = Path::new(&thumbnail.file_path);
+            if self
+                .thumbnails
+                .thumbnail_checksum(thumbnail_path)?
+                .as_deref()
+                == Some(expected)
+            {
+                continue;
+            }
+
+            self.thumbnails.discard_thumbnail(thumbnail_path)?;
+            let image_id = entry.image.id;
+            let regenerated = self
+                .thumbnails
+                .ensure_thumbnail(
+                    Path::new(&entry.image.file_path),
+                    &command.cache_root,
+                    image_id,
+                )
+                .and_then(|thumb| {
+                    self.catalog.upsert_thumbnail(
+                        image_id,
+                        &thumb.file_path,
+                        i64::from(thumb.width),
+                        i64::from(thumb.height),
+                        thumb.checksum.as_deref(),
+                        &self.clock.now_timestamp_string(),
+                    )
+                });
+            match regenerated {
+                Ok(()) => report.regenerated.push(image_id),
+                Err(error) => report.failures.push((image_id, error.to_string())),
+            }
+        }
+        Ok(report)
+    }
+
+    pub fn move_image(&self, command: MoveImageCommand) -> Result<i64, ApplicationError> {
+        if let SortMove::To(index) = command.target {
+            if index < 0 {
+                return Err(ApplicationError::InvalidInput(format!(
+                    "sort index must not be negative: {index}"
+                )));
+            }
+        }
+        self.require_image(command.image_id)?;
+        self.catalog.move_image(command.image_id, command.target)
+    }
+
+    pub fn open_image(&self, command: OpenImageCommand) -> Result<DecodedImage, ApplicationError> {
+        let image = self.require_image(command.image_id)?;
+        self.decoder.decode_for_preview(Path::new(&image.file_path))
+    }
+
+    pub fn show_edit(&self, command: ShowEditCommand) -> Result<EditParams, ApplicationError> {
+        self.catalog
+            .find_edit(command.image_id)?
+            .map(|stored| {
+                serde_json::from_str::<EditParams>(&stored.edit_params_json)
+                    .map_err(|error| ApplicationError::Persistence(error.to_string()))
+            })
+            .transpose()?
+            .ok_or_else(|| {
+                ApplicationError::NotFound(format!(
+                    "edit not found for image id={}",
+                    command.image_id.get()
+                ))
+            })
+    }
+
+    pub fn show_edits(
+        &self,
+        command: ShowEditsCommand,
+    ) -> Result<HashMap<ImageId, EditParams>, ApplicationError> {
+        self.catalog
+            .find_edits(&command.image_ids)?
+            .into_iter()
+            .map(|(image_id, stored)| {
+                serde_json::from_str::<EditParams>(&stored.edit_params_json)
+                    .map(|params| (image_id, params))
+                    .map_err(|error| ApplicationError::Persistence(error.to_string()))
+            })
+            .collect()
+    }
+
+    pub fn set_edit(&self, command: SetEditCommand) -> Result<(), ApplicationError> {
+        command.params.validate_ranges()?;
+        let now = self.clock.now_timestamp_string();
+        let edit_json = serde_json::to_string(&command.params)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        self.catalog
+            .upsert_edit(command.image_id, &edit_json, &now)
+            .map_err(|error| {
+                error.with_context(format!(
+                    "set_edit failed for image {}",
+                    command.image_id.get()
+                ))
+            })
+    }
+
+    pub fn copy_edit(&self, command: CopyEditCommand) -> Result<String, ApplicationError> {
+        self.require_image(command.from)?;
+        let params = self.show_edit(ShowEditCommand {
+            image_id: command.from,
+        })?;
+        serde_json::to_string(&params)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))
+    }
+
+    // Targets that are not in the catalog are reported and skipped so one stale
+    // id does not abort the rest of the batch.
+    pub fn paste_edit(
+        &self,
+        command: PasteEditCommand,
+    ) -> Result<PasteEditReport, ApplicationError> {
+        if command.to.is_empty() {
+            return Err(ApplicationError::InvalidInput(
+                "paste_edit requires at least one target image".to_string(),
+            ));
+        }
+        let params = serde_json::from_str::<EditParams>(&command.edit_json).map_err(|error| {
+            ApplicationError::InvalidInput(format!("invalid edit json: {error}"))
+        })?;
+        params.validate_ranges()?;
+        let edit_json = serde_json::to_string(&params)
+            .map_err(|error| ApplicationError::Persistence(error.to_string()))?;
+        let now = self.clock.now_timestamp_string();
+
+        let mut report = PasteEditReport::default();
+        for image_id in command.to {
+            match self.require_image(image_id) {
+                Ok(_) => {
+                    self.catalog
+                        .upsert_edit(image_id, &edit_json, &now)
+                        .map_err(|error| {
+                            error.with_context(format!(
+                                "paste_edit failed for image {}",
+                                image_id.get()
+                            ))
+                        })?;
+                    report.applied.push(image_id);
+                }
+                Err(ApplicationError::NotFound(message)) => {
+                    report.skipped.push(PasteEditFailure { image_id, message });
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(report)
+    }
+
+    pub fn save_preset(&self, command: SavePresetCommand) -> Result<EditParams, ApplicationError> {
+        let name = preset_name(&command.name)?;
+        self.require_image(command.image_id)?;
+        let params = self.show_edit(ShowEditCommand {
+            image_id: command.image_id,
+        })?;
+        params.validate_ranges()?;
+        self.catalog.save_preset(name, &params)?;
+        Ok(params)
+    }
+
+    pub fn list_presets(
+        &self,
+        _command: ListPresetsCommand,
+    ) -> Result<Vec<EditPreset>, ApplicationError> {
+        self.catalog.list_presets()
+    }
+
+    // All targets are checked before any edit is written, so a typo in one id
+    // leaves the batch untouched.
+    pub fn apply_preset(
+        &self,
+        command: ApplyPresetCommand,
+    ) -> Result<EditParams, ApplicationError> {
+        let name = preset_name(&command.name)?;
+        if command.image_ids.is_empty() {
+            return Err(ApplicationError::InvalidInput(
+                "apply_preset requires at least one target image".to_string(),
+            ));
+        }
+        let preset = self
+            .catalog
+            .find_preset(name)?
+            .ok_or_else(|| ApplicationError::NotFound(format!("preset not found: {name}")))?;
+        preset.params.validate_ranges()?;
+        for image_id in &command.image_ids {
+            self.require_image(*image_id)?;
+        }
+        for image_id in command.image_ids {
+            self.set_edit(SetEditCommand {
+                image_id,
+                params: preset.params,
+            })?;
+        }
+        Ok(preset.params)
+    }
+
+    pub fn reset_edit(&self, command: ResetEditCommand) -> Result<(), ApplicationError> {
+        self.require_image(command.image_id)?;
+        let now = self.clock.now_timestamp_string();
+        self.catalog
+            .upsert_edit(command.image_id, &default_edit_json()?, &now)
+    }
+
+    pub fn reset_all_edits(
+        &self,
+        _command: ResetAllEditsCommand,
+    ) -> Result<usize, ApplicationError> {
+        let images = self.catalog.list_images(
+            ImageSort::CaptureDate,
+            &ImageFilter::default(),
+            ImagePage::default(),
+        )?;
+        let now = self.clock.now_timestamp_string();
+        let default_edit_json = default_edit_json()?;
+        for image in &images {
+            self.catalog
+                .upsert_edit(image.id, &default_edit_json, &now)?;
+        }
+        Ok(images.len())
+    }
+
+    pub fn set_rating(&self, command: SetRatingCommand) -> Result<(), ApplicationError> {
+        if !(0..=MAX_RATING).contains(&command.rating) {
+            return Err(ApplicationError::InvalidInput(format!(
+                "rating must be between 0 and {MAX_RATING}: {}",
+                command.rating
+            )));
+        }
+        self.require_image(command.image_id)?;
+        self.catalog.set_rating(command.image_id, command.rating)
+    }
+
+    pub fn set_flag(&self, command: SetFlagCommand) -> Result<(), ApplicationError> {
+        if !(FLAG_REJECT..=FLAG_PICK).contains(&command.flag) {
+            return Err(ApplicationError::InvalidInput(format!(
+                "flag must be between {FLAG_REJECT} and {FLAG_PICK}: {}",
+                command.flag
+            )));
+        }
+        self.require_image(command.image_id)?;
+        self.catalog.set_flag(command.image_id, command.flag)
+    }
+
+    pub fn set_caption(&self, command: SetCaptionCommand) -> Result<(), ApplicationError> {
+        self.require_image(command.image_id)?;
+        let caption = command.caption.trim();
+        self.catalog
+            .set_caption(command.image_id, (!caption.is_empty()).then_some(caption))
+    }
+
+    pub fn show_caption(
+        &self,
+        command: ShowCaptionCommand,
+    ) -> Result<Option<String>, ApplicationError> {
+        self.require_image(command.image_id)?;
+        self.catalog.find_caption(command.image_id)
+    }
+
+    pub fn stack_images(&self, command: StackImagesCommand) -> Result<i64, ApplicationError> {
+        if command.image_ids.len() < 2 {
+            return Err(ApplicationError::InvalidInput(format!(
+                "a stack needs at least 2 images, got {}",
+                command.image_ids.len()
+            )));
+        }
+        for (index, image_id) in command.image_ids.iter().enumerate() {
+            if command.image_ids[..index].contains(image_id) {
+                return Err(ApplicationError::InvalidInput(format!(
+                    "image id={} listed twice",
+                    image_id.get()
+                )));
+            }
+            self.require_image(*image_id)?;
+        }
+        self.catalog.create_stack(&command.image_ids)
+    }
+
+    pub fn set_stack_pick(&self, command: SetStackPickCommand) -> Result<i64, ApplicationError> {
+        self.require_image(command.image_id)?;
+        self.catalog
+            .set_stack_pick(command.image_id)?
+            .ok_or_else(|| {
+                ApplicationError::InvalidInput(format!(
+                    "image id={} is not in a stack",
+                    command.image_id.get()
+                ))
+            })
+    }
+
+    pub fn submit_preview(&self, command: SubmitPreviewCommand) -> Result<(), ApplicationError> {
+        command.params.validate()?;
+        if let Some(region) = &command.source_region {
+            region.validate()?;
+        }
+        if let Some(view) = &command.view_region {
+            view.validate()?;
+        }
+        if command.comparison.is_some() {
+            ComparisonMode::validate_split_fraction(command.split_fraction)?;
+        }
+        let image = self.require_image(command.image_id)?;
+
+        self.preview
+            .submit_preview(PreviewRequest {
+                image_id: command.image_id,
+                source_path: image.file_path,
+                source_pixels: command.source_pixels,
+                source_region: command.source_region,
+                view_region: command.view_region,
+                params: command.params,
+                target_width: command.target_width,
+                target_height: command.target_height,
+                want_histogram: command.want_histogram,
+                comparison: command.comparison,
+                split_fraction: command.split_fraction,
+            })
+            .map_err(|error| {
+                error.with_context(format!(
+                    "preview submit failed for image {}",
+                    command.image_id.get()
+                ))
+            })
+    }
+
+    pub fn poll_preview(
+        &self,
+        _command: PollPreviewCommand,
+    ) -> Result<Option<PreviewFrame>, ApplicationError> {
+        self.preview.try_receive_preview()
+    }
+
+    pub fn preview_metrics(
+        &self,
+        _query: PreviewMetricsQuery,
+    ) -> Result<PreviewMetrics, ApplicationError> {
+        self.preview.metrics()
+    }
+
+    pub fn preview_backend_info(
+        &self,
+        _query: PreviewBackendQuery,
+    ) -> Result<PreviewBackendInfo, ApplicationError> {
+        self.preview.backend_info()
+    }
+}
+
+fn export_rendered(
+    decoder: &dyn ImageDecoder,
+    encoder: &dyn ImageEncoder,
+    image: &ImageRecord,
+    params: &EditParams,
+    output_dir: &str,
+) -> Result<PathBuf, ApplicationError> {
+    let source = decoder.decode_full(Path::new(&image.file_path))?;
+    let source = apply_geometry(&source, params).unwrap_or(source);
+    let rendered = TiledRender::new(&source, params, EXPORT_STRIP_ROWS);
+    let stem = Path::new(&image.file_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "image".to_string());
+    let output_path = format!("{output_dir}/{}-{stem}.jpg", image.id.get());
+    encoder.write_jpeg_tiled(Path::new(&output_path), &rendered, EXPORT_JPEG_QUALITY)
+}
+
+// Rejects filters that can never match and a zero page size, for both list
+// queries.
+fn validate_list_command(command: &ListImagesCommand) -> Result<(), ApplicationError> {
+    if let (Some(min), Some(max)) = (command.filter.focal_min, command.filter.focal_max) {
+        if min > max {
+            return Err(ApplicationError::InvalidInput(format!(
+                "focal range is empty: min {min} > max {max}"
+            )));
+        }
+    }
+    if let (Some(min), Some(max)) = (command.filter.iso_min, command.filter.iso_max) {
+        if min > max {
+            return Err(ApplicationError::InvalidInput(format!(
+                "iso range is empty: min {min} > max {max}"
+            )));
+        }
+    }
+    for date in [&command.filter.from, &command.filter.to]
+        .into_iter()
+        .flatten()
+    {
+        if !is_iso_date(date) {
+            return Err(ApplicationError::InvalidInput(format!(
+                "date must be YYYY-MM-DD, got {date}"
+            )));
+        }
+    }
+    if let (Some(from), Some(to)) = (&command.filter.from, &command.filter.to) {
+        if from > to {
+            return Err(ApplicationError::InvalidInput(format!(
+                "date range is empty: from {from} > to {to}"
+            )));
+        }
+    }
+    if command.page.limit == Some(0) {
+        return Err(ApplicationError::InvalidInput(
+            "list limit must be at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn is_iso_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(index, byte)| match index {
+            4 | 7 => *byte == b'-',
+            _ => byte.is_ascii_digit(),
+        })
+}
+
+fn preset_name(name: &str) -> Result<&str, ApplicationError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApplicationError::InvalidInput(
+            "preset name must not be empty".to_string(),
+        ));
+    }
+    Ok(name)
+}
+
+fn new_image(
+    path: &Path,
+    metadata_json: String,
+    capture: CaptureMetadata,
+    now: &str,
+) -> crate::NewImage {
+    crate::NewImage {
+        file_path: path.to_string_lossy().to_string(),
+        import_date: now.to_string(),
+        capture_date: capture.capture_date,
+        camera_model: capture.camera_model,
+        iso: capture.iso,
+        lens_model: capture.lens_model,
+        focal_length: capture.focal_length,
+        rating: 0,
+        flag: 0,
+        metadata_json,
+        content_hash: None,
+    }
+}
+
+fn default_edit_json() -> Result<String, ApplicationError> {
+    let edit = EditParams::default();
+    edit.validate()?;
+    serde_json::to_string(&edit).map_err(|error| ApplicationError::Persistence(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    use lite_room_domain::{
+        detect_image_kind, DecodedBuffer, DecodedImage, ImageId, ImageKind, ImageRecord,
+        RawPixelFormat,
+    };
+
+    use super::*;
+
+    struct FakeCatalog {
+        initialized: std::cell::Cell<bool>,
+        next_id: std::cell::Cell<i64>,
+        images: std::cell::RefCell<HashMap<i64, ImageRecord>>,
+        edits: std::cell::RefCell<HashMap<i64, crate::StoredEdit>>,
+        checkpoint: std::cell::RefCell<Option<ImportCheckpoint>>,
+        captions: std::cell::RefCell<HashMap<i64, String>>,
+        stacks: std::cell::RefCell<HashMap<i64, i64>>,
+        import_failures: std::cell::RefCell<Vec<ImportFailure>>,
+        presets: std::cell::RefCell<Vec<crate::EditPreset>>,
+    }
+
+    #[derive(Default)]
+    struct FakePreviewPipeline {
+        submitted: std::cell::RefCell<Vec<lite_room_domain::PreviewRequest>>,
+        responses: std::cell::RefCell<Vec<lite_room_domain::PreviewFrame>>,
+    }
+
+    impl PreviewPipeline for FakePreviewPipeline {
+        fn submit_preview(
+            &self,
+            request: lite_room_domain::PreviewRequest,
+        ) -> Result<(), ApplicationError> {
+            self.submitted.borrow_mut().push(request);
+            Ok(())
+        }
+
+        fn try_receive_preview(
+            &self,
+        ) -> Result<Option<lite_room_domain::PreviewFrame>, ApplicationError> {
+            Ok(self.responses.borrow_mut().pop())
+        }
+
+        fn metrics(&self) -> Result<lite_room_domain::PreviewMetrics, ApplicationError> {
+            Ok(lite_room_domain::PreviewMetrics::default())
+        }
+
+        fn backend_info(&self) -> Result<lite_room_domain::PreviewBackendInfo, ApplicationError> {
+            Ok(lite_room_domain::PreviewBackendInfo {
+                backend: lite_room_domain::PreviewBackend::Cpu,
+                adapter_name: "fake".to_string(),
+            })
+        }
+    }
+
+    impl FakeCatalog {
+        fn new() -> Self {
+            Self {
+                initialized: std::cell::Cell::new(false),
+                next_id: std::cell::Cell::new(1),
+                images: std::cell::RefCell::new(HashMap::new()),
+                edits: std::cell::RefCell::new(HashMap::new()),
+                checkpoint: std::cell::RefCell::new(None),
+                captions: std::cell::RefCell::new(HashMap::new()),
+                stacks: std::cell::RefCell::new(HashMap::new()),
+                import_failures: std::cell::RefCell::new(Vec::new()),
+                presets: std::cell::RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl CatalogRepository for FakeCatalog {
+        fn initialize(&self) -> Result<(), ApplicationError> {
+            self.initialized.set(true);
+            Ok(())
+        }
+
+        fn upsert_image(
+            &self,
+            image: &crate::NewImage,
+        ) -> Result<crate::UpsertImageResult, ApplicationError> {
+            let mut images = self.images.borrow_mut();
+            if let Some(found) = images
+                .values()
+                .find(|entry| entry.file_path == image.file_path)
+            {
+                return Ok(crate::UpsertImageResult {
+                    image_id: found.id,
+                    inserted: false,
+                });
             }
 
             let id_value = self.next_id.get();
@@ -287,6 +1485,7 @@ mod tests {
                     rating: image.rating,
                     flag: image.flag,
                     metadata_json: image.metadata_json.clone(),
+                    caption: None,
                 },
             );
             Ok(crate::UpsertImageResult {
@@ -327,33 +1526,236 @@ mod tests {
             Ok(())
         }
 
-        fn find_edit(
-            &self,
-            image_id: ImageId,
-        ) -> Result<Option<crate::StoredEdit>, ApplicationError> {
-            Ok(self.edits.borrow().get(&image_id.get()).cloned())
+        fn find_edit(
+            &self,
+            image_id: ImageId,
+        ) -> Result<Option<crate::StoredEdit>, ApplicationError> {
+            Ok(self.edits.borrow().get(&image_id.get()).cloned())
+        }
+
+        fn find_edits(
+            &self,
+            image_ids: &[ImageId],
+        ) -> Result<HashMap<ImageId, crate::StoredEdit>, ApplicationError> {
+            let edits = self.edits.borrow();
+            Ok(image_ids
+                .iter()
+                .filter_map(|id| edits.get(&id.get()).map(|edit| (*id, edit.clone())))
+                .collect())
+        }
+
+        fn upsert_thumbnail(
+            &self,
+            _image_id: ImageId,
+            _file_path: &str,
+            _width: i64,
+            _height: i64,
+            _checksum: Option<&str>,
+            _updated_at: &str,
+        ) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+
+        fn list_images(
+            &self,
+            _sort: lite_room_domain::ImageSort,
+            _filter: &ImageFilter,
+            page: ImagePage,
+        ) -> Result<Vec<ImageRecord>, ApplicationError> {
+            let images = self.images.borrow();
+            let window = images.values().skip(page.offset);
+            Ok(window
+                .take(page.limit.unwrap_or(usize::MAX))
+                .cloned()
+                .collect())
+        }
+
+        fn list_images_with_thumbnails(
+            &self,
+            sort: lite_room_domain::ImageSort,
+            filter: &ImageFilter,
+            page: ImagePage,
+        ) -> Result<Vec<crate::ImageWithThumbnail>, ApplicationError> {
+            Ok(self
+                .list_images(sort, filter, page)?
+                .into_iter()
+                .map(|image| crate::ImageWithThumbnail {
+                    image,
+                    thumbnail: None,
+                })
+                .collect())
+        }
+
+        fn move_image(
+            &self,
+            _image_id: ImageId,
+            _target: SortMove,
+        ) -> Result<i64, ApplicationError> {
+            Ok(0)
+        }
+
+        fn find_image_by_id(
+            &self,
+            image_id: ImageId,
+        ) -> Result<Option<ImageRecord>, ApplicationError> {
+            Ok(self.images.borrow().get(&image_id.get()).cloned())
+        }
+
+        fn find_content_duplicate(
+            &self,
+            _content_hash: &str,
+            _file_path: &str,
+        ) -> Result<Option<ImageId>, ApplicationError> {
+            Ok(None)
+        }
+
+        fn set_rating(&self, image_id: ImageId, rating: i64) -> Result<(), ApplicationError> {
+            if let Some(image) = self.images.borrow_mut().get_mut(&image_id.get()) {
+                image.rating = rating;
+            }
+            Ok(())
+        }
+
+        fn set_flag(&self, image_id: ImageId, flag: i64) -> Result<(), ApplicationError> {
+            if let Some(image) = self.images.borrow_mut().get_mut(&image_id.get()) {
+                image.flag = flag;
+            }
+            Ok(())
+        }
+
+        fn fill_capture_metadata(
+            &self,
+            image_id: ImageId,
+            capture: &CaptureMetadata,
+        ) -> Result<bool, ApplicationError> {
+            let mut images = self.images.borrow_mut();
+            let Some(image) = images.get_mut(&image_id.get()) else {
+                return Ok(false);
+            };
+            if image.capture_date.is_some() || capture.capture_date.is_none() {
+                return Ok(false);
+            }
+            image.capture_date = capture.capture_date.clone();
+            Ok(true)
+        }
+
+        fn relink_image(&self, image_id: ImageId, file_path: &str) -> Result<(), ApplicationError> {
+            if let Some(image) = self.images.borrow_mut().get_mut(&image_id.get()) {
+                image.file_path = file_path.to_string();
+            }
+            Ok(())
+        }
+
+        fn delete_image(&self, image_id: ImageId) -> Result<Option<Vec<String>>, ApplicationError> {
+            if self.images.borrow_mut().remove(&image_id.get()).is_none() {
+                return Ok(None);
+            }
+            self.edits.borrow_mut().remove(&image_id.get());
+            self.captions.borrow_mut().remove(&image_id.get());
+            Ok(Some(Vec::new()))
+        }
+
+        fn set_caption(
+            &self,
+            image_id: ImageId,
+            caption: Option<&str>,
+        ) -> Result<(), ApplicationError> {
+            let mut captions = self.captions.borrow_mut();
+            match caption {
+                Some(caption) => captions.insert(image_id.get(), caption.to_string()),
+                None => captions.remove(&image_id.get()),
+            };
+            if let Some(image) = self.images.borrow_mut().get_mut(&image_id.get()) {
+                image.caption = caption.map(str::to_string);
+            }
+            Ok(())
+        }
+
+        fn find_caption(&self, image_id: ImageId) -> Result<Option<String>, ApplicationError> {
+            Ok(self.captions.borrow().get(&image_id.get()).cloned())
+        }
+
+        fn create_stack(&self, image_ids: &[ImageId]) -> Result<i64, ApplicationError> {
+            let mut stacks = self.stacks.borrow_mut();
+            let stack_id = stacks.values().max().copied().unwrap_or(0) + 1;
+            for image_id in image_ids {
+                stacks.insert(image_id.get(), stack_id);
+            }
+            Ok(stack_id)
+        }
+
+        fn set_stack_pick(&self, image_id: ImageId) -> Result<Option<i64>, ApplicationError> {
+            Ok(self.stacks.borrow().get(&image_id.get()).copied())
+        }
+
+        fn find_import_checkpoint(&self) -> Result<Option<ImportCheckpoint>, ApplicationError> {
+            Ok(self.checkpoint.borrow().clone())
+        }
+
+        fn save_import_checkpoint(
+            &self,
+            checkpoint: &ImportCheckpoint,
+            _updated_at: &str,
+        ) -> Result<(), ApplicationError> {
+            *self.checkpoint.borrow_mut() = Some(checkpoint.clone());
+            Ok(())
+        }
+
+        fn clear_import_checkpoint(&self) -> Result<(), ApplicationError> {
+            *self.checkpoint.borrow_mut() = None;
+            Ok(())
+        }
+
+        fn record_import_failure(&self, failure: &ImportFailure) -> Result<(), ApplicationError> {
+            let mut failures = self.import_failures.borrow_mut();
+            failures.retain(|existing| existing.file_path != failure.file_path);
+            failures.push(failure.clone());
+            Ok(())
+        }
+
+        fn list_import_failures(&self) -> Result<Vec<ImportFailure>, ApplicationError> {
+            Ok(self.import_failures.borrow().clone())
+        }
+
+        fn clear_import_failure(&self, file_path: &str) -> Result<(), ApplicationError> {
+            self.import_failures
+                .borrow_mut()
+                .retain(|failure| failure.file_path != file_path);
+            Ok(())
         }
 
-        fn upsert_thumbnail(
-            &self,
-            _image_id: ImageId,
-            _file_path: &str,
-            _width: i64,
-            _height: i64,
-            _updated_at: &str,
-        ) -> Result<(), ApplicationError> {
+        fn save_preset(&self, name: &str, params: &EditParams) -> Result<(), ApplicationError> {
+            let mut presets = self.presets.borrow_mut();
+            presets.retain(|preset| preset.name != name);
+            presets.push(crate::EditPreset {
+                name: name.to_string(),
+                params: *params,
+            });
             Ok(())
         }
 
-        fn list_images(&self) -> Result<Vec<ImageRecord>, ApplicationError> {
-            Ok(self.images.borrow().values().cloned().collect())
+        fn list_presets(&self) -> Result<Vec<crate::EditPreset>, ApplicationError> {
+            Ok(self.presets.borrow().clone())
         }
 
-        fn find_image_by_id(
-            &self,
-            image_id: ImageId,
-        ) -> Result<Option<ImageRecord>, ApplicationError> {
-            Ok(self.images.borrow().get(&image_id.get()).cloned())
+        fn find_preset(&self, name: &str) -> Result<Option<crate::EditPreset>, ApplicationError> {
+            Ok(self
+                .presets
+                .borrow()
+                .iter()
+                .find(|preset| preset.name == name)
+                .cloned())
+        }
+
+        fn optimize(&self) -> Result<crate::OptimizeReport, ApplicationError> {
+            Ok(crate::OptimizeReport {
+                size_before_bytes: 0,
+                size_after_bytes: 0,
+            })
+        }
+
+        fn inspect_schema(&self) -> Result<crate::SchemaReport, ApplicationError> {
+            Ok(crate::SchemaReport::default())
         }
     }
 
@@ -365,31 +1767,58 @@ mod tests {
         fn scan_supported(
             &self,
             _folder: &str,
+            resume_after: Option<&Path>,
+            _workers: usize,
         ) -> Result<crate::FileScanSummary, ApplicationError> {
-            let scanned_files = self.files.len();
-            let files: Vec<crate::ScannedFile> = self
+            let walked: Vec<&PathBuf> = self
                 .files
                 .iter()
-                .map(|path| {
-                    let ext = path
-                        .extension()
-                        .and_then(|part| part.to_str())
-                        .unwrap_or_default()
-                        .to_ascii_lowercase();
-                    crate::ScannedFile {
-                        canonical_path: path.clone(),
-                        extension: ext,
-                        file_size: 100,
-                        image_kind: detect_image_kind(path),
-                    }
-                })
+                .filter(|path| resume_after.is_none_or(|after| path.as_path() > after))
+                .collect();
+            let scanned_files = walked.len();
+            let (supported, unsupported): (Vec<&PathBuf>, Vec<&PathBuf>) = walked
+                .into_iter()
+                .partition(|path| detect_image_kind(path) != ImageKind::Unsupported);
+            let files: Vec<crate::ScannedFile> = supported
+                .into_iter()
+                .filter_map(|path| self.scan_file(path).expect("fake scan"))
                 .collect();
             Ok(crate::FileScanSummary {
                 scanned_files,
                 supported_files: files.len(),
                 files,
+                unsupported: unsupported.into_iter().cloned().collect(),
             })
         }
+
+        fn scan_file(&self, path: &Path) -> Result<Option<crate::ScannedFile>, ApplicationError> {
+            let ext = path
+                .extension()
+                .and_then(|part| part.to_str())
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            let frame_count = (ext == "gif").then_some(3);
+            let grayscale = path
+                .file_stem()
+                .is_some_and(|stem| stem.to_string_lossy().starts_with("mono"));
+            Ok(Some(crate::ScannedFile {
+                canonical_path: path.to_path_buf(),
+                extension: ext,
+                file_size: 100,
+                image_kind: detect_image_kind(path),
+                frame_count,
+                grayscale,
+                capture: crate::CaptureMetadata::default(),
+                content_hash: None,
+            }))
+        }
+
+        // Files named `gone*` stand in for photos moved or deleted on disk.
+        fn file_exists(&self, path: &Path) -> bool {
+            !path
+                .file_stem()
+                .is_some_and(|stem| stem.to_string_lossy().starts_with("gone"))
+        }
     }
 
     struct FakeThumbs;
@@ -405,87 +1834,661 @@ mod tests {
                 file_path: format!("{cache_root}/thumbs/{}.jpg", image_id.get()),
                 width: 256,
                 height: 256,
+                checksum: None,
+            })
+        }
+
+        fn thumbnail_checksum(
+            &self,
+            _thumbnail_path: &Path,
+        ) -> Result<Option<String>, ApplicationError> {
+            Ok(None)
+        }
+
+        fn discard_thumbnail(&self, _thumbnail_path: &Path) -> Result<(), ApplicationError> {
+            Ok(())
+        }
+    }
+
+    struct FakeDecoder;
+
+    impl ImageDecoder for FakeDecoder {
+        fn decode_for_preview(&self, path: &Path) -> Result<DecodedImage, ApplicationError> {
+            Ok(DecodedImage {
+                width: 64,
+                height: 48,
+                kind: detect_image_kind(path),
+                grayscale: false,
+            })
+        }
+
+        fn decode_full(&self, path: &Path) -> Result<DecodedBuffer, ApplicationError> {
+            if path.to_string_lossy().contains("corrupt") {
+                return Err(ApplicationError::Decode("corrupt source".to_string()));
+            }
+            let value = if path.to_string_lossy().contains("dark") {
+                26
+            } else {
+                204
+            };
+            Ok(DecodedBuffer {
+                width: 4,
+                height: 4,
+                rgb: vec![value; 4 * 4 * 3],
+            })
+        }
+
+        fn decode_downsampled(
+            &self,
+            path: &Path,
+            _max_side: u32,
+        ) -> Result<DecodedBuffer, ApplicationError> {
+            self.decode_full(path)
+        }
+    }
+
+    struct FakeEncoder;
+
+    impl ImageEncoder for FakeEncoder {
+        fn write_jpeg(
+            &self,
+            path: &Path,
+            _image: &DecodedBuffer,
+            _quality: u8,
+        ) -> Result<PathBuf, ApplicationError> {
+            Ok(path.to_path_buf())
+        }
+
+        fn write_jpeg_tiled(
+            &self,
+            path: &Path,
+            _image: &TiledRender<'_>,
+            _quality: u8,
+        ) -> Result<PathBuf, ApplicationError> {
+            Ok(path.to_path_buf())
+        }
+
+        fn write_raw(
+            &self,
+            path: &Path,
+            _image: &TiledRender<'_>,
+            _format: RawPixelFormat,
+        ) -> Result<PathBuf, ApplicationError> {
+            Ok(path.to_path_buf())
+        }
+
+        fn write_sidecar(&self, path: &Path, _contents: &str) -> Result<PathBuf, ApplicationError> {
+            Ok(path.to_path_buf())
+        }
+    }
+
+    struct FakeClock;
+
+    impl Clock for FakeClock {
+        fn now_timestamp_string(&self) -> String {
+            "123".to_string()
+        }
+    }
+
+    #[test]
+    fn import_and_open_image_workflow() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner {
+                files: vec![PathBuf::from("/tmp/sample.jpg")],
+            }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+
+        service
+            .bootstrap_catalog(BootstrapCatalogCommand)
+            .expect("bootstrap should work");
+
+        let report = service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import should work");
+        assert_eq!(report.scanned_files, 1);
+        assert_eq!(report.supported_files, 1);
+        assert_eq!(report.newly_imported, 1);
+
+        let images = service
+            .list_images(ListImagesCommand::default())
+            .expect("list should work");
+        assert_eq!(images.len(), 1);
+
+        let decoded = service
+            .open_image(OpenImageCommand {
+                image_id: images[0].id,
+            })
+            .expect("open should work");
+        assert_eq!(decoded.width, 64);
+        assert_eq!(decoded.kind, ImageKind::Jpeg);
+    }
+
+    #[test]
+    fn folder_import_reports_progress_after_each_supported_file() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner {
+                files: vec![
+                    PathBuf::from("/tmp/a.jpg"),
+                    PathBuf::from("/tmp/notes.txt"),
+                    PathBuf::from("/tmp/b.gif"),
+                    PathBuf::from("/tmp/c.jpg"),
+                ],
+            }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+
+        let mut progress = Vec::new();
+        let report = service
+            .import_folder_with_progress(
+                ImportFolderCommand {
+                    folder: "/tmp".to_string(),
+                    cache_root: "cache".to_string(),
+                    resume: false,
+                    strict: false,
+                    limit: None,
+                    workers: 2,
+                },
+                |update| progress.push(update),
+            )
+            .expect("import should work");
+
+        assert_eq!(progress.len(), report.supported_files);
+        assert_eq!(progress.len(), 3);
+        let processed: Vec<usize> = progress.iter().map(|update| update.processed).collect();
+        assert_eq!(processed, vec![1, 2, 3]);
+        assert!(progress.iter().all(|update| update.total == 3));
+        assert_eq!(progress[2].imported, 3);
+        assert_eq!(progress[1].current_path, "/tmp/b.gif");
+    }
+
+    #[test]
+    fn import_records_gif_frame_count() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner {
+                files: vec![PathBuf::from("/tmp/clip.gif")],
+            }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+
+        service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import should work");
+
+        let image = service
+            .list_images(ListImagesCommand::default())
+            .expect("list should work")
+            .remove(0);
+        let metadata: serde_json::Value =
+            serde_json::from_str(&image.metadata_json).expect("metadata json");
+        assert_eq!(metadata["frame_count"], 3);
+        assert!(metadata.get("grayscale").is_none());
+    }
+
+    #[test]
+    fn import_flags_grayscale_sources_in_metadata() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner {
+                files: vec![PathBuf::from("/tmp/mono-portrait.jpg")],
+            }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+
+        service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import should work");
+
+        let image = service
+            .list_images(ListImagesCommand::default())
+            .expect("list should work")
+            .remove(0);
+        let metadata: serde_json::Value =
+            serde_json::from_str(&image.metadata_json).expect("metadata json");
+        assert_eq!(metadata["grayscale"], true);
+    }
+
+    #[test]
+    fn import_files_skips_images_already_in_catalog() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner { files: vec![] }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        let command = ImportFilesCommand {
+            paths: vec![PathBuf::from("/tmp/tethered.jpg")],
+            cache_root: "cache".to_string(),
+        };
+
+        let first = service.import_files(command.clone()).expect("first import");
+        let second = service.import_files(command).expect("second import");
+
+        assert_eq!(first.newly_imported, 1);
+        assert_eq!(second.newly_imported, 0);
+    }
+
+    #[test]
+    fn strict_import_rejects_unsupported_files() {
+        let files = vec![
+            PathBuf::from("/ingest/keep.jpg"),
+            PathBuf::from("/ingest/notes.txt"),
+        ];
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner { files }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        let command = |strict| ImportFolderCommand {
+            folder: "/ingest".to_string(),
+            cache_root: "cache".to_string(),
+            resume: false,
+            strict,
+            limit: None,
+            workers: 1,
+        };
+
+        let strict = service.import_folder(command(true));
+        assert!(
+            matches!(&strict, Err(ApplicationError::InvalidInput(message)) if message.contains("/ingest/notes.txt")),
+            "unexpected strict result: {strict:?}"
+        );
+        assert!(service
+            .list_images(ListImagesCommand::default())
+            .expect("list")
+            .is_empty());
+
+        let relaxed = service
+            .import_folder(command(false))
+            .expect("non-strict import should work");
+        assert_eq!(relaxed.scanned_files, 2);
+        assert_eq!(relaxed.newly_imported, 1);
+    }
+
+    #[test]
+    fn resumed_import_processes_only_files_after_checkpoint() {
+        let files: Vec<PathBuf> = ["a.jpg", "b.jpg", "c.jpg", "d.jpg"]
+            .iter()
+            .map(|name| PathBuf::from(format!("/photos/{name}")))
+            .collect();
+        let catalog = FakeCatalog::new();
+        *catalog.checkpoint.borrow_mut() = Some(ImportCheckpoint {
+            folder: "/photos".to_string(),
+            last_path: "/photos/b.jpg".to_string(),
+        });
+        let service = ApplicationService::new(
+            Box::new(catalog),
+            Box::new(FakeScanner { files }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+
+        let mismatched = service.import_folder(ImportFolderCommand {
+            folder: "/elsewhere".to_string(),
+            cache_root: "cache".to_string(),
+            resume: true,
+            strict: false,
+            limit: None,
+            workers: 1,
+        });
+        assert!(matches!(mismatched, Err(ApplicationError::InvalidInput(_))));
+
+        let report = service
+            .import_folder(ImportFolderCommand {
+                folder: "/photos".to_string(),
+                cache_root: "cache".to_string(),
+                resume: true,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("resumed import should work");
+        assert_eq!(report.scanned_files, 2);
+        assert_eq!(report.newly_imported, 2);
+        let imported: Vec<String> = service
+            .list_images(ListImagesCommand::default())
+            .expect("list")
+            .into_iter()
+            .map(|image| image.file_path)
+            .collect();
+        assert_eq!(imported.len(), 2);
+        assert!(imported.contains(&"/photos/c.jpg".to_string()));
+        assert!(imported.contains(&"/photos/d.jpg".to_string()));
+
+        let finished = service.import_folder(ImportFolderCommand {
+            folder: "/photos".to_string(),
+            cache_root: "cache".to_string(),
+            resume: true,
+            strict: false,
+            limit: None,
+            workers: 1,
+        });
+        assert!(matches!(finished, Err(ApplicationError::NotFound(_))));
+    }
+
+    #[test]
+    fn import_limit_stops_after_new_files_and_resume_continues() {
+        let files: Vec<PathBuf> = (1..=5)
+            .map(|index| PathBuf::from(format!("/photos/shot{index}.jpg")))
+            .collect();
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner { files }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        let import = |resume, limit| {
+            service.import_folder(ImportFolderCommand {
+                folder: "/photos".to_string(),
+                cache_root: "cache".to_string(),
+                resume,
+                strict: false,
+                limit,
+                workers: 1,
+            })
+        };
+
+        let report = import(false, Some(2)).expect("limited import");
+        assert_eq!(
+            (
+                report.scanned_files,
+                report.supported_files,
+                report.newly_imported
+            ),
+            (2, 2, 2)
+        );
+        let listed = service
+            .list_images(ListImagesCommand::default())
+            .expect("list");
+        assert_eq!(listed.len(), 2);
+
+        let resumed = import(true, None).expect("resume after limit");
+        assert_eq!((resumed.scanned_files, resumed.newly_imported), (3, 3));
+        assert!(matches!(
+            import(true, None),
+            Err(ApplicationError::NotFound(_))
+        ));
+        assert!(matches!(
+            import(false, Some(0)),
+            Err(ApplicationError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn export_all_reports_progress_and_isolates_failures() {
+        let mut files: Vec<PathBuf> = (1..=6)
+            .map(|index| PathBuf::from(format!("/tmp/shot{index}.jpg")))
+            .collect();
+        files.push(PathBuf::from("/tmp/corrupt.jpg"));
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner { files }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
             })
+            .expect("import should work");
+
+        let mut progress = Vec::new();
+        let report = service
+            .export_all(
+                ExportAllCommand {
+                    output_dir: "out".to_string(),
+                    workers: 4,
+                },
+                |update| progress.push(update),
+            )
+            .expect("export should work");
+
+        assert_eq!(report.total, 7);
+        assert_eq!(report.exported.len(), 6);
+        for index in 1..=6 {
+            let expected = PathBuf::from(format!("out/{index}-shot{index}.jpg"));
+            assert!(report.exported.contains(&expected), "missing {expected:?}");
         }
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].image_id.get(), 7);
+
+        let completed: Vec<usize> = progress.iter().map(|update| update.completed).collect();
+        assert_eq!(completed, (1..=7).collect::<Vec<_>>());
+        assert!(progress.iter().all(|update| update.total == 7));
     }
 
-    struct FakeDecoder;
+    #[test]
+    fn open_missing_image_returns_not_found() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner { files: vec![] }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
 
-    impl ImageDecoder for FakeDecoder {
-        fn decode_for_preview(&self, path: &Path) -> Result<DecodedImage, ApplicationError> {
-            Ok(DecodedImage {
-                width: 64,
-                height: 48,
-                kind: detect_image_kind(path),
-            })
-        }
+        let result = service.open_image(OpenImageCommand {
+            image_id: ImageId::new(99).expect("id"),
+        });
+
+        assert!(matches!(result, Err(ApplicationError::NotFound(_))));
     }
 
-    struct FakeClock;
+    #[test]
+    fn delete_missing_image_returns_not_found() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner { files: vec![] }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
 
-    impl Clock for FakeClock {
-        fn now_timestamp_string(&self) -> String {
-            "123".to_string()
+        let result = service.delete_image(DeleteImageCommand {
+            image_id: ImageId::new(99).expect("id"),
+        });
+
+        assert!(matches!(result, Err(ApplicationError::NotFound(_))));
+    }
+
+    #[test]
+    fn list_rejects_malformed_and_inverted_date_ranges() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner { files: vec![] }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        let list = |from: &str, to: &str| {
+            service.list_images(ListImagesCommand {
+                filter: ImageFilter {
+                    from: Some(from.to_string()),
+                    to: Some(to.to_string()),
+                    ..ImageFilter::default()
+                },
+                ..ListImagesCommand::default()
+            })
+        };
+        assert!(list("2024-01-01", "2024-01-01").is_ok());
+        for (from, to) in [("2024-02-01", "2024-01-31"), ("2024-1-01", "2024-02-01")] {
+            assert!(matches!(
+                list(from, to),
+                Err(ApplicationError::InvalidInput(_))
+            ));
         }
+
+        let joined = service.list_images_with_thumbnails(ListImagesCommand {
+            page: ImagePage {
+                limit: Some(0),
+                offset: 0,
+            },
+            ..ListImagesCommand::default()
+        });
+        assert!(matches!(joined, Err(ApplicationError::InvalidInput(_))));
     }
 
     #[test]
-    fn import_and_open_image_workflow() {
+    fn prune_removes_only_images_whose_file_is_gone() {
         let service = ApplicationService::new(
             Box::new(FakeCatalog::new()),
             Box::new(FakeScanner {
-                files: vec![PathBuf::from("/tmp/sample.jpg")],
+                files: vec![
+                    PathBuf::from("/tmp/gone.jpg"),
+                    PathBuf::from("/tmp/kept.jpg"),
+                ],
             }),
             Box::new(FakeThumbs),
             Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
             Box::new(FakeClock),
             Box::<FakePreviewPipeline>::default(),
         );
-
         service
-            .bootstrap_catalog(BootstrapCatalogCommand)
-            .expect("bootstrap should work");
-
-        let report = service
             .import_folder(ImportFolderCommand {
                 folder: "/tmp".to_string(),
                 cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
             })
             .expect("import should work");
-        assert_eq!(report.scanned_files, 1);
-        assert_eq!(report.supported_files, 1);
-        assert_eq!(report.newly_imported, 1);
 
-        let images = service
-            .list_images(ListImagesCommand)
-            .expect("list should work");
-        assert_eq!(images.len(), 1);
+        let report = service.prune_missing(PruneMissingCommand).expect("prune");
 
-        let decoded = service
-            .open_image(OpenImageCommand {
-                image_id: images[0].id,
-            })
-            .expect("open should work");
-        assert_eq!(decoded.width, 64);
-        assert_eq!(decoded.kind, ImageKind::Jpeg);
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.pruned.len(), 1);
+        assert_eq!(report.pruned[0].1, "/tmp/gone.jpg");
+        let remaining: Vec<String> = service
+            .list_images(ListImagesCommand::default())
+            .expect("list")
+            .into_iter()
+            .map(|image| image.file_path)
+            .collect();
+        assert_eq!(remaining, vec!["/tmp/kept.jpg".to_string()]);
     }
 
     #[test]
-    fn open_missing_image_returns_not_found() {
+    fn relink_rejects_an_image_that_is_not_missing() {
         let service = ApplicationService::new(
             Box::new(FakeCatalog::new()),
-            Box::new(FakeScanner { files: vec![] }),
+            Box::new(FakeScanner {
+                files: vec![
+                    PathBuf::from("/tmp/gone.jpg"),
+                    PathBuf::from("/tmp/kept.jpg"),
+                ],
+            }),
             Box::new(FakeThumbs),
             Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
             Box::new(FakeClock),
             Box::<FakePreviewPipeline>::default(),
         );
+        service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import should work");
+        let images = service
+            .list_images(ListImagesCommand::default())
+            .expect("list");
+        let id_of = |path: &str| {
+            images
+                .iter()
+                .find(|image| image.file_path == path)
+                .expect("imported")
+                .id
+        };
 
-        let result = service.open_image(OpenImageCommand {
-            image_id: ImageId::new(99).expect("id"),
+        let present = service.relink(RelinkCommand {
+            image_id: id_of("/tmp/kept.jpg"),
+            new_path: "/tmp/moved.jpg".to_string(),
         });
+        assert!(
+            matches!(&present, Err(ApplicationError::InvalidInput(message)) if message.contains("not missing")),
+            "unexpected result: {present:?}"
+        );
 
-        assert!(matches!(result, Err(ApplicationError::NotFound(_))));
+        let relinked = service
+            .relink(RelinkCommand {
+                image_id: id_of("/tmp/gone.jpg"),
+                new_path: "/tmp/moved.jpg".to_string(),
+            })
+            .expect("relink missing image");
+        assert_eq!(relinked.file_path, "/tmp/moved.jpg");
     }
 
     #[test]
@@ -497,6 +2500,7 @@ mod tests {
             }),
             Box::new(FakeThumbs),
             Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
             Box::new(FakeClock),
             Box::<FakePreviewPipeline>::default(),
         );
@@ -505,12 +2509,16 @@ mod tests {
             .import_folder(ImportFolderCommand {
                 folder: "/tmp".to_string(),
                 cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
             })
             .expect("import should work");
         assert_eq!(report.newly_imported, 1);
 
         let image = service
-            .list_images(ListImagesCommand)
+            .list_images(ListImagesCommand::default())
             .expect("list should work")
             .into_iter()
             .next()
@@ -521,8 +2529,11 @@ mod tests {
             contrast: 0.1,
             temperature: -5.0,
             tint: 2.0,
-            highlights: -10.0,
-            shadows: 8.0,
+            highlights: -4.0,
+            shadows: 3.0,
+            saturation: 1.5,
+            vibrance: -0.75,
+            ..EditParams::default()
         };
 
         service
@@ -536,5 +2547,344 @@ mod tests {
             .show_edit(ShowEditCommand { image_id: image.id })
             .expect("show edit should work");
         assert_eq!(loaded, params);
+
+        let rejected = service.set_edit(SetEditCommand {
+            image_id: image.id,
+            params: params.with_exposure(1000.0),
+        });
+        assert!(
+            matches!(
+                &rejected,
+                Err(ApplicationError::Domain(DomainError::EditParamOutOfRange {
+                    field: "exposure",
+                    ..
+                }))
+            ),
+            "unexpected result: {rejected:?}"
+        );
+        assert_eq!(
+            service
+                .show_edit(ShowEditCommand { image_id: image.id })
+                .expect("show edit should work"),
+            params
+        );
+    }
+
+    #[test]
+    fn copied_edit_is_pasted_onto_existing_targets_and_missing_ones_are_skipped() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner {
+                files: vec![
+                    PathBuf::from("/tmp/a.jpg"),
+                    PathBuf::from("/tmp/b.jpg"),
+                    PathBuf::from("/tmp/c.jpg"),
+                ],
+            }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import should work");
+        let id = |value| ImageId::new(value).expect("id");
+        let params = EditParams::default().with_exposure(1.25).with_vibrance(0.5);
+        service
+            .set_edit(SetEditCommand {
+                image_id: id(1),
+                params,
+            })
+            .expect("set edit should work");
+
+        let edit_json = service
+            .copy_edit(CopyEditCommand { from: id(1) })
+            .expect("copy edit should work");
+        let report = service
+            .paste_edit(PasteEditCommand {
+                edit_json: edit_json.clone(),
+                to: vec![id(2), id(99), id(3)],
+            })
+            .expect("paste edit should work");
+
+        assert_eq!(report.applied, vec![id(2), id(3)]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].image_id, id(99));
+        for target in [2, 3] {
+            assert_eq!(
+                service
+                    .show_edit(ShowEditCommand {
+                        image_id: id(target)
+                    })
+                    .expect("show edit should work"),
+                params
+            );
+        }
+
+        let out_of_range = serde_json::to_string(&params.with_contrast(1000.0)).expect("json");
+        for edit_json in [out_of_range, "{not json".to_string()] {
+            assert!(service
+                .paste_edit(PasteEditCommand {
+                    edit_json,
+                    to: vec![id(2)],
+                })
+                .is_err());
+        }
+        assert_eq!(
+            service
+                .show_edit(ShowEditCommand { image_id: id(2) })
+                .expect("show edit should work"),
+            params
+        );
+        assert!(matches!(
+            service.copy_edit(CopyEditCommand { from: id(99) }),
+            Err(ApplicationError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn reset_edit_restores_default_blob() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner {
+                files: vec![PathBuf::from("/tmp/sample.jpg")],
+            }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import should work");
+        let image_id = ImageId::new(1).expect("id");
+        service
+            .set_edit(SetEditCommand {
+                image_id,
+                params: EditParams::default().with_exposure(1.5).with_shadows(-2.0),
+            })
+            .expect("set edit should work");
+
+        service
+            .reset_edit(ResetEditCommand { image_id })
+            .expect("reset should work");
+        let loaded = service
+            .show_edit(ShowEditCommand { image_id })
+            .expect("show edit should work");
+        assert_eq!(loaded, EditParams::default());
+
+        let missing = service.reset_edit(ResetEditCommand {
+            image_id: ImageId::new(42).expect("id"),
+        });
+        assert!(matches!(missing, Err(ApplicationError::NotFound(_))));
+        assert_eq!(
+            service
+                .reset_all_edits(ResetAllEditsCommand)
+                .expect("reset all"),
+            1
+        );
+    }
+
+    #[test]
+    fn merge_hdr_registers_merged_image() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner {
+                files: vec![
+                    PathBuf::from("/tmp/dark.jpg"),
+                    PathBuf::from("/tmp/bright.jpg"),
+                ],
+            }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import should work");
+        let mut image_ids: Vec<ImageId> = service
+            .list_images(ListImagesCommand::default())
+            .expect("list should work")
+            .into_iter()
+            .map(|image| image.id)
+            .collect();
+        image_ids.sort_by_key(|id| id.get());
+
+        let single = service.merge_hdr(MergeHdrCommand {
+            image_ids: vec![image_ids[0]],
+            cache_root: "cache".to_string(),
+        });
+        assert!(matches!(
+            single,
+            Err(ApplicationError::Domain(DomainError::BracketTooSmall(1)))
+        ));
+
+        let merged_id = service
+            .merge_hdr(MergeHdrCommand {
+                image_ids: image_ids.clone(),
+                cache_root: "cache".to_string(),
+            })
+            .expect("merge should work");
+        assert!(!image_ids.contains(&merged_id));
+
+        let merged = service
+            .list_images(ListImagesCommand::default())
+            .expect("list should work")
+            .into_iter()
+            .find(|image| image.id == merged_id)
+            .expect("merged image is cataloged");
+        assert_eq!(merged.file_path, "cache/merged/hdr-1-123.jpg");
+        assert!(merged.metadata_json.contains("merged_from"));
+        assert!(service
+            .show_edit(ShowEditCommand {
+                image_id: merged_id
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn auto_exposure_brightens_a_dark_image_and_stores_it_only_when_applied() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner {
+                files: vec![PathBuf::from("/tmp/dark.jpg")],
+            }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import should work");
+        let image_id = service
+            .list_images(ListImagesCommand::default())
+            .expect("list should work")[0]
+            .id;
+
+        let suggested = service
+            .suggest_auto_exposure(AutoExposureCommand {
+                image_id,
+                apply: false,
+            })
+            .expect("suggestion");
+        assert!(suggested > 0.0 && suggested <= 5.0, "suggested={suggested}");
+        let stored = service
+            .show_edit(ShowEditCommand { image_id })
+            .expect("edit");
+        assert_eq!(stored.exposure, 0.0);
+
+        let applied = service
+            .suggest_auto_exposure(AutoExposureCommand {
+                image_id,
+                apply: true,
+            })
+            .expect("applied");
+        assert_eq!(applied, suggested);
+        let stored = service
+            .show_edit(ShowEditCommand { image_id })
+            .expect("edit");
+        assert_eq!(stored.exposure, suggested);
+    }
+
+    #[test]
+    fn match_exposure_brightens_darker_target_toward_reference() {
+        let service = ApplicationService::new(
+            Box::new(FakeCatalog::new()),
+            Box::new(FakeScanner {
+                files: vec![
+                    PathBuf::from("/tmp/bright.jpg"),
+                    PathBuf::from("/tmp/dark.jpg"),
+                ],
+            }),
+            Box::new(FakeThumbs),
+            Box::new(FakeDecoder),
+            Box::new(FakeEncoder),
+            Box::new(FakeClock),
+            Box::<FakePreviewPipeline>::default(),
+        );
+        service
+            .import_folder(ImportFolderCommand {
+                folder: "/tmp".to_string(),
+                cache_root: "cache".to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import should work");
+        let images = service
+            .list_images(ListImagesCommand::default())
+            .expect("list should work");
+        let id_of = |name: &str| {
+            images
+                .iter()
+                .find(|image| image.file_path.ends_with(name))
+                .expect("image imported")
+                .id
+        };
+        let (reference_id, target_id) = (id_of("bright.jpg"), id_of("dark.jpg"));
+
+        let matched = service
+            .match_exposure(MatchExposureCommand {
+                reference_id,
+                target_ids: vec![target_id],
+            })
+            .expect("match should work");
+
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].0, target_id);
+        let stored = service
+            .show_edit(ShowEditCommand {
+                image_id: target_id,
+            })
+            .expect("target edit");
+        assert!(stored.exposure > 0.0);
+        assert_eq!(stored.exposure, matched[0].1);
+
+        let source = FakeDecoder
+            .decode_full(Path::new("/tmp/dark.jpg"))
+            .expect("decode");
+        let reference = FakeDecoder
+            .decode_full(Path::new("/tmp/bright.jpg"))
+            .expect("decode");
+        let before = mean_linear_luminance(&source);
+        let after = mean_linear_luminance(&apply_edits(&source, &stored));
+        let goal = mean_linear_luminance(&reference);
+        assert!((after - goal).abs() < (before - goal).abs() * 0.05);
     }
 }
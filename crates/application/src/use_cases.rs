@@ -1,17 +1,48 @@
-use lite_room_domain::EditParams;
-use lite_room_domain::ImageId;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use lite_room_domain::{ComparisonMode, DecodedBuffer, EditParams, RawPixelFormat};
+use lite_room_domain::{
+    ImageFilter, ImageId, ImagePage, ImageSort, SortMove, SourceRegion, ViewRegion,
+};
 
 #[derive(Debug, Clone, Default)]
 pub struct BootstrapCatalogCommand;
 
+#[derive(Debug, Clone, Default)]
+pub struct OptimizeCatalogCommand;
+
+#[derive(Debug, Clone, Default)]
+pub struct InspectSchemaCommand;
+
 #[derive(Debug, Clone)]
 pub struct ImportFolderCommand {
     pub folder: String,
     pub cache_root: String,
+    pub resume: bool,
+    pub strict: bool,
+    pub limit: Option<usize>,
+    pub workers: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportFilesCommand {
+    pub paths: Vec<PathBuf>,
+    pub cache_root: String,
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct ListImagesCommand;
+pub struct ListImagesCommand {
+    pub sort: ImageSort,
+    pub filter: ImageFilter,
+    pub page: ImagePage,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MoveImageCommand {
+    pub image_id: ImageId,
+    pub target: SortMove,
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct OpenImageCommand {
@@ -23,6 +54,11 @@ pub struct ShowEditCommand {
     pub image_id: ImageId,
 }
 
+#[derive(Debug, Clone)]
+pub struct ShowEditsCommand {
+    pub image_ids: Vec<ImageId>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SetEditCommand {
     pub image_id: ImageId,
@@ -30,11 +66,151 @@ pub struct SetEditCommand {
 }
 
 #[derive(Debug, Clone, Copy)]
+pub struct CopyEditCommand {
+    pub from: ImageId,
+}
+
+#[derive(Debug, Clone)]
+pub struct PasteEditCommand {
+    pub edit_json: String,
+    pub to: Vec<ImageId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SavePresetCommand {
+    pub name: String,
+    pub image_id: ImageId,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApplyPresetCommand {
+    pub name: String,
+    pub image_ids: Vec<ImageId>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListPresetsCommand;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ResetEditCommand {
+    pub image_id: ImageId,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ResetAllEditsCommand;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SetRatingCommand {
+    pub image_id: ImageId,
+    pub rating: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SetFlagCommand {
+    pub image_id: ImageId,
+    pub flag: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SetCaptionCommand {
+    pub image_id: ImageId,
+    pub caption: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShowCaptionCommand {
+    pub image_id: ImageId,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListImportFailuresCommand;
+
+#[derive(Debug, Clone)]
+pub struct RetryImportFailuresCommand {
+    pub cache_root: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BackfillMetadataCommand;
+
+#[derive(Debug, Clone, Default)]
+pub struct PruneMissingCommand;
+
+#[derive(Debug, Clone)]
+pub struct RelinkCommand {
+    pub image_id: ImageId,
+    pub new_path: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DeleteImageCommand {
+    pub image_id: ImageId,
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyThumbnailsCommand {
+    pub cache_root: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StackImagesCommand {
+    pub image_ids: Vec<ImageId>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SetStackPickCommand {
+    pub image_id: ImageId,
+}
+
+#[derive(Debug, Clone)]
 pub struct SubmitPreviewCommand {
     pub image_id: ImageId,
     pub params: EditParams,
     pub target_width: u32,
     pub target_height: u32,
+    pub source_pixels: Option<Arc<DecodedBuffer>>,
+    pub source_region: Option<SourceRegion>,
+    pub view_region: Option<ViewRegion>,
+    pub want_histogram: bool,
+    pub comparison: Option<ComparisonMode>,
+    pub split_fraction: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AutoExposureCommand {
+    pub image_id: ImageId,
+    pub apply: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchExposureCommand {
+    pub reference_id: ImageId,
+    pub target_ids: Vec<ImageId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeHdrCommand {
+    pub image_ids: Vec<ImageId>,
+    pub cache_root: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportImageCommand {
+    pub image_id: ImageId,
+    pub output_path: String,
+    pub quality: Option<u8>,
+    pub raw_format: Option<RawPixelFormat>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WriteSidecarCommand {
+    pub image_id: ImageId,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportAllCommand {
+    pub output_dir: String,
+    pub workers: usize,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -42,3 +218,6 @@ pub struct PollPreviewCommand;
 
 #[derive(Debug, Clone, Default)]
 pub struct PreviewMetricsQuery;
+
+#[derive(Debug, Clone, Default)]
+pub struct PreviewBackendQuery;
@@ -0,0 +1,86 @@
+use crate::DecodedBuffer;
+
+const MAX_MATCHED_EXPOSURE: f32 = 5.0;
+// Encoded mid grey, the brightness an auto exposure aims the mean at.
+const AUTO_EXPOSURE_TARGET: f32 = 0.5;
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn luma(red: f32, green: f32, blue: f32) -> f32 {
+    0.2126 * red + 0.7152 * green + 0.0722 * blue
+}
+
+pub fn mean_linear_luminance(image: &DecodedBuffer) -> f32 {
+    let pixels = image.rgb.len() / 3;
+    if pixels == 0 {
+        return 0.0;
+    }
+    let total: f32 = image
+        .rgb
+        .chunks_exact(3)
+        .map(|pixel| {
+            let [red, green, blue] = [pixel[0], pixel[1], pixel[2]]
+                .map(|channel| srgb_to_linear(channel as f32 / 255.0));
+            luma(red, green, blue)
+        })
+        .sum();
+    total / pixels as f32
+}
+
+pub fn matching_exposure(reference_luminance: f32, target_luminance: f32) -> Option<f32> {
+    // Exposure gain multiplies encoded values, so compare the linear means re-encoded.
+    let reference = linear_to_srgb(reference_luminance);
+    let target = linear_to_srgb(target_luminance);
+    if target <= f32::EPSILON || reference <= f32::EPSILON {
+        return None;
+    }
+    Some(
+        (reference / target)
+            .log2()
+            .clamp(-MAX_MATCHED_EXPOSURE, MAX_MATCHED_EXPOSURE),
+    )
+}
+
+pub fn auto_exposure(mean_linear_luminance: f32) -> Option<f32> {
+    matching_exposure(srgb_to_linear(AUTO_EXPOSURE_TARGET), mean_linear_luminance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_linear_roundtrip() {
+        for step in 0..=10 {
+            let value = step as f32 / 10.0;
+            let back = linear_to_srgb(srgb_to_linear(value));
+            assert!((value - back).abs() < 0.0001, "value={value} back={back}");
+        }
+    }
+
+    #[test]
+    fn auto_exposure_pulls_the_mean_toward_mid_grey() {
+        let mid = srgb_to_linear(0.5);
+        assert!(auto_exposure(mid).expect("mid grey").abs() < 0.0001);
+        assert!((auto_exposure(srgb_to_linear(0.25)).expect("dark") - 1.0).abs() < 0.0001);
+        assert!(auto_exposure(srgb_to_linear(1.0)).expect("bright") < 0.0);
+        assert_eq!(auto_exposure(srgb_to_linear(0.001)), Some(5.0));
+        assert_eq!(auto_exposure(0.0), None);
+    }
+}
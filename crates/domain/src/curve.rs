@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+
+use crate::DomainError;
+
+pub const MAX_CURVE_POINTS: usize = 16;
+pub const CURVE_LUT_SIZE: usize = 256;
+
+pub type CurveLut = [u8; CURVE_LUT_SIZE];
+
+// Control points (input, output) in 0..1, stored inline so EditParams stays
+// Copy. The curve is anchored at (0, 0) and (1, 1) unless a point sits on
+// either end; an empty curve is the identity.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(try_from = "Vec<(f32, f32)>", into = "Vec<(f32, f32)>")]
+pub struct ToneCurve {
+    points: [(f32, f32); MAX_CURVE_POINTS],
+    len: u8,
+}
+
+impl ToneCurve {
+    pub fn new(points: &[(f32, f32)]) -> Result<Self, DomainError> {
+        if points.len() > MAX_CURVE_POINTS {
+            return Err(DomainError::InvalidToneCurve {
+                curve: "curve",
+                reason: "too many control points",
+            });
+        }
+        let mut curve = Self::default();
+        curve.points[..points.len()].copy_from_slice(points);
+        curve.len = points.len() as u8;
+        Ok(curve)
+    }
+
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points[..self.len as usize]
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn validate(&self, curve: &'static str) -> Result<(), DomainError> {
+        let in_range = |value: f32| (0.0..=1.0).contains(&value);
+        if !self
+            .points()
+            .iter()
+            .all(|&(x, y)| in_range(x) && in_range(y))
+        {
+            return Err(DomainError::InvalidToneCurve {
+                curve,
+                reason: "control points must be within [0, 1]",
+            });
+        }
+        if self.points().windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err(DomainError::InvalidToneCurve {
+                curve,
+                reason: "control point inputs must be strictly increasing",
+            });
+        }
+        Ok(())
+    }
+
+    // Piecewise-linear through the anchored points, sampled at every 8-bit level.
+    pub fn lut(&self) -> CurveLut {
+        let mut knots = Vec::with_capacity(self.len as usize + 2);
+        if self.points().first().is_none_or(|&(x, _)| x > 0.0) {
+            knots.push((0.0, 0.0));
+        }
+        knots.extend_from_slice(self.points());
+        if self.points().last().is_none_or(|&(x, _)| x < 1.0) {
+            knots.push((1.0, 1.0));
+        }
+
+        let mut lut = [0_u8; CURVE_LUT_SIZE];
+        let mut segment = 0;
+        for (level, entry) in lut.iter_mut().enumerate() {
+            let x = level as f32 / (CURVE_LUT_SIZE - 1) as f32;
+            while segment + 2 < knots.len() && x > knots[segment + 1].0 {
+                segment += 1;
+            }
+            let ((x0, y0), (x1, y1)) = (knots[segment], knots[segment + 1]);
+            let t = ((x - x0) / (x1 - x0)).clamp(0.0, 1.0);
+            *entry = ((y0 + (y1 - y0) * t).clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+        lut
+    }
+}
+
+impl TryFrom<Vec<(f32, f32)>> for ToneCurve {
+    type Error = DomainError;
+
+    fn try_from(points: Vec<(f32, f32)>) -> Result<Self, Self::Error> {
+        Self::new(&points)
+    }
+}
+
+impl From<ToneCurve> for Vec<(f32, f32)> {
+    fn from(curve: ToneCurve) -> Self {
+        curve.points().to_vec()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ChannelCurves {
+    #[serde(default)]
+    pub red: ToneCurve,
+    #[serde(default)]
+    pub green: ToneCurve,
+    #[serde(default)]
+    pub blue: ToneCurve,
+}
+
+impl ChannelCurves {
+    pub fn is_identity(&self) -> bool {
+        self.red.is_identity() && self.green.is_identity() && self.blue.is_identity()
+    }
+
+    pub fn validate(&self) -> Result<(), DomainError> {
+        self.red.validate("red")?;
+        self.green.validate("green")?;
+        self.blue.validate("blue")
+    }
+
+    pub fn luts(&self) -> [CurveLut; 3] {
+        [self.red.lut(), self.green.lut(), self.blue.lut()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_curve_is_the_identity_lut() {
+        let lut = ToneCurve::default().lut();
+        assert!(lut
+            .iter()
+            .enumerate()
+            .all(|(level, &value)| value as usize == level));
+    }
+
+    #[test]
+    fn single_point_curve_interpolates_through_the_anchors() {
+        let lut = ToneCurve::new(&[(0.5, 0.75)]).expect("curve").lut();
+        assert_eq!((lut[0], lut[255]), (0, 255));
+        assert_eq!(lut[128], 192);
+        assert!(lut.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn each_channel_is_validated_on_its_own() {
+        let valid = ToneCurve::new(&[(0.25, 0.2), (0.75, 0.9)]).expect("curve");
+        let unsorted = ToneCurve::new(&[(0.75, 0.9), (0.25, 0.2)]).expect("curve");
+        let out_of_range = ToneCurve::new(&[(0.5, 1.5)]).expect("curve");
+
+        let curves = ChannelCurves {
+            red: valid,
+            green: unsorted,
+            blue: out_of_range,
+        };
+        assert!(matches!(
+            curves.validate(),
+            Err(DomainError::InvalidToneCurve { curve: "green", .. })
+        ));
+        assert!(matches!(
+            ChannelCurves {
+                green: valid,
+                ..curves
+            }
+            .validate(),
+            Err(DomainError::InvalidToneCurve { curve: "blue", .. })
+        ));
+        assert!(ToneCurve::new(&[(0.5, f32::NAN)])
+            .expect("curve")
+            .validate("red")
+            .is_err());
+        assert!(ToneCurve::new(&[(0.0, 0.0); MAX_CURVE_POINTS + 1]).is_err());
+    }
+
+    #[test]
+    fn curves_serialize_as_point_lists() {
+        let curves = ChannelCurves {
+            red: ToneCurve::new(&[(0.5, 0.75)]).expect("curve"),
+            ..ChannelCurves::default()
+        };
+        let json = serde_json::to_string(&curves).expect("json");
+        assert_eq!(json, r#"{"red":[[0.5,0.75]],"green":[],"blue":[]}"#);
+        assert_eq!(
+            serde_json::from_str::<ChannelCurves>(r#"{"red":[[0.5,0.75]]}"#).expect("parse"),
+            curves
+        );
+    }
+}
@@ -1,6 +1,30 @@
 use serde::{Deserialize, Serialize};
 
-use crate::DomainError;
+use crate::{ChannelCurves, CropRect, DomainError, ToneCurve};
+
+const NEUTRAL_KELVIN: f32 = 5_500.0;
+const KELVIN_PER_TEMPERATURE_UNIT: f32 = 800.0;
+const TEMPERATURE_RANGE: f32 = EDIT_PARAM_LIMIT;
+
+pub const EDIT_PARAM_LIMIT: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditField {
+    Exposure,
+    Contrast,
+    Temperature,
+    Tint,
+    Highlights,
+    Shadows,
+    Saturation,
+    Vibrance,
+}
+
+impl EditField {
+    fn mask_bit(self) -> u8 {
+        1 << (self as u8)
+    }
+}
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct EditParams {
@@ -10,6 +34,22 @@ pub struct EditParams {
     pub tint: f32,
     pub highlights: f32,
     pub shadows: f32,
+    #[serde(default)]
+    pub saturation: f32,
+    #[serde(default)]
+    pub vibrance: f32,
+    #[serde(default)]
+    pub grain: f32,
+    #[serde(default)]
+    pub disabled_fields: u8,
+    #[serde(default, skip_serializing_if = "ToneCurve::is_identity")]
+    pub tone_curve: ToneCurve,
+    #[serde(default, skip_serializing_if = "ChannelCurves::is_identity")]
+    pub channel_curves: ChannelCurves,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crop: Option<CropRect>,
+    #[serde(default)]
+    pub rotation_degrees: f32,
 }
 
 impl Default for EditParams {
@@ -21,6 +61,14 @@ impl Default for EditParams {
             tint: 0.0,
             highlights: 0.0,
             shadows: 0.0,
+            saturation: 0.0,
+            vibrance: 0.0,
+            grain: 0.0,
+            disabled_fields: 0,
+            tone_curve: ToneCurve::default(),
+            channel_curves: ChannelCurves::default(),
+            crop: None,
+            rotation_degrees: 0.0,
         }
     }
 }
@@ -45,8 +93,162 @@ impl EditParams {
         if !self.shadows.is_finite() {
             return Err(DomainError::NonFiniteEditParam("shadows"));
         }
+        if !self.saturation.is_finite() {
+            return Err(DomainError::NonFiniteEditParam("saturation"));
+        }
+        if !self.vibrance.is_finite() {
+            return Err(DomainError::NonFiniteEditParam("vibrance"));
+        }
+        if !self.grain.is_finite() {
+            return Err(DomainError::NonFiniteEditParam("grain"));
+        }
+        if !self.rotation_degrees.is_finite() {
+            return Err(DomainError::NonFiniteEditParam("rotation_degrees"));
+        }
+        if let Some(crop) = &self.crop {
+            crop.validate()?;
+        }
+        self.tone_curve.validate("tone")?;
+        self.channel_curves.validate()
+    }
+
+    pub fn validate_ranges(&self) -> Result<(), DomainError> {
+        self.validate()?;
+        for (field, value) in self.named_values() {
+            if !(-EDIT_PARAM_LIMIT..=EDIT_PARAM_LIMIT).contains(&value) {
+                return Err(DomainError::EditParamOutOfRange { field, value });
+            }
+        }
+        if !(0.0..=EDIT_PARAM_LIMIT).contains(&self.grain) {
+            return Err(DomainError::InvalidGrain(self.grain));
+        }
         Ok(())
     }
+
+    pub fn clamp_to_range(&self) -> EditParams {
+        let clamp = |value: f32| value.clamp(-EDIT_PARAM_LIMIT, EDIT_PARAM_LIMIT);
+        EditParams {
+            exposure: clamp(self.exposure),
+            contrast: clamp(self.contrast),
+            temperature: clamp(self.temperature),
+            tint: clamp(self.tint),
+            highlights: clamp(self.highlights),
+            shadows: clamp(self.shadows),
+            saturation: clamp(self.saturation),
+            vibrance: clamp(self.vibrance),
+            grain: self.grain.clamp(0.0, EDIT_PARAM_LIMIT),
+            disabled_fields: self.disabled_fields,
+            tone_curve: self.tone_curve,
+            channel_curves: self.channel_curves,
+            crop: self.crop,
+            rotation_degrees: self.rotation_degrees,
+        }
+    }
+
+    fn named_values(&self) -> [(&'static str, f32); 8] {
+        [
+            ("exposure", self.exposure),
+            ("contrast", self.contrast),
+            ("temperature", self.temperature),
+            ("tint", self.tint),
+            ("highlights", self.highlights),
+            ("shadows", self.shadows),
+            ("saturation", self.saturation),
+            ("vibrance", self.vibrance),
+        ]
+    }
+
+    pub fn with_exposure(self, exposure: f32) -> Self {
+        Self { exposure, ..self }
+    }
+
+    pub fn with_contrast(self, contrast: f32) -> Self {
+        Self { contrast, ..self }
+    }
+
+    pub fn with_temperature(self, temperature: f32) -> Self {
+        Self {
+            temperature,
+            ..self
+        }
+    }
+
+    pub fn with_tint(self, tint: f32) -> Self {
+        Self { tint, ..self }
+    }
+
+    pub fn with_highlights(self, highlights: f32) -> Self {
+        Self { highlights, ..self }
+    }
+
+    pub fn with_shadows(self, shadows: f32) -> Self {
+        Self { shadows, ..self }
+    }
+
+    pub fn with_saturation(self, saturation: f32) -> Self {
+        Self { saturation, ..self }
+    }
+
+    pub fn with_vibrance(self, vibrance: f32) -> Self {
+        Self { vibrance, ..self }
+    }
+
+    pub fn with_grain(self, grain: f32) -> Self {
+        Self { grain, ..self }
+    }
+
+    pub fn is_enabled(&self, field: EditField) -> bool {
+        self.disabled_fields & field.mask_bit() == 0
+    }
+
+    pub fn set_enabled(&mut self, field: EditField, enabled: bool) {
+        if enabled {
+            self.disabled_fields &= !field.mask_bit();
+        } else {
+            self.disabled_fields |= field.mask_bit();
+        }
+    }
+
+    pub fn effective(&self) -> EditParams {
+        let defaults = EditParams::default();
+        let pick = |field: EditField, value: f32, default: f32| {
+            if self.is_enabled(field) {
+                value
+            } else {
+                default
+            }
+        };
+        EditParams {
+            exposure: pick(EditField::Exposure, self.exposure, defaults.exposure),
+            contrast: pick(EditField::Contrast, self.contrast, defaults.contrast),
+            temperature: pick(
+                EditField::Temperature,
+                self.temperature,
+                defaults.temperature,
+            ),
+            tint: pick(EditField::Tint, self.tint, defaults.tint),
+            highlights: pick(EditField::Highlights, self.highlights, defaults.highlights),
+            shadows: pick(EditField::Shadows, self.shadows, defaults.shadows),
+            saturation: pick(EditField::Saturation, self.saturation, defaults.saturation),
+            vibrance: pick(EditField::Vibrance, self.vibrance, defaults.vibrance),
+            grain: self.grain,
+            disabled_fields: 0,
+            tone_curve: self.tone_curve,
+            channel_curves: self.channel_curves,
+            crop: self.crop,
+            rotation_degrees: self.rotation_degrees,
+        }
+    }
+}
+
+pub fn temperature_to_kelvin(temperature: f32) -> f32 {
+    NEUTRAL_KELVIN
+        + temperature.clamp(-TEMPERATURE_RANGE, TEMPERATURE_RANGE) * KELVIN_PER_TEMPERATURE_UNIT
+}
+
+pub fn kelvin_to_temperature(kelvin: f32) -> f32 {
+    ((kelvin - NEUTRAL_KELVIN) / KELVIN_PER_TEMPERATURE_UNIT)
+        .clamp(-TEMPERATURE_RANGE, TEMPERATURE_RANGE)
 }
 
 #[cfg(test)]
@@ -62,6 +264,100 @@ mod tests {
         assert_eq!(params.tint, 0.0);
         assert_eq!(params.highlights, 0.0);
         assert_eq!(params.shadows, 0.0);
+        assert_eq!(params.saturation, 0.0);
+        assert_eq!(params.vibrance, 0.0);
+        assert_eq!(params.grain, 0.0);
+    }
+
+    #[test]
+    fn range_checks_name_the_field_and_clamp_bounds_every_value() {
+        let params = EditParams::default()
+            .with_exposure(1000.0)
+            .with_tint(-7.5)
+            .with_vibrance(2.0);
+
+        assert_eq!(
+            params.validate_ranges(),
+            Err(DomainError::EditParamOutOfRange {
+                field: "exposure",
+                value: 1000.0
+            })
+        );
+        let clamped = params.clamp_to_range();
+        assert_eq!(
+            (clamped.exposure, clamped.tint, clamped.vibrance),
+            (5.0, -5.0, 2.0)
+        );
+        assert_eq!(clamped.validate_ranges(), Ok(()));
+        assert!(matches!(
+            params.with_shadows(f32::NAN).validate_ranges(),
+            Err(DomainError::NonFiniteEditParam("shadows"))
+        ));
+
+        let grainy = EditParams::default().with_grain(-0.5);
+        assert_eq!(
+            grainy.validate_ranges(),
+            Err(DomainError::InvalidGrain(-0.5))
+        );
+        assert_eq!(grainy.clamp_to_range().grain, 0.0);
+        assert_eq!(grainy.with_grain(9.0).clamp_to_range().grain, 5.0);
+    }
+
+    #[test]
+    fn six_field_edit_json_deserializes_with_zero_color_intensity() {
+        let params: EditParams = serde_json::from_str(
+            r#"{"exposure":1.0,"contrast":0.0,"temperature":0.0,"tint":0.0,"highlights":0.0,"shadows":-0.5}"#,
+        )
+        .expect("legacy edit json");
+        assert_eq!(params.exposure, 1.0);
+        assert_eq!(params.saturation, 0.0);
+        assert_eq!(params.vibrance, 0.0);
+        assert_eq!(params.grain, 0.0);
+        assert!(matches!(
+            params.with_vibrance(f32::INFINITY).validate(),
+            Err(DomainError::NonFiniteEditParam("vibrance"))
+        ));
+    }
+
+    #[test]
+    fn chained_setters_match_manual_construction() {
+        let built = EditParams::default()
+            .with_exposure(1.0)
+            .with_shadows(-0.5)
+            .with_exposure(1.5);
+        let manual = EditParams {
+            exposure: 1.5,
+            shadows: -0.5,
+            ..EditParams::default()
+        };
+        assert_eq!(built, manual);
+    }
+
+    #[test]
+    fn disabled_fields_render_as_default_but_keep_value() {
+        let mut params = EditParams {
+            contrast: 2.5,
+            exposure: 1.0,
+            ..EditParams::default()
+        };
+        params.set_enabled(EditField::Contrast, false);
+
+        assert!(!params.is_enabled(EditField::Contrast));
+        assert_eq!(params.contrast, 2.5);
+        let effective = params.effective();
+        assert_eq!(effective.contrast, 0.0);
+        assert_eq!(effective.exposure, 1.0);
+    }
+
+    #[test]
+    fn kelvin_mapping_roundtrips_and_clamps() {
+        assert_eq!(temperature_to_kelvin(0.0), 5_500.0);
+        assert_eq!(kelvin_to_temperature(6_500.0), 1.25);
+        assert_eq!(
+            temperature_to_kelvin(kelvin_to_temperature(3_100.0)),
+            3_100.0
+        );
+        assert_eq!(kelvin_to_temperature(50_000.0), 5.0);
     }
 
     #[test]
@@ -1,9 +1,25 @@
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DomainError {
     InvalidImageId(i64),
     NonFiniteEditParam(&'static str),
+    EditParamOutOfRange {
+        field: &'static str,
+        value: f32,
+    },
+    BracketTooSmall(usize),
+    BracketDimensionMismatch,
+    InvalidSourceRegion,
+    InvalidViewRegion,
+    InvalidCrop,
+    InvalidSplitFraction(f32),
+    InvalidGrain(f32),
+    InvalidChannelOrder(String),
+    InvalidToneCurve {
+        curve: &'static str,
+        reason: &'static str,
+    },
 }
 
 impl Display for DomainError {
@@ -11,6 +27,56 @@ impl Display for DomainError {
         match self {
             Self::InvalidImageId(value) => write!(f, "image id must be positive, got {value}"),
             Self::NonFiniteEditParam(name) => write!(f, "edit parameter {name} must be finite"),
+            Self::EditParamOutOfRange { field, value } => write!(
+                f,
+                "edit parameter {field} must be within [{}, {}], got {value}",
+                -crate::EDIT_PARAM_LIMIT,
+                crate::EDIT_PARAM_LIMIT
+            ),
+            Self::BracketTooSmall(count) => {
+                write!(f, "exposure bracket needs at least 2 images, got {count}")
+            }
+            Self::BracketDimensionMismatch => {
+                write!(f, "exposure bracket images must share identical dimensions")
+            }
+            Self::InvalidSourceRegion => {
+                write!(
+                    f,
+                    "source region must be a non-empty rectangle inside the image"
+                )
+            }
+            Self::InvalidViewRegion => {
+                write!(
+                    f,
+                    "view region needs a center inside the image and a zoom within [1, {}]",
+                    crate::MAX_VIEW_ZOOM
+                )
+            }
+            Self::InvalidCrop => {
+                write!(f, "crop must be a non-empty rectangle inside the image")
+            }
+            Self::InvalidSplitFraction(value) => {
+                write!(
+                    f,
+                    "comparison split fraction must be within [0, 1], got {value}"
+                )
+            }
+            Self::InvalidGrain(value) => {
+                write!(
+                    f,
+                    "grain must be within [0, {}], got {value}",
+                    crate::EDIT_PARAM_LIMIT
+                )
+            }
+            Self::InvalidChannelOrder(value) => {
+                write!(
+                    f,
+                    "raw channel order must be rgb, bgr, rgba or bgra, got {value}"
+                )
+            }
+            Self::InvalidToneCurve { curve, reason } => {
+                write!(f, "{curve} tone curve is invalid: {reason}")
+            }
         }
     }
 }
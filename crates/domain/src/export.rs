@@ -0,0 +1,89 @@
+use crate::{DecodedBuffer, DomainError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelOrder {
+    Rgb,
+    Bgr,
+}
+
+// Headerless 8-bit dump: rows top to bottom, pixels left to right, channels
+// in `channel_order` followed by an opaque alpha byte when `alpha` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawPixelFormat {
+    pub channel_order: ChannelOrder,
+    pub alpha: bool,
+}
+
+impl RawPixelFormat {
+    pub fn parse(name: &str) -> Result<Self, DomainError> {
+        let (channel_order, alpha) = match name.trim().to_ascii_lowercase().as_str() {
+            "rgb" => (ChannelOrder::Rgb, false),
+            "bgr" => (ChannelOrder::Bgr, false),
+            "rgba" => (ChannelOrder::Rgb, true),
+            "bgra" => (ChannelOrder::Bgr, true),
+            _ => return Err(DomainError::InvalidChannelOrder(name.to_string())),
+        };
+        Ok(Self {
+            channel_order,
+            alpha,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match (self.channel_order, self.alpha) {
+            (ChannelOrder::Rgb, false) => "rgb8",
+            (ChannelOrder::Bgr, false) => "bgr8",
+            (ChannelOrder::Rgb, true) => "rgba8",
+            (ChannelOrder::Bgr, true) => "bgra8",
+        }
+    }
+
+    pub fn channels(self) -> usize {
+        if self.alpha {
+            4
+        } else {
+            3
+        }
+    }
+
+    pub fn pack(self, image: &DecodedBuffer) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(image.rgb.len() / 3 * self.channels());
+        for pixel in image.rgb.chunks_exact(3) {
+            match self.channel_order {
+                ChannelOrder::Rgb => bytes.extend_from_slice(pixel),
+                ChannelOrder::Bgr => bytes.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]),
+            }
+            if self.alpha {
+                bytes.push(u8::MAX);
+            }
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_orders_channels_and_appends_opaque_alpha() {
+        let image = DecodedBuffer {
+            width: 2,
+            height: 1,
+            rgb: vec![10, 20, 30, 40, 50, 60],
+        };
+        let pack = |name| RawPixelFormat::parse(name).expect("format").pack(&image);
+
+        assert_eq!(pack("rgb"), vec![10, 20, 30, 40, 50, 60]);
+        assert_eq!(pack("BGR"), vec![30, 20, 10, 60, 50, 40]);
+        assert_eq!(pack("bgra"), vec![30, 20, 10, 255, 60, 50, 40, 255]);
+        assert_eq!(
+            RawPixelFormat::parse("rgba").expect("format").name(),
+            "rgba8"
+        );
+        assert_eq!(
+            RawPixelFormat::parse("grb"),
+            Err(DomainError::InvalidChannelOrder("grb".to_string()))
+        );
+    }
+}
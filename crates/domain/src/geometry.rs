@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{DecodedBuffer, DomainError, EditParams};
+
+// Fractions of the rotated frame, so a crop keeps its framing when the source
+// is decoded at a different resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CropRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl CropRect {
+    pub const FULL: CropRect = CropRect {
+        x: 0.0,
+        y: 0.0,
+        width: 1.0,
+        height: 1.0,
+    };
+
+    pub fn validate(&self) -> Result<(), DomainError> {
+        let values = [self.x, self.y, self.width, self.height];
+        if values.iter().any(|value| !value.is_finite())
+            || self.x < 0.0
+            || self.y < 0.0
+            || self.width <= 0.0
+            || self.height <= 0.0
+            || self.x + self.width > 1.0 + f32::EPSILON
+            || self.y + self.height > 1.0 + f32::EPSILON
+        {
+            return Err(DomainError::InvalidCrop);
+        }
+        Ok(())
+    }
+}
+
+// Rotation then crop, mapped backwards from output pixels to source pixels.
+// Whole quarter turns are exact and swap the frame's axes. What is left over
+// (within ±45°) straightens about the center, zoomed just enough that no
+// corner of the frame falls outside the source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Framing {
+    quarter_turns: u8,
+    straighten_sin: f32,
+    straighten_cos: f32,
+    crop: CropRect,
+}
+
+impl Framing {
+    // `None` when the edit leaves the frame untouched.
+    pub fn new(params: &EditParams) -> Option<Self> {
+        let crop = params.crop.unwrap_or(CropRect::FULL);
+        let degrees = match params.rotation_degrees {
+            degrees if degrees.is_finite() => degrees.rem_euclid(360.0),
+            _ => 0.0,
+        };
+        if crop == CropRect::FULL && degrees == 0.0 {
+            return None;
+        }
+        let quarter_turns = (degrees / 90.0).round();
+        let straighten = (degrees - quarter_turns * 90.0).to_radians();
+        Some(Self {
+            quarter_turns: (quarter_turns as u8) % 4,
+            straighten_sin: straighten.sin(),
+            straighten_cos: straighten.cos(),
+            crop,
+        })
+    }
+
+    pub fn swaps_axes(&self) -> bool {
+        self.quarter_turns % 2 == 1
+    }
+
+    pub fn crop(&self) -> CropRect {
+        self.crop
+    }
+
+    // Size of the framed image for a `width` x `height` unrotated frame.
+    pub fn output_size(&self, width: usize, height: usize) -> (usize, usize) {
+        let (width, height) = if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let scaled =
+            |size: usize, fraction: f32| ((size as f32 * fraction).round() as usize).max(1);
+        (
+            scaled(width, self.crop.width),
+            scaled(height, self.crop.height),
+        )
+    }
+
+    // Source pixel for the point (u, v) of the framed image, both in 0..1.
+    pub fn source_pixel(
+        &self,
+        u: f32,
+        v: f32,
+        source_width: usize,
+        source_height: usize,
+    ) -> (usize, usize) {
+        let (width, height) = (source_width as f32, source_height as f32);
+        let (frame_width, frame_height) = if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let x = (self.crop.x + u * self.crop.width) * frame_width - frame_width / 2.0;
+        let y = (self.crop.y + v * self.crop.height) * frame_height - frame_height / 2.0;
+
+        let (sin, cos) = (self.straighten_sin, self.straighten_cos);
+        let aspect = (frame_width / frame_height).max(frame_height / frame_width);
+        let zoom = cos + aspect * sin.abs();
+        let (x, y) = (
+            (cos * x + sin * y) / zoom + frame_width / 2.0,
+            (cos * y - sin * x) / zoom + frame_height / 2.0,
+        );
+
+        let (x, y) = match self.quarter_turns {
+            1 => (y, height - x),
+            2 => (width - x, height - y),
+            3 => (width - y, x),
+            _ => (x, y),
+        };
+        let clamp = |value: f32, size: usize| (value.max(0.0) as usize).min(size - 1);
+        (clamp(x, source_width), clamp(y, source_height))
+    }
+}
+
+// The framed image at source resolution, or `None` when the edit has no
+// rotation or crop.
+pub fn apply_geometry(image: &DecodedBuffer, params: &EditParams) -> Option<DecodedBuffer> {
+    let framing = Framing::new(params)?;
+    let (source_width, source_height) = (image.width as usize, image.height as usize);
+    if source_width == 0 || source_height == 0 {
+        return None;
+    }
+    let (width, height) = framing.output_size(source_width, source_height);
+    let mut rgb = Vec::with_capacity(width * height * 3);
+    for y in 0..height {
+        let v = (y as f32 + 0.5) / height as f32;
+        for x in 0..width {
+            let u = (x as f32 + 0.5) / width as f32;
+            let (src_x, src_y) = framing.source_pixel(u, v, source_width, source_height);
+            let offset = (src_y * source_width + src_x) * 3;
+            rgb.extend_from_slice(&image.rgb[offset..offset + 3]);
+        }
+    }
+    Some(DecodedBuffer {
+        width: width as u32,
+        height: height as u32,
+        rgb,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crop_must_stay_inside_the_frame_and_rotation_must_be_finite() {
+        let crop = |x, y, width, height| EditParams {
+            crop: Some(CropRect {
+                x,
+                y,
+                width,
+                height,
+            }),
+            ..EditParams::default()
+        };
+        assert_eq!(crop(0.25, 0.0, 0.75, 1.0).validate(), Ok(()));
+        assert_eq!(
+            crop(0.5, 0.0, 0.75, 1.0).validate(),
+            Err(DomainError::InvalidCrop)
+        );
+        assert_eq!(
+            crop(0.0, 0.0, 0.0, 1.0).validate(),
+            Err(DomainError::InvalidCrop)
+        );
+        assert_eq!(
+            crop(f32::NAN, 0.0, 0.5, 0.5).validate(),
+            Err(DomainError::InvalidCrop)
+        );
+        let spun = EditParams {
+            rotation_degrees: f32::INFINITY,
+            ..EditParams::default()
+        };
+        assert_eq!(
+            spun.validate(),
+            Err(DomainError::NonFiniteEditParam("rotation_degrees"))
+        );
+    }
+
+    #[test]
+    fn edits_without_geometry_leave_the_frame_alone() {
+        let params: EditParams = serde_json::from_str(
+            r#"{"exposure":0.5,"contrast":0.0,"temperature":0.0,"tint":0.0,"highlights":0.0,"shadows":0.0}"#,
+        )
+        .expect("legacy edit json");
+        assert_eq!((params.crop, params.rotation_degrees), (None, 0.0));
+        assert!(Framing::new(&params).is_none());
+        assert!(Framing::new(&EditParams {
+            rotation_degrees: 360.0,
+            crop: Some(CropRect::FULL),
+            ..params
+        })
+        .is_none());
+    }
+
+    #[test]
+    fn half_turn_reverses_pixels_and_crop_keeps_its_fraction() {
+        let image = DecodedBuffer {
+            width: 3,
+            height: 2,
+            rgb: (0..6_u8).flat_map(|level| [level; 3]).collect(),
+        };
+        let turned = apply_geometry(
+            &image,
+            &EditParams {
+                rotation_degrees: 180.0,
+                ..EditParams::default()
+            },
+        )
+        .expect("rotated");
+        let levels = |buffer: &DecodedBuffer| -> Vec<u8> {
+            buffer.rgb.chunks_exact(3).map(|pixel| pixel[0]).collect()
+        };
+        assert_eq!((turned.width, turned.height), (3, 2));
+        assert_eq!(levels(&turned), vec![5, 4, 3, 2, 1, 0]);
+
+        let cropped = apply_geometry(
+            &image,
+            &EditParams {
+                crop: Some(CropRect {
+                    x: 1.0 / 3.0,
+                    y: 0.5,
+                    width: 2.0 / 3.0,
+                    height: 0.5,
+                }),
+                ..EditParams::default()
+            },
+        )
+        .expect("cropped");
+        assert_eq!((cropped.width, cropped.height), (2, 1));
+        assert_eq!(levels(&cropped), vec![4, 5]);
+    }
+}
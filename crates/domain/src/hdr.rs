@@ -0,0 +1,95 @@
+use crate::color::{linear_to_srgb, luma, srgb_to_linear};
+use crate::{DecodedBuffer, DomainError};
+
+const MIN_BRACKET_FRAMES: usize = 2;
+const MIN_EXPOSURE_WEIGHT: f32 = 0.02;
+
+pub fn merge_exposure_bracket(frames: &[DecodedBuffer]) -> Result<DecodedBuffer, DomainError> {
+    if frames.len() < MIN_BRACKET_FRAMES {
+        return Err(DomainError::BracketTooSmall(frames.len()));
+    }
+
+    let width = frames[0].width;
+    let height = frames[0].height;
+    let expected_len = width as usize * height as usize * 3;
+    if frames
+        .iter()
+        .any(|frame| frame.width != width || frame.height != height)
+        || frames.iter().any(|frame| frame.rgb.len() != expected_len)
+    {
+        return Err(DomainError::BracketDimensionMismatch);
+    }
+
+    let mut rgb = vec![0_u8; expected_len];
+    for offset in (0..expected_len).step_by(3) {
+        let mut sum = [0.0_f32; 3];
+        let mut weight_sum = 0.0_f32;
+        for frame in frames {
+            let pixel = [
+                frame.rgb[offset] as f32 / 255.0,
+                frame.rgb[offset + 1] as f32 / 255.0,
+                frame.rgb[offset + 2] as f32 / 255.0,
+            ];
+            let weight = exposure_weight(luma(pixel[0], pixel[1], pixel[2]));
+            for channel in 0..3 {
+                sum[channel] += srgb_to_linear(pixel[channel]) * weight;
+            }
+            weight_sum += weight;
+        }
+
+        for channel in 0..3 {
+            let merged = tone_map(sum[channel] / weight_sum);
+            rgb[offset + channel] = (linear_to_srgb(merged) * 255.0).round() as u8;
+        }
+    }
+
+    Ok(DecodedBuffer { width, height, rgb })
+}
+
+fn exposure_weight(value: f32) -> f32 {
+    (1.0 - (2.0 * value - 1.0).abs()).max(MIN_EXPOSURE_WEIGHT)
+}
+
+fn tone_map(linear: f32) -> f32 {
+    2.0 * linear / (1.0 + linear)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, value: u8) -> DecodedBuffer {
+        DecodedBuffer {
+            width,
+            height,
+            rgb: vec![value; width as usize * height as usize * 3],
+        }
+    }
+
+    fn mean(buffer: &DecodedBuffer) -> f32 {
+        buffer.rgb.iter().map(|value| *value as f32).sum::<f32>() / buffer.rgb.len() as f32
+    }
+
+    #[test]
+    fn merged_brightness_lies_between_under_and_over() {
+        let under = solid(4, 4, 26);
+        let over = solid(4, 4, 204);
+        let merged = merge_exposure_bracket(&[under.clone(), over.clone()]).expect("merge");
+
+        assert_eq!((merged.width, merged.height), (4, 4));
+        assert!(mean(&merged) > mean(&under));
+        assert!(mean(&merged) < mean(&over));
+    }
+
+    #[test]
+    fn merge_rejects_single_frame_and_mismatched_sizes() {
+        assert!(matches!(
+            merge_exposure_bracket(&[solid(2, 2, 10)]),
+            Err(DomainError::BracketTooSmall(1))
+        ));
+        assert!(matches!(
+            merge_exposure_bracket(&[solid(2, 2, 10), solid(3, 2, 10)]),
+            Err(DomainError::BracketDimensionMismatch)
+        ));
+    }
+}
@@ -1,8 +1,15 @@
 use std::path::Path;
 
+use serde::Serialize;
+
 use crate::DomainError;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub const MAX_RATING: i64 = 5;
+pub const FLAG_REJECT: i64 = -1;
+pub const FLAG_NONE: i64 = 0;
+pub const FLAG_PICK: i64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub struct ImageId(i64);
 
 impl ImageId {
@@ -21,11 +28,15 @@ impl ImageId {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ImageKind {
     Jpeg,
+    Gif,
+    Png,
+    Tiff,
+    Heic,
     Raw,
     Unsupported,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ImageRecord {
     pub id: ImageId,
     pub file_path: String,
@@ -34,6 +45,47 @@ pub struct ImageRecord {
     pub rating: i64,
     pub flag: i64,
     pub metadata_json: String,
+    pub caption: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageSort {
+    // Newest capture (or import) date first.
+    #[default]
+    CaptureDate,
+    CaptureDateAsc,
+    RatingDesc,
+    FilePathAsc,
+    Custom,
+}
+
+// A window into a sorted listing; the default covers every row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImagePage {
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageFilter {
+    pub lens: Option<String>,
+    pub focal_min: Option<f64>,
+    pub focal_max: Option<f64>,
+    pub camera: Option<String>,
+    pub iso_min: Option<i64>,
+    pub iso_max: Option<i64>,
+    // Inclusive `YYYY-MM-DD` bounds on the capture date, or the import date
+    // for images without one.
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub collapse_stacks: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortMove {
+    Up,
+    Down,
+    To(i64),
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -41,6 +93,15 @@ pub struct ImportReport {
     pub scanned_files: usize,
     pub supported_files: usize,
     pub newly_imported: usize,
+    pub skipped_duplicates: usize,
+    pub imported: Vec<String>,
+    pub failures: Vec<ImportFileFailure>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportFileFailure {
+    pub file_path: String,
+    pub reason: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -48,6 +109,14 @@ pub struct DecodedImage {
     pub width: u32,
     pub height: u32,
     pub kind: ImageKind,
+    pub grayscale: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
 }
 
 pub fn detect_image_kind(path: &Path) -> ImageKind {
@@ -57,6 +126,10 @@ pub fn detect_image_kind(path: &Path) -> ImageKind {
 
     match ext.to_ascii_lowercase().as_str() {
         "jpg" | "jpeg" => ImageKind::Jpeg,
+        "gif" => ImageKind::Gif,
+        "png" => ImageKind::Png,
+        "tif" | "tiff" => ImageKind::Tiff,
+        "heic" | "heif" => ImageKind::Heic,
         "cr2" | "nef" | "arw" | "dng" => ImageKind::Raw,
         _ => ImageKind::Unsupported,
     }
@@ -79,8 +152,12 @@ mod tests {
     fn image_kind_detection_works() {
         assert_eq!(detect_image_kind(Path::new("a.jpg")), ImageKind::Jpeg);
         assert_eq!(detect_image_kind(Path::new("a.nef")), ImageKind::Raw);
+        assert_eq!(detect_image_kind(Path::new("a.HEIC")), ImageKind::Heic);
+        assert_eq!(detect_image_kind(Path::new("a.png")), ImageKind::Png);
+        assert_eq!(detect_image_kind(Path::new("a.TIF")), ImageKind::Tiff);
+        assert_eq!(detect_image_kind(Path::new("a.tiff")), ImageKind::Tiff);
         assert_eq!(
-            detect_image_kind(Path::new("a.png")),
+            detect_image_kind(Path::new("a.bmp")),
             ImageKind::Unsupported
         );
     }
@@ -1,9 +1,35 @@
+mod color;
+mod curve;
 mod edit;
 mod error;
+mod export;
+mod geometry;
+mod hdr;
 mod image;
 mod preview;
+mod render;
+mod xmp;
 
-pub use edit::EditParams;
+pub use color::{
+    auto_exposure, linear_to_srgb, luma, matching_exposure, mean_linear_luminance, srgb_to_linear,
+};
+pub use curve::{ChannelCurves, CurveLut, ToneCurve, CURVE_LUT_SIZE, MAX_CURVE_POINTS};
+pub use edit::{
+    kelvin_to_temperature, temperature_to_kelvin, EditField, EditParams, EDIT_PARAM_LIMIT,
+};
 pub use error::DomainError;
-pub use image::{detect_image_kind, DecodedImage, ImageId, ImageKind, ImageRecord, ImportReport};
-pub use preview::{PreviewFrame, PreviewMetrics, PreviewRequest};
+pub use export::{ChannelOrder, RawPixelFormat};
+pub use geometry::{apply_geometry, CropRect, Framing};
+pub use hdr::merge_exposure_bracket;
+pub use image::{
+    detect_image_kind, DecodedBuffer, DecodedImage, ImageFilter, ImageId, ImageKind, ImagePage,
+    ImageRecord, ImageSort, ImportFileFailure, ImportReport, SortMove, FLAG_NONE, FLAG_PICK,
+    FLAG_REJECT, MAX_RATING,
+};
+pub use preview::{
+    ClippingStats, ComparisonMode, PreviewBackend, PreviewBackendInfo, PreviewFrame,
+    PreviewHistogram, PreviewMetrics, PreviewRequest, SourceRegion, ViewRegion,
+    DEFAULT_SPLIT_FRACTION, HISTOGRAM_BINS, MAX_VIEW_ZOOM,
+};
+pub use render::{apply_edits, PixelAdjustments, TiledRender, EXPORT_STRIP_ROWS};
+pub use xmp::edit_params_to_xmp;
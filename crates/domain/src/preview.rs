@@ -1,12 +1,181 @@
-use crate::{EditParams, ImageId};
+use std::sync::Arc;
+
+use crate::{DecodedBuffer, DomainError, EditParams, ImageId};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct PreviewRequest {
     pub image_id: ImageId,
     pub source_path: String,
+    pub source_pixels: Option<Arc<DecodedBuffer>>,
+    pub source_region: Option<SourceRegion>,
+    pub view_region: Option<ViewRegion>,
     pub params: EditParams,
     pub target_width: u32,
     pub target_height: u32,
+    pub want_histogram: bool,
+    pub comparison: Option<ComparisonMode>,
+    pub split_fraction: f32,
+}
+
+pub const HISTOGRAM_BINS: usize = 256;
+pub const DEFAULT_SPLIT_FRACTION: f32 = 0.5;
+pub const MAX_VIEW_ZOOM: f32 = 32.0;
+
+impl PreviewRequest {
+    // The part of the framed image to render. A source region, when both are
+    // set, is a fraction of the zoomed view rather than of the whole image.
+    pub fn effective_region(&self) -> Option<SourceRegion> {
+        let view = self
+            .view_region
+            .filter(|view| !view.is_fit())
+            .map(|view| view.source_region());
+        match (view, self.source_region) {
+            (Some(view), Some(region)) => Some(SourceRegion {
+                left: view.left + region.left * view.width,
+                top: view.top + region.top * view.height,
+                width: region.width * view.width,
+                height: region.height * view.height,
+            }),
+            (view, region) => view.or(region),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonMode {
+    SplitVertical,
+    SplitHorizontal,
+    FullOriginal,
+}
+
+impl ComparisonMode {
+    pub fn validate_split_fraction(split_fraction: f32) -> Result<(), DomainError> {
+        if !(0.0..=1.0).contains(&split_fraction) {
+            return Err(DomainError::InvalidSplitFraction(split_fraction));
+        }
+        Ok(())
+    }
+
+    // The unedited source always occupies the top-left corner: the left columns
+    // for a vertical divider, the top rows for a horizontal one.
+    pub fn original_extent(
+        self,
+        split_fraction: f32,
+        width: usize,
+        height: usize,
+    ) -> (usize, usize) {
+        let split = |size: usize| {
+            ((split_fraction.clamp(0.0, 1.0) * size as f32).round() as usize).min(size)
+        };
+        match self {
+            Self::SplitVertical => (split(width), height),
+            Self::SplitHorizontal => (width, split(height)),
+            Self::FullOriginal => (width, height),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourceRegion {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl SourceRegion {
+    pub fn validate(&self) -> Result<(), DomainError> {
+        let values = [self.left, self.top, self.width, self.height];
+        if values.iter().any(|value| !value.is_finite())
+            || self.left < 0.0
+            || self.top < 0.0
+            || self.width <= 0.0
+            || self.height <= 0.0
+            || self.left + self.width > 1.0 + f32::EPSILON
+            || self.top + self.height > 1.0 + f32::EPSILON
+        {
+            return Err(DomainError::InvalidSourceRegion);
+        }
+        Ok(())
+    }
+
+    pub fn pixel_bounds(&self, width: usize, height: usize) -> (usize, usize, usize, usize) {
+        let (x, region_width) = region_span(self.left, self.width, width);
+        let (y, region_height) = region_span(self.top, self.height, height);
+        (x, y, region_width, region_height)
+    }
+}
+
+// A zoomed window onto the framed image: its center as fractions of the
+// image and a magnification where 1 fits the whole image. The window is
+// 1/zoom of the image on each axis and slides back inside at the edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewRegion {
+    pub center_x: f32,
+    pub center_y: f32,
+    pub zoom: f32,
+}
+
+impl ViewRegion {
+    pub const FIT: ViewRegion = ViewRegion {
+        center_x: 0.5,
+        center_y: 0.5,
+        zoom: 1.0,
+    };
+
+    pub fn validate(&self) -> Result<(), DomainError> {
+        let inside = (0.0..=1.0).contains(&self.center_x) && (0.0..=1.0).contains(&self.center_y);
+        if !inside || !(1.0..=MAX_VIEW_ZOOM).contains(&self.zoom) {
+            return Err(DomainError::InvalidViewRegion);
+        }
+        Ok(())
+    }
+
+    pub fn is_fit(&self) -> bool {
+        self.zoom <= 1.0
+    }
+
+    pub fn source_region(&self) -> SourceRegion {
+        let extent = 1.0 / self.zoom;
+        let start = |center: f32| (center - extent / 2.0).clamp(0.0, 1.0 - extent);
+        SourceRegion {
+            left: start(self.center_x),
+            top: start(self.center_y),
+            width: extent,
+            height: extent,
+        }
+    }
+
+    // Zooms about the current center, keeping the window inside the image.
+    pub fn zoomed_by(&self, factor: f32) -> Self {
+        let zoom = (self.zoom * factor).clamp(1.0, MAX_VIEW_ZOOM);
+        Self { zoom, ..*self }.settled()
+    }
+
+    // Moves the center by fractions of the visible window.
+    pub fn panned_by(&self, dx: f32, dy: f32) -> Self {
+        Self {
+            center_x: self.center_x + dx / self.zoom,
+            center_y: self.center_y + dy / self.zoom,
+            zoom: self.zoom,
+        }
+        .settled()
+    }
+
+    fn settled(self) -> Self {
+        let region = self.source_region();
+        Self {
+            center_x: region.left + region.width / 2.0,
+            center_y: region.top + region.height / 2.0,
+            zoom: self.zoom,
+        }
+    }
+}
+
+fn region_span(start: f32, extent: f32, size: usize) -> (usize, usize) {
+    let begin = ((start * size as f32).round() as usize).min(size.saturating_sub(1));
+    let end = (((start + extent) * size as f32).round() as usize).clamp(begin + 1, size.max(1));
+    (begin, end - begin)
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,6 +186,72 @@ pub struct PreviewFrame {
     pub height: u32,
     pub render_time_ms: u64,
     pub pixels: Vec<u32>,
+    pub histogram: Option<PreviewHistogram>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewHistogram {
+    pub red: [u32; HISTOGRAM_BINS],
+    pub green: [u32; HISTOGRAM_BINS],
+    pub blue: [u32; HISTOGRAM_BINS],
+    pub luma: [u32; HISTOGRAM_BINS],
+}
+
+impl Default for PreviewHistogram {
+    fn default() -> Self {
+        Self {
+            red: [0; HISTOGRAM_BINS],
+            green: [0; HISTOGRAM_BINS],
+            blue: [0; HISTOGRAM_BINS],
+            luma: [0; HISTOGRAM_BINS],
+        }
+    }
+}
+
+impl PreviewHistogram {
+    pub fn from_pixels(pixels: &[u32]) -> Self {
+        let mut histogram = Self::default();
+        for pixel in pixels {
+            let red = (pixel >> 16) & 0xFF;
+            let green = (pixel >> 8) & 0xFF;
+            let blue = pixel & 0xFF;
+            histogram.red[red as usize] += 1;
+            histogram.green[green as usize] += 1;
+            histogram.blue[blue as usize] += 1;
+            // Rec. 709 weights in 16-bit fixed point; they sum to 65536.
+            let luma = (13_933 * red + 46_871 * green + 4_732 * blue + 32_768) >> 16;
+            histogram.luma[luma as usize] += 1;
+        }
+        histogram
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClippingStats {
+    pub highlight_percent: f32,
+    pub shadow_percent: f32,
+}
+
+impl PreviewFrame {
+    pub fn clipping_stats(&self) -> ClippingStats {
+        if self.pixels.is_empty() {
+            return ClippingStats::default();
+        }
+        let mut highlights = 0_usize;
+        let mut shadows = 0_usize;
+        for pixel in &self.pixels {
+            match pixel & 0x00FF_FFFF {
+                0x00FF_FFFF => highlights += 1,
+                0 => shadows += 1,
+                _ => {}
+            }
+        }
+        let total = self.pixels.len() as f32;
+        ClippingStats {
+            highlight_percent: highlights as f32 * 100.0 / total,
+            shadow_percent: shadows as f32 * 100.0 / total,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -27,4 +262,118 @@ pub struct PreviewMetrics {
     pub dropped_frames: u64,
     pub last_render_time_ms: Option<u64>,
     pub p95_render_time_ms: Option<u64>,
+    pub gpu_failures: u64,
+    pub gpu_disabled: bool,
+    pub cache_hits: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewBackend {
+    Gpu,
+    Cpu,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewBackendInfo {
+    pub backend: PreviewBackend,
+    pub adapter_name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clipping_stats_count_fully_clipped_pixels() {
+        let mut pixels = vec![0x0080_8080; 16];
+        pixels[..4].fill(0x00FF_FFFF);
+        pixels[4] = 0x00FF_FF00;
+        pixels[5] = 0;
+        let frame = PreviewFrame {
+            image_id: ImageId::new(1).expect("id"),
+            sequence: 1,
+            width: 4,
+            height: 4,
+            render_time_ms: 0,
+            pixels,
+            histogram: None,
+        };
+
+        let stats = frame.clipping_stats();
+        assert!((stats.highlight_percent - 25.0).abs() < 1e-4);
+        assert!((stats.shadow_percent - 6.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn comparison_extent_splits_along_the_requested_axis() {
+        assert_eq!(
+            ComparisonMode::SplitVertical.original_extent(0.25, 8, 4),
+            (2, 4)
+        );
+        assert_eq!(
+            ComparisonMode::SplitHorizontal.original_extent(0.5, 8, 5),
+            (8, 3)
+        );
+        assert_eq!(
+            ComparisonMode::FullOriginal.original_extent(0.0, 8, 4),
+            (8, 4)
+        );
+        assert_eq!(
+            ComparisonMode::SplitVertical.original_extent(1.0, 8, 4),
+            (8, 4)
+        );
+        assert!(ComparisonMode::validate_split_fraction(0.0).is_ok());
+        for invalid in [-0.1, 1.5, f32::NAN] {
+            assert!(matches!(
+                ComparisonMode::validate_split_fraction(invalid),
+                Err(DomainError::InvalidSplitFraction(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn histogram_bins_solid_colors_by_channel_and_luma() {
+        let mut pixels = vec![0x00C8_6420; 6];
+        pixels.extend([0x00FF_FFFF, 0]);
+
+        let histogram = PreviewHistogram::from_pixels(&pixels);
+
+        assert_eq!(
+            (histogram.red[200], histogram.green[100], histogram.blue[32]),
+            (6, 6, 6)
+        );
+        assert_eq!((histogram.red[255], histogram.red[0]), (1, 1));
+        assert_eq!(
+            histogram.luma[116], 6,
+            "0.2126*200 + 0.7152*100 + 0.0722*32"
+        );
+        assert_eq!((histogram.luma[255], histogram.luma[0]), (1, 1));
+        assert_eq!(histogram.luma.iter().sum::<u32>(), 8);
+    }
+
+    #[test]
+    fn view_region_stays_inside_the_image_while_zooming_and_panning() {
+        let view = ViewRegion::FIT.zoomed_by(4.0);
+        assert_eq!(
+            view.source_region(),
+            SourceRegion {
+                left: 0.375,
+                top: 0.375,
+                width: 0.25,
+                height: 0.25,
+            }
+        );
+        let corner = view.panned_by(-10.0, 10.0);
+        assert_eq!((corner.center_x, corner.center_y), (0.125, 0.875));
+        assert_eq!(corner.zoomed_by(0.01), ViewRegion::FIT);
+        assert!(ViewRegion::FIT.zoomed_by(1_000.0).validate().is_ok());
+        assert_eq!(
+            ViewRegion {
+                zoom: 0.5,
+                ..ViewRegion::FIT
+            }
+            .validate(),
+            Err(DomainError::InvalidViewRegion)
+        );
+    }
 }
@@ -0,0 +1,377 @@
+use crate::{CurveLut, DecodedBuffer, EditParams};
+
+pub const EXPORT_STRIP_ROWS: u32 = 256;
+// Peak grain offset per grain unit, in 0..1 channel values.
+const GRAIN_AMPLITUDE: f32 = 0.04;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelAdjustments {
+    exposure_gain: f32,
+    contrast_factor: f32,
+    temperature_shift: f32,
+    tint_shift: f32,
+    highlights_strength: f32,
+    shadows_strength: f32,
+    saturation_factor: f32,
+    vibrance_strength: f32,
+    grain_strength: f32,
+    tone_lut: Option<CurveLut>,
+    channel_luts: Option<[CurveLut; 3]>,
+}
+
+impl PixelAdjustments {
+    pub fn new(params: &EditParams) -> Self {
+        let params = params.effective().clamp_to_range();
+        Self {
+            exposure_gain: 2_f32.powf(params.exposure),
+            contrast_factor: 1.0 + params.contrast * 0.12,
+            temperature_shift: params.temperature * 0.035,
+            tint_shift: params.tint * 0.035,
+            highlights_strength: params.highlights * 0.08,
+            shadows_strength: params.shadows * 0.08,
+            saturation_factor: 1.0 + params.saturation * 0.2,
+            vibrance_strength: params.vibrance * 0.2,
+            grain_strength: params.grain * GRAIN_AMPLITUDE,
+            tone_lut: (!params.tone_curve.is_identity()).then(|| params.tone_curve.lut()),
+            channel_luts: (!params.channel_curves.is_identity())
+                .then(|| params.channel_curves.luts()),
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        !self.adjusts_exposure_contrast()
+            && self.temperature_shift == 0.0
+            && self.tint_shift == 0.0
+            && !self.adjusts_tones()
+            && !self.adjusts_color_intensity()
+            && self.grain_strength == 0.0
+            && self.tone_lut.is_none()
+            && self.channel_luts.is_none()
+    }
+
+    pub fn apply(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let mut rgb = rgb;
+        if self.adjusts_exposure_contrast() {
+            rgb = rgb.map(|channel| self.exposure_contrast(channel));
+        }
+        if let Some(lut) = &self.tone_lut {
+            rgb = rgb.map(|channel| lut[channel as usize]);
+        }
+        if self.temperature_shift != 0.0 {
+            rgb[0] = shift_channel(rgb[0], self.temperature_shift);
+            rgb[2] = shift_channel(rgb[2], -self.temperature_shift);
+        }
+        if self.tint_shift != 0.0 {
+            rgb[1] = shift_channel(rgb[1], self.tint_shift);
+        }
+        if self.adjusts_tones() {
+            rgb = rgb.map(|channel| self.highlights_shadows(channel));
+        }
+        if self.adjusts_color_intensity() {
+            rgb = self.color_intensity(rgb);
+        }
+        // Channel curves grade the finished color, so they run last.
+        if let Some([red, green, blue]) = &self.channel_luts {
+            rgb = [
+                red[rgb[0] as usize],
+                green[rgb[1] as usize],
+                blue[rgb[2] as usize],
+            ];
+        }
+        rgb
+    }
+
+    // `apply` plus grain, which needs the pixel's position in the rendered
+    // frame so the same render always lays down the same grain.
+    pub fn apply_at(&self, rgb: [u8; 3], x: u32, y: u32) -> [u8; 3] {
+        let rgb = self.apply(rgb);
+        if self.grain_strength == 0.0 {
+            return rgb;
+        }
+        let offset = grain_noise(x, y) * self.grain_strength;
+        rgb.map(|channel| to_channel(channel as f32 / 255.0 + offset))
+    }
+
+    fn adjusts_exposure_contrast(&self) -> bool {
+        self.exposure_gain != 1.0 || self.contrast_factor != 1.0
+    }
+
+    fn adjusts_tones(&self) -> bool {
+        self.highlights_strength != 0.0 || self.shadows_strength != 0.0
+    }
+
+    fn adjusts_color_intensity(&self) -> bool {
+        self.saturation_factor != 1.0 || self.vibrance_strength != 0.0
+    }
+
+    // Saturation scales chroma around luma uniformly. Vibrance scales it by how
+    // muted the pixel already is, at half strength for skin-like r > g > b hues.
+    fn color_intensity(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let [red, green, blue] = rgb.map(|channel| channel as f32 / 255.0);
+        let luma = 0.2126 * red + 0.7152 * green + 0.0722 * blue;
+        let chroma = red.max(green).max(blue) - red.min(green).min(blue);
+        let skin_weight = if red > green && green > blue {
+            0.5
+        } else {
+            1.0
+        };
+        let vibrance_factor = 1.0 + self.vibrance_strength * (1.0 - chroma) * skin_weight;
+        let factor = (self.saturation_factor * vibrance_factor).max(0.0);
+        [red, green, blue].map(|channel| to_channel(luma + (channel - luma) * factor))
+    }
+
+    fn exposure_contrast(&self, channel: u8) -> u8 {
+        let exposed = channel as f32 / 255.0 * self.exposure_gain;
+        to_channel((exposed - 0.5) * self.contrast_factor + 0.5)
+    }
+
+    fn highlights_shadows(&self, channel: u8) -> u8 {
+        let value = channel as f32 / 255.0;
+        let highlight_component = (value - 0.5).max(0.0) * self.highlights_strength;
+        let shadow_component = (0.5 - value).max(0.0) * self.shadows_strength;
+        to_channel(value + shadow_component - highlight_component)
+    }
+}
+
+pub fn apply_edits(image: &DecodedBuffer, params: &EditParams) -> DecodedBuffer {
+    let adjustments = PixelAdjustments::new(params);
+    let mut rgb = image.rgb.clone();
+    adjust_rgb(&adjustments, &mut rgb, image.width, 0);
+    DecodedBuffer {
+        width: image.width,
+        height: image.height,
+        rgb,
+    }
+}
+
+// The edited image, produced one horizontal strip at a time so export never
+// holds a second full-size buffer next to the decoded source. Every current
+// adjustment is per pixel, so strips don't overlap; a neighborhood stage
+// (sharpening) would have to render its radius of extra rows around each strip.
+#[derive(Debug, Clone, Copy)]
+pub struct TiledRender<'a> {
+    source: &'a DecodedBuffer,
+    adjustments: PixelAdjustments,
+    strip_rows: u32,
+}
+
+impl<'a> TiledRender<'a> {
+    pub fn new(source: &'a DecodedBuffer, params: &EditParams, strip_rows: u32) -> Self {
+        Self {
+            source,
+            adjustments: PixelAdjustments::new(params),
+            strip_rows: strip_rows.max(1),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.source.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.source.height
+    }
+
+    pub fn strip_rows(&self) -> u32 {
+        self.strip_rows
+    }
+
+    // Edited rows `first_row..first_row + strip_rows`, clipped to the image.
+    pub fn strip(&self, first_row: u32) -> DecodedBuffer {
+        let rows = self
+            .strip_rows
+            .min(self.source.height.saturating_sub(first_row));
+        let row_bytes = self.source.width as usize * 3;
+        let start = first_row as usize * row_bytes;
+        let mut rgb = self.source.rgb[start..start + rows as usize * row_bytes].to_vec();
+        adjust_rgb(&self.adjustments, &mut rgb, self.source.width, first_row);
+        DecodedBuffer {
+            width: self.source.width,
+            height: rows,
+            rgb,
+        }
+    }
+
+    pub fn strips(&self) -> impl Iterator<Item = DecodedBuffer> + '_ {
+        (0..self.source.height)
+            .step_by(self.strip_rows as usize)
+            .map(|first_row| self.strip(first_row))
+    }
+}
+
+fn adjust_rgb(adjustments: &PixelAdjustments, rgb: &mut [u8], width: u32, first_row: u32) {
+    if adjustments.is_identity() {
+        return;
+    }
+    let width = width.max(1) as usize;
+    for (index, pixel) in rgb.chunks_exact_mut(3).enumerate() {
+        let (x, y) = ((index % width) as u32, first_row + (index / width) as u32);
+        let adjusted = adjustments.apply_at([pixel[0], pixel[1], pixel[2]], x, y);
+        pixel.copy_from_slice(&adjusted);
+    }
+}
+
+// Value noise in -1..1 from an integer hash of the pixel position (lowbias32),
+// so it needs no RNG state. The preview shader computes the same hash.
+fn grain_noise(x: u32, y: u32) -> f32 {
+    let hash = |mut value: u32| {
+        value ^= value >> 16;
+        value = value.wrapping_mul(0x7feb_352d);
+        value ^= value >> 15;
+        value = value.wrapping_mul(0x846c_a68b);
+        value ^ (value >> 16)
+    };
+    let bits = hash(x.wrapping_add(hash(y))) >> 8;
+    bits as f32 / 16_777_215.0 * 2.0 - 1.0
+}
+
+fn shift_channel(channel: u8, shift: f32) -> u8 {
+    to_channel(channel as f32 / 255.0 + shift)
+}
+
+fn to_channel(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChannelCurves, ToneCurve};
+
+    #[test]
+    fn apply_edits_matches_known_solid_color() {
+        let image = DecodedBuffer {
+            width: 2,
+            height: 1,
+            rgb: vec![128; 6],
+        };
+        let params = EditParams {
+            exposure: -1.0,
+            temperature: 1.0,
+            ..EditParams::default()
+        };
+
+        let edited = apply_edits(&image, &params);
+
+        assert_eq!((edited.width, edited.height), (2, 1));
+        assert_eq!(edited.rgb, vec![73, 64, 55, 73, 64, 55]);
+        assert_eq!(apply_edits(&image, &EditParams::default()), image);
+    }
+
+    #[test]
+    fn strips_concatenate_to_the_whole_image_edit() {
+        let image = DecodedBuffer {
+            width: 3,
+            height: 5,
+            rgb: (0..45).map(|value| (value * 5) as u8).collect(),
+        };
+        let params = EditParams::default()
+            .with_exposure(0.4)
+            .with_saturation(0.6)
+            .with_grain(1.5);
+        let tiled = TiledRender::new(&image, &params, 2);
+
+        let strips: Vec<_> = tiled.strips().collect();
+        assert_eq!(
+            strips.iter().map(|strip| strip.height).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        let joined: Vec<u8> = strips.iter().flat_map(|strip| strip.rgb.clone()).collect();
+        assert_eq!(joined, apply_edits(&image, &params).rgb);
+    }
+
+    #[test]
+    fn grain_is_a_no_op_at_zero_and_repeats_exactly_across_renders() {
+        let image = DecodedBuffer {
+            width: 8,
+            height: 8,
+            rgb: vec![128; 8 * 8 * 3],
+        };
+        let params = EditParams::default().with_exposure(0.5);
+        assert!(PixelAdjustments::new(&EditParams::default().with_grain(0.0)).is_identity());
+        let plain = apply_edits(&image, &params.with_grain(0.0));
+        assert_eq!(plain, apply_edits(&image, &params));
+
+        let grainy = apply_edits(&image, &params.with_grain(2.0));
+        assert_eq!(grainy, apply_edits(&image, &params.with_grain(2.0)));
+        assert_ne!(grainy.rgb, plain.rgb);
+        let mut levels: Vec<u8> = grainy.rgb.chunks_exact(3).map(|pixel| pixel[0]).collect();
+        levels.sort_unstable();
+        levels.dedup();
+        assert!(levels.len() > 4, "grain should vary per pixel: {levels:?}");
+        for (pixel, base) in grainy.rgb.chunks_exact(3).zip(plain.rgb.chunks_exact(3)) {
+            assert!(pixel[0] == pixel[1] && pixel[1] == pixel[2]);
+            assert!((pixel[0] as i32 - base[0] as i32).abs() <= 21);
+        }
+    }
+
+    #[test]
+    fn red_curve_lifting_midtones_brightens_only_red_of_gray() {
+        let params = EditParams {
+            channel_curves: ChannelCurves {
+                red: ToneCurve::new(&[(0.5, 0.75)]).expect("curve"),
+                ..ChannelCurves::default()
+            },
+            ..EditParams::default()
+        };
+        let adjustments = PixelAdjustments::new(&params);
+
+        assert!(!adjustments.is_identity());
+        assert_eq!(adjustments.apply([128, 128, 128]), [192, 128, 128]);
+        assert_eq!(adjustments.apply([0, 0, 0]), [0, 0, 0]);
+        assert_eq!(adjustments.apply([255, 255, 255]), [255, 255, 255]);
+    }
+
+    #[test]
+    fn tone_curve_lifting_midtones_brightens_gray_after_exposure() {
+        let curve = ToneCurve::new(&[(0.5, 0.75)]).expect("curve");
+        let params = EditParams {
+            tone_curve: curve,
+            ..EditParams::default()
+        };
+        let adjustments = PixelAdjustments::new(&params);
+        assert!(!adjustments.is_identity());
+        assert_eq!(adjustments.apply([128, 128, 128]), [192, 192, 192]);
+        assert_eq!(adjustments.apply([0, 0, 0]), [0, 0, 0]);
+        assert_eq!(adjustments.apply([255, 255, 255]), [255, 255, 255]);
+
+        // One stop up takes 64 to 128 before the curve sees it.
+        let brightened = PixelAdjustments::new(&EditParams {
+            exposure: 1.0,
+            ..params
+        });
+        assert_eq!(brightened.apply([64, 64, 64]), [192, 192, 192]);
+        assert!(PixelAdjustments::new(&EditParams::default()).is_identity());
+    }
+
+    #[test]
+    fn saturation_scales_chroma_and_vibrance_spares_saturated_pixels() {
+        let muted = [140_u8, 120, 110];
+        let vivid = [20_u8, 200, 60];
+
+        let desaturated = PixelAdjustments::new(&EditParams::default().with_saturation(-5.0));
+        let [red, green, blue] = desaturated.apply(muted);
+        assert!(
+            red == green && green == blue,
+            "fully desaturated: {red} {green} {blue}"
+        );
+
+        let boosted = PixelAdjustments::new(&EditParams::default().with_saturation(1.0));
+        let spread =
+            |[red, green, blue]: [u8; 3]| red.max(green).max(blue) - red.min(green).min(blue);
+        assert!(spread(boosted.apply(vivid)) > spread(vivid));
+
+        let vibrance = PixelAdjustments::new(&EditParams::default().with_vibrance(2.0));
+        let muted_gain = spread(vibrance.apply([110, 130, 140])) as f32 / 30.0;
+        let vivid_gain = spread(vibrance.apply(vivid)) as f32 / spread(vivid) as f32;
+        assert!(
+            muted_gain > vivid_gain,
+            "muted {muted_gain} vivid {vivid_gain}"
+        );
+        let skin_gain = spread(vibrance.apply([140, 130, 110])) as f32 / 30.0;
+        assert!(
+            skin_gain < muted_gain,
+            "skin {skin_gain} muted {muted_gain}"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{temperature_to_kelvin, EditParams};
+
+// Camera Raw keeps exposure in stops like we do, but its tone sliders span
+// ±100, tint ±150 and white balance 2000-50000K.
+const TONE_SLIDER_SCALE: f32 = 20.0;
+const TINT_SCALE: f32 = 30.0;
+const MIN_XMP_KELVIN: f32 = 2000.0;
+const MAX_XMP_KELVIN: f32 = 50000.0;
+
+// A minimal XMP sidecar carrying the basic sliders as Camera Raw (`crs:`)
+// develop settings, plus the caption as `dc:description`. Disabled fields are
+// written at their neutral value; crop, curves and the other settings are left
+// out.
+pub fn edit_params_to_xmp(params: &EditParams, caption: Option<&str>) -> String {
+    let params = params.effective().clamp_to_range();
+    let kelvin = temperature_to_kelvin(params.temperature).clamp(MIN_XMP_KELVIN, MAX_XMP_KELVIN);
+    let attributes = [
+        ("WhiteBalance", "Custom".to_string()),
+        ("Exposure2012", format!("{:+.2}", params.exposure + 0.0)),
+        ("Contrast2012", slider(params.contrast * TONE_SLIDER_SCALE)),
+        ("Temperature", format!("{}", kelvin.round() as i32)),
+        ("Tint", slider(params.tint * TINT_SCALE)),
+        (
+            "Highlights2012",
+            slider(params.highlights * TONE_SLIDER_SCALE),
+        ),
+        ("Shadows2012", slider(params.shadows * TONE_SLIDER_SCALE)),
+    ];
+    let mut xmp = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n \
+         <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n  \
+         <rdf:Description rdf:about=\"\"\n    \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n    \
+         xmlns:crs=\"http://ns.adobe.com/camera-raw-settings/1.0/\"",
+    );
+    for (name, value) in attributes {
+        xmp.push_str(&format!("\n    crs:{name}=\"{value}\""));
+    }
+    match caption {
+        Some(caption) => xmp.push_str(&format!(
+            ">\n   <dc:description>\n    <rdf:Alt>\n     \
+             <rdf:li xml:lang=\"x-default\">{}</rdf:li>\n    \
+             </rdf:Alt>\n   </dc:description>\n  </rdf:Description>\n",
+            escape_xml(caption)
+        )),
+        None => xmp.push_str("/>\n"),
+    }
+    xmp.push_str(" </rdf:RDF>\n</x:xmpmeta>\n");
+    xmp
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+// Whole numbers, with an explicit sign on positive values as Lightroom writes
+// them.
+fn slider(value: f32) -> String {
+    match value.round() as i32 {
+        positive if positive > 0 => format!("+{positive}"),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EditField;
+
+    #[test]
+    fn sliders_map_onto_camera_raw_ranges() {
+        let params = EditParams {
+            exposure: 0.5,
+            contrast: -1.0,
+            temperature: 1.0,
+            tint: 0.5,
+            highlights: -2.0,
+            shadows: 1.5,
+            ..EditParams::default()
+        };
+        let xmp = edit_params_to_xmp(&params, None);
+        for expected in [
+            "xmlns:crs=\"http://ns.adobe.com/camera-raw-settings/1.0/\"",
+            "crs:Exposure2012=\"+0.50\"",
+            "crs:Contrast2012=\"-20\"",
+            "crs:Temperature=\"6300\"",
+            "crs:Tint=\"+15\"",
+            "crs:Highlights2012=\"-40\"",
+            "crs:Shadows2012=\"+30\"",
+        ] {
+            assert!(xmp.contains(expected), "missing {expected} in {xmp}");
+        }
+
+        let mut cold = EditParams {
+            exposure: 2.0,
+            temperature: -5.0,
+            ..EditParams::default()
+        };
+        cold.set_enabled(EditField::Exposure, false);
+        let xmp = edit_params_to_xmp(&cold, None);
+        assert!(xmp.contains("crs:Temperature=\"2000\""), "{xmp}");
+        assert!(xmp.contains("crs:Exposure2012=\"+0.00\""), "{xmp}");
+        assert!(xmp.contains("crs:Contrast2012=\"0\""), "{xmp}");
+        assert!(!xmp.contains("dc:description"), "{xmp}");
+    }
+
+    #[test]
+    fn caption_is_written_as_an_escaped_dc_description() {
+        let xmp = edit_params_to_xmp(&EditParams::default(), Some("Fish & chips <\"Brighton\">"));
+        assert!(
+            xmp.contains(
+                "<rdf:li xml:lang=\"x-default\">Fish &amp; chips &lt;&quot;Brighton&quot;&gt;</rdf:li>"
+            ),
+            "{xmp}"
+        );
+        assert!(
+            xmp.contains("xmlns:dc=\"http://purl.org/dc/elements/1.1/\""),
+            "{xmp}"
+        );
+        assert!(xmp.contains("crs:Shadows2012=\"0\">"), "{xmp}");
+        assert!(xmp.contains("</rdf:Description>\n </rdf:RDF>"), "{xmp}");
+    }
+}
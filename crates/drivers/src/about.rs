@@ -0,0 +1,82 @@
+use lite_room_application::{ApplicationError, ApplicationService, PreviewBackendQuery};
+use lite_room_domain::{PreviewBackend, PreviewBackendInfo};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AboutInfo {
+    pub version: &'static str,
+    pub gpu_feature: bool,
+    pub raw_feature: bool,
+    pub heic_feature: bool,
+    pub backend: PreviewBackendInfo,
+}
+
+impl AboutInfo {
+    pub fn new(backend: PreviewBackendInfo) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            gpu_feature: cfg!(feature = "gpu"),
+            raw_feature: cfg!(feature = "raw"),
+            heic_feature: cfg!(feature = "heic"),
+            backend,
+        }
+    }
+
+    pub fn collect(service: &ApplicationService) -> Result<Self, ApplicationError> {
+        let backend = service.preview_backend_info(PreviewBackendQuery)?;
+        Ok(Self::new(backend))
+    }
+}
+
+pub fn present_about(info: &AboutInfo) -> String {
+    format!(
+        "lite-room {}\nfeatures: gpu={} raw={} heic={}\npreview backend: {} (adapter={})",
+        info.version,
+        on_off(info.gpu_feature),
+        on_off(info.raw_feature),
+        on_off(info.heic_feature),
+        backend_name(info.backend.backend),
+        info.backend.adapter_name
+    )
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn backend_name(backend: PreviewBackend) -> &'static str {
+    match backend {
+        PreviewBackend::Gpu => "gpu",
+        PreviewBackend::Cpu => "cpu",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn about_info_reflects_compiled_features() {
+        let info = AboutInfo::new(PreviewBackendInfo {
+            backend: PreviewBackend::Cpu,
+            adapter_name: "cpu-stage".to_string(),
+        });
+
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.gpu_feature, cfg!(feature = "gpu"));
+        assert_eq!(info.raw_feature, cfg!(feature = "raw"));
+        assert_eq!(info.heic_feature, cfg!(feature = "heic"));
+
+        let text = present_about(&info);
+        let expected_gpu = if cfg!(feature = "gpu") {
+            "gpu=on"
+        } else {
+            "gpu=off"
+        };
+        assert!(text.contains(expected_gpu));
+        assert!(text.contains("preview backend: cpu (adapter=cpu-stage)"));
+    }
+}
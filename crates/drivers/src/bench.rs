@@ -0,0 +1,356 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lite_room_application::{ApplicationError, PreviewPipeline};
+use lite_room_domain::{
+    DecodedBuffer, EditParams, ImageId, PreviewBackendInfo, PreviewFrame, PreviewMetrics,
+    PreviewRequest, DEFAULT_SPLIT_FRACTION,
+};
+
+pub const DEFAULT_BENCH_ITERATIONS: usize = 20;
+pub const BENCH_TARGET_WIDTH: u32 = 1280;
+pub const BENCH_TARGET_HEIGHT: u32 = 800;
+const WARMUP_ITERATIONS: usize = 3;
+const COMPARE_ITERATIONS: usize = 5;
+const FRAME_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy)]
+pub struct BenchSettings {
+    pub iterations: usize,
+    pub target_width: u32,
+    pub target_height: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub backend: PreviewBackendInfo,
+    pub iterations: usize,
+    pub width: u32,
+    pub height: u32,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+    pub megapixels_per_second: f64,
+    pub metrics: PreviewMetrics,
+}
+
+#[derive(Debug, Clone)]
+pub struct BackendTiming {
+    pub backend: PreviewBackendInfo,
+    pub median: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelDifference {
+    pub max: u8,
+    pub mean: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompareReport {
+    pub width: u32,
+    pub height: u32,
+    pub cpu: BackendTiming,
+    pub gpu: Result<BackendTiming, String>,
+    pub difference: Option<PixelDifference>,
+}
+
+pub fn run_bench(
+    pipeline: &dyn PreviewPipeline,
+    source: Arc<DecodedBuffer>,
+    settings: BenchSettings,
+) -> Result<BenchReport, ApplicationError> {
+    if settings.iterations == 0 {
+        return Err(ApplicationError::InvalidInput(
+            "bench iterations must be at least 1".to_string(),
+        ));
+    }
+    let request = bench_request(source, settings.target_width, settings.target_height)?;
+
+    for _ in 0..WARMUP_ITERATIONS {
+        render_once(pipeline, &request)?;
+    }
+    let mut samples = Vec::with_capacity(settings.iterations);
+    let mut frame_size = (0, 0);
+    for _ in 0..settings.iterations {
+        let (elapsed, frame) = render_once(pipeline, &request)?;
+        samples.push(elapsed);
+        frame_size = (frame.width, frame.height);
+    }
+    samples.sort_unstable();
+
+    let median = samples[samples.len() / 2];
+    let p95 = samples[((samples.len() - 1) as f64 * 0.95).round() as usize];
+    let megapixels = f64::from(frame_size.0) * f64::from(frame_size.1) / 1_000_000.0;
+    Ok(BenchReport {
+        backend: pipeline.backend_info()?,
+        iterations: settings.iterations,
+        width: frame_size.0,
+        height: frame_size.1,
+        min: samples[0],
+        median,
+        p95,
+        max: samples[samples.len() - 1],
+        megapixels_per_second: megapixels / median.as_secs_f64().max(f64::EPSILON),
+        metrics: pipeline.metrics()?,
+    })
+}
+
+pub fn compare_backends(
+    cpu: &dyn PreviewPipeline,
+    gpu: Result<&dyn PreviewPipeline, String>,
+    source: Arc<DecodedBuffer>,
+) -> Result<CompareReport, ApplicationError> {
+    let request = bench_request(source, BENCH_TARGET_WIDTH, BENCH_TARGET_HEIGHT)?;
+    let (cpu_timing, cpu_frame) = time_backend(cpu, &request)?;
+    let (gpu, difference) = match gpu {
+        Ok(gpu) => {
+            let (gpu_timing, gpu_frame) = time_backend(gpu, &request)?;
+            let difference = pixel_difference(&cpu_frame, &gpu_frame)?;
+            (Ok(gpu_timing), Some(difference))
+        }
+        Err(reason) => (Err(reason), None),
+    };
+    Ok(CompareReport {
+        width: cpu_frame.width,
+        height: cpu_frame.height,
+        cpu: cpu_timing,
+        gpu,
+        difference,
+    })
+}
+
+pub fn present_compare(report: &CompareReport) -> String {
+    let mut lines = vec![
+        format!("compare-backends: size={}x{}", report.width, report.height),
+        present_timing(&report.cpu),
+    ];
+    match &report.gpu {
+        Ok(gpu) => {
+            lines.push(present_timing(gpu));
+            lines.push(format!(
+                "gpu speedup: {:.2}x",
+                report.cpu.median.as_secs_f64() / gpu.median.as_secs_f64().max(f64::EPSILON)
+            ));
+        }
+        Err(reason) => lines.push(format!("gpu: unavailable ({reason})")),
+    }
+    if let Some(difference) = report.difference {
+        lines.push(format!(
+            "difference: max={} mean={:.3}",
+            difference.max, difference.mean
+        ));
+    }
+    lines.join("\n")
+}
+
+pub fn present_bench(report: &BenchReport) -> String {
+    format!(
+        "bench: backend={} (adapter={}) iterations={} size={}x{}\nrender: min={:.2}ms median={:.2}ms p95={:.2}ms max={:.2}ms\nthroughput: {:.1} MP/s\npipeline: completed={} canceled={} p95_render_ms={}",
+        crate::config::backend_name(report.backend.backend),
+        report.backend.adapter_name,
+        report.iterations,
+        report.width,
+        report.height,
+        millis(report.min),
+        millis(report.median),
+        millis(report.p95),
+        millis(report.max),
+        report.megapixels_per_second,
+        report.metrics.completed_jobs,
+        report.metrics.canceled_jobs,
+        report
+            .metrics
+            .p95_render_time_ms
+            .map_or_else(|| "n/a".to_string(), |value| value.to_string())
+    )
+}
+
+fn bench_request(
+    source: Arc<DecodedBuffer>,
+    target_width: u32,
+    target_height: u32,
+) -> Result<PreviewRequest, ApplicationError> {
+    Ok(PreviewRequest {
+        image_id: ImageId::new(1)?,
+        source_path: String::new(),
+        source_pixels: Some(source),
+        source_region: None,
+        view_region: None,
+        params: EditParams::default()
+            .with_exposure(0.5)
+            .with_contrast(0.3)
+            .with_temperature(0.4)
+            .with_shadows(0.2),
+        target_width,
+        target_height,
+        want_histogram: false,
+        comparison: None,
+        split_fraction: DEFAULT_SPLIT_FRACTION,
+    })
+}
+
+fn time_backend(
+    pipeline: &dyn PreviewPipeline,
+    request: &PreviewRequest,
+) -> Result<(BackendTiming, PreviewFrame), ApplicationError> {
+    let (_, mut frame) = render_once(pipeline, request)?;
+    let mut samples = Vec::with_capacity(COMPARE_ITERATIONS);
+    for _ in 0..COMPARE_ITERATIONS {
+        let (elapsed, latest) = render_once(pipeline, request)?;
+        samples.push(elapsed);
+        frame = latest;
+    }
+    samples.sort_unstable();
+    Ok((
+        BackendTiming {
+            backend: pipeline.backend_info()?,
+            median: samples[samples.len() / 2],
+        },
+        frame,
+    ))
+}
+
+fn pixel_difference(
+    cpu: &PreviewFrame,
+    gpu: &PreviewFrame,
+) -> Result<PixelDifference, ApplicationError> {
+    if (cpu.width, cpu.height) != (gpu.width, gpu.height) || cpu.pixels.len() != gpu.pixels.len() {
+        return Err(ApplicationError::Io(format!(
+            "backend frame sizes differ: cpu {}x{}, gpu {}x{}",
+            cpu.width, cpu.height, gpu.width, gpu.height
+        )));
+    }
+    let mut max = 0_u8;
+    let mut total = 0_u64;
+    for (cpu_pixel, gpu_pixel) in cpu.pixels.iter().zip(&gpu.pixels) {
+        for shift in [16, 8, 0] {
+            let delta = ((cpu_pixel >> shift) as u8).abs_diff((gpu_pixel >> shift) as u8);
+            max = max.max(delta);
+            total += u64::from(delta);
+        }
+    }
+    let channels = (cpu.pixels.len() * 3).max(1);
+    Ok(PixelDifference {
+        max,
+        mean: total as f64 / channels as f64,
+    })
+}
+
+fn present_timing(timing: &BackendTiming) -> String {
+    format!(
+        "{}: median={:.2}ms (adapter={})",
+        crate::config::backend_name(timing.backend.backend),
+        millis(timing.median),
+        timing.backend.adapter_name
+    )
+}
+
+fn render_once(
+    pipeline: &dyn PreviewPipeline,
+    request: &PreviewRequest,
+) -> Result<(Duration, PreviewFrame), ApplicationError> {
+    let started = Instant::now();
+    pipeline.submit_preview(request.clone())?;
+    loop {
+        if let Some(frame) = pipeline.try_receive_preview()? {
+            return Ok((started.elapsed(), frame));
+        }
+        if started.elapsed() > FRAME_TIMEOUT {
+            return Err(ApplicationError::Io(
+                "timed out waiting for bench frame".to_string(),
+            ));
+        }
+        thread::yield_now();
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lite_room_adapters::BackgroundPreviewPipeline;
+    use lite_room_domain::PreviewBackend;
+
+    #[test]
+    fn bench_on_small_image_reports_nonzero_timings() {
+        let pipeline = BackgroundPreviewPipeline::with_backend(PreviewBackend::Cpu)
+            .expect("cpu pipeline")
+            .with_cache_bytes(0);
+        let source = Arc::new(DecodedBuffer {
+            width: 8,
+            height: 8,
+            rgb: [90_u8, 120, 150].repeat(64),
+        });
+
+        let report = run_bench(
+            &pipeline,
+            source,
+            BenchSettings {
+                iterations: 4,
+                target_width: 64,
+                target_height: 48,
+            },
+        )
+        .expect("bench");
+
+        assert_eq!(report.iterations, 4);
+        assert_eq!((report.width, report.height), (64, 48));
+        assert!(report.min > Duration::ZERO);
+        assert!(report.min <= report.median && report.median <= report.p95);
+        assert!(report.p95 <= report.max);
+        assert!(report.megapixels_per_second > 0.0);
+        assert_eq!(report.metrics.completed_jobs, 4 + WARMUP_ITERATIONS as u64);
+        assert!(present_bench(&report).contains("backend=cpu"));
+    }
+
+    #[test]
+    fn compare_without_gpu_reports_cpu_timing_and_unavailable_gpu() {
+        let cpu = BackgroundPreviewPipeline::with_backend(PreviewBackend::Cpu)
+            .expect("cpu pipeline")
+            .with_cache_bytes(0);
+        let source = Arc::new(DecodedBuffer {
+            width: 8,
+            height: 8,
+            rgb: [90_u8, 120, 150].repeat(64),
+        });
+
+        let report = compare_backends(&cpu, Err("no adapter".to_string()), source)
+            .expect("compare should not fail without gpu");
+
+        assert_eq!(report.cpu.backend.backend, PreviewBackend::Cpu);
+        assert!(report.cpu.median > Duration::ZERO);
+        assert!(report.gpu.is_err());
+        assert_eq!(report.difference, None);
+        let text = present_compare(&report);
+        assert!(text.contains("cpu: median="));
+        assert!(text.contains("gpu: unavailable (no adapter)"));
+    }
+
+    #[test]
+    fn pixel_difference_reports_max_and_mean_channel_delta() {
+        let frame = |pixels: Vec<u32>| PreviewFrame {
+            image_id: ImageId::new(1).expect("id"),
+            sequence: 1,
+            width: 2,
+            height: 1,
+            render_time_ms: 1,
+            pixels,
+            histogram: None,
+        };
+
+        let difference = pixel_difference(
+            &frame(vec![0x102030, 0x000000]),
+            &frame(vec![0x102036, 0x000300]),
+        )
+        .expect("same size");
+
+        assert_eq!(difference.max, 6);
+        assert!((difference.mean - 9.0 / 6.0).abs() < 1e-9);
+    }
+}
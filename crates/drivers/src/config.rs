@@ -1,7 +1,35 @@
+use std::path::{Component, Path, PathBuf};
+
+use lite_room_adapters::{
+    ThumbnailFormat, DEFAULT_MAX_DECODE_DIMENSION, DEFAULT_PREVIEW_MAX_PIXELS, THUMBNAIL_SIZE,
+};
+use lite_room_application::ApplicationError;
+use lite_room_domain::{PreviewBackend, PreviewBackendInfo};
+
+const CACHE_ARTIFACT_DIRS: [&str; 3] = ["thumbs", "merged", "reports"];
+const CATALOG_PATH_ENV: &str = "LITE_ROOM_CATALOG_PATH";
+const CACHE_DIR_ENV: &str = "LITE_ROOM_CACHE_DIR";
+const MAX_DECODE_DIMENSION_ENV: &str = "LITE_ROOM_MAX_DECODE_DIMENSION";
+const AUTOSAVE_DEBOUNCE_MS_ENV: &str = "LITE_ROOM_AUTOSAVE_DEBOUNCE_MS";
+const TARGET_FPS_ENV: &str = "LITE_ROOM_TARGET_FPS";
+const IMPORT_REPORT_ENV: &str = "LITE_ROOM_IMPORT_REPORT";
+const PREVIEW_MAX_PIXELS_ENV: &str = "LITE_ROOM_PREVIEW_MAX_PIXELS";
+const PREFETCH_NEIGHBORS_ENV: &str = "LITE_ROOM_PREFETCH_NEIGHBORS";
+const DECODE_CACHE_MB_ENV: &str = "LITE_ROOM_DECODE_CACHE_MB";
+const THUMBNAIL_FORMAT_ENV: &str = "LITE_ROOM_THUMBNAIL_FORMAT";
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub catalog_path: String,
     pub cache_dir: String,
+    pub max_decode_dimension: u32,
+    pub autosave_debounce_ms: u64,
+    pub target_fps: u32,
+    pub import_report: bool,
+    pub preview_max_pixels: usize,
+    pub prefetch_neighbors: usize,
+    pub decode_cache_mb: usize,
+    pub thumbnail_format: ThumbnailFormat,
 }
 
 impl Default for AppConfig {
@@ -9,6 +37,297 @@ impl Default for AppConfig {
         Self {
             catalog_path: "catalog.sqlite3".to_string(),
             cache_dir: "cache".to_string(),
+            max_decode_dimension: DEFAULT_MAX_DECODE_DIMENSION,
+            autosave_debounce_ms: 300,
+            target_fps: 60,
+            import_report: false,
+            preview_max_pixels: DEFAULT_PREVIEW_MAX_PIXELS,
+            prefetch_neighbors: 2,
+            decode_cache_mb: 256,
+            thumbnail_format: ThumbnailFormat::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn from_env() -> Result<Self, ApplicationError> {
+        Self::resolve(|key| std::env::var(key).ok())
+    }
+
+    pub fn resolve(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ApplicationError> {
+        let mut config = Self::default();
+        if let Some(catalog_path) = lookup(CATALOG_PATH_ENV) {
+            config.catalog_path = catalog_path;
+        }
+        if let Some(cache_dir) = lookup(CACHE_DIR_ENV) {
+            config.cache_dir = cache_dir;
+        }
+        if let Some(value) = lookup(MAX_DECODE_DIMENSION_ENV) {
+            config.max_decode_dimension = parse_env_number(MAX_DECODE_DIMENSION_ENV, &value)?;
+        }
+        if let Some(value) = lookup(AUTOSAVE_DEBOUNCE_MS_ENV) {
+            config.autosave_debounce_ms = parse_env_number(AUTOSAVE_DEBOUNCE_MS_ENV, &value)?;
+        }
+        if let Some(value) = lookup(TARGET_FPS_ENV) {
+            config.target_fps = parse_env_number(TARGET_FPS_ENV, &value)?;
+        }
+        if let Some(value) = lookup(IMPORT_REPORT_ENV) {
+            config.import_report = parse_env_flag(IMPORT_REPORT_ENV, &value)?;
+        }
+        if let Some(value) = lookup(PREVIEW_MAX_PIXELS_ENV) {
+            config.preview_max_pixels = parse_env_number(PREVIEW_MAX_PIXELS_ENV, &value)?;
+        }
+        if let Some(value) = lookup(PREFETCH_NEIGHBORS_ENV) {
+            config.prefetch_neighbors = parse_env_number(PREFETCH_NEIGHBORS_ENV, &value)?;
+        }
+        if let Some(value) = lookup(DECODE_CACHE_MB_ENV) {
+            config.decode_cache_mb = parse_env_number(DECODE_CACHE_MB_ENV, &value)?;
+        }
+        if let Some(value) = lookup(THUMBNAIL_FORMAT_ENV) {
+            config.thumbnail_format = ThumbnailFormat::parse(&value).map_err(|error| {
+                ApplicationError::InvalidInput(format!("invalid {THUMBNAIL_FORMAT_ENV}: {error}"))
+            })?;
+        }
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ApplicationError> {
+        if self.target_fps == 0 {
+            return Err(ApplicationError::InvalidInput(
+                "target_fps must be at least 1".to_string(),
+            ));
+        }
+        if self.preview_max_pixels == 0 {
+            return Err(ApplicationError::InvalidInput(
+                "preview_max_pixels must be at least 1".to_string(),
+            ));
+        }
+
+        let catalog = normalize_path(Path::new(&self.catalog_path));
+        let cache = normalize_path(Path::new(&self.cache_dir));
+
+        if cache.starts_with(&catalog) {
+            return Err(ApplicationError::InvalidInput(format!(
+                "cache_dir {} overlaps catalog_path {}",
+                self.cache_dir, self.catalog_path
+            )));
+        }
+        for artifact_dir in CACHE_ARTIFACT_DIRS {
+            if catalog.starts_with(cache.join(artifact_dir)) {
+                return Err(ApplicationError::InvalidInput(format!(
+                    "catalog_path {} is inside the cache {artifact_dir} directory {}",
+                    self.catalog_path, self.cache_dir
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Absolute form of a configured path for output. Existing paths are
+// canonicalized; a catalog or cache that is not created yet is resolved
+// against the working directory instead.
+pub fn display_path(path: &str) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| normalize_path(Path::new(path)))
+        .to_string_lossy()
+        .to_string()
+}
+
+pub fn present_config(config: &AppConfig, backend: &PreviewBackendInfo) -> String {
+    format!(
+        "catalog_path: {}\ncache_dir: {}\nmax_decode_dimension: {}\nautosave_debounce_ms: {}\ntarget_fps: {}\nimport_report: {}\npreview_max_pixels: {}\nprefetch_neighbors: {}\ndecode_cache_mb: {}\nthumbnail_size: {}\nthumbnail_format: {}\npreview backend: {} (adapter={})",
+        display_path(&config.catalog_path),
+        display_path(&config.cache_dir),
+        config.max_decode_dimension,
+        config.autosave_debounce_ms,
+        config.target_fps,
+        config.import_report,
+        config.preview_max_pixels,
+        config.prefetch_neighbors,
+        config.decode_cache_mb,
+        THUMBNAIL_SIZE,
+        config.thumbnail_format.name(),
+        backend_name(backend.backend),
+        backend.adapter_name
+    )
+}
+
+pub fn present_config_json(config: &AppConfig, backend: &PreviewBackendInfo) -> String {
+    serde_json::json!({
+        "catalog_path": display_path(&config.catalog_path),
+        "cache_dir": display_path(&config.cache_dir),
+        "max_decode_dimension": config.max_decode_dimension,
+        "autosave_debounce_ms": config.autosave_debounce_ms,
+        "target_fps": config.target_fps,
+        "import_report": config.import_report,
+        "preview_max_pixels": config.preview_max_pixels,
+        "prefetch_neighbors": config.prefetch_neighbors,
+        "decode_cache_mb": config.decode_cache_mb,
+        "thumbnail_size": THUMBNAIL_SIZE,
+        "thumbnail_format": config.thumbnail_format.name(),
+        "preview_backend": {
+            "backend": backend_name(backend.backend),
+            "adapter": backend.adapter_name,
+        },
+    })
+    .to_string()
+}
+
+pub fn backend_name(backend: PreviewBackend) -> &'static str {
+    match backend {
+        PreviewBackend::Gpu => "gpu",
+        PreviewBackend::Cpu => "cpu",
+    }
+}
+
+fn parse_env_number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, ApplicationError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ApplicationError::InvalidInput(format!("invalid {key}: {value}")))
+}
+
+fn parse_env_flag(key: &str, value: &str) -> Result<bool, ApplicationError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" | "" => Ok(false),
+        _ => Err(ApplicationError::InvalidInput(format!(
+            "invalid {key}: {value}"
+        ))),
+    }
+}
+
+fn normalize_path(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(catalog_path: &str, cache_dir: &str) -> AppConfig {
+        AppConfig {
+            catalog_path: catalog_path.to_string(),
+            cache_dir: cache_dir.to_string(),
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn config_presenter_reflects_resolved_overrides() {
+        let config = AppConfig::resolve(|key| match key {
+            "LITE_ROOM_CACHE_DIR" => Some("/var/cache/lite-room".to_string()),
+            "LITE_ROOM_AUTOSAVE_DEBOUNCE_MS" => Some("750".to_string()),
+            "LITE_ROOM_IMPORT_REPORT" => Some("true".to_string()),
+            "LITE_ROOM_PREVIEW_MAX_PIXELS" => Some("8000000".to_string()),
+            "LITE_ROOM_THUMBNAIL_FORMAT" => Some("WebP".to_string()),
+            _ => None,
+        })
+        .expect("resolve config");
+        let backend = PreviewBackendInfo {
+            backend: PreviewBackend::Cpu,
+            adapter_name: "cpu-stage".to_string(),
+        };
+
+        let text = present_config(&config, &backend);
+        let catalog = std::env::current_dir()
+            .expect("cwd")
+            .join("catalog.sqlite3");
+        assert!(text.contains(&format!("catalog_path: {}", catalog.display())));
+        assert!(text.contains("cache_dir: /var/cache/lite-room"));
+        assert!(text.contains("autosave_debounce_ms: 750"));
+        assert!(text.contains("import_report: true"));
+        assert!(text.contains("preview_max_pixels: 8000000"));
+        assert!(text.contains("thumbnail_format: webp"));
+        assert!(text.contains("preview backend: cpu (adapter=cpu-stage)"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&present_config_json(&config, &backend)).expect("json");
+        assert_eq!(json["autosave_debounce_ms"], 750);
+        assert_eq!(json["catalog_path"], catalog.to_string_lossy().as_ref());
+        assert_eq!(json["thumbnail_size"], THUMBNAIL_SIZE);
+        assert_eq!(json["thumbnail_format"], "webp");
+
+        assert!(matches!(
+            AppConfig::resolve(
+                |key| (key == "LITE_ROOM_MAX_DECODE_DIMENSION").then(|| "huge".to_string())
+            ),
+            Err(ApplicationError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            AppConfig::resolve(
+                |key| (key == "LITE_ROOM_IMPORT_REPORT").then(|| "maybe".to_string())
+            ),
+            Err(ApplicationError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            AppConfig::resolve(
+                |key| (key == "LITE_ROOM_THUMBNAIL_FORMAT").then(|| "avif".to_string())
+            ),
+            Err(ApplicationError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn relative_paths_are_displayed_absolute_without_existing() {
+        let cwd = std::env::current_dir().expect("cwd");
+        let shown = display_path("not-created/../catalogs/new.sqlite3");
+
+        assert!(Path::new(&shown).is_absolute());
+        assert_eq!(
+            PathBuf::from(shown),
+            normalize_path(&cwd).join("catalogs/new.sqlite3")
+        );
+    }
+
+    #[test]
+    fn overlapping_catalog_and_cache_are_rejected() {
+        assert!(AppConfig::default().validate().is_ok());
+        assert!(AppConfig {
+            preview_max_pixels: 0,
+            ..AppConfig::default()
+        }
+        .validate()
+        .is_err());
+        assert!(config("/data/catalog.sqlite3", "/data/cache")
+            .validate()
+            .is_ok());
+        assert!(config("/data/cache/catalog.sqlite3", "/data/cache")
+            .validate()
+            .is_ok());
+
+        for (catalog_path, cache_dir) in [
+            ("/data/catalog.sqlite3", "/data/catalog.sqlite3"),
+            ("/data/cache/thumbs/catalog.sqlite3", "/data/cache"),
+            (
+                "/data/cache/merged/../thumbs/catalog.sqlite3",
+                "/data/./cache",
+            ),
+        ] {
+            assert!(
+                matches!(
+                    config(catalog_path, cache_dir).validate(),
+                    Err(ApplicationError::InvalidInput(_))
+                ),
+                "expected conflict for {catalog_path} and {cache_dir}"
+            );
         }
     }
 }
@@ -0,0 +1,224 @@
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+
+use lite_room_application::{
+    ApplicationError, ApplicationService, InspectSchemaCommand, SchemaReport,
+};
+
+use crate::config::{display_path, AppConfig};
+
+const THUMBNAIL_DIR: &str = "thumbs";
+const WRITE_PROBE_FILE: &str = ".lite-room-doctor-probe";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryStatus {
+    pub path: String,
+    pub exists: bool,
+    pub writable: bool,
+}
+
+impl DirectoryStatus {
+    // A directory that does not exist yet counts as writable when its nearest
+    // existing ancestor is, since the cache creates it on first use.
+    pub fn check(path: &Path) -> Self {
+        let exists = path.is_dir();
+        let writable = if exists {
+            probe_writable(path)
+        } else {
+            std::path::absolute(path)
+                .unwrap_or_else(|_| path.to_path_buf())
+                .ancestors()
+                .skip(1)
+                .find(|ancestor| ancestor.is_dir())
+                .is_some_and(probe_writable)
+        };
+        Self {
+            path: display_path(&path.to_string_lossy()),
+            exists,
+            writable,
+        }
+    }
+
+    fn state(&self) -> &'static str {
+        match (self.exists, self.writable) {
+            (true, true) => "ok",
+            (false, true) => "missing, will be created",
+            (true, false) => "not writable",
+            (false, false) => "missing, cannot be created",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorReport {
+    pub catalog_path: String,
+    pub schema: SchemaReport,
+    pub cache_dir: DirectoryStatus,
+    pub thumbnail_dir: DirectoryStatus,
+}
+
+impl DoctorReport {
+    pub fn collect(
+        service: &ApplicationService,
+        config: &AppConfig,
+    ) -> Result<Self, ApplicationError> {
+        let schema = service.inspect_schema(InspectSchemaCommand)?;
+        let cache_dir = Path::new(&config.cache_dir);
+        Ok(Self {
+            catalog_path: display_path(&config.catalog_path),
+            schema,
+            cache_dir: DirectoryStatus::check(cache_dir),
+            thumbnail_dir: DirectoryStatus::check(&cache_dir.join(THUMBNAIL_DIR)),
+        })
+    }
+
+    pub fn problem_count(&self) -> usize {
+        let version_mismatch = self.schema.applied_version != self.schema.expected_version;
+        usize::from(version_mismatch)
+            + self.schema.missing_tables.len()
+            + self.schema.missing_columns.len()
+            + usize::from(!self.cache_dir.writable)
+            + usize::from(!self.thumbnail_dir.writable)
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.problem_count() == 0
+    }
+}
+
+pub fn present_doctor(report: &DoctorReport) -> String {
+    let mut lines = vec![format!(
+        "catalog: {} (schema version {} of {})",
+        report.catalog_path, report.schema.applied_version, report.schema.expected_version
+    )];
+    for table in &report.schema.missing_tables {
+        lines.push(format!("missing table: {table}"));
+    }
+    for column in &report.schema.missing_columns {
+        lines.push(format!("missing column: {column}"));
+    }
+    for (name, status) in [
+        ("cache_dir", &report.cache_dir),
+        ("thumbnail_dir", &report.thumbnail_dir),
+    ] {
+        lines.push(format!("{name}: {} ({})", status.path, status.state()));
+    }
+    lines.push(format!("status: {}", health_name(report)));
+    lines.join("\n")
+}
+
+pub fn present_doctor_json(report: &DoctorReport) -> String {
+    let directory = |status: &DirectoryStatus| {
+        serde_json::json!({
+            "path": status.path,
+            "exists": status.exists,
+            "writable": status.writable,
+        })
+    };
+    serde_json::json!({
+        "status": health_name(report),
+        "catalog_path": report.catalog_path,
+        "schema": {
+            "applied_version": report.schema.applied_version,
+            "expected_version": report.schema.expected_version,
+            "missing_tables": report.schema.missing_tables,
+            "missing_columns": report.schema.missing_columns,
+        },
+        "cache_dir": directory(&report.cache_dir),
+        "thumbnail_dir": directory(&report.thumbnail_dir),
+    })
+    .to_string()
+}
+
+fn health_name(report: &DoctorReport) -> &'static str {
+    if report.is_healthy() {
+        "healthy"
+    } else {
+        "unhealthy"
+    }
+}
+
+fn probe_writable(dir: &Path) -> bool {
+    let probe = dir.join(WRITE_PROBE_FILE);
+    let created = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .is_ok();
+    created && fs::remove_file(&probe).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lite_room_application::BootstrapCatalogCommand;
+    use tempfile::TempDir;
+
+    use crate::build_application_service;
+
+    fn config(dir: &TempDir) -> AppConfig {
+        AppConfig {
+            catalog_path: dir
+                .path()
+                .join("catalog.sqlite3")
+                .to_string_lossy()
+                .to_string(),
+            cache_dir: dir.path().join("cache").to_string_lossy().to_string(),
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn fresh_catalog_is_healthy_before_the_cache_exists() {
+        let dir = TempDir::new().expect("tempdir");
+        let config = config(&dir);
+        let service = build_application_service(&config);
+        service
+            .bootstrap_catalog(BootstrapCatalogCommand)
+            .expect("bootstrap");
+
+        let report = DoctorReport::collect(&service, &config).expect("doctor");
+        assert!(report.is_healthy(), "unexpected report: {report:?}");
+        assert!(!report.cache_dir.exists && report.cache_dir.writable);
+        assert!(!dir.path().join("cache").exists());
+
+        let text = present_doctor(&report);
+        assert!(text.contains("thumbnail_dir:"));
+        assert!(text.contains("(missing, will be created)"));
+        assert!(text.ends_with("status: healthy"));
+        let json: serde_json::Value =
+            serde_json::from_str(&present_doctor_json(&report)).expect("json");
+        assert_eq!(json["status"], "healthy");
+        assert_eq!(json["schema"]["missing_tables"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn catalog_missing_the_edits_table_is_reported() {
+        let dir = TempDir::new().expect("tempdir");
+        let config = config(&dir);
+        let service = build_application_service(&config);
+        service
+            .bootstrap_catalog(BootstrapCatalogCommand)
+            .expect("bootstrap");
+        fs::create_dir_all(dir.path().join("cache/thumbs")).expect("cache dirs");
+        rusqlite::Connection::open(&config.catalog_path)
+            .and_then(|conn| conn.execute_batch("DROP TABLE edits;"))
+            .expect("drop edits table");
+
+        let report = DoctorReport::collect(&service, &config).expect("doctor");
+        assert!(!report.is_healthy());
+        assert_eq!(report.problem_count(), 1);
+        assert_eq!(report.schema.missing_tables, vec!["edits".to_string()]);
+        assert_eq!(report.thumbnail_dir.state(), "ok");
+
+        let text = present_doctor(&report);
+        assert!(text.contains("missing table: edits"));
+        assert!(text.ends_with("status: unhealthy"));
+        let json: serde_json::Value =
+            serde_json::from_str(&present_doctor_json(&report)).expect("json");
+        assert_eq!(
+            json["schema"]["missing_tables"],
+            serde_json::json!(["edits"])
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lite_room_adapters::present_import_report_json;
+use lite_room_application::ApplicationError;
+use lite_room_domain::ImportReport;
+
+use crate::config::AppConfig;
+
+const REPORTS_DIR: &str = "reports";
+
+// An explicit --report path wins; otherwise the config flag drops a
+// timestamped report under the cache dir so repeated imports never collide.
+pub fn resolve_report_path(
+    explicit: Option<&str>,
+    config: &AppConfig,
+    timestamp: &str,
+) -> Option<PathBuf> {
+    match explicit {
+        Some(path) => Some(PathBuf::from(path)),
+        None if config.import_report => Some(
+            Path::new(&config.cache_dir)
+                .join(REPORTS_DIR)
+                .join(format!("import-{timestamp}.json")),
+        ),
+        None => None,
+    }
+}
+
+pub fn write_import_report(
+    path: &Path,
+    folder: &str,
+    finished_at: &str,
+    report: &ImportReport,
+) -> Result<(), ApplicationError> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).map_err(|error| ApplicationError::Io(error.to_string()))?;
+    }
+    fs::write(
+        path,
+        present_import_report_json(folder, finished_at, report),
+    )
+    .map_err(|error| ApplicationError::Io(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+    use lite_room_adapters::{
+        BackgroundPreviewPipeline, FsImageEncoder, FsThumbnailGenerator, ImageCrateDecoder,
+        SqliteCatalogRepository, SystemClock, WalkdirFileScanner, DEFAULT_MAX_DECODE_DIMENSION,
+    };
+    use lite_room_application::{ApplicationService, BootstrapCatalogCommand, ImportFolderCommand};
+    use lite_room_domain::PreviewBackend;
+    use tempfile::TempDir;
+
+    #[test]
+    fn import_report_file_parses_with_matching_counts() {
+        let dir = TempDir::new().expect("tempdir");
+        let photos = dir.path().join("photos");
+        fs::create_dir_all(&photos).expect("photos dir");
+        ImageBuffer::from_pixel(8, 8, Rgb([120_u8, 90, 60]))
+            .save(photos.join("good.jpg"))
+            .expect("save jpeg");
+        fs::write(photos.join("broken.jpg"), b"not a jpeg").expect("write broken");
+        fs::write(photos.join("notes.txt"), b"skip me").expect("write text");
+
+        let service = ApplicationService::new(
+            Box::new(SqliteCatalogRepository::new(
+                dir.path()
+                    .join("catalog.sqlite3")
+                    .to_string_lossy()
+                    .to_string(),
+            )),
+            Box::new(WalkdirFileScanner),
+            Box::new(FsThumbnailGenerator::new(DEFAULT_MAX_DECODE_DIMENSION)),
+            Box::new(ImageCrateDecoder::new(DEFAULT_MAX_DECODE_DIMENSION)),
+            Box::new(FsImageEncoder),
+            Box::new(SystemClock),
+            Box::new(
+                BackgroundPreviewPipeline::with_backend(PreviewBackend::Cpu).expect("cpu pipeline"),
+            ),
+        );
+        service
+            .bootstrap_catalog(BootstrapCatalogCommand)
+            .expect("bootstrap");
+        let folder = photos.to_string_lossy().to_string();
+        let report = service
+            .import_folder(ImportFolderCommand {
+                folder: folder.clone(),
+                cache_root: dir.path().join("cache").to_string_lossy().to_string(),
+                resume: false,
+                strict: false,
+                limit: None,
+                workers: 1,
+            })
+            .expect("import");
+
+        let config = AppConfig {
+            cache_dir: dir.path().join("cache").to_string_lossy().to_string(),
+            import_report: true,
+            ..AppConfig::default()
+        };
+        let path = resolve_report_path(None, &config, "1700000000").expect("report path");
+        assert_eq!(
+            path,
+            dir.path().join("cache/reports/import-1700000000.json")
+        );
+        write_import_report(&path, &folder, "1700000000", &report).expect("write report");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).expect("read report"))
+                .expect("report json");
+        assert_eq!(json["folder"], folder.as_str());
+        assert_eq!(json["scanned_files"], 3);
+        assert_eq!(json["supported_files"], 2);
+        assert_eq!(json["newly_imported"], 1);
+        assert_eq!(json["failed"], 1);
+        assert!(json["imported"][0]
+            .as_str()
+            .is_some_and(|path| path.ends_with("good.jpg")));
+        assert!(json["failures"][0]["file_path"]
+            .as_str()
+            .is_some_and(|path| path.ends_with("broken.jpg")));
+        assert!(json["failures"][0]["reason"].is_string());
+
+        assert_eq!(
+            resolve_report_path(Some("out.json"), &config, "1"),
+            Some(PathBuf::from("out.json"))
+        );
+        assert_eq!(resolve_report_path(None, &AppConfig::default(), "1"), None);
+    }
+}
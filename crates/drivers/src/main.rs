@@ -1,90 +1,444 @@
+mod about;
+mod bench;
 mod config;
+mod doctor;
+mod import_report;
 mod logging;
+mod prefetch;
 mod ui;
+mod watch;
 
+use std::path::Path;
 use std::process::ExitCode;
+use std::sync::Arc;
 
-use config::AppConfig;
+use about::{present_about, AboutInfo};
+use bench::{
+    compare_backends, present_bench, present_compare, run_bench, BenchSettings,
+    BENCH_TARGET_HEIGHT, BENCH_TARGET_WIDTH, DEFAULT_BENCH_ITERATIONS,
+};
+use config::{display_path, present_config, present_config_json, AppConfig};
+use doctor::{present_doctor, present_doctor_json, DoctorReport};
+use import_report::{resolve_report_path, write_import_report};
 use lite_room_adapters::{
-    present_decoded, present_edit_params, present_image_row, BackgroundPreviewPipeline,
+    present_caption, present_caption_json, present_decoded, present_edit_params,
+    present_edit_params_json, present_error_json, present_image_row, present_images_json,
+    present_import_failure, present_preset, BackgroundPreviewPipeline, FsImageEncoder,
     FsThumbnailGenerator, ImageCrateDecoder, SqliteCatalogRepository, SystemClock,
     WalkdirFileScanner,
 };
 use lite_room_application::{
-    ApplicationService, BootstrapCatalogCommand, ImportFolderCommand, ListImagesCommand,
-    OpenImageCommand, SetEditCommand, ShowEditCommand,
+    ApplicationError, ApplicationService, ApplyPresetCommand, AutoExposureCommand,
+    BackfillMetadataCommand, BootstrapCatalogCommand, Clock, CopyEditCommand, DeleteImageCommand,
+    ExportAllCommand, ExportImageCommand, ImageDecoder, ImportFolderCommand, ListImagesCommand,
+    ListImportFailuresCommand, ListPresetsCommand, MergeHdrCommand, MoveImageCommand,
+    OpenImageCommand, OptimizeCatalogCommand, PasteEditCommand, PreviewBackendQuery,
+    PreviewPipeline, PruneMissingCommand, RelinkCommand, ResetAllEditsCommand, ResetEditCommand,
+    RetryImportFailuresCommand, SavePresetCommand, SetCaptionCommand, SetEditCommand,
+    SetFlagCommand, SetRatingCommand, SetStackPickCommand, ShowCaptionCommand, ShowEditCommand,
+    StackImagesCommand, VerifyThumbnailsCommand, WriteSidecarCommand,
+};
+use lite_room_domain::{
+    EditParams, ImageFilter, ImageId, ImagePage, ImageSort, PreviewBackend, RawPixelFormat,
+    SortMove, FLAG_NONE, FLAG_PICK, FLAG_REJECT,
 };
-use lite_room_domain::{EditParams, ImageId};
 
 fn main() -> ExitCode {
     logging::init_logging();
-    let args: Vec<String> = std::env::args().collect();
-    let config = AppConfig::default();
-
-    let service = build_application_service(&config);
-    if let Err(error) = service.bootstrap_catalog(BootstrapCatalogCommand) {
-        eprintln!("failed to bootstrap lite-room: {error}");
-        return ExitCode::from(1);
-    }
+    let (args, json_output) = split_json_flag(std::env::args().collect());
+    let config = match AppConfig::from_env().and_then(|config| {
+        config.validate()?;
+        Ok(config)
+    }) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("invalid lite-room configuration: {error}");
+            return ExitCode::from(1);
+        }
+    };
 
-    let command = parse_command(&args);
-    match run_command(command, &service, &config) {
+    match execute(&args, &config, json_output) {
         Ok(()) => ExitCode::SUCCESS,
+        Err(CommandError::Usage(msg)) if json_output => {
+            eprintln!("{}", present_error_json("usage", &msg));
+            ExitCode::from(2)
+        }
         Err(CommandError::Usage(msg)) => {
             eprintln!("{msg}");
             print_usage();
             ExitCode::from(2)
         }
-        Err(CommandError::Runtime(msg)) => {
-            eprintln!("{msg}");
+        Err(CommandError::Runtime { code, message }) if json_output => {
+            eprintln!("{}", present_error_json(code, &message));
             ExitCode::from(1)
         }
+        Err(CommandError::Runtime { message, .. }) => {
+            eprintln!("{message}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+// Every command but `doctor` first brings the catalog up to the current
+// schema. `doctor` reports the catalog as it is on disk, so it must neither
+// migrate nor create it.
+fn execute(args: &[String], config: &AppConfig, json_output: bool) -> Result<(), CommandError> {
+    let command = parse_command(args)?;
+    let service = build_application_service(config);
+    if !matches!(command, Command::Doctor) {
+        service
+            .bootstrap_catalog(BootstrapCatalogCommand)
+            .map_err(|error| CommandError::Runtime {
+                code: error.code(),
+                message: format!(
+                    "failed to bootstrap lite-room (catalog {}): {error}",
+                    display_path(&config.catalog_path)
+                ),
+            })?;
     }
+    run_command(command, &service, config, json_output)
 }
 
 fn build_application_service(config: &AppConfig) -> ApplicationService {
     ApplicationService::new(
         Box::new(SqliteCatalogRepository::new(config.catalog_path.clone())),
         Box::new(WalkdirFileScanner),
-        Box::new(FsThumbnailGenerator),
-        Box::new(ImageCrateDecoder),
+        Box::new(
+            FsThumbnailGenerator::new(config.max_decode_dimension)
+                .with_format(config.thumbnail_format),
+        ),
+        Box::new(ImageCrateDecoder::new(config.max_decode_dimension)),
+        Box::new(FsImageEncoder),
         Box::new(SystemClock),
-        Box::new(BackgroundPreviewPipeline::new()),
+        Box::new(BackgroundPreviewPipeline::with_max_pixels(
+            config.preview_max_pixels,
+        )),
     )
 }
 
 #[derive(Debug, Clone)]
 enum Command {
-    Ui,
-    Import { folder: String },
-    List,
-    Open { image_id: i64 },
-    ShowEdit { image_id: i64 },
-    SetEdit { image_id: i64, params: EditParams },
+    Ui {
+        fps: Option<u32>,
+        histogram_window: bool,
+    },
+    About,
+    Config,
+    Import {
+        folder: String,
+        resume: bool,
+        strict: bool,
+        limit: Option<usize>,
+        workers: usize,
+        report: Option<String>,
+    },
+    Watch {
+        folder: String,
+    },
+    Failures {
+        retry: bool,
+    },
+    List {
+        sort: ImageSort,
+        filter: ImageFilter,
+        page: ImagePage,
+    },
+    Move {
+        image_id: i64,
+        target: SortMove,
+    },
+    Open {
+        image_id: i64,
+    },
+    ShowEdit {
+        image_id: i64,
+    },
+    SetEdit {
+        image_id: i64,
+        params: Box<EditParams>,
+    },
+    ResetEdit {
+        image_id: i64,
+    },
+    ResetAllEdits,
+    CopyEdit {
+        image_id: i64,
+    },
+    SavePreset {
+        name: String,
+        image_id: i64,
+    },
+    ApplyPreset {
+        name: String,
+        image_ids: Vec<i64>,
+    },
+    Presets,
+    PasteEdit {
+        edit_json: String,
+        image_ids: Vec<i64>,
+    },
+    ApplyEdit {
+        source_id: i64,
+        image_ids: Vec<i64>,
+    },
+    Caption {
+        image_id: i64,
+        caption: Option<String>,
+    },
+    Rate {
+        image_id: i64,
+        rating: i64,
+    },
+    Flag {
+        image_id: i64,
+        flag: i64,
+    },
+    MergeHdr {
+        image_ids: Vec<i64>,
+    },
+    Stack {
+        image_ids: Vec<i64>,
+    },
+    StackPick {
+        image_id: i64,
+    },
+    Export {
+        image_id: i64,
+        output_path: String,
+        quality: Option<u8>,
+        raw_format: Option<RawPixelFormat>,
+    },
+    ExportAll {
+        output_dir: String,
+        workers: usize,
+    },
+    Optimize,
+    Verify,
+    Doctor,
+    BackfillExif,
+    Prune,
+    Relink {
+        image_id: i64,
+        new_path: String,
+    },
+    Delete {
+        image_id: i64,
+    },
+    Sidecar {
+        image_id: i64,
+    },
+    AutoExposure {
+        image_id: i64,
+        apply: bool,
+    },
+    Bench {
+        image: String,
+        backend: Option<PreviewBackend>,
+        iterations: usize,
+    },
+    CompareBackends {
+        image: String,
+    },
 }
 
 #[derive(Debug, Clone)]
 enum CommandError {
     Usage(String),
-    Runtime(String),
+    Runtime { code: &'static str, message: String },
+}
+
+impl CommandError {
+    fn service(context: &str, error: ApplicationError) -> Self {
+        Self::Runtime {
+            code: error.code(),
+            message: format!("{context} failed: {error}"),
+        }
+    }
+
+    fn runtime(message: String) -> Self {
+        Self::Runtime {
+            code: "runtime",
+            message,
+        }
+    }
+}
+
+fn split_json_flag(args: Vec<String>) -> (Vec<String>, bool) {
+    let json_output = args.iter().skip(1).any(|arg| arg == "--json");
+    let args = args
+        .into_iter()
+        .enumerate()
+        .filter(|(index, arg)| *index == 0 || arg != "--json")
+        .map(|(_, arg)| arg)
+        .collect();
+    (args, json_output)
 }
 
 fn parse_command(args: &[String]) -> Result<Command, CommandError> {
     if args.len() <= 1 {
-        return Ok(Command::Ui);
+        return Ok(Command::Ui {
+            fps: None,
+            histogram_window: false,
+        });
     }
 
     match args[1].as_str() {
-        "ui" => Ok(Command::Ui),
+        "ui" => {
+            let mut fps = None;
+            let mut histogram_window = false;
+            let mut options = args[2..].iter();
+            while let Some(option) = options.next() {
+                match option.as_str() {
+                    "--fps" => {
+                        let value = options
+                            .next()
+                            .ok_or_else(|| CommandError::Usage("missing fps value".to_string()))?;
+                        fps = Some(
+                            value
+                                .parse::<u32>()
+                                .ok()
+                                .filter(|fps| *fps > 0)
+                                .ok_or_else(|| {
+                                    CommandError::Usage(format!("invalid fps: {value}"))
+                                })?,
+                        );
+                    }
+                    "--histogram-window" => histogram_window = true,
+                    other => {
+                        return Err(CommandError::Usage(format!("unknown ui option: {other}")));
+                    }
+                }
+            }
+            Ok(Command::Ui {
+                fps,
+                histogram_window,
+            })
+        }
+        "about" | "--version" | "-V" => Ok(Command::About),
+        "config" => Ok(Command::Config),
         "import" => {
             if args.len() < 3 {
-                return Err(CommandError::Usage("missing folder path".to_string()));
+                return Err(CommandError::Usage(
+                    "missing folder or file path".to_string(),
+                ));
+            }
+            let mut resume = false;
+            let mut strict = false;
+            let mut limit = None;
+            let mut workers = default_workers();
+            let mut report = None;
+            let mut options = args[3..].iter();
+            while let Some(option) = options.next() {
+                match option.as_str() {
+                    "--resume" => resume = true,
+                    "--strict" => strict = true,
+                    "--limit" => {
+                        let value = options.next().ok_or_else(|| {
+                            CommandError::Usage("missing limit value".to_string())
+                        })?;
+                        limit = Some(
+                            value
+                                .parse::<usize>()
+                                .ok()
+                                .filter(|limit| *limit > 0)
+                                .ok_or_else(|| {
+                                    CommandError::Usage(format!("invalid limit: {value}"))
+                                })?,
+                        );
+                    }
+                    "--workers" => workers = parse_worker_count(options.next())?,
+                    "--report" => {
+                        let value = options.next().ok_or_else(|| {
+                            CommandError::Usage("missing report path".to_string())
+                        })?;
+                        report = Some(value.clone());
+                    }
+                    other => {
+                        return Err(CommandError::Usage(format!(
+                            "unknown import option: {other}"
+                        )));
+                    }
+                }
             }
             Ok(Command::Import {
                 folder: args[2].clone(),
+                resume,
+                strict,
+                limit,
+                workers,
+                report,
+            })
+        }
+        "watch" => {
+            if args.len() < 3 {
+                return Err(CommandError::Usage("missing folder path".to_string()));
+            }
+            Ok(Command::Watch {
+                folder: args[2].clone(),
             })
         }
-        "list" => Ok(Command::List),
+        "list" => {
+            let mut sort = ImageSort::CaptureDate;
+            let mut filter = ImageFilter::default();
+            let mut page = ImagePage::default();
+            let mut options = args[2..].iter();
+            while let Some(option) = options.next() {
+                if option == "--collapse-stacks" {
+                    filter.collapse_stacks = true;
+                    continue;
+                }
+                let value = options
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("missing value for {option}")))?;
+                match option.as_str() {
+                    "--sort" => {
+                        sort = match value.as_str() {
+                            "date" => ImageSort::CaptureDate,
+                            "date-asc" => ImageSort::CaptureDateAsc,
+                            "rating" => ImageSort::RatingDesc,
+                            "path" => ImageSort::FilePathAsc,
+                            "custom" => ImageSort::Custom,
+                            other => {
+                                return Err(CommandError::Usage(format!("invalid sort: {other}")));
+                            }
+                        };
+                    }
+                    "--lens" => filter.lens = Some(value.clone()),
+                    "--focal-min" => filter.focal_min = Some(parse_focal_arg(value)?),
+                    "--focal-max" => filter.focal_max = Some(parse_focal_arg(value)?),
+                    "--camera" => filter.camera = Some(value.clone()),
+                    "--iso-min" => filter.iso_min = Some(parse_iso_arg(value)?),
+                    "--iso-max" => filter.iso_max = Some(parse_iso_arg(value)?),
+                    "--from" => filter.from = Some(value.clone()),
+                    "--to" => filter.to = Some(value.clone()),
+                    "--limit" => page.limit = Some(parse_list_count("limit", value)?),
+                    "--offset" => page.offset = parse_list_count("offset", value)?,
+                    other => {
+                        return Err(CommandError::Usage(format!("unknown list option: {other}")));
+                    }
+                }
+            }
+            Ok(Command::List { sort, filter, page })
+        }
+        "move" => {
+            if args.len() != 4 {
+                return Err(CommandError::Usage(
+                    "move requires 2 args: <image_id> <up|down|index>".to_string(),
+                ));
+            }
+            let image_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            let target =
+                match args[3].as_str() {
+                    "up" => SortMove::Up,
+                    "down" => SortMove::Down,
+                    value => SortMove::To(value.parse::<i64>().map_err(|_| {
+                        CommandError::Usage(format!("invalid sort index: {value}"))
+                    })?),
+                };
+            Ok(Command::Move { image_id, target })
+        }
         "open" => {
             if args.len() < 3 {
                 return Err(CommandError::Usage("missing image id".to_string()));
@@ -104,9 +458,9 @@ fn parse_command(args: &[String]) -> Result<Command, CommandError> {
             Ok(Command::ShowEdit { image_id })
         }
         "set-edit" => {
-            if args.len() != 9 {
+            if !matches!(args.len(), 9 | 11 | 12) {
                 return Err(CommandError::Usage(
-                    "set-edit requires 8 args: <image_id> <exposure> <contrast> <temperature> <tint> <highlights> <shadows>".to_string(),
+                    "set-edit requires 7, 9 or 10 args: <image_id> <exposure> <contrast> <temperature> <tint> <highlights> <shadows> [<saturation> <vibrance> [<grain>]]".to_string(),
                 ));
             }
             let image_id = args[2]
@@ -119,13 +473,450 @@ fn parse_command(args: &[String]) -> Result<Command, CommandError> {
                 tint: parse_f32_arg("tint", &args[6])?,
                 highlights: parse_f32_arg("highlights", &args[7])?,
                 shadows: parse_f32_arg("shadows", &args[8])?,
+                saturation: args
+                    .get(9)
+                    .map_or(Ok(0.0), |value| parse_f32_arg("saturation", value))?,
+                vibrance: args
+                    .get(10)
+                    .map_or(Ok(0.0), |value| parse_f32_arg("vibrance", value))?,
+                grain: args
+                    .get(11)
+                    .map_or(Ok(0.0), |value| parse_f32_arg("grain", value))?,
+                ..EditParams::default()
+            };
+            Ok(Command::SetEdit {
+                image_id,
+                params: Box::new(params),
+            })
+        }
+        "reset-edit" => match args.get(2).map(String::as_str) {
+            None => Err(CommandError::Usage("missing image id or --all".to_string())),
+            Some("--all") => Ok(Command::ResetAllEdits),
+            Some(value) => {
+                let image_id = value
+                    .parse::<i64>()
+                    .map_err(|_| CommandError::Usage(format!("invalid image id: {value}")))?;
+                Ok(Command::ResetEdit { image_id })
+            }
+        },
+        "save-preset" => {
+            if args.len() != 4 {
+                return Err(CommandError::Usage(
+                    "save-preset requires <name> <image_id>".to_string(),
+                ));
+            }
+            let image_id = args[3]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[3])))?;
+            Ok(Command::SavePreset {
+                name: args[2].clone(),
+                image_id,
+            })
+        }
+        "apply-preset" => {
+            if args.len() < 4 {
+                return Err(CommandError::Usage(
+                    "apply-preset requires <name> and at least one image id".to_string(),
+                ));
+            }
+            Ok(Command::ApplyPreset {
+                name: args[2].clone(),
+                image_ids: parse_image_ids(&args[3..])?,
+            })
+        }
+        "presets" => Ok(Command::Presets),
+        "copy-edit" => {
+            let Some(value) = args.get(2) else {
+                return Err(CommandError::Usage("missing image id".to_string()));
             };
-            Ok(Command::SetEdit { image_id, params })
+            let image_id = value
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {value}")))?;
+            Ok(Command::CopyEdit { image_id })
+        }
+        "paste-edit" => {
+            if args.len() < 4 {
+                return Err(CommandError::Usage(
+                    "paste-edit requires <edit_json> and at least one image id".to_string(),
+                ));
+            }
+            Ok(Command::PasteEdit {
+                edit_json: args[2].clone(),
+                image_ids: parse_image_ids(&args[3..])?,
+            })
+        }
+        "apply-edit" => {
+            if args.len() < 4 {
+                return Err(CommandError::Usage(
+                    "apply-edit requires a source id and at least one target id".to_string(),
+                ));
+            }
+            let source_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            Ok(Command::ApplyEdit {
+                source_id,
+                image_ids: parse_image_ids(&args[3..])?,
+            })
+        }
+        "caption" => {
+            if args.len() < 3 || args.len() > 4 {
+                return Err(CommandError::Usage(
+                    "caption requires <image_id> [text]".to_string(),
+                ));
+            }
+            let image_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            Ok(Command::Caption {
+                image_id,
+                caption: args.get(3).cloned(),
+            })
+        }
+        "rate" => {
+            if args.len() != 4 {
+                return Err(CommandError::Usage(
+                    "rate requires <image_id> <0-5>".to_string(),
+                ));
+            }
+            let image_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            let rating = args[3]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid rating: {}", args[3])))?;
+            Ok(Command::Rate { image_id, rating })
+        }
+        "flag" => {
+            if args.len() != 4 {
+                return Err(CommandError::Usage(
+                    "flag requires <image_id> <pick|reject|none>".to_string(),
+                ));
+            }
+            let image_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            let flag = match args[3].as_str() {
+                "pick" => FLAG_PICK,
+                "reject" => FLAG_REJECT,
+                "none" => FLAG_NONE,
+                other => return Err(CommandError::Usage(format!("invalid flag: {other}"))),
+            };
+            Ok(Command::Flag { image_id, flag })
+        }
+        "merge-hdr" => {
+            if args.len() < 4 {
+                return Err(CommandError::Usage(
+                    "merge-hdr requires at least 2 image ids".to_string(),
+                ));
+            }
+            let image_ids = args[2..]
+                .iter()
+                .map(|value| {
+                    value
+                        .parse::<i64>()
+                        .map_err(|_| CommandError::Usage(format!("invalid image id: {value}")))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Command::MergeHdr { image_ids })
+        }
+        "stack" => {
+            if args.len() < 4 {
+                return Err(CommandError::Usage(
+                    "stack requires at least 2 image ids".to_string(),
+                ));
+            }
+            Ok(Command::Stack {
+                image_ids: parse_image_ids(&args[2..])?,
+            })
+        }
+        "stack-pick" => {
+            if args.len() < 3 {
+                return Err(CommandError::Usage("missing image id".to_string()));
+            }
+            let image_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            Ok(Command::StackPick { image_id })
+        }
+        "export" => {
+            if args.len() < 4 {
+                return Err(CommandError::Usage(
+                    "usage: export <image_id> <output_path> [quality] [--raw rgb|bgr|rgba|bgra]"
+                        .to_string(),
+                ));
+            }
+            let image_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            let mut quality = None;
+            let mut raw_format = None;
+            let mut options = args[4..].iter();
+            while let Some(option) = options.next() {
+                if option == "--raw" {
+                    let value = options.next().ok_or_else(|| {
+                        CommandError::Usage("missing raw channel order".to_string())
+                    })?;
+                    raw_format = Some(
+                        RawPixelFormat::parse(value)
+                            .map_err(|error| CommandError::Usage(error.to_string()))?,
+                    );
+                } else if quality.is_none() {
+                    quality = Some(
+                        option
+                            .parse::<u8>()
+                            .ok()
+                            .filter(|quality| (1..=100).contains(quality))
+                            .ok_or_else(|| {
+                                CommandError::Usage(format!("invalid quality: {option}"))
+                            })?,
+                    );
+                } else {
+                    return Err(CommandError::Usage(format!(
+                        "unknown export option: {option}"
+                    )));
+                }
+            }
+            if raw_format.is_some() && quality.is_some() {
+                return Err(CommandError::Usage(
+                    "quality only applies to JPEG export".to_string(),
+                ));
+            }
+            Ok(Command::Export {
+                image_id,
+                output_path: args[3].clone(),
+                quality,
+                raw_format,
+            })
+        }
+        "export-all" => {
+            let Some(output_dir) = args.get(2) else {
+                return Err(CommandError::Usage("missing output directory".to_string()));
+            };
+            let workers = match args.get(3).map(String::as_str) {
+                None => default_workers(),
+                Some("--workers") => parse_worker_count(args.get(4))?,
+                Some(other) => {
+                    return Err(CommandError::Usage(format!(
+                        "unknown export-all option: {other}"
+                    )));
+                }
+            };
+            Ok(Command::ExportAll {
+                output_dir: output_dir.clone(),
+                workers,
+            })
+        }
+        "failures" => match args.get(2).map(String::as_str) {
+            None => Ok(Command::Failures { retry: false }),
+            Some("--retry") => Ok(Command::Failures { retry: true }),
+            Some(other) => Err(CommandError::Usage(format!(
+                "unknown failures option: {other}"
+            ))),
+        },
+        "optimize" => Ok(Command::Optimize),
+        "verify" => Ok(Command::Verify),
+        "doctor" => Ok(Command::Doctor),
+        "backfill-exif" => Ok(Command::BackfillExif),
+        "prune" => Ok(Command::Prune),
+        "relink" => {
+            if args.len() != 4 {
+                return Err(CommandError::Usage(
+                    "relink requires <image_id> <new_path>".to_string(),
+                ));
+            }
+            let image_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            Ok(Command::Relink {
+                image_id,
+                new_path: args[3].clone(),
+            })
+        }
+        "delete" => {
+            if args.len() != 3 {
+                return Err(CommandError::Usage(
+                    "delete requires <image_id>".to_string(),
+                ));
+            }
+            let image_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            Ok(Command::Delete { image_id })
+        }
+        "sidecar" => {
+            if args.len() != 3 {
+                return Err(CommandError::Usage(
+                    "sidecar requires <image_id>".to_string(),
+                ));
+            }
+            let image_id = args[2]
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {}", args[2])))?;
+            Ok(Command::Sidecar { image_id })
+        }
+        "auto-exposure" => {
+            let usage =
+                || CommandError::Usage("auto-exposure requires <image_id> [--apply]".to_string());
+            let raw_id = args.get(2).ok_or_else(usage)?;
+            let apply = match &args[3..] {
+                [] => false,
+                [flag] if flag == "--apply" => true,
+                _ => return Err(usage()),
+            };
+            let image_id = raw_id
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {raw_id}")))?;
+            Ok(Command::AutoExposure { image_id, apply })
+        }
+        "bench" => {
+            let Some(image) = args.get(2) else {
+                return Err(CommandError::Usage("missing image path".to_string()));
+            };
+            let mut backend = None;
+            let mut iterations = DEFAULT_BENCH_ITERATIONS;
+            let mut options = args[3..].iter();
+            while let Some(option) = options.next() {
+                let value = options
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("missing value for {option}")))?;
+                match option.as_str() {
+                    "--backend" => {
+                        backend = Some(match value.as_str() {
+                            "cpu" => PreviewBackend::Cpu,
+                            "gpu" => PreviewBackend::Gpu,
+                            other => {
+                                return Err(CommandError::Usage(format!(
+                                    "invalid backend: {other}"
+                                )));
+                            }
+                        });
+                    }
+                    "--iters" => {
+                        iterations = value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|iterations| *iterations > 0)
+                            .ok_or_else(|| {
+                                CommandError::Usage(format!("invalid iteration count: {value}"))
+                            })?;
+                    }
+                    other => {
+                        return Err(CommandError::Usage(format!(
+                            "unknown bench option: {other}"
+                        )));
+                    }
+                }
+            }
+            Ok(Command::Bench {
+                image: image.clone(),
+                backend,
+                iterations,
+            })
+        }
+        "compare-backends" => {
+            let Some(image) = args.get(2) else {
+                return Err(CommandError::Usage("missing image path".to_string()));
+            };
+            Ok(Command::CompareBackends {
+                image: image.clone(),
+            })
         }
         other => Err(CommandError::Usage(format!("unknown command: {other}"))),
     }
 }
 
+fn parse_image_ids(values: &[String]) -> Result<Vec<i64>, CommandError> {
+    values
+        .iter()
+        .map(|value| {
+            value
+                .parse::<i64>()
+                .map_err(|_| CommandError::Usage(format!("invalid image id: {value}")))
+        })
+        .collect()
+}
+
+fn paste_edit(
+    service: &ApplicationService,
+    command_name: &'static str,
+    edit_json: String,
+    image_ids: Vec<i64>,
+) -> Result<(), CommandError> {
+    let to = image_ids
+        .into_iter()
+        .map(ImageId::new)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+    let report = service
+        .paste_edit(PasteEditCommand { edit_json, to })
+        .map_err(|error| CommandError::service(command_name, error))?;
+    for skipped in &report.skipped {
+        eprintln!(
+            "skipped image id={}: {}",
+            skipped.image_id.get(),
+            skipped.message
+        );
+    }
+    println!(
+        "pasted edit to {} image(s), skipped={}",
+        report.applied.len(),
+        report.skipped.len()
+    );
+    if report.skipped.is_empty() {
+        Ok(())
+    } else {
+        Err(CommandError::runtime(format!(
+            "{} target image(s) were skipped",
+            report.skipped.len()
+        )))
+    }
+}
+
+fn flag_name(flag: i64) -> &'static str {
+    match flag {
+        FLAG_PICK => "pick",
+        FLAG_REJECT => "reject",
+        _ => "none",
+    }
+}
+
+fn default_workers() -> usize {
+    std::thread::available_parallelism()
+        .map(|workers| workers.get())
+        .unwrap_or(1)
+}
+
+fn parse_worker_count(value: Option<&String>) -> Result<usize, CommandError> {
+    let value = value.ok_or_else(|| CommandError::Usage("missing worker count".to_string()))?;
+    value
+        .parse::<usize>()
+        .ok()
+        .filter(|workers| *workers > 0)
+        .ok_or_else(|| CommandError::Usage(format!("invalid worker count: {value}")))
+}
+
+fn parse_list_count(name: &str, value: &str) -> Result<usize, CommandError> {
+    value
+        .parse::<usize>()
+        .map_err(|_| CommandError::Usage(format!("invalid {name}: {value}")))
+}
+
+fn parse_iso_arg(value: &str) -> Result<i64, CommandError> {
+    value
+        .parse::<u32>()
+        .map(i64::from)
+        .map_err(|_| CommandError::Usage(format!("invalid iso: {value}")))
+}
+
+fn parse_focal_arg(value: &str) -> Result<f64, CommandError> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|focal| focal.is_finite() && *focal >= 0.0)
+        .ok_or_else(|| CommandError::Usage(format!("invalid focal length: {value}")))
+}
+
 fn parse_f32_arg(name: &str, value: &str) -> Result<f32, CommandError> {
     value
         .parse::<f32>()
@@ -133,53 +924,139 @@ fn parse_f32_arg(name: &str, value: &str) -> Result<f32, CommandError> {
 }
 
 fn run_command(
-    command: Result<Command, CommandError>,
+    command: Command,
     service: &ApplicationService,
     config: &AppConfig,
+    json_output: bool,
 ) -> Result<(), CommandError> {
-    match command? {
-        Command::Ui => {
+    match command {
+        Command::Ui {
+            fps,
+            histogram_window,
+        } => {
+            let config = &AppConfig {
+                target_fps: fps.unwrap_or(config.target_fps),
+                ..config.clone()
+            };
             let images = service
-                .list_images(ListImagesCommand)
-                .map_err(|error| CommandError::Runtime(error.to_string()))?;
+                .list_images(ListImagesCommand::default())
+                .map_err(|error| CommandError::service("ui", error))?;
             let image_count = images.len();
-            let active_image_id = images.first().map(|image| image.id);
-            let active_image_path = images.first().map(|image| image.file_path.clone());
+            let initial_image = ui::select_initial_image(&images);
+            let active_image_id = initial_image.map(|image| image.id);
+            let active_image_path = initial_image.map(|image| image.file_path.clone());
             let initial_params = if let Some(image_id) = active_image_id {
                 service
                     .show_edit(ShowEditCommand { image_id })
-                    .map_err(|error| CommandError::Runtime(error.to_string()))?
+                    .map_err(|error| CommandError::service("ui", error))?
             } else {
                 EditParams::default()
             };
             ui::launch_window(
                 service,
-                &config.catalog_path,
-                &config.cache_dir,
+                config,
                 image_count,
                 active_image_id,
                 active_image_path,
                 initial_params,
+                histogram_window,
             )
-            .map_err(CommandError::Runtime)
+            .map_err(CommandError::runtime)
+        }
+        Command::About => {
+            let info = AboutInfo::collect(service)
+                .map_err(|error| CommandError::service("about", error))?;
+            println!("{}", present_about(&info));
+            Ok(())
+        }
+        Command::Config => {
+            let backend = service
+                .preview_backend_info(PreviewBackendQuery)
+                .map_err(|error| CommandError::service("config", error))?;
+            if json_output {
+                println!("{}", present_config_json(config, &backend));
+            } else {
+                println!("{}", present_config(config, &backend));
+            }
+            Ok(())
+        }
+        Command::Import {
+            folder,
+            resume,
+            strict,
+            limit,
+            workers,
+            report: report_path,
+        } => {
+            let folder = std::fs::canonicalize(&folder)
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or(folder);
+            let report = service
+                .import_folder_with_progress(
+                    ImportFolderCommand {
+                        folder: folder.clone(),
+                        cache_root: config.cache_dir.clone(),
+                        resume,
+                        strict,
+                        limit,
+                        workers,
+                    },
+                    |progress| eprintln!("imported {}/{}", progress.processed, progress.total),
+                )
+                .map_err(|error| CommandError::service("import", error))?;
+            println!(
+                "import finished: scanned={}, supported={}, newly_imported={}, duplicates={}, failed={}",
+                report.scanned_files,
+                report.supported_files,
+                report.newly_imported,
+                report.skipped_duplicates,
+                report.failures.len()
+            );
+            let finished_at = SystemClock.now_timestamp_string();
+            if let Some(path) = resolve_report_path(report_path.as_deref(), config, &finished_at) {
+                write_import_report(&path, &folder, &finished_at, &report)
+                    .map_err(|error| CommandError::service("import", error))?;
+                println!("import report written to {}", path.display());
+            }
+            Ok(())
+        }
+        Command::Failures { retry: false } => {
+            let failures = service
+                .list_import_failures(ListImportFailuresCommand)
+                .map_err(|error| CommandError::service("failures", error))?;
+            if failures.is_empty() {
+                println!("no import failures recorded");
+                return Ok(());
+            }
+            for failure in &failures {
+                println!("{}", present_import_failure(failure));
+            }
+            Ok(())
         }
-        Command::Import { folder } => {
+        Command::Failures { retry: true } => {
             let report = service
-                .import_folder(ImportFolderCommand {
-                    folder,
+                .retry_import_failures(RetryImportFailuresCommand {
                     cache_root: config.cache_dir.clone(),
                 })
-                .map_err(|error| CommandError::Runtime(format!("import failed: {error}")))?;
+                .map_err(|error| CommandError::service("failures --retry", error))?;
             println!(
-                "import finished: scanned={}, supported={}, newly_imported={}",
-                report.scanned_files, report.supported_files, report.newly_imported
+                "retry finished: retried={}, still_failing={}",
+                report.scanned_files,
+                report.failures.len()
             );
             Ok(())
         }
-        Command::List => {
+        Command::Watch { folder } => {
+            watch::watch_folder(service, &folder, &config.cache_dir).map_err(CommandError::runtime)
+        }
+        Command::List { sort, filter, page } => {
             let images = service
-                .list_images(ListImagesCommand)
-                .map_err(|error| CommandError::Runtime(format!("list failed: {error}")))?;
+                .list_images(ListImagesCommand { sort, filter, page })
+                .map_err(|error| CommandError::service("list", error))?;
+            if json_output {
+                println!("{}", present_images_json(&images));
+                return Ok(());
+            }
             if images.is_empty() {
                 println!("no images in catalog");
                 return Ok(());
@@ -189,12 +1066,25 @@ fn run_command(
             }
             Ok(())
         }
+        Command::Move { image_id, target } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let sort_index = service
+                .move_image(MoveImageCommand { image_id, target })
+                .map_err(|error| CommandError::service("move", error))?;
+            println!(
+                "moved image id={} to sort_index={}",
+                image_id.get(),
+                sort_index
+            );
+            Ok(())
+        }
         Command::Open { image_id } => {
             let image_id = ImageId::new(image_id)
                 .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
             let decoded = service
                 .open_image(OpenImageCommand { image_id })
-                .map_err(|error| CommandError::Runtime(format!("open failed: {error}")))?;
+                .map_err(|error| CommandError::service("open", error))?;
             println!("{}", present_decoded(image_id.get(), &decoded));
             Ok(())
         }
@@ -203,37 +1093,501 @@ fn run_command(
                 .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
             let params = service
                 .show_edit(ShowEditCommand { image_id })
-                .map_err(|error| CommandError::Runtime(format!("show-edit failed: {error}")))?;
-            println!("{}", present_edit_params(image_id.get(), &params));
+                .map_err(|error| CommandError::service("show-edit", error))?;
+            if json_output {
+                println!("{}", present_edit_params_json(image_id.get(), &params));
+            } else {
+                println!("{}", present_edit_params(image_id.get(), &params));
+            }
             Ok(())
         }
         Command::SetEdit { image_id, params } => {
             let image_id = ImageId::new(image_id)
                 .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
             service
-                .set_edit(SetEditCommand { image_id, params })
-                .map_err(|error| CommandError::Runtime(format!("set-edit failed: {error}")))?;
+                .set_edit(SetEditCommand {
+                    image_id,
+                    params: *params,
+                })
+                .map_err(|error| CommandError::service("set-edit", error))?;
             println!("{}", present_edit_params(image_id.get(), &params));
             Ok(())
         }
+        Command::ResetEdit { image_id } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            service
+                .reset_edit(ResetEditCommand { image_id })
+                .map_err(|error| CommandError::service("reset-edit", error))?;
+            println!(
+                "{}",
+                present_edit_params(image_id.get(), &EditParams::default())
+            );
+            Ok(())
+        }
+        Command::ResetAllEdits => {
+            let count = service
+                .reset_all_edits(ResetAllEditsCommand)
+                .map_err(|error| CommandError::service("reset-edit", error))?;
+            println!("reset edits for {count} images");
+            Ok(())
+        }
+        Command::CopyEdit { image_id } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let edit_json = service
+                .copy_edit(CopyEditCommand { from: image_id })
+                .map_err(|error| CommandError::service("copy-edit", error))?;
+            println!("{edit_json}");
+            Ok(())
+        }
+        Command::SavePreset { name, image_id } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            service
+                .save_preset(SavePresetCommand {
+                    name: name.clone(),
+                    image_id,
+                })
+                .map_err(|error| CommandError::service("save-preset", error))?;
+            println!(
+                "saved preset {} from image id={}",
+                name.trim(),
+                image_id.get()
+            );
+            Ok(())
+        }
+        Command::ApplyPreset { name, image_ids } => {
+            let image_ids = image_ids
+                .into_iter()
+                .map(ImageId::new)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let count = image_ids.len();
+            service
+                .apply_preset(ApplyPresetCommand {
+                    name: name.clone(),
+                    image_ids,
+                })
+                .map_err(|error| CommandError::service("apply-preset", error))?;
+            println!("applied preset {} to {count} image(s)", name.trim());
+            Ok(())
+        }
+        Command::Presets => {
+            let presets = service
+                .list_presets(ListPresetsCommand)
+                .map_err(|error| CommandError::service("presets", error))?;
+            if presets.is_empty() {
+                println!("no presets saved");
+            }
+            for preset in &presets {
+                println!("{}", present_preset(preset));
+            }
+            Ok(())
+        }
+        Command::PasteEdit {
+            edit_json,
+            image_ids,
+        } => paste_edit(service, "paste-edit", edit_json, image_ids),
+        Command::ApplyEdit {
+            source_id,
+            image_ids,
+        } => {
+            let source_id = ImageId::new(source_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let edit_json = service
+                .copy_edit(CopyEditCommand { from: source_id })
+                .map_err(|error| CommandError::service("apply-edit", error))?;
+            paste_edit(service, "apply-edit", edit_json, image_ids)
+        }
+        Command::Caption { image_id, caption } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            if let Some(caption) = caption {
+                service
+                    .set_caption(SetCaptionCommand { image_id, caption })
+                    .map_err(|error| CommandError::service("caption", error))?;
+            }
+            let caption = service
+                .show_caption(ShowCaptionCommand { image_id })
+                .map_err(|error| CommandError::service("caption", error))?;
+            if json_output {
+                println!(
+                    "{}",
+                    present_caption_json(image_id.get(), caption.as_deref())
+                );
+            } else {
+                println!("{}", present_caption(image_id.get(), caption.as_deref()));
+            }
+            Ok(())
+        }
+        Command::Rate { image_id, rating } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            service
+                .set_rating(SetRatingCommand { image_id, rating })
+                .map_err(|error| CommandError::service("rate", error))?;
+            println!("rated image id={} rating={rating}", image_id.get());
+            Ok(())
+        }
+        Command::Flag { image_id, flag } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            service
+                .set_flag(SetFlagCommand { image_id, flag })
+                .map_err(|error| CommandError::service("flag", error))?;
+            println!(
+                "flagged image id={} flag={}",
+                image_id.get(),
+                flag_name(flag)
+            );
+            Ok(())
+        }
+        Command::Stack { image_ids } => {
+            let image_ids = image_ids
+                .into_iter()
+                .map(ImageId::new)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let pick = image_ids[0].get();
+            let count = image_ids.len();
+            let stack_id = service
+                .stack_images(StackImagesCommand { image_ids })
+                .map_err(|error| CommandError::service("stack", error))?;
+            println!("stacked {count} images into stack_id={stack_id} (pick id={pick})");
+            Ok(())
+        }
+        Command::StackPick { image_id } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let stack_id = service
+                .set_stack_pick(SetStackPickCommand { image_id })
+                .map_err(|error| CommandError::service("stack-pick", error))?;
+            println!(
+                "image id={} is now the pick of stack_id={stack_id}",
+                image_id.get()
+            );
+            Ok(())
+        }
+        Command::MergeHdr { image_ids } => {
+            let image_ids = image_ids
+                .into_iter()
+                .map(ImageId::new)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let source_count = image_ids.len();
+            let merged_id = service
+                .merge_hdr(MergeHdrCommand {
+                    image_ids,
+                    cache_root: config.cache_dir.clone(),
+                })
+                .map_err(|error| CommandError::service("merge-hdr", error))?;
+            println!(
+                "merged {} images into image {}",
+                source_count,
+                merged_id.get()
+            );
+            Ok(())
+        }
+        Command::Export {
+            image_id,
+            output_path,
+            quality,
+            raw_format,
+        } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let written = service
+                .export_image(ExportImageCommand {
+                    image_id,
+                    output_path,
+                    quality,
+                    raw_format,
+                })
+                .map_err(|error| CommandError::service("export", error))?;
+            println!(
+                "exported image id={} to {}",
+                image_id.get(),
+                written.display()
+            );
+            Ok(())
+        }
+        Command::ExportAll {
+            output_dir,
+            workers,
+        } => {
+            let report = service
+                .export_all(
+                    ExportAllCommand {
+                        output_dir,
+                        workers,
+                    },
+                    |progress| eprintln!("exported {}/{}", progress.completed, progress.total),
+                )
+                .map_err(|error| CommandError::service("export-all", error))?;
+            for failure in &report.failures {
+                eprintln!(
+                    "export failed for image id={}: {}",
+                    failure.image_id.get(),
+                    failure.message
+                );
+            }
+            println!(
+                "export finished: total={}, exported={}, failed={}",
+                report.total,
+                report.exported.len(),
+                report.failures.len()
+            );
+            if report.failures.is_empty() {
+                Ok(())
+            } else {
+                Err(CommandError::runtime(format!(
+                    "{} image(s) failed to export",
+                    report.failures.len()
+                )))
+            }
+        }
+        Command::Optimize => {
+            let report = service
+                .optimize_catalog(OptimizeCatalogCommand)
+                .map_err(|error| CommandError::service("optimize", error))?;
+            println!(
+                "optimize finished: size_before={} bytes, size_after={} bytes",
+                report.size_before_bytes, report.size_after_bytes
+            );
+            Ok(())
+        }
+        Command::Verify => {
+            let report = service
+                .verify_thumbnails(VerifyThumbnailsCommand {
+                    cache_root: config.cache_dir.clone(),
+                })
+                .map_err(|error| CommandError::service("verify", error))?;
+            for image_id in &report.regenerated {
+                println!(
+                    "regenerated corrupted thumbnail for image id={}",
+                    image_id.get()
+                );
+            }
+            for (image_id, message) in &report.failures {
+                eprintln!(
+                    "failed to regenerate thumbnail for image id={}: {message}",
+                    image_id.get()
+                );
+            }
+            println!(
+                "verify finished: checked={} unchecked={} regenerated={} failed={}",
+                report.checked,
+                report.unchecked,
+                report.regenerated.len(),
+                report.failures.len()
+            );
+            Ok(())
+        }
+        Command::Doctor => {
+            let report = DoctorReport::collect(service, config)
+                .map_err(|error| CommandError::service("doctor", error))?;
+            if json_output {
+                println!("{}", present_doctor_json(&report));
+            } else {
+                println!("{}", present_doctor(&report));
+            }
+            if report.is_healthy() {
+                Ok(())
+            } else {
+                Err(CommandError::runtime(format!(
+                    "doctor found {} problem(s)",
+                    report.problem_count()
+                )))
+            }
+        }
+        Command::BackfillExif => {
+            let report = service
+                .backfill_metadata(BackfillMetadataCommand)
+                .map_err(|error| CommandError::service("backfill-exif", error))?;
+            println!(
+                "backfill finished: checked={} updated={} missing={}",
+                report.checked, report.updated, report.missing
+            );
+            Ok(())
+        }
+        Command::Prune => {
+            let report = service
+                .prune_missing(PruneMissingCommand)
+                .map_err(|error| CommandError::service("prune", error))?;
+            for (image_id, file_path) in &report.pruned {
+                println!("pruned image id={} {}", image_id.get(), file_path);
+            }
+            println!(
+                "prune finished: checked={} pruned={}",
+                report.checked,
+                report.pruned.len()
+            );
+            Ok(())
+        }
+        Command::Relink { image_id, new_path } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let image = service
+                .relink(RelinkCommand { image_id, new_path })
+                .map_err(|error| CommandError::service("relink", error))?;
+            println!(
+                "relinked image id={} to {}",
+                image.id.get(),
+                image.file_path
+            );
+            Ok(())
+        }
+        Command::Delete { image_id } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let thumbnails = service
+                .delete_image(DeleteImageCommand { image_id })
+                .map_err(|error| CommandError::service("delete", error))?;
+            println!(
+                "deleted image id={} (removed {} thumbnail(s))",
+                image_id.get(),
+                thumbnails.len()
+            );
+            Ok(())
+        }
+        Command::Sidecar { image_id } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let path = service
+                .write_sidecar(WriteSidecarCommand { image_id })
+                .map_err(|error| CommandError::service("sidecar", error))?;
+            println!(
+                "wrote sidecar for image id={} to {}",
+                image_id.get(),
+                path.display()
+            );
+            Ok(())
+        }
+        Command::AutoExposure { image_id, apply } => {
+            let image_id = ImageId::new(image_id)
+                .map_err(|error| CommandError::Usage(format!("invalid image id: {error}")))?;
+            let exposure = service
+                .suggest_auto_exposure(AutoExposureCommand { image_id, apply })
+                .map_err(|error| CommandError::service("auto-exposure", error))?;
+            println!(
+                "suggested exposure for image id={}: {:+.2} stops{}",
+                image_id.get(),
+                exposure,
+                if apply { " (applied)" } else { "" }
+            );
+            Ok(())
+        }
+        Command::Bench {
+            image,
+            backend,
+            iterations,
+        } => {
+            let source = ImageCrateDecoder::new(config.max_decode_dimension)
+                .decode_full(Path::new(&image))
+                .map_err(|error| CommandError::service("bench", error))?;
+            let pipeline = match backend {
+                Some(backend) => BackgroundPreviewPipeline::with_backend_and_max_pixels(
+                    backend,
+                    config.preview_max_pixels,
+                )
+                .map_err(|error| CommandError::service("bench", error))?,
+                None => BackgroundPreviewPipeline::with_max_pixels(config.preview_max_pixels),
+            }
+            .with_cache_bytes(0);
+            let report = run_bench(
+                &pipeline,
+                Arc::new(source),
+                BenchSettings {
+                    iterations,
+                    target_width: BENCH_TARGET_WIDTH,
+                    target_height: BENCH_TARGET_HEIGHT,
+                },
+            )
+            .map_err(|error| CommandError::service("bench", error))?;
+            println!("{}", present_bench(&report));
+            Ok(())
+        }
+        Command::CompareBackends { image } => {
+            let source = ImageCrateDecoder::new(config.max_decode_dimension)
+                .decode_full(Path::new(&image))
+                .map_err(|error| CommandError::service("compare-backends", error))?;
+            let cpu = BackgroundPreviewPipeline::with_backend_and_max_pixels(
+                PreviewBackend::Cpu,
+                config.preview_max_pixels,
+            )
+            .map_err(|error| CommandError::service("compare-backends", error))?
+            .with_cache_bytes(0);
+            let gpu = BackgroundPreviewPipeline::with_backend_and_max_pixels(
+                PreviewBackend::Gpu,
+                config.preview_max_pixels,
+            )
+            .map(|pipeline| pipeline.with_cache_bytes(0));
+            let report = compare_backends(
+                &cpu,
+                gpu.as_ref()
+                    .map(|pipeline| pipeline as &dyn PreviewPipeline)
+                    .map_err(ToString::to_string),
+                Arc::new(source),
+            )
+            .map_err(|error| CommandError::service("compare-backends", error))?;
+            println!("{}", present_compare(&report));
+            Ok(())
+        }
     }
 }
 
 fn print_usage() {
-    println!("usage:");
-    println!("  lite-room ui");
-    println!("  lite-room import <folder>");
-    println!("  lite-room list");
+    println!("usage: lite-room [--json] <command>");
+    println!("  lite-room ui [--fps N] [--histogram-window]");
+    println!("  lite-room about");
+    println!("  lite-room config");
+    println!(
+        "  lite-room import <folder|file> [--resume] [--strict] [--limit N] [--workers N] [--report <path>]"
+    );
+    println!("  lite-room watch <folder>");
+    println!("  lite-room failures [--retry]");
+    println!(
+        "  lite-room list [--sort date|date-asc|rating|path|custom] [--lens <text>] [--focal-min <mm>] [--focal-max <mm>] [--camera <text>] [--iso-min N] [--iso-max N] [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--collapse-stacks] [--limit N] [--offset N]"
+    );
+    println!("  lite-room move <image_id> <up|down|index>");
     println!("  lite-room open <image_id>");
     println!("  lite-room show-edit <image_id>");
     println!(
-        "  lite-room set-edit <image_id> <exposure> <contrast> <temperature> <tint> <highlights> <shadows>"
+        "  lite-room set-edit <image_id> <exposure> <contrast> <temperature> <tint> <highlights> <shadows> [<saturation> <vibrance> [<grain>]]"
     );
+    println!("  lite-room reset-edit <image_id|--all>");
+    println!("  lite-room save-preset <name> <image_id>");
+    println!("  lite-room apply-preset <name> <image_id> [image_id...]");
+    println!("  lite-room presets");
+    println!("  lite-room copy-edit <image_id>");
+    println!("  lite-room paste-edit <edit_json> <image_id> [image_id...]");
+    println!("  lite-room apply-edit <source_id> <image_id> [image_id...]");
+    println!("  lite-room caption <image_id> [text]");
+    println!("  lite-room rate <image_id> <0-5>");
+    println!("  lite-room flag <image_id> <pick|reject|none>");
+    println!("  lite-room stack <image_id> <image_id> [image_id...]");
+    println!("  lite-room stack-pick <image_id>");
+    println!("  lite-room merge-hdr <image_id> <image_id> [image_id...]");
+    println!("  lite-room export <image_id> <output_path> [quality] [--raw rgb|bgr|rgba|bgra]");
+    println!("  lite-room export-all <output_dir> [--workers N]");
+    println!("  lite-room optimize");
+    println!("  lite-room verify");
+    println!("  lite-room doctor");
+    println!("  lite-room backfill-exif");
+    println!("  lite-room prune");
+    println!("  lite-room relink <image_id> <new_path>");
+    println!("  lite-room delete <image_id>");
+    println!("  lite-room sidecar <image_id>");
+    println!("  lite-room auto-exposure <image_id> [--apply]");
+    println!("  lite-room bench <image> [--backend cpu|gpu] [--iters N]");
+    println!("  lite-room compare-backends <image>");
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lite_room_domain::ChannelOrder;
 
     #[test]
     fn parse_import_command() {
@@ -243,7 +1597,370 @@ mod tests {
             "photos".to_string(),
         ];
         let command = parse_command(&args).expect("import should parse");
-        assert!(matches!(command, Command::Import { .. }));
+        assert!(matches!(command, Command::Import { resume: false, .. }));
+
+        let mut resumed = args.clone();
+        resumed.push("--strict".to_string());
+        resumed.push("--resume".to_string());
+        let command = parse_command(&resumed).expect("resumed import should parse");
+        assert!(matches!(
+            command,
+            Command::Import {
+                resume: true,
+                strict: true,
+                limit: None,
+                ..
+            }
+        ));
+
+        let mut limited = args.clone();
+        limited.extend(["--limit".to_string(), "100".to_string()]);
+        let command = parse_command(&limited).expect("limited import should parse");
+        assert!(matches!(
+            command,
+            Command::Import {
+                limit: Some(100),
+                ..
+            }
+        ));
+        limited[4] = "0".to_string();
+        assert!(matches!(
+            parse_command(&limited),
+            Err(CommandError::Usage(_))
+        ));
+
+        let mut parallel = args.clone();
+        parallel.extend(["--workers".to_string(), "8".to_string()]);
+        let command = parse_command(&parallel).expect("parallel import should parse");
+        assert!(matches!(command, Command::Import { workers: 8, .. }));
+        parallel[4] = "0".to_string();
+        assert!(matches!(
+            parse_command(&parallel),
+            Err(CommandError::Usage(_))
+        ));
+
+        let mut reported = args.clone();
+        reported.extend(["--report".to_string(), "import.json".to_string()]);
+        let command = parse_command(&reported).expect("reported import should parse");
+        assert!(
+            matches!(command, Command::Import { report: Some(ref path), .. } if path == "import.json")
+        );
+        reported.pop();
+        assert!(matches!(
+            parse_command(&reported),
+            Err(CommandError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn parse_ui_fps_flag() {
+        let args = vec![
+            "lite-room".to_string(),
+            "ui".to_string(),
+            "--fps".to_string(),
+            "30".to_string(),
+        ];
+        let command = parse_command(&args).expect("ui should parse");
+        assert!(matches!(
+            command,
+            Command::Ui {
+                fps: Some(30),
+                histogram_window: false
+            }
+        ));
+
+        let mut detached = args.clone();
+        detached.insert(2, "--histogram-window".to_string());
+        assert!(matches!(
+            parse_command(&detached),
+            Ok(Command::Ui {
+                fps: Some(30),
+                histogram_window: true
+            })
+        ));
+
+        let mut zero = args.clone();
+        zero[3] = "0".to_string();
+        assert!(matches!(parse_command(&zero), Err(CommandError::Usage(_))));
+    }
+
+    #[test]
+    fn parse_bench_options() {
+        let args = vec![
+            "lite-room".to_string(),
+            "bench".to_string(),
+            "photo.jpg".to_string(),
+        ];
+        let command = parse_command(&args).expect("bench should parse");
+        assert!(matches!(
+            command,
+            Command::Bench {
+                backend: None,
+                iterations: DEFAULT_BENCH_ITERATIONS,
+                ..
+            }
+        ));
+
+        let mut tuned = args.clone();
+        tuned.extend(["--iters", "5", "--backend", "cpu"].map(str::to_string));
+        let command = parse_command(&tuned).expect("bench options should parse");
+        assert!(matches!(
+            command,
+            Command::Bench {
+                backend: Some(PreviewBackend::Cpu),
+                iterations: 5,
+                ..
+            }
+        ));
+
+        let mut zero = args.clone();
+        zero.extend(["--iters", "0"].map(str::to_string));
+        assert!(matches!(parse_command(&zero), Err(CommandError::Usage(_))));
+    }
+
+    #[test]
+    fn parse_compare_backends_requires_image() {
+        let args = ["lite-room", "compare-backends", "photo.jpg"].map(str::to_string);
+        assert!(matches!(
+            parse_command(&args),
+            Ok(Command::CompareBackends { image }) if image == "photo.jpg"
+        ));
+        assert!(matches!(
+            parse_command(&args[..2]),
+            Err(CommandError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn parse_about_aliases() {
+        for alias in ["about", "--version", "-V"] {
+            let args = vec!["lite-room".to_string(), alias.to_string()];
+            let command = parse_command(&args).expect("about should parse");
+            assert!(matches!(command, Command::About));
+        }
+    }
+
+    #[test]
+    fn parse_list_sort_and_move_commands() {
+        let list = vec![
+            "lite-room".to_string(),
+            "list".to_string(),
+            "--sort".to_string(),
+            "custom".to_string(),
+        ];
+        let command = parse_command(&list).expect("list should parse");
+        assert!(matches!(
+            command,
+            Command::List {
+                sort: ImageSort::Custom,
+                ..
+            }
+        ));
+
+        let filtered = vec![
+            "lite-room".to_string(),
+            "list".to_string(),
+            "--lens".to_string(),
+            "50mm".to_string(),
+            "--focal-min".to_string(),
+            "35".to_string(),
+            "--from".to_string(),
+            "2024-03-01".to_string(),
+            "--camera".to_string(),
+            "X-T5".to_string(),
+            "--iso-max".to_string(),
+            "800".to_string(),
+        ];
+        let command = parse_command(&filtered).expect("filtered list should parse");
+        assert!(matches!(
+            command,
+            Command::List { filter, .. }
+                if filter.lens.as_deref() == Some("50mm")
+                    && filter.focal_min == Some(35.0)
+                    && filter.from.as_deref() == Some("2024-03-01")
+                    && filter.to.is_none()
+                    && filter.camera.as_deref() == Some("X-T5")
+                    && filter.iso_min.is_none()
+                    && filter.iso_max == Some(800)
+        ));
+
+        let collapsed =
+            ["lite-room", "list", "--collapse-stacks", "--sort", "custom"].map(str::to_string);
+        assert!(matches!(
+            parse_command(&collapsed),
+            Ok(Command::List { sort: ImageSort::Custom, filter, page })
+                if filter.collapse_stacks && page == ImagePage::default()
+        ));
+
+        let paged = [
+            "lite-room",
+            "list",
+            "--sort",
+            "rating",
+            "--limit",
+            "20",
+            "--offset",
+            "40",
+        ]
+        .map(str::to_string);
+        assert!(matches!(
+            parse_command(&paged),
+            Ok(Command::List {
+                sort: ImageSort::RatingDesc,
+                page: ImagePage {
+                    limit: Some(20),
+                    offset: 40
+                },
+                ..
+            })
+        ));
+        let negative = ["lite-room", "list", "--offset", "-1"].map(str::to_string);
+        assert!(matches!(
+            parse_command(&negative),
+            Err(CommandError::Usage(message)) if message == "invalid offset: -1"
+        ));
+
+        let front = vec![
+            "lite-room".to_string(),
+            "move".to_string(),
+            "4".to_string(),
+            "0".to_string(),
+        ];
+        let command = parse_command(&front).expect("move should parse");
+        assert!(matches!(
+            command,
+            Command::Move {
+                image_id: 4,
+                target: SortMove::To(0)
+            }
+        ));
+    }
+
+    #[test]
+    fn json_flag_is_stripped_and_errors_keep_code() {
+        let args = vec![
+            "lite-room".to_string(),
+            "--json".to_string(),
+            "list".to_string(),
+        ];
+        let (args, json_output) = split_json_flag(args);
+        assert!(json_output);
+        assert_eq!(args, vec!["lite-room".to_string(), "list".to_string()]);
+
+        let error = CommandError::service(
+            "open",
+            ApplicationError::NotFound("image not found for id=9".to_string()),
+        );
+        assert!(matches!(
+            error,
+            CommandError::Runtime {
+                code: "not_found",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_export_all_worker_count() {
+        let args = vec![
+            "lite-room".to_string(),
+            "export-all".to_string(),
+            "out".to_string(),
+            "--workers".to_string(),
+            "4".to_string(),
+        ];
+        let command = parse_command(&args).expect("export-all should parse");
+        assert!(matches!(
+            command,
+            Command::ExportAll { output_dir, workers: 4 } if output_dir == "out"
+        ));
+
+        let mut zero = args.clone();
+        zero[4] = "0".to_string();
+        assert!(matches!(parse_command(&zero), Err(CommandError::Usage(_))));
+    }
+
+    #[test]
+    fn parse_export_with_optional_quality() {
+        let args = vec![
+            "lite-room".to_string(),
+            "export".to_string(),
+            "7".to_string(),
+            "out.jpg".to_string(),
+        ];
+        let command = parse_command(&args).expect("export should parse");
+        assert!(matches!(
+            command,
+            Command::Export { image_id: 7, output_path, quality: None, raw_format: None } if output_path == "out.jpg"
+        ));
+
+        let mut with_quality = args.clone();
+        with_quality.push("80".to_string());
+        let command = parse_command(&with_quality).expect("export with quality should parse");
+        assert!(matches!(
+            command,
+            Command::Export {
+                quality: Some(80),
+                ..
+            }
+        ));
+        with_quality[4] = "101".to_string();
+        assert!(matches!(
+            parse_command(&with_quality),
+            Err(CommandError::Usage(_))
+        ));
+
+        let mut raw = args.clone();
+        raw.extend(["--raw".to_string(), "bgra".to_string()]);
+        let command = parse_command(&raw).expect("raw export should parse");
+        assert!(matches!(
+            command,
+            Command::Export {
+                quality: None,
+                raw_format: Some(RawPixelFormat {
+                    channel_order: ChannelOrder::Bgr,
+                    alpha: true,
+                }),
+                ..
+            }
+        ));
+        raw[5] = "cmyk".to_string();
+        assert!(matches!(parse_command(&raw), Err(CommandError::Usage(_))));
+        raw[5] = "rgb".to_string();
+        raw.push("80".to_string());
+        assert!(matches!(parse_command(&raw), Err(CommandError::Usage(_))));
+    }
+
+    #[test]
+    fn parse_rate_and_flag_commands() {
+        let args = |values: &[&str]| {
+            std::iter::once("lite-room")
+                .chain(values.iter().copied())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert!(matches!(
+            parse_command(&args(&["rate", "3", "5"])),
+            Ok(Command::Rate {
+                image_id: 3,
+                rating: 5
+            })
+        ));
+        assert!(matches!(
+            parse_command(&args(&["flag", "3", "reject"])),
+            Ok(Command::Flag {
+                image_id: 3,
+                flag: FLAG_REJECT
+            })
+        ));
+        assert!(matches!(
+            parse_command(&args(&["flag", "3", "maybe"])),
+            Err(CommandError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_command(&args(&["rate", "3"])),
+            Err(CommandError::Usage(_))
+        ));
     }
 
     #[test]
@@ -272,5 +1989,284 @@ mod tests {
         ];
         let command = parse_command(&args).expect("set-edit should parse");
         assert!(matches!(command, Command::SetEdit { .. }));
+
+        let mut colored = args.clone();
+        colored.extend(["1.5", "-0.5"].map(str::to_string));
+        assert!(matches!(
+            parse_command(&colored),
+            Ok(Command::SetEdit { params, .. }) if params.saturation == 1.5 && params.vibrance == -0.5
+        ));
+        let mut grainy = colored.clone();
+        grainy.push("2.5".to_string());
+        assert!(matches!(
+            parse_command(&grainy),
+            Ok(Command::SetEdit { params, .. }) if params.grain == 2.5 && params.vibrance == -0.5
+        ));
+        colored.pop();
+        assert!(matches!(
+            parse_command(&colored),
+            Err(CommandError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn parse_reset_edit_variants() {
+        let one = vec![
+            "lite-room".to_string(),
+            "reset-edit".to_string(),
+            "3".to_string(),
+        ];
+        let command = parse_command(&one).expect("reset-edit should parse");
+        assert!(matches!(command, Command::ResetEdit { image_id: 3 }));
+
+        let all = vec![
+            "lite-room".to_string(),
+            "reset-edit".to_string(),
+            "--all".to_string(),
+        ];
+        let command = parse_command(&all).expect("reset-edit --all should parse");
+        assert!(matches!(command, Command::ResetAllEdits));
+    }
+
+    #[test]
+    fn parse_relink() {
+        let args = |rest: &[&str]| {
+            ["lite-room"]
+                .iter()
+                .chain(rest)
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(matches!(
+            parse_command(&args(&["relink", "7", "/photos/moved.jpg"])),
+            Ok(Command::Relink { image_id: 7, ref new_path }) if new_path == "/photos/moved.jpg"
+        ));
+        for invalid in [
+            &["relink", "7"][..],
+            &["relink", "x", "a.jpg"],
+            &["relink", "7", "a", "b"],
+        ] {
+            assert!(matches!(
+                parse_command(&args(invalid)),
+                Err(CommandError::Usage(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_delete() {
+        let args = |rest: &[&str]| {
+            ["lite-room"]
+                .iter()
+                .chain(rest)
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(matches!(
+            parse_command(&args(&["delete", "7"])),
+            Ok(Command::Delete { image_id: 7 })
+        ));
+        for invalid in [&["delete"][..], &["delete", "x"], &["delete", "7", "8"]] {
+            assert!(matches!(
+                parse_command(&args(invalid)),
+                Err(CommandError::Usage(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_sidecar() {
+        assert!(matches!(
+            parse_command(&[
+                "lite-room".to_string(),
+                "sidecar".to_string(),
+                "12".to_string()
+            ]),
+            Ok(Command::Sidecar { image_id: 12 })
+        ));
+        assert!(matches!(
+            parse_command(&["lite-room".to_string(), "sidecar".to_string()]),
+            Err(CommandError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn parse_auto_exposure() {
+        let args = |rest: &[&str]| {
+            ["lite-room", "auto-exposure"]
+                .iter()
+                .chain(rest)
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(matches!(
+            parse_command(&args(&["4"])),
+            Ok(Command::AutoExposure {
+                image_id: 4,
+                apply: false
+            })
+        ));
+        assert!(matches!(
+            parse_command(&args(&["4", "--apply"])),
+            Ok(Command::AutoExposure {
+                image_id: 4,
+                apply: true
+            })
+        ));
+        for bad in [&[][..], &["4", "--force"], &["4", "--apply", "5"], &["x"]] {
+            assert!(matches!(
+                parse_command(&args(bad)),
+                Err(CommandError::Usage(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_save_and_apply_preset() {
+        let args = |rest: &[&str]| {
+            ["lite-room"]
+                .iter()
+                .chain(rest)
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(matches!(
+            parse_command(&args(&["save-preset", "warm", "4"])),
+            Ok(Command::SavePreset { ref name, image_id: 4 }) if name == "warm"
+        ));
+        assert!(matches!(
+            parse_command(&args(&["apply-preset", "warm", "2", "3"])),
+            Ok(Command::ApplyPreset { ref name, ref image_ids }) if name == "warm" && image_ids == &[2, 3]
+        ));
+        assert!(matches!(
+            parse_command(&args(&["presets"])),
+            Ok(Command::Presets)
+        ));
+        for invalid in [
+            &["save-preset", "warm"][..],
+            &["save-preset", "warm", "x"],
+            &["apply-preset", "warm"],
+        ] {
+            assert!(
+                matches!(parse_command(&args(invalid)), Err(CommandError::Usage(_))),
+                "expected usage error for {invalid:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_copy_paste_and_apply_edit() {
+        let args = |rest: &[&str]| {
+            ["lite-room"]
+                .iter()
+                .chain(rest)
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(matches!(
+            parse_command(&args(&["copy-edit", "4"])),
+            Ok(Command::CopyEdit { image_id: 4 })
+        ));
+        assert!(matches!(
+            parse_command(&args(&["paste-edit", "{\"exposure\":1.0}", "2", "3"])),
+            Ok(Command::PasteEdit { ref edit_json, ref image_ids })
+                if edit_json == "{\"exposure\":1.0}" && image_ids == &[2, 3]
+        ));
+        assert!(matches!(
+            parse_command(&args(&["apply-edit", "1", "2", "3"])),
+            Ok(Command::ApplyEdit { source_id: 1, ref image_ids }) if image_ids == &[2, 3]
+        ));
+        for invalid in [
+            &["copy-edit"][..],
+            &["paste-edit", "{}"],
+            &["apply-edit", "1"],
+            &["apply-edit", "1", "two"],
+        ] {
+            assert!(
+                matches!(parse_command(&args(invalid)), Err(CommandError::Usage(_))),
+                "expected usage error for {invalid:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_merge_hdr_requires_two_ids() {
+        let one = vec![
+            "lite-room".to_string(),
+            "merge-hdr".to_string(),
+            "1".to_string(),
+        ];
+        assert!(matches!(parse_command(&one), Err(CommandError::Usage(_))));
+
+        let two = vec![
+            "lite-room".to_string(),
+            "merge-hdr".to_string(),
+            "1".to_string(),
+            "2".to_string(),
+        ];
+        let command = parse_command(&two).expect("merge-hdr should parse");
+        assert!(matches!(command, Command::MergeHdr { image_ids } if image_ids == vec![1, 2]));
+    }
+
+    #[test]
+    fn doctor_reports_an_old_catalog_without_migrating_or_creating_it() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let catalog_path = dir.path().join("catalog.sqlite3");
+        let config = AppConfig {
+            catalog_path: catalog_path.to_string_lossy().to_string(),
+            cache_dir: dir.path().join("cache").to_string_lossy().to_string(),
+            ..AppConfig::default()
+        };
+        let run = |command: &str| {
+            execute(
+                &["lite-room".to_string(), command.to_string()],
+                &config,
+                false,
+            )
+        };
+        let schema_version = || {
+            SqliteCatalogRepository::new(config.catalog_path.clone())
+                .current_schema_version()
+                .expect("schema version")
+        };
+
+        assert!(matches!(run("doctor"), Err(CommandError::Runtime { .. })));
+        assert!(!catalog_path.exists(), "doctor created the catalog");
+
+        let conn = rusqlite::Connection::open(&catalog_path).expect("open catalog");
+        conn.execute_batch(
+            "CREATE TABLE schema_migrations (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at TEXT NOT NULL
+            );",
+        )
+        .expect("migrations table");
+        for (index, (name, sql)) in lite_room_adapters::migrations::MIGRATIONS[..3]
+            .iter()
+            .enumerate()
+        {
+            conn.execute_batch(sql).expect("old migration");
+            conn.execute(
+                "INSERT INTO schema_migrations (version, name, applied_at) VALUES (?1, ?2, '1')",
+                rusqlite::params![index + 1, name],
+            )
+            .expect("record migration");
+        }
+        drop(conn);
+
+        let old = run("doctor");
+        assert!(
+            matches!(&old, Err(CommandError::Runtime { message, .. }) if message.starts_with("doctor found")),
+            "unexpected result: {old:?}"
+        );
+        assert_eq!(schema_version(), 3);
+
+        run("list").expect("list migrates the catalog");
+        assert_eq!(
+            schema_version(),
+            lite_room_adapters::migrations::MIGRATIONS.len()
+        );
+        run("doctor").expect("migrated catalog is healthy");
     }
 }
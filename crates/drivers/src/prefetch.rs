@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use lite_room_domain::{DecodedBuffer, ImageId};
+
+const PREFETCH_WORKERS: usize = 2;
+const PREFETCH_THREAD_NAME: &str = "lite-room-prefetch";
+
+pub type DecodeFn = dyn Fn(&str) -> Option<Arc<DecodedBuffer>> + Send + Sync;
+
+// Catalog indices to warm around `current`, nearest first and alternating
+// next/previous, wrapping at either end. Never includes `current` itself.
+pub fn prefetch_targets(current: usize, len: usize, neighbors: usize) -> Vec<usize> {
+    let mut targets = Vec::new();
+    if len < 2 || current >= len {
+        return targets;
+    }
+    for distance in 1..=neighbors.min(len - 1) {
+        for index in [(current + distance) % len, (current + len - distance) % len] {
+            if index != current && !targets.contains(&index) {
+                targets.push(index);
+            }
+        }
+    }
+    targets
+}
+
+// Decoded full-size sources keyed by image, evicting the least recently used
+// once the byte budget is exceeded. A source larger than the whole budget is
+// never kept.
+#[derive(Debug)]
+pub struct DecodedImageCache {
+    budget_bytes: usize,
+    used_bytes: usize,
+    entries: VecDeque<(ImageId, Arc<DecodedBuffer>)>,
+}
+
+impl DecodedImageCache {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            budget_bytes,
+            used_bytes: 0,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn get(&mut self, image_id: ImageId) -> Option<Arc<DecodedBuffer>> {
+        let position = self.entries.iter().position(|(id, _)| *id == image_id)?;
+        let entry = self.entries.remove(position)?;
+        let source = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(source)
+    }
+
+    pub fn contains(&self, image_id: ImageId) -> bool {
+        self.entries.iter().any(|(id, _)| *id == image_id)
+    }
+
+    pub fn insert(&mut self, image_id: ImageId, source: Arc<DecodedBuffer>) {
+        if let Some(position) = self.entries.iter().position(|(id, _)| *id == image_id) {
+            if let Some((_, old)) = self.entries.remove(position) {
+                self.used_bytes -= old.rgb.len();
+            }
+        }
+        let bytes = source.rgb.len();
+        if bytes > self.budget_bytes {
+            return;
+        }
+        while self.used_bytes + bytes > self.budget_bytes {
+            let Some((_, evicted)) = self.entries.pop_front() else {
+                break;
+            };
+            self.used_bytes -= evicted.rgb.len();
+        }
+        self.used_bytes += bytes;
+        self.entries.push_back((image_id, source));
+    }
+}
+
+struct PrefetchJob {
+    generation: u64,
+    image_id: ImageId,
+    path: String,
+}
+
+// A couple of background threads that decode neighbor images for the cache.
+// `warm` supersedes every job from earlier calls: queued jobs are dropped and
+// decodes already running are discarded when they finish.
+pub struct ImagePrefetcher {
+    jobs: Option<mpsc::Sender<PrefetchJob>>,
+    decoded: mpsc::Receiver<(u64, ImageId, Arc<DecodedBuffer>)>,
+    generation: Arc<AtomicU64>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl ImagePrefetcher {
+    pub fn new(decode: Arc<DecodeFn>) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<PrefetchJob>();
+        let (decoded_tx, decoded) = mpsc::channel();
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let generation = Arc::new(AtomicU64::new(0));
+        let workers = (0..PREFETCH_WORKERS)
+            .filter_map(|_| {
+                let job_rx = Arc::clone(&job_rx);
+                let decoded_tx = decoded_tx.clone();
+                let generation = Arc::clone(&generation);
+                let decode = Arc::clone(&decode);
+                thread::Builder::new()
+                    .name(PREFETCH_THREAD_NAME.to_string())
+                    .spawn(move || loop {
+                        let job = match job_rx.lock() {
+                            Ok(receiver) => receiver.recv(),
+                            Err(_) => return,
+                        };
+                        let Ok(job) = job else {
+                            return;
+                        };
+                        if job.generation != generation.load(Ordering::Acquire) {
+                            continue;
+                        }
+                        let Some(source) = decode(&job.path) else {
+                            continue;
+                        };
+                        if decoded_tx
+                            .send((job.generation, job.image_id, source))
+                            .is_err()
+                        {
+                            return;
+                        }
+                    })
+                    .ok()
+            })
+            .collect();
+        Self {
+            jobs: Some(job_tx),
+            decoded,
+            generation,
+            workers,
+        }
+    }
+
+    pub fn warm(&self, images: impl IntoIterator<Item = (ImageId, String)>) {
+        let generation = self.generation.fetch_add(1, Ordering::AcqRel) + 1;
+        let Some(jobs) = &self.jobs else {
+            return;
+        };
+        for (image_id, path) in images {
+            let _ = jobs.send(PrefetchJob {
+                generation,
+                image_id,
+                path,
+            });
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    // Moves finished decodes from the current generation into `cache`.
+    pub fn drain_into(&self, cache: &mut DecodedImageCache) -> usize {
+        let current = self.generation.load(Ordering::Acquire);
+        let mut stored = 0;
+        while let Ok((generation, image_id, source)) = self.decoded.try_recv() {
+            if generation == current {
+                cache.insert(image_id, source);
+                stored += 1;
+            }
+        }
+        stored
+    }
+}
+
+impl Drop for ImagePrefetcher {
+    fn drop(&mut self) {
+        self.cancel();
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn buffer(bytes: usize) -> Arc<DecodedBuffer> {
+        Arc::new(DecodedBuffer {
+            width: (bytes / 3) as u32,
+            height: 1,
+            rgb: vec![0; bytes],
+        })
+    }
+
+    fn id(value: i64) -> ImageId {
+        ImageId::new(value).expect("id")
+    }
+
+    #[test]
+    fn targets_alternate_nearest_neighbors_and_wrap_around() {
+        assert_eq!(prefetch_targets(3, 10, 2), vec![4, 2, 5, 1]);
+        assert_eq!(prefetch_targets(0, 10, 2), vec![1, 9, 2, 8]);
+        assert_eq!(prefetch_targets(9, 10, 1), vec![0, 8]);
+        assert_eq!(prefetch_targets(1, 3, 2), vec![2, 0]);
+        assert_eq!(prefetch_targets(0, 2, 2), vec![1]);
+        assert!(prefetch_targets(0, 1, 2).is_empty());
+        assert!(prefetch_targets(0, 0, 2).is_empty());
+        assert!(prefetch_targets(4, 10, 0).is_empty());
+    }
+
+    #[test]
+    fn cache_evicts_least_recently_used_to_stay_within_budget() {
+        let mut cache = DecodedImageCache::new(300);
+        cache.insert(id(1), buffer(120));
+        cache.insert(id(2), buffer(120));
+        assert!(cache.get(id(1)).is_some());
+        cache.insert(id(3), buffer(120));
+
+        assert!(cache.contains(id(1)) && cache.contains(id(3)));
+        assert!(!cache.contains(id(2)));
+        assert_eq!(cache.used_bytes, 240);
+
+        cache.insert(id(4), buffer(301));
+        assert!(!cache.contains(id(4)));
+        assert_eq!(cache.used_bytes, 240);
+    }
+
+    #[test]
+    fn warm_decodes_in_the_background_and_a_newer_warm_drops_stale_results() {
+        let decode: Arc<DecodeFn> = Arc::new(|path: &str| Some(buffer(path.len() * 3)));
+        let prefetcher = ImagePrefetcher::new(decode);
+        let mut cache = DecodedImageCache::new(1_000);
+
+        prefetcher.warm([(id(1), "a".to_string()), (id(2), "bb".to_string())]);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !(cache.contains(id(1)) && cache.contains(id(2))) {
+            prefetcher.drain_into(&mut cache);
+            assert!(Instant::now() < deadline, "prefetch never finished");
+            thread::yield_now();
+        }
+
+        prefetcher.warm([(id(3), "ccc".to_string())]);
+        prefetcher.cancel();
+        thread::sleep(Duration::from_millis(50));
+        prefetcher.drain_into(&mut cache);
+        assert!(!cache.contains(id(3)));
+    }
+}
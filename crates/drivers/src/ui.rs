@@ -1,14 +1,24 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use font8x8::UnicodeFonts;
 use image::io::Reader as ImageReader;
 use lite_room_application::{
     ApplicationService, ListImagesCommand, PollPreviewCommand, PreviewMetricsQuery,
-    SetEditCommand, ShowEditCommand, SubmitPreviewCommand,
+    SetEditCommand, SetFlagCommand, SetRatingCommand, ShowEditCommand, SubmitPreviewCommand,
+};
+use lite_room_domain::{
+    kelvin_to_temperature, temperature_to_kelvin, ClippingStats, DecodedBuffer, EditField,
+    EditParams, ImageId, ImageRecord, PreviewFrame, PreviewHistogram, PreviewMetrics, ViewRegion,
+    DEFAULT_SPLIT_FRACTION, FLAG_NONE, FLAG_PICK, FLAG_REJECT, HISTOGRAM_BINS, MAX_RATING,
 };
-use lite_room_domain::{EditParams, ImageId, PreviewFrame, PreviewMetrics};
 use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
 
+use crate::config::{display_path, AppConfig};
+use crate::prefetch::{prefetch_targets, DecodedImageCache, ImagePrefetcher};
+
 const SLIDER_MIN: f32 = -5.0;
 const SLIDER_MAX: f32 = 5.0;
 const WINDOW_WIDTH: usize = 1120;
@@ -23,6 +33,30 @@ const CONTROL_PANEL_WIDTH: usize = 300;
 const CONTROL_INSET: usize = 18;
 const SLIDER_HEIGHT: usize = 54;
 const SLIDER_GAP: usize = 14;
+const LOADING_DOT_COUNT: usize = 8;
+const LOADING_STEP_MS: u64 = 90;
+const DISABLED_SLIDER_COLOR: u32 = 0xBDB5A8;
+const PLACEHOLDER_GLYPH: [u8; 8] = [0x7E, 0x42, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x00];
+const MAX_KELVIN_ENTRY_DIGITS: usize = 5;
+const EDIT_HISTORY_CAPACITY: usize = 50;
+const RATING_ROW_GAP: usize = 16;
+const STAR_BUTTON_SIZE: usize = 20;
+const STAR_BUTTON_GAP: usize = 6;
+const FLAG_BUTTON_WIDTH: usize = 44;
+const ONBOARDING_BUTTON_WIDTH: usize = 120;
+const ONBOARDING_BUTTON_HEIGHT: usize = 28;
+const ONBOARDING_IMPORT_HINT: &str = "IMPORT PHOTOS WITH `lite-room import <folder>`";
+const FILM_STRIP_HEIGHT: usize = 76;
+const FILM_STRIP_GAP: usize = 12;
+const FILM_STRIP_PADDING: usize = 8;
+const FILM_THUMB_SIZE: usize = 60;
+const FILM_THUMB_GAP: usize = 8;
+const VIEW_ZOOM_STEP: f32 = 1.25;
+const DOUBLE_CLICK_MS: u64 = 400;
+const HISTOGRAM_WINDOW_WIDTH: usize = 544;
+const HISTOGRAM_WINDOW_HEIGHT: usize = 300;
+const HISTOGRAM_MARGIN: usize = 16;
+const HISTOGRAM_PLOT_HEIGHT: usize = 200;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SliderField {
@@ -34,6 +68,12 @@ enum SliderField {
     Shadows,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RatingButton {
+    Star(i64),
+    Flag(i64),
+}
+
 #[derive(Debug, Clone, Copy)]
 struct SliderSpec {
     field: SliderField,
@@ -74,6 +114,56 @@ impl DebouncedAutosave {
     }
 }
 
+// Committed edit states for the active image; `cursor` is the one on screen.
+// Pushing after an undo drops the redo tail, and the oldest state falls off
+// once `capacity` is reached.
+struct EditHistory {
+    capacity: usize,
+    states: VecDeque<EditParams>,
+    cursor: usize,
+}
+
+impl EditHistory {
+    fn new(capacity: usize, initial: EditParams) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            states: VecDeque::from([initial]),
+            cursor: 0,
+        }
+    }
+
+    fn reset(&mut self, initial: EditParams) {
+        self.states.clear();
+        self.states.push_back(initial);
+        self.cursor = 0;
+    }
+
+    fn push(&mut self, params: EditParams) {
+        if self.states.get(self.cursor) == Some(&params) {
+            return;
+        }
+        self.states.truncate(self.cursor + 1);
+        self.states.push_back(params);
+        if self.states.len() > self.capacity {
+            self.states.pop_front();
+        }
+        self.cursor = self.states.len() - 1;
+    }
+
+    fn undo(&mut self) -> Option<EditParams> {
+        self.cursor = self.cursor.checked_sub(1)?;
+        self.states.get(self.cursor).copied()
+    }
+
+    fn redo(&mut self) -> Option<EditParams> {
+        if self.cursor + 1 >= self.states.len() {
+            return None;
+        }
+        self.cursor += 1;
+        self.states.get(self.cursor).copied()
+    }
+}
+
 #[derive(Debug, Clone)]
 struct PreviewCanvas {
     width: usize,
@@ -81,9 +171,73 @@ struct PreviewCanvas {
     pixels: Vec<u32>,
 }
 
+#[derive(Debug, Default)]
+struct FilmStrip {
+    first_visible: usize,
+    tiles: HashMap<ImageId, Option<PreviewCanvas>>,
+}
+
+impl FilmStrip {
+    fn tile(&mut self, image_id: ImageId, thumbnail_path: Option<&str>) -> Option<&PreviewCanvas> {
+        self.tiles
+            .entry(image_id)
+            .or_insert_with(|| {
+                load_preview_source(thumbnail_path)
+                    .and_then(|source| fit_canvas(&source, FILM_THUMB_SIZE, FILM_THUMB_SIZE))
+            })
+            .as_ref()
+    }
+}
+
+struct HistogramWindow {
+    window: Window,
+    buffer: Vec<u32>,
+    histogram: PreviewHistogram,
+}
+
+impl HistogramWindow {
+    fn open(target_fps: u32) -> Result<Self, String> {
+        let mut window = Window::new(
+            "lite-room | histogram",
+            HISTOGRAM_WINDOW_WIDTH,
+            HISTOGRAM_WINDOW_HEIGHT,
+            WindowOptions::default(),
+        )
+        .map_err(|error| format!("failed to start histogram window: {error}"))?;
+        window.limit_update_rate(Some(frame_interval(target_fps)));
+        Ok(Self {
+            window,
+            buffer: vec![0; HISTOGRAM_WINDOW_WIDTH * HISTOGRAM_WINDOW_HEIGHT],
+            histogram: PreviewHistogram::default(),
+        })
+    }
+
+    fn present(
+        &mut self,
+        latest_frame: Option<&PreviewFrame>,
+        metrics: &PreviewMetrics,
+    ) -> Result<(), String> {
+        draw_histogram_panel(
+            &mut self.buffer,
+            HISTOGRAM_WINDOW_WIDTH,
+            &self.histogram,
+            latest_frame,
+            metrics,
+        );
+        self.window
+            .update_with_buffer(
+                &self.buffer,
+                HISTOGRAM_WINDOW_WIDTH,
+                HISTOGRAM_WINDOW_HEIGHT,
+            )
+            .map_err(|error| format!("failed to update histogram window: {error}"))
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct TitleTelemetry<'a> {
     latest_frame: Option<&'a PreviewFrame>,
+    clipping: Option<ClippingStats>,
     metrics: &'a PreviewMetrics,
     preview_canvas: Option<&'a PreviewCanvas>,
     image_index: Option<(usize, usize)>,
@@ -92,42 +246,80 @@ struct TitleTelemetry<'a> {
 
 pub fn launch_window(
     service: &ApplicationService,
-    catalog_path: &str,
-    cache_dir: &str,
+    config: &AppConfig,
     image_count: usize,
     image_id: Option<ImageId>,
     image_path: Option<String>,
     initial_params: EditParams,
+    histogram_window: bool,
 ) -> Result<(), String> {
     let width = WINDOW_WIDTH;
     let height = WINDOW_HEIGHT;
     let sliders = slider_specs();
+    let rating_top = rating_row_top(&sliders);
+    let onboarding = should_show_onboarding(image_count, image_id);
+    let catalog_path = display_path(&config.catalog_path);
+    let cache_dir = display_path(&config.cache_dir);
 
     let mut window = Window::new(
         &format!(
             "lite-room | catalog={} | cache={} | images={}",
-            catalog_path, cache_dir, image_count
+            ascii_display(&catalog_path),
+            ascii_display(&cache_dir),
+            image_count
         ),
         width,
         height,
         WindowOptions::default(),
     )
     .map_err(|error| format!("failed to start UI window: {error}"))?;
-    window.limit_update_rate(Some(Duration::from_micros(16_000)));
+    window.limit_update_rate(Some(frame_interval(config.target_fps)));
+    let mut histogram_window = if histogram_window {
+        Some(HistogramWindow::open(config.target_fps)?)
+    } else {
+        None
+    };
 
     let mut buffer = vec![0x222222_u32; width * height];
     let start = Instant::now();
     let mut params = initial_params;
-    let mut autosave = DebouncedAutosave::new(300);
+    let mut autosave = DebouncedAutosave::new(config.autosave_debounce_ms);
+    let mut history = EditHistory::new(EDIT_HISTORY_CAPACITY, params);
     let mut active_drag: Option<SliderField> = None;
+    let mut view = ViewRegion::FIT;
+    let mut pan_anchor: Option<(f32, f32)> = None;
+    let mut kelvin_entry = String::new();
     let mut was_mouse_down = false;
+    let mut last_slider_press: Option<(SliderField, u64)> = None;
     let mut latest_frame: Option<PreviewFrame> = None;
+    let mut latest_clipping: Option<ClippingStats> = None;
+    let mut preview_submitted = false;
+    let mut last_preview_error: Option<String> = None;
     let mut active_image_id = image_id;
     let mut active_image_path = image_path;
-    let mut preview = load_preview_canvas(active_image_path.as_deref(), width, height);
-    let catalog_images = service
-        .list_images(ListImagesCommand)
-        .map_err(|error| format!("list images failed: {error}"))?;
+    let mut decoded_cache =
+        DecodedImageCache::new(config.decode_cache_mb.saturating_mul(1024 * 1024));
+    let prefetcher = (config.prefetch_neighbors > 0)
+        .then(|| ImagePrefetcher::new(Arc::new(|path: &str| load_preview_source(Some(path)))));
+    let mut preview_source = cached_preview_source(
+        &mut decoded_cache,
+        active_image_id,
+        active_image_path.as_deref(),
+    );
+    let mut preview = preview_source
+        .as_deref()
+        .and_then(|source| preview_canvas_from_source(source, width, height));
+    let mut catalog_images = Vec::new();
+    let mut thumbnail_paths: HashMap<ImageId, String> = HashMap::new();
+    for entry in service
+        .list_images_with_thumbnails(ListImagesCommand::default())
+        .map_err(|error| format!("list images failed: {error}"))?
+    {
+        if let Some(thumbnail) = entry.thumbnail {
+            thumbnail_paths.insert(entry.image.id, thumbnail.file_path);
+        }
+        catalog_images.push(entry.image);
+    }
     let mut active_index = active_image_id.and_then(|id| {
         catalog_images
             .iter()
@@ -135,15 +327,73 @@ pub fn launch_window(
             .find(|(_, image)| image.id == id)
             .map(|(index, _)| index)
     });
+    let mut film_strip = FilmStrip::default();
+    if let (Some(prefetcher), Some(index)) = (&prefetcher, active_index) {
+        warm_neighbors(
+            prefetcher,
+            &decoded_cache,
+            &catalog_images,
+            index,
+            config.prefetch_neighbors,
+        );
+    }
 
     if let Some(id) = active_image_id {
-        submit_preview(service, id, params, width as u32, height as u32)?;
+        submit_preview(
+            service,
+            id,
+            params,
+            (width as u32, height as u32),
+            preview_source.clone(),
+            view,
+            histogram_window.is_some(),
+        )?;
+        preview_submitted = true;
     }
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
+        if histogram_window
+            .as_ref()
+            .is_some_and(|detached| detached.window.is_key_down(Key::Escape))
+        {
+            break;
+        }
+        if histogram_window
+            .as_ref()
+            .is_some_and(|detached| !detached.window.is_open())
+        {
+            histogram_window = None;
+        }
         let go_prev = window.is_key_pressed(Key::Left, KeyRepeat::No);
         let go_next = window.is_key_pressed(Key::Right, KeyRepeat::No);
-        if !catalog_images.is_empty() && (go_prev || go_next) {
+        let mouse_down = window.get_mouse_down(MouseButton::Left);
+        let mouse_pos = window.get_mouse_pos(MouseMode::Clamp);
+        let clicked_strip_index = if mouse_down && !was_mouse_down {
+            mouse_pos.and_then(|(mouse_x, mouse_y)| {
+                film_strip_index_at_position(
+                    mouse_x,
+                    mouse_y,
+                    width,
+                    height,
+                    film_strip.first_visible,
+                    catalog_images.len(),
+                )
+            })
+        } else {
+            None
+        };
+        let len = catalog_images.len();
+        let current = active_index.unwrap_or(0);
+        let requested_index = if len == 0 {
+            None
+        } else if go_next {
+            Some((current + 1) % len)
+        } else if go_prev {
+            Some((current + len - 1) % len)
+        } else {
+            clicked_strip_index.filter(|index| Some(*index) != active_index)
+        };
+        if let Some(next) = requested_index {
             if autosave.is_dirty() {
                 if let Some(id) = active_image_id {
                     persist_edit(service, id, params)?;
@@ -151,14 +401,6 @@ pub fn launch_window(
                 autosave.clear();
             }
 
-            let len = catalog_images.len();
-            let current = active_index.unwrap_or(0);
-            let next = if go_next {
-                (current + 1) % len
-            } else {
-                (current + len - 1) % len
-            };
-
             let next_image = &catalog_images[next];
             active_index = Some(next);
             active_image_id = Some(next_image.id);
@@ -168,37 +410,240 @@ pub fn launch_window(
                     image_id: next_image.id,
                 })
                 .map_err(|error| format!("show-edit failed during image switch: {error}"))?;
-            preview = load_preview_canvas(active_image_path.as_deref(), width, height);
+            history.reset(params);
+            view = ViewRegion::FIT;
+            if let Some(prefetcher) = &prefetcher {
+                prefetcher.drain_into(&mut decoded_cache);
+            }
+            preview_source = cached_preview_source(
+                &mut decoded_cache,
+                active_image_id,
+                active_image_path.as_deref(),
+            );
+            if let Some(prefetcher) = &prefetcher {
+                warm_neighbors(
+                    prefetcher,
+                    &decoded_cache,
+                    &catalog_images,
+                    next,
+                    config.prefetch_neighbors,
+                );
+            }
+            preview = preview_source
+                .as_deref()
+                .and_then(|source| preview_canvas_from_source(source, width, height));
             latest_frame = None;
-            submit_preview(service, next_image.id, params, width as u32, height as u32)?;
+            latest_clipping = None;
+            submit_preview(
+                service,
+                next_image.id,
+                params,
+                (width as u32, height as u32),
+                preview_source.clone(),
+                view,
+                histogram_window.is_some(),
+            )?;
+            preview_submitted = true;
         }
 
-        let mouse_down = window.get_mouse_down(MouseButton::Left);
-        let mouse_pos = window.get_mouse_pos(MouseMode::Clamp);
         let hovered_slider = mouse_pos
             .and_then(|(mouse_x, mouse_y)| slider_at_position(mouse_x, mouse_y, &sliders, width));
+        let over_preview = mouse_pos.is_some_and(|(mouse_x, mouse_y)| {
+            preview_panel_contains(mouse_x, mouse_y, width, height)
+        });
+        let previous_view = view;
+        if let Some((_, scroll_y)) = window.get_scroll_wheel().filter(|_| over_preview) {
+            if scroll_y > 0.0 {
+                view = view.zoomed_by(VIEW_ZOOM_STEP);
+            } else if scroll_y < 0.0 {
+                view = view.zoomed_by(1.0 / VIEW_ZOOM_STEP);
+            }
+        }
 
-        if mouse_down {
-            if let Some((mouse_x, _)) = mouse_pos {
+        let clicked_rating_button = if mouse_down && !was_mouse_down {
+            mouse_pos.and_then(|(mouse_x, mouse_y)| {
+                rating_button_at_position(mouse_x, mouse_y, rating_top, width)
+            })
+        } else {
+            None
+        };
+        if onboarding && mouse_down && !was_mouse_down {
+            let on_button = mouse_pos.is_some_and(|(mouse_x, mouse_y)| {
+                onboarding_button_contains(mouse_x, mouse_y, width, height)
+            });
+            if on_button {
+                println!(
+                    "to import photos, close this window and run `lite-room import <folder>` (or `lite-room watch <folder>`), then start `lite-room ui` again"
+                );
+            }
+        }
+        if let (Some(button), Some(image)) = (
+            clicked_rating_button,
+            active_index.and_then(|index| catalog_images.get_mut(index)),
+        ) {
+            apply_rating_button(service, image, button)?;
+        }
+
+        let shift_down = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+        let mut double_clicked_slider = None;
+        if mouse_down && !was_mouse_down && !shift_down {
+            let now_ms = start.elapsed().as_millis() as u64;
+            double_clicked_slider =
+                hovered_slider.filter(|field| is_double_click(last_slider_press, *field, now_ms));
+            last_slider_press = match double_clicked_slider {
+                Some(_) => None,
+                None => hovered_slider.map(|field| (field, now_ms)),
+            };
+        }
+        if mouse_down && !was_mouse_down && shift_down {
+            if let Some(field) = hovered_slider {
+                toggle_slider_enabled(&mut params, field);
+                history.push(params);
+                autosave.mark_dirty(start.elapsed().as_millis() as u64);
+                if let Some(id) = active_image_id {
+                    submit_preview(
+                        service,
+                        id,
+                        params,
+                        (width as u32, height as u32),
+                        preview_source.clone(),
+                        view,
+                        histogram_window.is_some(),
+                    )?;
+                }
+            }
+        } else if let Some(field) = double_clicked_slider {
+            if reset_slider_field(&mut params, field) {
+                history.push(params);
+                autosave.mark_dirty(start.elapsed().as_millis() as u64);
+                if let Some(id) = active_image_id {
+                    submit_preview(
+                        service,
+                        id,
+                        params,
+                        (width as u32, height as u32),
+                        preview_source.clone(),
+                        view,
+                        histogram_window.is_some(),
+                    )?;
+                }
+            }
+        } else if mouse_down {
+            if let Some((mouse_x, mouse_y)) = mouse_pos {
                 if !was_mouse_down {
                     active_drag = hovered_slider;
+                    pan_anchor =
+                        (active_drag.is_none() && over_preview).then_some((mouse_x, mouse_y));
+                }
+                if let Some(anchor) = pan_anchor {
+                    view = pan_view(view, anchor, (mouse_x, mouse_y), width, height);
+                    pan_anchor = Some((mouse_x, mouse_y));
                 }
                 if let Some(field) = active_drag {
                     if update_param_from_mouse(&mut params, field, mouse_x, width) {
                         let now_ms = start.elapsed().as_millis() as u64;
                         autosave.mark_dirty(now_ms);
                         if let Some(id) = active_image_id {
-                            submit_preview(service, id, params, width as u32, height as u32)?;
+                            submit_preview(
+                                service,
+                                id,
+                                params,
+                                (width as u32, height as u32),
+                                preview_source.clone(),
+                                view,
+                                histogram_window.is_some(),
+                            )?;
                         }
                     }
                 }
             }
         } else {
-            active_drag = None;
+            pan_anchor = None;
+            if active_drag.take().is_some() {
+                history.push(params);
+            }
+        }
+        if view != previous_view {
+            if let Some(id) = active_image_id {
+                submit_preview(
+                    service,
+                    id,
+                    params,
+                    (width as u32, height as u32),
+                    preview_source.clone(),
+                    view,
+                    histogram_window.is_some(),
+                )?;
+            }
         }
 
         was_mouse_down = mouse_down;
 
+        let ctrl_down = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        if ctrl_down && active_drag.is_none() {
+            let restored = if window.is_key_pressed(Key::Y, KeyRepeat::No)
+                || (shift_down && window.is_key_pressed(Key::Z, KeyRepeat::No))
+            {
+                history.redo()
+            } else if window.is_key_pressed(Key::Z, KeyRepeat::No) {
+                history.undo()
+            } else {
+                None
+            };
+            if let Some(restored) = restored {
+                params = restored;
+                autosave.mark_dirty(start.elapsed().as_millis() as u64);
+                if let Some(id) = active_image_id {
+                    submit_preview(
+                        service,
+                        id,
+                        params,
+                        (width as u32, height as u32),
+                        preview_source.clone(),
+                        view,
+                        histogram_window.is_some(),
+                    )?;
+                }
+            }
+        }
+
+        if hovered_slider == Some(SliderField::Temperature) {
+            for key in window.get_keys_pressed(KeyRepeat::No) {
+                match key {
+                    Key::Enter | Key::NumPadEnter => {
+                        if apply_kelvin_entry(&mut params, &kelvin_entry) {
+                            history.push(params);
+                            autosave.mark_dirty(start.elapsed().as_millis() as u64);
+                            if let Some(id) = active_image_id {
+                                submit_preview(
+                                    service,
+                                    id,
+                                    params,
+                                    (width as u32, height as u32),
+                                    preview_source.clone(),
+                                    view,
+                                    histogram_window.is_some(),
+                                )?;
+                            }
+                        }
+                        kelvin_entry.clear();
+                    }
+                    Key::Backspace => {
+                        kelvin_entry.pop();
+                    }
+                    other => {
+                        if let Some(digit) = key_digit(other) {
+                            if kelvin_entry.len() < MAX_KELVIN_ENTRY_DIGITS {
+                                kelvin_entry.push(digit);
+                            }
+                        }
+                    }
+                }
+            }
+        } else {
+            kelvin_entry.clear();
+        }
+
         let now_ms = start.elapsed().as_millis() as u64;
         if autosave.should_flush(now_ms) {
             if let Some(id) = active_image_id {
@@ -211,6 +656,22 @@ pub fn launch_window(
         draw_header(&mut buffer, width);
         draw_preview_shadow(&mut buffer, width, height);
         draw_preview_panel(&mut buffer, width, height, &preview);
+        if let Some(index) = active_index {
+            film_strip.first_visible =
+                film_strip_first_visible(index, film_strip.first_visible, film_strip_slots(width));
+        }
+        draw_film_strip(
+            &mut buffer,
+            width,
+            height,
+            &mut film_strip,
+            &catalog_images,
+            &thumbnail_paths,
+            active_index,
+        );
+        if onboarding {
+            draw_onboarding(&mut buffer, width, height);
+        }
         draw_sliders(
             &mut buffer,
             width,
@@ -220,13 +681,47 @@ pub fn launch_window(
             active_drag.or(hovered_slider),
             active_index.map(|index| (index + 1, catalog_images.len())),
         );
-
-        if let Some(frame) = service
-            .poll_preview(PollPreviewCommand)
-            .map_err(|error| format!("preview poll failed: {error}"))?
-        {
-            preview = Some(preview_canvas_from_frame(&frame, width, height));
-            latest_frame = Some(frame);
+        draw_kelvin_entry(&mut buffer, width, &sliders, &kelvin_entry);
+        let (rating, flag) = active_index
+            .and_then(|index| catalog_images.get(index))
+            .map(|image| (image.rating, image.flag))
+            .unwrap_or((0, FLAG_NONE));
+        draw_rating_buttons(&mut buffer, width, rating_top, rating, flag);
+
+        if let Some(prefetcher) = &prefetcher {
+            prefetcher.drain_into(&mut decoded_cache);
+        }
+        match service.poll_preview(PollPreviewCommand) {
+            Ok(Some(frame)) => {
+                let (canvas, clipping) = apply_preview_frame(
+                    &frame,
+                    width,
+                    height,
+                    histogram_window
+                        .as_mut()
+                        .map(|detached| &mut detached.histogram),
+                );
+                preview = Some(canvas);
+                latest_clipping = Some(clipping);
+                latest_frame = Some(frame);
+            }
+            Ok(None) => {}
+            Err(error) => {
+                let message = error.to_string();
+                if last_preview_error.as_deref() != Some(message.as_str()) {
+                    eprintln!("{message}");
+                    last_preview_error = Some(message);
+                }
+                preview_submitted = false;
+            }
+        }
+        if should_show_loading_indicator(preview_submitted, latest_frame.as_ref()) {
+            draw_loading_indicator(
+                &mut buffer,
+                width,
+                height,
+                start.elapsed().as_millis() as u64,
+            );
         }
         let metrics = service
             .preview_metrics(PreviewMetricsQuery)
@@ -237,13 +732,14 @@ pub fn launch_window(
         }
 
         window.set_title(&build_window_title(
-            catalog_path,
-            cache_dir,
+            &catalog_path,
+            &cache_dir,
             image_count,
             active_image_id,
             params,
             TitleTelemetry {
                 latest_frame: latest_frame.as_ref(),
+                clipping: latest_clipping,
                 metrics: &metrics,
                 preview_canvas: preview.as_ref(),
                 image_index: active_index.map(|index| (index + 1, catalog_images.len())),
@@ -254,6 +750,9 @@ pub fn launch_window(
         window
             .update_with_buffer(&buffer, width, height)
             .map_err(|error| format!("failed to update UI window: {error}"))?;
+        if let Some(detached) = histogram_window.as_mut() {
+            detached.present(latest_frame.as_ref(), &metrics)?;
+        }
     }
 
     if autosave.is_dirty() {
@@ -265,6 +764,55 @@ pub fn launch_window(
     Ok(())
 }
 
+pub fn select_initial_image(images: &[ImageRecord]) -> Option<&ImageRecord> {
+    images
+        .iter()
+        .find(|image| Path::new(&image.file_path).exists())
+        .or_else(|| images.first())
+}
+
+fn frame_interval(fps: u32) -> Duration {
+    Duration::from_micros(1_000_000 / u64::from(fps.max(1)))
+}
+
+fn apply_rating_button(
+    service: &ApplicationService,
+    image: &mut ImageRecord,
+    button: RatingButton,
+) -> Result<(), String> {
+    match button {
+        RatingButton::Star(star) => {
+            let rating = toggled_value(image.rating, star, 0);
+            service
+                .set_rating(SetRatingCommand {
+                    image_id: image.id,
+                    rating,
+                })
+                .map_err(|error| format!("set rating failed: {error}"))?;
+            image.rating = rating;
+        }
+        RatingButton::Flag(clicked) => {
+            let flag = toggled_value(image.flag, clicked, FLAG_NONE);
+            service
+                .set_flag(SetFlagCommand {
+                    image_id: image.id,
+                    flag,
+                })
+                .map_err(|error| format!("set flag failed: {error}"))?;
+            image.flag = flag;
+        }
+    }
+    Ok(())
+}
+
+fn toggled_value(current: i64, clicked: i64, cleared: i64) -> i64 {
+    if current == clicked {
+        cleared
+    } else {
+        clicked
+    }
+}
+
 fn persist_edit(
     service: &ApplicationService,
     image_id: ImageId,
@@ -279,8 +827,10 @@ fn submit_preview(
     service: &ApplicationService,
     image_id: ImageId,
     params: EditParams,
-    target_width: u32,
-    target_height: u32,
+    (target_width, target_height): (u32, u32),
+    source_pixels: Option<Arc<DecodedBuffer>>,
+    view: ViewRegion,
+    want_histogram: bool,
 ) -> Result<(), String> {
     service
         .submit_preview(SubmitPreviewCommand {
@@ -288,37 +838,88 @@ fn submit_preview(
             params,
             target_width,
             target_height,
+            source_pixels,
+            source_region: None,
+            view_region: (!view.is_fit()).then_some(view),
+            want_histogram,
+            comparison: None,
+            split_fraction: DEFAULT_SPLIT_FRACTION,
         })
         .map_err(|error| format!("preview submit failed: {error}"))
 }
 
-fn load_preview_canvas(
+fn cached_preview_source(
+    cache: &mut DecodedImageCache,
+    image_id: Option<ImageId>,
     image_path: Option<&str>,
-    window_width: usize,
-    window_height: usize,
-) -> Option<PreviewCanvas> {
+) -> Option<Arc<DecodedBuffer>> {
+    let Some(image_id) = image_id else {
+        return load_preview_source(image_path);
+    };
+    if let Some(source) = cache.get(image_id) {
+        return Some(source);
+    }
+    let source = load_preview_source(image_path)?;
+    cache.insert(image_id, source.clone());
+    Some(source)
+}
+
+// Replaces any warmup still queued for the previous image.
+fn warm_neighbors(
+    prefetcher: &ImagePrefetcher,
+    cache: &DecodedImageCache,
+    images: &[ImageRecord],
+    current: usize,
+    neighbors: usize,
+) {
+    prefetcher.warm(
+        prefetch_targets(current, images.len(), neighbors)
+            .into_iter()
+            .map(|index| &images[index])
+            .filter(|image| !cache.contains(image.id))
+            .map(|image| (image.id, image.file_path.clone())),
+    );
+}
+
+fn load_preview_source(image_path: Option<&str>) -> Option<Arc<DecodedBuffer>> {
     let path = image_path?;
     let image = ImageReader::open(path)
         .ok()?
         .with_guessed_format()
         .ok()?
         .decode()
-        .ok()?;
+        .ok()?
+        .to_rgb8();
 
-    let source = image.to_rgb8();
-    let src_width = source.width() as usize;
-    let src_height = source.height() as usize;
-    if src_width == 0 || src_height == 0 {
-        return None;
-    }
+    Some(Arc::new(DecodedBuffer {
+        width: image.width(),
+        height: image.height(),
+        rgb: image.into_raw(),
+    }))
+}
 
+fn preview_canvas_from_source(
+    source: &DecodedBuffer,
+    window_width: usize,
+    window_height: usize,
+) -> Option<PreviewCanvas> {
     let panel_left = preview_panel_left();
     let panel_right = preview_panel_right(window_width);
     let panel_top = preview_panel_top();
     let panel_bottom = preview_panel_bottom(window_height);
     let max_width = panel_right.saturating_sub(panel_left + 26);
     let max_height = panel_bottom.saturating_sub(panel_top + 26);
-    if max_width == 0 || max_height == 0 {
+    fit_canvas(source, max_width, max_height)
+}
+
+fn fit_canvas(
+    source: &DecodedBuffer,
+    max_width: usize,
+    max_height: usize,
+) -> Option<PreviewCanvas> {
+    let src_width = source.width as usize;
+    let src_height = source.height as usize;
+    if src_width == 0 || src_height == 0 || max_width == 0 || max_height == 0 {
         return None;
     }
 
@@ -331,8 +932,8 @@ fn load_preview_canvas(
         let src_y = y * src_height / dst_height;
         for x in 0..dst_width {
             let src_x = x * src_width / dst_width;
-            let pixel = source.get_pixel(src_x as u32, src_y as u32);
-            let [r, g, b] = pixel.0;
+            let offset = (src_y * src_width + src_x) * 3;
+            let [r, g, b] = [0, 1, 2].map(|channel| source.rgb[offset + channel]);
             pixels[y * dst_width + x] = ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
         }
     }
@@ -411,6 +1012,349 @@ fn draw_preview_panel(buffer: &mut [u32], width: usize, height: usize, preview:
     }
 }
 
+fn film_strip_top(height: usize) -> usize {
+    preview_panel_bottom(height) + FILM_STRIP_GAP
+}
+
+fn film_strip_thumb_top(height: usize) -> usize {
+    film_strip_top(height) + (FILM_STRIP_HEIGHT - FILM_THUMB_SIZE) / 2
+}
+
+fn film_strip_content_left() -> usize {
+    preview_panel_left() + FILM_STRIP_PADDING
+}
+
+fn film_strip_slots(width: usize) -> usize {
+    let content_width =
+        preview_panel_right(width).saturating_sub(preview_panel_left() + FILM_STRIP_PADDING * 2);
+    ((content_width + FILM_THUMB_GAP) / (FILM_THUMB_SIZE + FILM_THUMB_GAP)).max(1)
+}
+
+fn film_strip_first_visible(active: usize, first_visible: usize, slots: usize) -> usize {
+    if active < first_visible {
+        active
+    } else if active >= first_visible + slots {
+        active + 1 - slots
+    } else {
+        first_visible
+    }
+}
+
+fn film_strip_index_at_x(
+    x: usize,
+    first_visible: usize,
+    slots: usize,
+    count: usize,
+) -> Option<usize> {
+    let offset = x.checked_sub(film_strip_content_left())?;
+    let stride = FILM_THUMB_SIZE + FILM_THUMB_GAP;
+    let slot = offset / stride;
+    let index = first_visible + slot;
+    (slot < slots && offset % stride < FILM_THUMB_SIZE && index < count).then_some(index)
+}
+
+fn film_strip_index_at_position(
+    mouse_x: f32,
+    mouse_y: f32,
+    width: usize,
+    height: usize,
+    first_visible: usize,
+    count: usize,
+) -> Option<usize> {
+    let y = mouse_y.max(0.0) as usize;
+    let top = film_strip_thumb_top(height);
+    if y < top || y >= top + FILM_THUMB_SIZE {
+        return None;
+    }
+    film_strip_index_at_x(
+        mouse_x.max(0.0) as usize,
+        first_visible,
+        film_strip_slots(width),
+        count,
+    )
+}
+
+fn draw_film_strip(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    strip: &mut FilmStrip,
+    images: &[ImageRecord],
+    thumbnail_paths: &HashMap<ImageId, String>,
+    active_index: Option<usize>,
+) {
+    let left = preview_panel_left();
+    let top = film_strip_top(height);
+    let strip_width = preview_panel_right(width).saturating_sub(left);
+    fill_rect(
+        buffer,
+        width,
+        left,
+        top,
+        strip_width,
+        FILM_STRIP_HEIGHT,
+        0x1A1F29,
+    );
+    draw_rect(
+        buffer,
+        width,
+        left,
+        top,
+        strip_width,
+        FILM_STRIP_HEIGHT,
+        0xC8B89F,
+    );
+
+    let thumb_top = film_strip_thumb_top(height);
+    let first = strip.first_visible;
+    let last = (first + film_strip_slots(width)).min(images.len());
+    for (slot, index) in (first..last).enumerate() {
+        let image = &images[index];
+        let thumb_left = film_strip_content_left() + slot * (FILM_THUMB_SIZE + FILM_THUMB_GAP);
+        fill_rect(
+            buffer,
+            width,
+            thumb_left,
+            thumb_top,
+            FILM_THUMB_SIZE,
+            FILM_THUMB_SIZE,
+            0x2D2D2D,
+        );
+        if let Some(tile) = strip.tile(image.id, thumbnail_paths.get(&image.id).map(String::as_str))
+        {
+            let tile_left = thumb_left + (FILM_THUMB_SIZE - tile.width.min(FILM_THUMB_SIZE)) / 2;
+            let tile_top = thumb_top + (FILM_THUMB_SIZE - tile.height.min(FILM_THUMB_SIZE)) / 2;
+            blit_canvas(buffer, width, tile, tile_left, tile_top);
+        }
+        if Some(index) == active_index {
+            draw_rect(
+                buffer,
+                width,
+                thumb_left.saturating_sub(2),
+                thumb_top.saturating_sub(2),
+                FILM_THUMB_SIZE + 4,
+                FILM_THUMB_SIZE + 4,
+                0xFF996C,
+            );
+            draw_rect(
+                buffer,
+                width,
+                thumb_left.saturating_sub(1),
+                thumb_top.saturating_sub(1),
+                FILM_THUMB_SIZE + 2,
+                FILM_THUMB_SIZE + 2,
+                0xFF996C,
+            );
+        }
+    }
+}
+
+fn blit_canvas(buffer: &mut [u32], width: usize, canvas: &PreviewCanvas, left: usize, top: usize) {
+    for y in 0..canvas.height {
+        for x in 0..canvas.width {
+            set_pixel(
+                buffer,
+                width,
+                left + x,
+                top + y,
+                canvas.pixels[y * canvas.width + x],
+            );
+        }
+    }
+}
+
+fn should_show_onboarding(image_count: usize, image_id: Option<ImageId>) -> bool {
+    image_count == 0 && image_id.is_none()
+}
+
+fn onboarding_button_origin(width: usize, height: usize) -> (usize, usize) {
+    let center_x = (preview_panel_left() + preview_panel_right(width)) / 2;
+    let center_y = (preview_panel_top() + preview_panel_bottom(height)) / 2;
+    (
+        center_x.saturating_sub(ONBOARDING_BUTTON_WIDTH / 2),
+        center_y + 12,
+    )
+}
+
+fn onboarding_button_contains(mouse_x: f32, mouse_y: f32, width: usize, height: usize) -> bool {
+    let x = mouse_x.max(0.0) as usize;
+    let y = mouse_y.max(0.0) as usize;
+    let (left, top) = onboarding_button_origin(width, height);
+    x >= left
+        && x < left + ONBOARDING_BUTTON_WIDTH
+        && y >= top
+        && y < top + ONBOARDING_BUTTON_HEIGHT
+}
+
+fn draw_onboarding(buffer: &mut [u32], width: usize, height: usize) {
+    let center_x = (preview_panel_left() + preview_panel_right(width)) / 2;
+    let center_y = (preview_panel_top() + preview_panel_bottom(height)) / 2;
+    for (text, y, color) in [
+        ("NO PHOTOS IN THIS CATALOG YET", center_y - 36, 0xF7F2EA),
+        (ONBOARDING_IMPORT_HINT, center_y - 16, 0xBDB5A8),
+    ] {
+        let x = center_x.saturating_sub(text.len() * 4);
+        draw_text(buffer, width, x, y, text, color);
+    }
+
+    let (left, top) = onboarding_button_origin(width, height);
+    fill_rect(
+        buffer,
+        width,
+        left,
+        top,
+        ONBOARDING_BUTTON_WIDTH,
+        ONBOARDING_BUTTON_HEIGHT,
+        0xFF996C,
+    );
+    draw_rect(
+        buffer,
+        width,
+        left,
+        top,
+        ONBOARDING_BUTTON_WIDTH,
+        ONBOARDING_BUTTON_HEIGHT,
+        0xF7F2EA,
+    );
+    let label = "IMPORT...";
+    let label_x = left + (ONBOARDING_BUTTON_WIDTH - label.len() * 8) / 2;
+    draw_text(buffer, width, label_x, top + 10, label, 0x1B1F26);
+}
+
+fn should_show_loading_indicator(
+    preview_submitted: bool,
+    latest_frame: Option<&PreviewFrame>,
+) -> bool {
+    preview_submitted && latest_frame.is_none()
+}
+
+fn draw_loading_indicator(buffer: &mut [u32], width: usize, height: usize, elapsed_ms: u64) {
+    let radius = 9.0_f32;
+    let center_x = preview_panel_right(width).saturating_sub(36) as f32;
+    let center_y = preview_panel_bottom(height).saturating_sub(36) as f32;
+    let active = ((elapsed_ms / LOADING_STEP_MS) as usize) % LOADING_DOT_COUNT;
+
+    for dot in 0..LOADING_DOT_COUNT {
+        let angle = dot as f32 / LOADING_DOT_COUNT as f32 * std::f32::consts::TAU;
+        let x = (center_x + angle.cos() * radius).round() as usize;
+        let y = (center_y + angle.sin() * radius).round() as usize;
+        let age = (active + LOADING_DOT_COUNT - dot) % LOADING_DOT_COUNT;
+        let color = lerp_color(0xF7F2EA, 0x5A5048, age as f32 / LOADING_DOT_COUNT as f32);
+        fill_rect(buffer, width, x.saturating_sub(1), y.saturating_sub(1), 3, 3, color);
+    }
+}
+
+// The main preview and the detached histogram window read the same frame, so a
+// new frame updates both in one place. Frames rendered without a histogram keep
+// the previous one on screen.
+fn apply_preview_frame(
+    frame: &PreviewFrame,
+    width: usize,
+    height: usize,
+    histogram: Option<&mut PreviewHistogram>,
+) -> (PreviewCanvas, ClippingStats) {
+    if let (Some(shown), Some(rendered)) = (histogram, &frame.histogram) {
+        shown.clone_from(rendered);
+    }
+    (
+        preview_canvas_from_frame(frame, width, height),
+        frame.clipping_stats(),
+    )
+}
+
+fn draw_histogram_panel(
+    buffer: &mut [u32],
+    width: usize,
+    histogram: &PreviewHistogram,
+    latest_frame: Option<&PreviewFrame>,
+    metrics: &PreviewMetrics,
+) {
+    buffer.fill(0x1E1E1E);
+    let plot_width = HISTOGRAM_BINS * 2;
+    draw_rect(
+        buffer,
+        width,
+        HISTOGRAM_MARGIN - 1,
+        HISTOGRAM_MARGIN - 1,
+        plot_width + 2,
+        HISTOGRAM_PLOT_HEIGHT + 2,
+        0x4A4A4A,
+    );
+    let peak = histogram
+        .red
+        .iter()
+        .chain(&histogram.green)
+        .chain(&histogram.blue)
+        .copied()
+        .max()
+        .unwrap_or(0);
+    if peak > 0 {
+        let bar = |count: u32| (count as u64 * HISTOGRAM_PLOT_HEIGHT as u64 / peak as u64) as usize;
+        let baseline = HISTOGRAM_MARGIN + HISTOGRAM_PLOT_HEIGHT;
+        for bin in 0..HISTOGRAM_BINS {
+            let bars = [
+                (bar(histogram.red[bin]), 0xC00000),
+                (bar(histogram.green[bin]), 0x00C000),
+                (bar(histogram.blue[bin]), 0x0000C0),
+            ];
+            for row in 0..HISTOGRAM_PLOT_HEIGHT {
+                let color = bars
+                    .iter()
+                    .filter(|(bar_height, _)| row < *bar_height)
+                    .fold(0_u32, |color, (_, channel)| color | channel);
+                if color != 0 {
+                    let x = HISTOGRAM_MARGIN + bin * 2;
+                    set_pixel(buffer, width, x, baseline - 1 - row, color);
+                    set_pixel(buffer, width, x + 1, baseline - 1 - row, color);
+                }
+            }
+        }
+    }
+
+    let text_top = HISTOGRAM_MARGIN + HISTOGRAM_PLOT_HEIGHT + 16;
+    let frame_text = match latest_frame {
+        Some(frame) => {
+            let clipping = frame.clipping_stats();
+            format!(
+                "SEQ {} {}X{} {}MS CLIP HI {:.1}% LO {:.1}%",
+                frame.sequence,
+                frame.width,
+                frame.height,
+                frame.render_time_ms,
+                clipping.highlight_percent,
+                clipping.shadow_percent
+            )
+        }
+        None => "PREVIEW PENDING".to_string(),
+    };
+    draw_text(
+        buffer,
+        width,
+        HISTOGRAM_MARGIN,
+        text_top,
+        &frame_text,
+        0xE6E6E6,
+    );
+    let metrics_text = format!(
+        "JOBS S/C/X/D {}/{}/{}/{} P95 {}MS",
+        metrics.submitted_jobs,
+        metrics.completed_jobs,
+        metrics.canceled_jobs,
+        metrics.dropped_frames,
+        metrics
+            .p95_render_time_ms
+            .map_or_else(|| "-".to_string(), |value| value.to_string())
+    );
+    draw_text(
+        buffer,
+        width,
+        HISTOGRAM_MARGIN,
+        text_top + 16,
+        &metrics_text,
+        0xBDBDBD,
+    );
+}
+
 fn preview_canvas_from_frame(
     frame: &PreviewFrame,
     window_width: usize,
@@ -534,10 +1478,21 @@ fn draw_sliders(
     for slider in sliders {
         draw_slider_shell(buffer, width, slider.top);
         let value = get_param_value(params, slider.field);
+        let enabled = params.is_enabled(edit_field(slider.field));
+        let color = if enabled {
+            slider.color
+        } else {
+            DISABLED_SLIDER_COLOR
+        };
         let x = value_to_x(value, width);
-        draw_slider_track(buffer, width, slider.top, x, slider.color);
-        draw_slider_knob(buffer, width, x, slider.top, slider.color);
-        let label = format!("{} {:+.2}", slider_label(slider.field), value);
+        draw_slider_track(buffer, width, slider.top, x, color);
+        draw_slider_knob(buffer, width, x, slider.top, color);
+        let value_text = slider_value_text(slider.field, value);
+        let label = if enabled {
+            format!("{} {}", slider_label(slider.field), value_text)
+        } else {
+            format!("{} {} OFF", slider_label(slider.field), value_text)
+        };
         draw_text(
             buffer,
             width,
@@ -549,6 +1504,21 @@ fn draw_sliders(
     }
 }
 
+fn draw_kelvin_entry(buffer: &mut [u32], width: usize, sliders: &[SliderSpec], entry: &str) {
+    if entry.is_empty() {
+        return;
+    }
+    let Some(slider) = sliders
+        .iter()
+        .find(|slider| slider.field == SliderField::Temperature)
+    else {
+        return;
+    };
+    let text = format!("> {entry}_K");
+    let x = slider_right(width).saturating_sub(8 + text.len() * 8);
+    draw_text(buffer, width, x, slider.top + 6, &text, 0x1B1F26);
+}
+
 fn draw_control_text(
     buffer: &mut [u32],
     width: usize,
@@ -576,6 +1546,14 @@ fn draw_control_text(
         .map(|field| format!("{}: {}", slider_label(field), slider_effect(field)))
         .unwrap_or_else(|| "HOVER A SLIDER TO SEE EFFECT".to_string());
     draw_text(buffer, width, left + 22, top + 80, &focus_text, 0x4A3E2E);
+    draw_text(
+        buffer,
+        width,
+        left + 22,
+        top + 96,
+        "SHIFT+CLICK: TOGGLE SLIDER",
+        0x4A3E2E,
+    );
 
     if let Some(first) = sliders.first() {
         let y = first.top.saturating_sub(16);
@@ -583,6 +1561,32 @@ fn draw_control_text(
     }
 }
 
+fn draw_rating_buttons(buffer: &mut [u32], width: usize, top: usize, rating: i64, flag: i64) {
+    for star in 1..=MAX_RATING {
+        let left = star_button_left(star, width);
+        if star <= rating {
+            fill_rect(buffer, width, left, top, STAR_BUTTON_SIZE, STAR_BUTTON_SIZE, 0xE0A43A);
+            draw_char(buffer, width, left + 6, top + 6, '*', 0x1B1F26);
+        } else {
+            fill_rect(buffer, width, left, top, STAR_BUTTON_SIZE, STAR_BUTTON_SIZE, 0xF0E3D0);
+            draw_char(buffer, width, left + 6, top + 6, '*', 0x6A5B47);
+        }
+        draw_rect(buffer, width, left, top, STAR_BUTTON_SIZE, STAR_BUTTON_SIZE, 0xCCBBA4);
+    }
+
+    for (value, label, active_color) in [
+        (FLAG_PICK, "PICK", 0x9CD8BE),
+        (FLAG_REJECT, "REJ", 0xFF996C),
+    ] {
+        let left = flag_button_left(value, width);
+        let fill = if flag == value { active_color } else { 0xF0E3D0 };
+        fill_rect(buffer, width, left, top, FLAG_BUTTON_WIDTH, STAR_BUTTON_SIZE, fill);
+        draw_rect(buffer, width, left, top, FLAG_BUTTON_WIDTH, STAR_BUTTON_SIZE, 0xCCBBA4);
+        let text_x = left + (FLAG_BUTTON_WIDTH - label.len() * 8) / 2;
+        draw_text(buffer, width, text_x, top + 6, label, 0x1B1F26);
+    }
+}
+
 fn draw_slider_shell(buffer: &mut [u32], width: usize, top: usize) {
     let left = slider_left(width);
     let right = slider_right(width);
@@ -800,7 +1804,37 @@ fn preview_panel_right(width: usize) -> usize {
 }
 
 fn preview_panel_bottom(height: usize) -> usize {
-    height.saturating_sub(WORKAREA_BOTTOM_MARGIN)
+    height.saturating_sub(WORKAREA_BOTTOM_MARGIN + FILM_STRIP_HEIGHT + FILM_STRIP_GAP)
+}
+
+fn preview_panel_contains(mouse_x: f32, mouse_y: f32, width: usize, height: usize) -> bool {
+    let x = mouse_x.max(0.0) as usize;
+    let y = mouse_y.max(0.0) as usize;
+    x >= preview_panel_left()
+        && x < preview_panel_right(width)
+        && y >= preview_panel_top()
+        && y < preview_panel_bottom(height)
+}
+
+// Dragging moves the image with the pointer, so the view center moves the
+// other way by the drag's share of the panel.
+fn pan_view(
+    view: ViewRegion,
+    (from_x, from_y): (f32, f32),
+    (to_x, to_y): (f32, f32),
+    width: usize,
+    height: usize,
+) -> ViewRegion {
+    let panel_width = preview_panel_right(width)
+        .saturating_sub(preview_panel_left())
+        .max(1);
+    let panel_height = preview_panel_bottom(height)
+        .saturating_sub(preview_panel_top())
+        .max(1);
+    view.panned_by(
+        (from_x - to_x) / panel_width as f32,
+        (from_y - to_y) / panel_height as f32,
+    )
 }
 
 fn control_panel_left(width: usize) -> usize {
@@ -838,6 +1872,56 @@ fn slider_at_position(
         .map(|spec| spec.field)
 }
 
+fn rating_row_top(sliders: &[SliderSpec]) -> usize {
+    sliders
+        .last()
+        .map(|slider| slider.top + SLIDER_HEIGHT + RATING_ROW_GAP)
+        .unwrap_or_else(|| control_panel_top() + 126)
+}
+
+fn star_button_left(star: i64, width: usize) -> usize {
+    slider_left(width) + 8 + (star as usize - 1) * (STAR_BUTTON_SIZE + STAR_BUTTON_GAP)
+}
+
+fn flag_button_left(flag: i64, width: usize) -> usize {
+    let reject_left = slider_right(width).saturating_sub(8 + FLAG_BUTTON_WIDTH);
+    if flag == FLAG_REJECT {
+        reject_left
+    } else {
+        reject_left.saturating_sub(FLAG_BUTTON_WIDTH + STAR_BUTTON_GAP)
+    }
+}
+
+fn star_rating_at_x(x: usize, width: usize) -> Option<i64> {
+    (1..=MAX_RATING).find(|star| {
+        let left = star_button_left(*star, width);
+        x >= left && x < left + STAR_BUTTON_SIZE
+    })
+}
+
+fn rating_button_at_position(
+    mouse_x: f32,
+    mouse_y: f32,
+    row_top: usize,
+    width: usize,
+) -> Option<RatingButton> {
+    let x = mouse_x.max(0.0) as usize;
+    let y = mouse_y.max(0.0) as usize;
+    if y < row_top || y >= row_top + STAR_BUTTON_SIZE {
+        return None;
+    }
+    if let Some(star) = star_rating_at_x(x, width) {
+        return Some(RatingButton::Star(star));
+    }
+    [FLAG_PICK, FLAG_REJECT]
+        .into_iter()
+        .find(|flag| {
+            let left = flag_button_left(*flag, width);
+            x >= left && x < left + FLAG_BUTTON_WIDTH
+        })
+        .map(RatingButton::Flag)
+}
+
 fn update_param_from_mouse(
     params: &mut EditParams,
     field: SliderField,
@@ -845,19 +1929,40 @@ fn update_param_from_mouse(
     width: usize,
 ) -> bool {
     let updated_value = x_to_value(mouse_x, width);
-    let slot = match field {
+    let slot = param_slot(params, field);
+    if (*slot - updated_value).abs() < 0.0001 {
+        return false;
+    }
+    *slot = updated_value;
+    true
+}
+
+// Puts one slider back at neutral, leaving the others and its enabled flag
+// alone. Returns false when it was already there.
+fn reset_slider_field(params: &mut EditParams, field: SliderField) -> bool {
+    let slot = param_slot(params, field);
+    if *slot == 0.0 {
+        return false;
+    }
+    *slot = 0.0;
+    true
+}
+
+fn is_double_click(previous: Option<(SliderField, u64)>, field: SliderField, now_ms: u64) -> bool {
+    previous.is_some_and(|(previous_field, pressed_ms)| {
+        previous_field == field && now_ms.saturating_sub(pressed_ms) <= DOUBLE_CLICK_MS
+    })
+}
+
+fn param_slot(params: &mut EditParams, field: SliderField) -> &mut f32 {
+    match field {
         SliderField::Exposure => &mut params.exposure,
         SliderField::Contrast => &mut params.contrast,
         SliderField::Temperature => &mut params.temperature,
         SliderField::Tint => &mut params.tint,
         SliderField::Highlights => &mut params.highlights,
         SliderField::Shadows => &mut params.shadows,
-    };
-    if (*slot - updated_value).abs() < 0.0001 {
-        return false;
     }
-    *slot = updated_value;
-    true
 }
 
 fn value_to_x(value: f32, width: usize) -> usize {
@@ -887,6 +1992,59 @@ fn get_param_value(params: EditParams, field: SliderField) -> f32 {
     }
 }
 
+fn edit_field(field: SliderField) -> EditField {
+    match field {
+        SliderField::Exposure => EditField::Exposure,
+        SliderField::Contrast => EditField::Contrast,
+        SliderField::Temperature => EditField::Temperature,
+        SliderField::Tint => EditField::Tint,
+        SliderField::Highlights => EditField::Highlights,
+        SliderField::Shadows => EditField::Shadows,
+    }
+}
+
+fn slider_value_text(field: SliderField, value: f32) -> String {
+    match field {
+        SliderField::Temperature => format!("{:.0}K", temperature_to_kelvin(value)),
+        _ => format!("{:+.2}", value),
+    }
+}
+
+fn apply_kelvin_entry(params: &mut EditParams, entry: &str) -> bool {
+    let Ok(kelvin) = entry.parse::<u32>() else {
+        return false;
+    };
+    let temperature = kelvin_to_temperature(kelvin as f32);
+    if temperature == params.temperature {
+        return false;
+    }
+    params.temperature = temperature;
+    true
+}
+
+fn key_digit(key: Key) -> Option<char> {
+    let digit = match key {
+        Key::Key0 | Key::NumPad0 => 0,
+        Key::Key1 | Key::NumPad1 => 1,
+        Key::Key2 | Key::NumPad2 => 2,
+        Key::Key3 | Key::NumPad3 => 3,
+        Key::Key4 | Key::NumPad4 => 4,
+        Key::Key5 | Key::NumPad5 => 5,
+        Key::Key6 | Key::NumPad6 => 6,
+        Key::Key7 | Key::NumPad7 => 7,
+        Key::Key8 | Key::NumPad8 => 8,
+        Key::Key9 | Key::NumPad9 => 9,
+        _ => return None,
+    };
+    char::from_digit(digit, 10)
+}
+
+fn toggle_slider_enabled(params: &mut EditParams, field: SliderField) {
+    let field = edit_field(field);
+    let enabled = params.is_enabled(field);
+    params.set_enabled(field, !enabled);
+}
+
 fn set_pixel(buffer: &mut [u32], width: usize, x: usize, y: usize, color: u32) {
     let height = buffer.len() / width;
     if x < width && y < height {
@@ -906,7 +2064,7 @@ fn draw_text(buffer: &mut [u32], width: usize, x: usize, y: usize, text: &str, c
 }
 
 fn draw_char(buffer: &mut [u32], width: usize, x: usize, y: usize, ch: char, color: u32) {
-    let glyph = font8x8::BASIC_FONTS.get(ch).unwrap_or([0; 8]);
+    let glyph = glyph_for(ch);
     for (row, bits) in glyph.iter().enumerate() {
         for col in 0..8 {
             if (bits >> col) & 1 == 1 {
@@ -916,6 +2074,19 @@ fn draw_char(buffer: &mut [u32], width: usize, x: usize, y: usize, ch: char, col
     }
 }
 
+fn glyph_for(ch: char) -> [u8; 8] {
+    font8x8::BASIC_FONTS
+        .get(ch)
+        .or_else(|| font8x8::LATIN_FONTS.get(ch))
+        .unwrap_or(PLACEHOLDER_GLYPH)
+}
+
+fn ascii_display(text: &str) -> String {
+    text.chars()
+        .map(|ch| if ch.is_ascii() { ch } else { '?' })
+        .collect()
+}
+
 fn field_name(field: SliderField) -> &'static str {
     match field {
         SliderField::Exposure => "exposure",
@@ -970,10 +2141,18 @@ fn build_window_title(
     telemetry: TitleTelemetry<'_>,
 ) -> String {
     let preview_info = match telemetry.latest_frame {
-        Some(frame) => format!(
-            "preview seq={} {}x{} {}ms",
-            frame.sequence, frame.width, frame.height, frame.render_time_ms
-        ),
+        Some(frame) => {
+            let clipping = telemetry.clipping.unwrap_or_default();
+            format!(
+                "preview seq={} {}x{} {}ms | clip hi={:.1}% lo={:.1}%",
+                frame.sequence,
+                frame.width,
+                frame.height,
+                frame.render_time_ms,
+                clipping.highlight_percent,
+                clipping.shadow_percent
+            )
+        }
         None => "preview pending".to_string(),
     };
     let p95_text = telemetry
@@ -987,13 +2166,19 @@ fn build_window_title(
         .map(|value| value.to_string())
         .unwrap_or_else(|| "-".to_string());
     let metric_info = format!(
-        "jobs s/c/x/d={}/{}/{}/{} last={}ms p95={}ms",
+        "jobs s/c/x/d={}/{}/{}/{} hits={} last={}ms p95={}ms{}",
         telemetry.metrics.submitted_jobs,
         telemetry.metrics.completed_jobs,
         telemetry.metrics.canceled_jobs,
         telemetry.metrics.dropped_frames,
+        telemetry.metrics.cache_hits,
         last_text,
-        p95_text
+        p95_text,
+        if telemetry.metrics.gpu_disabled {
+            " gpu=disabled"
+        } else {
+            ""
+        }
     );
     let canvas_info = telemetry
         .preview_canvas
@@ -1010,9 +2195,9 @@ fn build_window_title(
 
     match image_id {
         Some(image_id) => format!(
-            "lite-room | catalog={} | cache={} | images={} | {} | edit image={} | drag sliders | {} | {} | {} | {} | {} | esc quit",
-            catalog_path,
-            cache_dir,
+            "lite-room | catalog={} | cache={} | images={} | {} | edit image={} | drag sliders | ctrl+z undo | {} | {} | {} | {} | {} | esc quit",
+            ascii_display(catalog_path),
+            ascii_display(cache_dir),
             image_count,
             nav_info,
             image_id.get(),
@@ -1024,8 +2209,8 @@ fn build_window_title(
         ),
         None => format!(
             "lite-room | catalog={} | cache={} | images={} | {} | no image to edit | {} | {} | {} | {} | esc quit",
-            catalog_path,
-            cache_dir,
+            ascii_display(catalog_path),
+            ascii_display(cache_dir),
             image_count,
             nav_info,
             preview_info,
@@ -1040,7 +2225,7 @@ fn slider_effect(field: SliderField) -> &'static str {
     match field {
         SliderField::Exposure => "overall brightness",
         SliderField::Contrast => "light-dark separation",
-        SliderField::Temperature => "warm to cool color balance",
+        SliderField::Temperature => "warm/cool, type kelvin",
         SliderField::Tint => "green to magenta balance",
         SliderField::Highlights => "bright area detail",
         SliderField::Shadows => "dark area detail",
@@ -1051,6 +2236,233 @@ fn slider_effect(field: SliderField) -> &'static str {
 mod tests {
     use super::*;
 
+    fn catalog_image(id: i64, file_path: &str) -> ImageRecord {
+        ImageRecord {
+            id: ImageId::new(id).expect("id"),
+            file_path: file_path.to_string(),
+            import_date: "1".to_string(),
+            capture_date: None,
+            rating: 0,
+            flag: 0,
+            metadata_json: "{}".to_string(),
+            caption: None,
+        }
+    }
+
+    #[test]
+    fn initial_image_skips_missing_files() {
+        let existing = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        let images = vec![
+            catalog_image(1, "/definitely/missing/photo.jpg"),
+            catalog_image(2, existing),
+        ];
+        let selected = select_initial_image(&images).expect("selected image");
+        assert_eq!(selected.id.get(), 2);
+
+        let all_missing = vec![catalog_image(3, "/definitely/missing/photo.jpg")];
+        let selected = select_initial_image(&all_missing).expect("fallback image");
+        assert_eq!(selected.id.get(), 3);
+    }
+
+    #[test]
+    fn empty_catalog_shows_onboarding() {
+        assert!(should_show_onboarding(0, None));
+        assert!(!should_show_onboarding(3, ImageId::new(1).ok()));
+
+        let (left, top) = onboarding_button_origin(WINDOW_WIDTH, WINDOW_HEIGHT);
+        assert!(onboarding_button_contains(
+            (left + 4) as f32,
+            (top + 4) as f32,
+            WINDOW_WIDTH,
+            WINDOW_HEIGHT
+        ));
+        assert!(!onboarding_button_contains(
+            left.saturating_sub(1) as f32,
+            (top + 4) as f32,
+            WINDOW_WIDTH,
+            WINDOW_HEIGHT
+        ));
+    }
+
+    #[test]
+    fn dragging_the_preview_right_moves_the_view_left() {
+        let view = ViewRegion::FIT.zoomed_by(4.0);
+        let panel_width = (preview_panel_right(WINDOW_WIDTH) - preview_panel_left()) as f32;
+        let panned = pan_view(
+            view,
+            (200.0, 300.0),
+            (200.0 + panel_width / 2.0, 300.0),
+            WINDOW_WIDTH,
+            WINDOW_HEIGHT,
+        );
+        assert!((panned.center_x - 0.375).abs() < 1e-6);
+        assert_eq!((panned.center_y, panned.zoom), (0.5, 4.0));
+        assert_eq!(
+            pan_view(
+                ViewRegion::FIT,
+                (0.0, 0.0),
+                (90.0, 40.0),
+                WINDOW_WIDTH,
+                WINDOW_HEIGHT
+            ),
+            ViewRegion::FIT
+        );
+        assert!(preview_panel_contains(
+            200.0,
+            300.0,
+            WINDOW_WIDTH,
+            WINDOW_HEIGHT
+        ));
+        assert!(!preview_panel_contains(
+            control_panel_left(WINDOW_WIDTH) as f32 + 1.0,
+            300.0,
+            WINDOW_WIDTH,
+            WINDOW_HEIGHT
+        ));
+    }
+
+    #[test]
+    fn film_strip_hit_test_accounts_for_scroll() {
+        let left = film_strip_content_left();
+        let stride = FILM_THUMB_SIZE + FILM_THUMB_GAP;
+        let slots = film_strip_slots(WINDOW_WIDTH);
+        assert!(slots > 2);
+
+        assert_eq!(film_strip_index_at_x(left + 1, 0, slots, 20), Some(0));
+        assert_eq!(film_strip_index_at_x(left + stride + 5, 0, slots, 20), Some(1));
+        assert_eq!(film_strip_index_at_x(left + stride + 5, 7, slots, 20), Some(8));
+        assert_eq!(film_strip_index_at_x(left + FILM_THUMB_SIZE + 1, 0, slots, 20), None);
+        assert_eq!(film_strip_index_at_x(left.saturating_sub(1), 0, slots, 20), None);
+        assert_eq!(film_strip_index_at_x(left + stride * 2, 0, slots, 2), None);
+        assert_eq!(film_strip_index_at_x(left + stride * slots, 0, slots, 100), None);
+
+        assert_eq!(film_strip_first_visible(3, 0, slots), 0);
+        assert_eq!(film_strip_first_visible(slots, 0, slots), 1);
+        assert_eq!(film_strip_first_visible(2, 5, slots), 2);
+    }
+
+    #[test]
+    fn frame_interval_converts_fps_to_micros() {
+        assert_eq!(frame_interval(60), Duration::from_micros(16_666));
+        assert_eq!(frame_interval(30), Duration::from_micros(33_333));
+        assert_eq!(frame_interval(144), Duration::from_micros(6_944));
+        assert_eq!(frame_interval(0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn edit_history_undoes_redoes_and_drops_redo_tail_on_push() {
+        let base = EditParams::default();
+        let brighter = base.with_exposure(1.0);
+        let warmer = brighter.with_temperature(0.5);
+        let mut history = EditHistory::new(3, base);
+        assert_eq!(history.undo(), None);
+
+        history.push(brighter);
+        history.push(brighter);
+        history.push(warmer);
+        assert_eq!(history.undo(), Some(brighter));
+        assert_eq!(history.undo(), Some(base));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(brighter));
+
+        let cooler = brighter.with_temperature(-0.5);
+        history.push(cooler);
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(brighter));
+
+        history.redo();
+        history.push(cooler.with_contrast(1.0));
+        assert_eq!(history.undo(), Some(cooler));
+        assert_eq!(history.undo(), Some(brighter));
+        assert_eq!(
+            history.undo(),
+            None,
+            "oldest state beyond capacity is dropped"
+        );
+
+        history.reset(warmer);
+        assert_eq!((history.undo(), history.redo()), (None, None));
+    }
+
+    #[test]
+    fn star_hit_test_maps_click_to_rating() {
+        let width = WINDOW_WIDTH;
+        let third = star_button_left(3, width) + STAR_BUTTON_SIZE / 2;
+        assert_eq!(star_rating_at_x(third, width), Some(3));
+        assert_eq!(star_rating_at_x(star_button_left(1, width), width), Some(1));
+        assert_eq!(
+            star_rating_at_x(star_button_left(2, width) - 1, width),
+            None
+        );
+        assert_eq!(star_rating_at_x(slider_left(width), width), None);
+
+        let top = rating_row_top(&slider_specs());
+        assert_eq!(
+            rating_button_at_position(third as f32, (top + 4) as f32, top, width),
+            Some(RatingButton::Star(3))
+        );
+        let reject = flag_button_left(FLAG_REJECT, width) + 4;
+        assert_eq!(
+            rating_button_at_position(reject as f32, (top + 4) as f32, top, width),
+            Some(RatingButton::Flag(FLAG_REJECT))
+        );
+        assert_eq!(toggled_value(0, 3, 0), 3);
+        assert_eq!(toggled_value(3, 3, 0), 0);
+    }
+
+    #[test]
+    fn toggling_slider_keeps_value() {
+        let mut params = EditParams {
+            contrast: 1.5,
+            ..EditParams::default()
+        };
+        toggle_slider_enabled(&mut params, SliderField::Contrast);
+        assert!(!params.is_enabled(EditField::Contrast));
+        assert_eq!(get_param_value(params, SliderField::Contrast), 1.5);
+
+        toggle_slider_enabled(&mut params, SliderField::Contrast);
+        assert!(params.is_enabled(EditField::Contrast));
+    }
+
+    #[test]
+    fn double_click_resets_only_that_slider() {
+        let mut params = EditParams {
+            exposure: 1.25,
+            shadows: -2.0,
+            ..EditParams::default()
+        };
+        params.set_enabled(EditField::Shadows, false);
+        assert!(reset_slider_field(&mut params, SliderField::Shadows));
+        assert_eq!((params.shadows, params.exposure), (0.0, 1.25));
+        assert!(!params.is_enabled(EditField::Shadows));
+        assert!(!reset_slider_field(&mut params, SliderField::Shadows));
+
+        let first = Some((SliderField::Exposure, 1_000));
+        assert!(is_double_click(first, SliderField::Exposure, 1_300));
+        assert!(!is_double_click(first, SliderField::Exposure, 1_500));
+        assert!(!is_double_click(first, SliderField::Tint, 1_100));
+        assert!(!is_double_click(None, SliderField::Exposure, 1_100));
+    }
+
+    #[test]
+    fn draw_text_uses_placeholder_for_missing_glyphs() {
+        let width = 64;
+        let mut mixed = vec![0_u32; width * 8];
+        draw_text(&mut mixed, width, 0, 0, "A\u{2603}B", 0xFFFFFF);
+        let mut plain = vec![0_u32; width * 8];
+        draw_text(&mut plain, width, 16, 0, "B", 0xFFFFFF);
+
+        let columns = |buffer: &[u32], left: usize| {
+            (0..8)
+                .flat_map(|y| (left..left + 8).map(move |x| (x, y)))
+                .map(|(x, y)| buffer[y * width + x])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(columns(&mixed, 16), columns(&plain, 16));
+        assert!(columns(&mixed, 8).contains(&0xFFFFFF));
+        assert_eq!(ascii_display("/photos/caf\u{e9}"), "/photos/caf?");
+    }
+
     #[test]
     fn debounce_flushes_after_threshold() {
         let mut debounce = DebouncedAutosave::new(300);
@@ -1059,6 +2471,78 @@ mod tests {
         assert!(debounce.should_flush(400));
     }
 
+    #[test]
+    fn loading_indicator_shows_until_first_frame() {
+        let frame = PreviewFrame {
+            image_id: ImageId::new(1).expect("id"),
+            sequence: 1,
+            width: 1,
+            height: 1,
+            render_time_ms: 0,
+            pixels: vec![0],
+            histogram: None,
+        };
+
+        assert!(!should_show_loading_indicator(false, None));
+        assert!(should_show_loading_indicator(true, None));
+        assert!(!should_show_loading_indicator(true, Some(&frame)));
+    }
+
+    #[test]
+    fn preview_frame_feeds_canvas_and_detached_histogram() {
+        let pixels = vec![0; 4];
+        let mut frame = PreviewFrame {
+            image_id: ImageId::new(1).expect("id"),
+            sequence: 4,
+            width: 4,
+            height: 1,
+            render_time_ms: 2,
+            histogram: Some(PreviewHistogram::from_pixels(&pixels)),
+            pixels,
+        };
+        let mut histogram = PreviewHistogram::default();
+
+        let (canvas, clipping) =
+            apply_preview_frame(&frame, WINDOW_WIDTH, WINDOW_HEIGHT, Some(&mut histogram));
+        assert!(canvas.width > 0 && canvas.height > 0);
+        assert_eq!(clipping, frame.clipping_stats());
+        assert_eq!(Some(&histogram), frame.histogram.as_ref());
+
+        frame.histogram = None;
+        frame.pixels = vec![0xFFFFFF; 4];
+        apply_preview_frame(&frame, WINDOW_WIDTH, WINDOW_HEIGHT, Some(&mut histogram));
+        assert_eq!((histogram.red[0], histogram.red[255]), (4, 0));
+
+        let mut buffer = vec![0; HISTOGRAM_WINDOW_WIDTH * HISTOGRAM_WINDOW_HEIGHT];
+        draw_histogram_panel(
+            &mut buffer,
+            HISTOGRAM_WINDOW_WIDTH,
+            &histogram,
+            Some(&frame),
+            &PreviewMetrics::default(),
+        );
+        let bottom_of_first_bin = (HISTOGRAM_MARGIN + HISTOGRAM_PLOT_HEIGHT - 1)
+            * HISTOGRAM_WINDOW_WIDTH
+            + HISTOGRAM_MARGIN;
+        assert_eq!(buffer[bottom_of_first_bin], 0xC0C0C0);
+    }
+
+    #[test]
+    fn kelvin_entry_sets_temperature() {
+        let mut params = EditParams::default();
+        assert_eq!(slider_value_text(SliderField::Temperature, 0.0), "5500K");
+
+        assert!(apply_kelvin_entry(&mut params, "6500"));
+        assert_eq!(params.temperature, kelvin_to_temperature(6_500.0));
+        assert_eq!(
+            slider_value_text(SliderField::Temperature, params.temperature),
+            "6500K"
+        );
+
+        assert!(!apply_kelvin_entry(&mut params, ""));
+        assert_eq!(key_digit(Key::NumPad7), Some('7'));
+    }
+
     #[test]
     fn x_and_value_mapping_roundtrip() {
         let width = 900;
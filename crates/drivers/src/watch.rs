@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use lite_room_application::{ApplicationService, ImportFilesCommand};
+use lite_room_domain::{detect_image_kind, ImageKind};
+use notify::{EventKind, RecursiveMode, Watcher};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const STABLE_AFTER_MS: u64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PendingFile {
+    size: u64,
+    changed_at_ms: u64,
+}
+
+#[derive(Debug)]
+pub struct StabilityTracker {
+    stable_after_ms: u64,
+    pending: HashMap<PathBuf, PendingFile>,
+}
+
+impl StabilityTracker {
+    pub fn new(stable_after_ms: u64) -> Self {
+        Self {
+            stable_after_ms,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn observe(&mut self, path: PathBuf, size: Option<u64>, now_ms: u64) {
+        let Some(size) = size else {
+            self.pending.remove(&path);
+            return;
+        };
+
+        let entry = self.pending.entry(path).or_insert(PendingFile {
+            size,
+            changed_at_ms: now_ms,
+        });
+        if entry.size != size {
+            entry.size = size;
+            entry.changed_at_ms = now_ms;
+        }
+    }
+
+    pub fn take_stable(&mut self, now_ms: u64) -> Vec<PathBuf> {
+        let mut stable: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, file)| now_ms.saturating_sub(file.changed_at_ms) >= self.stable_after_ms)
+            .map(|(path, _)| path.clone())
+            .collect();
+        stable.sort();
+        for path in &stable {
+            self.pending.remove(path);
+        }
+        stable
+    }
+
+    pub fn pending_paths(&self) -> Vec<PathBuf> {
+        self.pending.keys().cloned().collect()
+    }
+}
+
+pub fn watch_folder(
+    service: &ApplicationService,
+    folder: &str,
+    cache_root: &str,
+) -> Result<(), String> {
+    let folder_path = Path::new(folder);
+    if !folder_path.is_dir() {
+        return Err(format!(
+            "folder does not exist or is not a directory: {folder}"
+        ));
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)
+        .map_err(|error| format!("failed to start file watcher: {error}"))?;
+    watcher
+        .watch(folder_path, RecursiveMode::Recursive)
+        .map_err(|error| format!("failed to watch {folder}: {error}"))?;
+    println!("watching {folder} for new images (ctrl-c to stop)");
+
+    let start = Instant::now();
+    let mut tracker = StabilityTracker::new(STABLE_AFTER_MS);
+    loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    let now_ms = start.elapsed().as_millis() as u64;
+                    for path in event.paths {
+                        if is_watched_image(&path) {
+                            let size = file_size(&path);
+                            tracker.observe(path, size, now_ms);
+                        }
+                    }
+                }
+            }
+            Ok(Err(error)) => eprintln!("watch error: {error}"),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err("file watcher stopped unexpectedly".to_string());
+            }
+        }
+
+        let now_ms = start.elapsed().as_millis() as u64;
+        for path in tracker.pending_paths() {
+            let size = file_size(&path);
+            tracker.observe(path, size, now_ms);
+        }
+
+        let stable = tracker.take_stable(now_ms);
+        if stable.is_empty() {
+            continue;
+        }
+        match service.import_files(ImportFilesCommand {
+            paths: stable,
+            cache_root: cache_root.to_string(),
+        }) {
+            Ok(report) if report.newly_imported > 0 => println!(
+                "auto-import: supported={}, newly_imported={}",
+                report.supported_files, report.newly_imported
+            ),
+            Ok(_) => {}
+            Err(error) => eprintln!("auto-import failed: {error}"),
+        }
+    }
+}
+
+fn is_watched_image(path: &Path) -> bool {
+    detect_image_kind(path) != ImageKind::Unsupported
+}
+
+fn file_size(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_import_only_after_size_stabilizes() {
+        let mut tracker = StabilityTracker::new(1_000);
+        let photo = PathBuf::from("/ingest/photo.jpg");
+
+        tracker.observe(photo.clone(), Some(1_024), 0);
+        tracker.observe(photo.clone(), Some(4_096), 400);
+        tracker.observe(photo.clone(), Some(4_096), 900);
+        assert!(tracker.take_stable(1_200).is_empty());
+
+        tracker.observe(photo.clone(), Some(4_096), 1_300);
+        assert_eq!(tracker.take_stable(1_400), vec![photo.clone()]);
+        assert!(tracker.take_stable(5_000).is_empty());
+
+        let removed = PathBuf::from("/ingest/removed.jpg");
+        tracker.observe(removed.clone(), Some(10), 0);
+        tracker.observe(removed, None, 100);
+        assert!(tracker.take_stable(5_000).is_empty());
+    }
+}